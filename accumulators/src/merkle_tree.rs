@@ -1,6 +1,6 @@
 use noah_algebra::{
     bls12_381::BLSScalar,
-    collections::{hash_map::Iter, HashMap},
+    collections::{hash_map::Iter, BTreeMap, HashMap},
     prelude::*,
 };
 use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381, ANEMOI_JIVE_381_SALTS};
@@ -20,6 +20,9 @@ const LEAF_START: u64 = 102945566047324;
 const KEY_PAD: [u8; 4] = [0, 0, 0, 0];
 const ROOT_KEY: [u8; 12] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
 const ENTRY_COUNT_KEY: [u8; 4] = [0, 0, 0, 1];
+const PRUNED_BEFORE_KEY: [u8; 4] = [0, 0, 0, 2];
+const HISTORY_SINCE_KEY: [u8; 4] = [0, 0, 0, 3];
+const UNDO_LOG_PAD: [u8; 4] = [0, 0, 0, 4];
 
 ///
 /// PersistentMerkleTree is a 3-ary merkle tree
@@ -53,12 +56,36 @@ const ENTRY_COUNT_KEY: [u8; 4] = [0, 0, 0, 1];
 /// ```
 pub struct PersistentMerkleTree<'a, D: MerkleDB> {
     entry_count: u64,
+    pruned_before: u64,
+    // the oldest version that can be rolled back to with the undo logs, if they are kept.
+    history_since: Option<u64>,
+    // the committed values of the nodes written since the last commit.
+    undo: BTreeMap<u64, Option<Vec<u8>>>,
     store: PrefixedStore<'a, D>,
 }
 
 impl<'a, D: MerkleDB> PersistentMerkleTree<'a, D> {
-    /// Generates a new PersistentMerkleTree based on a sessioned KV store
-    pub fn new(mut store: PrefixedStore<'a, D>) -> Result<PersistentMerkleTree<'a, D>> {
+    /// Generates a new PersistentMerkleTree based on a sessioned KV store.
+    /// The past versions are read from the history of the underlying `ChainState`, unless the
+    /// store keeps undo logs already, see `new_with_history`.
+    pub fn new(store: PrefixedStore<'a, D>) -> Result<PersistentMerkleTree<'a, D>> {
+        Self::open(store, false)
+    }
+
+    /// Generates a new PersistentMerkleTree that keeps an undo log of each committed version,
+    /// so that the roots and proofs of the versions from now on remain available without a
+    /// version window in the underlying `ChainState`, until they are pruned by `prune_before`.
+    /// Each added leaf costs an extra read of the nodes that it overwrites, and the undo logs
+    /// grow with each version until they are pruned. Once enabled, the undo logs are also kept
+    /// when the store is opened with `new`.
+    pub fn new_with_history(store: PrefixedStore<'a, D>) -> Result<PersistentMerkleTree<'a, D>> {
+        Self::open(store, true)
+    }
+
+    fn open(
+        mut store: PrefixedStore<'a, D>,
+        with_history: bool,
+    ) -> Result<PersistentMerkleTree<'a, D>> {
         let mut entry_count = 0;
 
         if let Some(bytes) = store.get(&ENTRY_COUNT_KEY)? {
@@ -69,6 +96,9 @@ impl<'a, D: MerkleDB> PersistentMerkleTree<'a, D> {
         } else {
             store.set(&ROOT_KEY, BLSScalar::zero().noah_to_bytes())?;
             store.set(&ENTRY_COUNT_KEY, 0u64.to_be_bytes().to_vec())?;
+            if with_history {
+                store.set(&HISTORY_SINCE_KEY, 0u64.to_be_bytes().to_vec())?;
+            }
 
             if !store.state_mut().cache_mut().good2_commit() {
                 store.state_mut().discard_session();
@@ -79,7 +109,37 @@ impl<'a, D: MerkleDB> PersistentMerkleTree<'a, D> {
             store.state_mut().commit(0).c(d!())?;
         }
 
-        Ok(PersistentMerkleTree { entry_count, store })
+        let mut pruned_before = 0;
+        if let Some(bytes) = store.get(&PRUNED_BEFORE_KEY)? {
+            let array: [u8; 8] = [
+                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+            ];
+            pruned_before = u64::from_be_bytes(array);
+        }
+
+        // a tree that enables the undo logs later only has them from its current version on.
+        let history_since = match store.get(&HISTORY_SINCE_KEY)? {
+            Some(bytes) => {
+                let array: [u8; 8] = [
+                    bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+                ];
+                Some(u64::from_be_bytes(array))
+            }
+            None if with_history => {
+                let version = store.height()?;
+                store.set(&HISTORY_SINCE_KEY, version.to_be_bytes().to_vec())?;
+                Some(version)
+            }
+            None => None,
+        };
+
+        Ok(PersistentMerkleTree {
+            entry_count,
+            pruned_before,
+            history_since,
+            undo: BTreeMap::new(),
+            store,
+        })
     }
 
     /// add a new leaf and return the leaf uid.
@@ -133,6 +193,10 @@ impl<'a, D: MerkleDB> PersistentMerkleTree<'a, D> {
         for (k, v) in cache.iter() {
             let mut store_key = KEY_PAD.to_vec();
             store_key.extend(k.to_be_bytes());
+            if self.history_since.is_some() && !self.undo.contains_key(k) {
+                let committed = self.store.get(&store_key)?;
+                self.undo.insert(*k, committed);
+            }
             self.store.set(&store_key, v.to_vec())?;
         }
 
@@ -149,56 +213,7 @@ impl<'a, D: MerkleDB> PersistentMerkleTree<'a, D> {
 
     /// generate leaf's merkle proof by uid and the depth.
     pub fn generate_proof_with_depth(&self, id: u64, depth: usize) -> Result<Proof> {
-        if depth > TREE_DEPTH || id > 3u64.pow(depth as u32) {
-            return Err(eg!("tree depth is invalid for generate proof"));
-        }
-
-        let keys = get_path_keys(id);
-
-        let nodes: Vec<ProofNode> = keys[0..depth]
-            .iter()
-            .map(|(key_id, path)| {
-                let (left_key_id, mid_key_id, right_key_id) = match path {
-                    TreePath::Left => (*key_id, key_id + 1, key_id + 2),
-                    TreePath::Middle => (key_id - 1, *key_id, key_id + 1),
-                    TreePath::Right => (key_id - 2, key_id - 1, *key_id),
-                };
-
-                let mut node = ProofNode {
-                    left: Default::default(),
-                    mid: Default::default(),
-                    right: Default::default(),
-                    path: *path,
-                };
-
-                // if current node is not present in store then it is not a valid uid to generate
-                let mut cur_key = KEY_PAD.to_vec();
-                cur_key.extend(key_id.to_be_bytes());
-                if !self.store.exists(&cur_key)? {
-                    return Err(eg!("uid not found in tree, cannot generate proof"));
-                }
-
-                let mut left_key = KEY_PAD.to_vec();
-                left_key.extend(left_key_id.to_be_bytes());
-                if let Some(b) = self.store.get(&left_key)? {
-                    node.left = BLSScalar::noah_from_bytes(b.as_slice())?;
-                }
-
-                let mut mid_key = KEY_PAD.to_vec();
-                mid_key.extend(mid_key_id.to_be_bytes());
-                if let Some(b) = self.store.get(&mid_key)? {
-                    node.mid = BLSScalar::noah_from_bytes(b.as_slice())?;
-                }
-
-                let mut right_key = KEY_PAD.to_vec();
-                right_key.extend(right_key_id.to_be_bytes());
-                if let Some(b) = self.store.get(&right_key)? {
-                    node.right = BLSScalar::noah_from_bytes(b.as_slice())?;
-                }
-
-                Ok(node)
-            })
-            .collect::<Result<Vec<ProofNode>>>()?;
+        let nodes = build_proof_nodes(id, depth, |key| self.store.get(key))?;
 
         Ok(Proof {
             nodes: nodes,
@@ -208,6 +223,30 @@ impl<'a, D: MerkleDB> PersistentMerkleTree<'a, D> {
         })
     }
 
+    /// generate leaf's merkle proof by uid, against the root of a past version.
+    /// The version must not be pruned, otherwise an error is returned.
+    pub fn generate_proof_at_version(&self, uid: u64, version: u64) -> Result<Proof> {
+        if version == 0 || version > self.version() {
+            return Err(eg!("version not committed, cannot generate proof"));
+        }
+        let get = self.reader_at_version(version)?;
+
+        let root = match get(&ROOT_KEY[..]).c(d!())? {
+            Some(hash) => BLSScalar::noah_from_bytes(hash.as_slice())?,
+            // the root is present in every version, so a missing root means a pruned version.
+            None => return Err(eg!(NoahError::ParameterError)),
+        };
+
+        let nodes = build_proof_nodes(uid, TREE_DEPTH, &get)?;
+
+        Ok(Proof {
+            nodes,
+            root,
+            root_version: version,
+            uid,
+        })
+    }
+
     /// get tree current root
     pub fn get_root(&self) -> Result<BLSScalar> {
         self.get_root_with_depth(TREE_DEPTH)
@@ -230,6 +269,9 @@ impl<'a, D: MerkleDB> PersistentMerkleTree<'a, D> {
 
     /// get tree root by depth and version.
     pub fn get_root_with_depth_and_version(&self, depth: usize, version: u64) -> Result<BLSScalar> {
+        if version < self.pruned_before {
+            return Err(eg!(NoahError::ParameterError));
+        }
         if version == 0 {
             return Ok(BLSScalar::zero());
         }
@@ -240,26 +282,98 @@ impl<'a, D: MerkleDB> PersistentMerkleTree<'a, D> {
         }
         let mut store_key = KEY_PAD.to_vec();
         store_key.extend(pos.to_be_bytes());
-        match self.store.get_v(&store_key, version)? {
+        let get = self.reader_at_version(version)?;
+        match get(&store_key[..])? {
             Some(hash) => BLSScalar::noah_from_bytes(hash.as_slice()),
             None => Err(eg!("root hash key not found at this depth and version")),
         }
     }
 
     /// commit to store and add the tree version.
+    /// If the undo logs are kept, the committed values of the nodes written in this version are
+    /// kept in its undo log.
     pub fn commit(&mut self) -> Result<u64> {
         let height = self.store.height()?;
 
+        if !self.undo.is_empty() {
+            self.store
+                .set(&undo_log_key(height + 1), encode_undo_log(&self.undo))?;
+        }
+
         if !self.store.state_mut().cache_mut().good2_commit() {
             self.store.state_mut().discard_session();
+            self.undo.clear();
 
             return Err(eg!("store commit no good"));
         }
 
         let (_, ver) = self.store.state_mut().commit(height + 1).c(d!())?;
+        self.undo.clear();
         Ok(ver)
     }
 
+    /// Return a reader of the nodes at a committed `version` that is not pruned, by their keys.
+    /// The current nodes are rolled back with the undo logs of the later versions, while the
+    /// versions without undo logs are read from the history of the store.
+    fn reader_at_version(&self, version: u64) -> Result<NodeReader<'_>> {
+        if version < self.pruned_before || version > self.version() {
+            return Err(eg!(NoahError::ParameterError));
+        }
+        if self.history_since.map_or(true, |since| version < since) {
+            return Ok(Box::new(move |key: &[u8]| self.store.get_v(key, version)));
+        }
+
+        // the oldest undo log is applied last, so that it gives the values at `version`.
+        let mut rollback = self.undo.clone();
+        for v in (version + 1..=self.version()).rev() {
+            if let Some(bytes) = self.store.get(&undo_log_key(v))? {
+                rollback.extend(decode_undo_log(&bytes)?);
+            }
+        }
+
+        Ok(Box::new(move |key: &[u8]| {
+            if key.len() == KEY_PAD.len() + 8 && key[..KEY_PAD.len()] == KEY_PAD {
+                let mut array = [0u8; 8];
+                array.copy_from_slice(&key[KEY_PAD.len()..]);
+                if let Some(value) = rollback.get(&u64::from_be_bytes(array)) {
+                    return Ok(value.clone());
+                }
+            }
+            self.store.get(key)
+        }))
+    }
+
+    /// prune the versions older than `version`, which must be committed already.
+    /// Afterwards, roots and proofs are only available from `version` on; the current tree
+    /// is untouched. The undo logs that are only needed by the pruned versions are deleted
+    /// from the store, and the deletion and the boundary are persisted at next commit.
+    /// The versions without undo logs are still read from the history of the underlying
+    /// `ChainState`, which is only bounded by its version window.
+    pub fn prune_before(&mut self, version: u64) -> Result<()> {
+        if version > self.version() {
+            return Err(eg!(NoahError::ParameterError));
+        }
+        if version <= self.pruned_before {
+            return Ok(());
+        }
+
+        // the undo log of a version rolls the tree back to the previous version.
+        if let Some(since) = self.history_since {
+            for v in (self.pruned_before.max(since) + 1)..=version {
+                self.store.delete(&undo_log_key(v))?;
+            }
+        }
+        self.store
+            .set(&PRUNED_BEFORE_KEY, version.to_be_bytes().to_vec())?;
+        self.pruned_before = version;
+        Ok(())
+    }
+
+    /// get the oldest version whose roots and proofs are still available.
+    pub fn pruned_before(&self) -> u64 {
+        self.pruned_before
+    }
+
     /// get leaf hash by uid
     pub fn get_leaf(&self, uid: u64) -> Result<Option<BLSScalar>> {
         let mut store_key = KEY_PAD.to_vec();
@@ -702,6 +816,127 @@ impl Cache {
     }
 }
 
+/// A reader of the nodes of the tree at some version, by their keys in the store.
+type NodeReader<'a> = Box<dyn Fn(&[u8]) -> Result<Option<Vec<u8>>> + 'a>;
+
+fn undo_log_key(version: u64) -> Vec<u8> {
+    let mut key = UNDO_LOG_PAD.to_vec();
+    key.extend(version.to_be_bytes());
+    key
+}
+
+/// Encode the previous values of the nodes written in a version, as the key of each node,
+/// followed by a zero byte if the node did not exist, or by a one byte, the length of the
+/// value as a big-endian u32, and the value.
+fn encode_undo_log(undo: &BTreeMap<u64, Option<Vec<u8>>>) -> Vec<u8> {
+    let mut bytes = vec![];
+    for (key, value) in undo.iter() {
+        bytes.extend(key.to_be_bytes());
+        match value {
+            None => bytes.push(0),
+            Some(value) => {
+                bytes.push(1);
+                bytes.extend((value.len() as u32).to_be_bytes());
+                bytes.extend_from_slice(value);
+            }
+        }
+    }
+    bytes
+}
+
+/// Decode an undo log encoded by `encode_undo_log`.
+fn decode_undo_log(mut bytes: &[u8]) -> Result<Vec<(u64, Option<Vec<u8>>)>> {
+    let mut undo = vec![];
+    while !bytes.is_empty() {
+        if bytes.len() < 9 {
+            return Err(eg!(NoahError::DeserializationError));
+        }
+        let mut array = [0u8; 8];
+        array.copy_from_slice(&bytes[..8]);
+        let key = u64::from_be_bytes(array);
+        let flag = bytes[8];
+        bytes = &bytes[9..];
+
+        match flag {
+            0 => undo.push((key, None)),
+            1 => {
+                if bytes.len() < 4 {
+                    return Err(eg!(NoahError::DeserializationError));
+                }
+                let mut len = [0u8; 4];
+                len.copy_from_slice(&bytes[..4]);
+                let len = u32::from_be_bytes(len) as usize;
+                bytes = &bytes[4..];
+                if bytes.len() < len {
+                    return Err(eg!(NoahError::DeserializationError));
+                }
+                undo.push((key, Some(bytes[..len].to_vec())));
+                bytes = &bytes[len..];
+            }
+            _ => return Err(eg!(NoahError::DeserializationError)),
+        }
+    }
+    Ok(undo)
+}
+
+/// Build the nodes of the Merkle proof of the leaf `id`, reading the tree from `get`.
+fn build_proof_nodes<F: Fn(&[u8]) -> Result<Option<Vec<u8>>>>(
+    id: u64,
+    depth: usize,
+    get: F,
+) -> Result<Vec<ProofNode>> {
+    if depth > TREE_DEPTH || id > 3u64.pow(depth as u32) {
+        return Err(eg!("tree depth is invalid for generate proof"));
+    }
+
+    let keys = get_path_keys(id);
+
+    keys[0..depth]
+        .iter()
+        .map(|(key_id, path)| {
+            let (left_key_id, mid_key_id, right_key_id) = match path {
+                TreePath::Left => (*key_id, key_id + 1, key_id + 2),
+                TreePath::Middle => (key_id - 1, *key_id, key_id + 1),
+                TreePath::Right => (key_id - 2, key_id - 1, *key_id),
+            };
+
+            let mut node = ProofNode {
+                left: Default::default(),
+                mid: Default::default(),
+                right: Default::default(),
+                path: *path,
+            };
+
+            // if current node is not present in store then it is not a valid uid to generate
+            let mut cur_key = KEY_PAD.to_vec();
+            cur_key.extend(key_id.to_be_bytes());
+            if get(&cur_key)?.is_none() {
+                return Err(eg!("uid not found in tree, cannot generate proof"));
+            }
+
+            let mut left_key = KEY_PAD.to_vec();
+            left_key.extend(left_key_id.to_be_bytes());
+            if let Some(b) = get(&left_key)? {
+                node.left = BLSScalar::noah_from_bytes(b.as_slice())?;
+            }
+
+            let mut mid_key = KEY_PAD.to_vec();
+            mid_key.extend(mid_key_id.to_be_bytes());
+            if let Some(b) = get(&mid_key)? {
+                node.mid = BLSScalar::noah_from_bytes(b.as_slice())?;
+            }
+
+            let mut right_key = KEY_PAD.to_vec();
+            right_key.extend(right_key_id.to_be_bytes());
+            if let Some(b) = get(&right_key)? {
+                node.right = BLSScalar::noah_from_bytes(b.as_slice())?;
+            }
+
+            Ok(node)
+        })
+        .collect::<Result<Vec<ProofNode>>>()
+}
+
 fn get_path_keys(uid: u64) -> Vec<(u64, TreePath)> {
     let mut keys = vec![];
     let mut key = LEAF_START + uid;
@@ -729,7 +964,116 @@ fn get_path_keys(uid: u64) -> Vec<(u64, TreePath)> {
 
 #[cfg(test)]
 mod tests {
-    use super::{get_path_keys, TreePath};
+    use super::{
+        get_path_keys, undo_log_key, verify, PersistentMerkleTree, TreePath, HISTORY_SINCE_KEY,
+        TREE_DEPTH,
+    };
+    use mem_db::MemoryDB;
+    use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+    use storage::state::{ChainState, State};
+    use storage::store::{PrefixedStore, Store};
+
+    #[test]
+    fn test_prune_before_deletes_history() {
+        // without a version window, the store keeps no history besides the undo logs.
+        let fdb = MemoryDB::new();
+        let cs = Arc::new(RwLock::new(ChainState::new(fdb, "test_db".to_string(), 0)));
+        let mut state = State::new(cs, false);
+        let store = PrefixedStore::new("my_store", &mut state);
+        let mut mt = PersistentMerkleTree::new_with_history(store).unwrap();
+
+        let mut prng = test_rng();
+        let mut leaves = vec![];
+        let mut versions = vec![];
+        for _ in 0..4 {
+            let leaf = BLSScalar::random(&mut prng);
+            mt.add_commitment_hash(leaf).unwrap();
+            leaves.push(leaf);
+            versions.push(mt.commit().unwrap());
+        }
+        let undo_logs = |mt: &PersistentMerkleTree<MemoryDB>| {
+            (1..=mt.version())
+                .filter(|v| mt.store.get(&undo_log_key(*v)).unwrap().is_some())
+                .count()
+        };
+        assert_eq!(undo_logs(&mt), 4);
+
+        let old_root = mt
+            .get_root_with_depth_and_version(TREE_DEPTH, versions[1])
+            .unwrap();
+        let proof = mt.generate_proof_at_version(0, versions[1]).unwrap();
+        assert_eq!(proof.root, old_root);
+        assert!(verify(leaves[0], &proof));
+        assert!(mt.generate_proof_at_version(2, versions[1]).is_err());
+
+        mt.prune_before(versions[2]).unwrap();
+        mt.commit().unwrap();
+        assert_eq!(undo_logs(&mt), 2);
+
+        assert!(mt.generate_proof_at_version(0, versions[1]).is_err());
+        assert!(mt
+            .get_root_with_depth_and_version(TREE_DEPTH, versions[1])
+            .is_err());
+        let proof = mt.generate_proof_at_version(0, versions[2]).unwrap();
+        assert!(verify(leaves[0], &proof));
+        assert!(mt.generate_proof_at_version(3, versions[2]).is_err());
+        for (uid, leaf) in leaves.iter().enumerate() {
+            assert!(verify(*leaf, &mt.generate_proof(uid as u64).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_history_is_opt_in() {
+        let fdb = MemoryDB::new();
+        let cs = Arc::new(RwLock::new(ChainState::new(
+            fdb,
+            "test_db".to_string(),
+            100,
+        )));
+        let mut state = State::new(cs, false);
+        let mut prng = test_rng();
+
+        // a tree without history writes no undo logs
+        let first_leaf = BLSScalar::random(&mut prng);
+        let v1 = {
+            let store = PrefixedStore::new("my_store", &mut state);
+            let mut mt = PersistentMerkleTree::new(store).unwrap();
+            mt.add_commitment_hash(first_leaf).unwrap();
+            let v1 = mt.commit().unwrap();
+            assert!(mt.store.get(&HISTORY_SINCE_KEY).unwrap().is_none());
+            assert!(mt.store.get(&undo_log_key(v1)).unwrap().is_none());
+            assert!(mt.undo.is_empty());
+            v1
+        };
+
+        // the history is enabled from the current version on
+        let v2 = {
+            let store = PrefixedStore::new("my_store", &mut state);
+            let mut mt = PersistentMerkleTree::new_with_history(store).unwrap();
+            assert_eq!(mt.history_since, Some(v1));
+            mt.add_commitment_hash(BLSScalar::random(&mut prng))
+                .unwrap();
+            let v2 = mt.commit().unwrap();
+            assert!(mt.store.get(&undo_log_key(v2)).unwrap().is_some());
+
+            let proof = mt.generate_proof_at_version(0, v1).unwrap();
+            assert!(verify(first_leaf, &proof));
+            v2
+        };
+
+        // and kept when the store is opened again with `new`
+        let store = PrefixedStore::new("my_store", &mut state);
+        let mut mt = PersistentMerkleTree::new(store).unwrap();
+        assert_eq!(mt.history_since, Some(v1));
+        mt.add_commitment_hash(BLSScalar::random(&mut prng))
+            .unwrap();
+        let v3 = mt.commit().unwrap();
+        assert!(mt.store.get(&undo_log_key(v3)).unwrap().is_some());
+        let proof = mt.generate_proof_at_version(0, v2).unwrap();
+        assert!(verify(first_leaf, &proof));
+    }
 
     #[test]
     fn test_merkle_tree_path() {
@@ -1,6 +1,7 @@
 use mem_db::MemoryDB;
 use noah_accumulators::merkle_tree::{verify, PersistentMerkleTree, TREE_DEPTH};
 use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+use noah_crypto::basic::anemoi_jive::AnemoiJive381;
 use parking_lot::RwLock;
 use std::sync::Arc;
 use std::time::Instant;
@@ -46,3 +47,123 @@ fn test_merkle_tree() {
         root1
     );
 }
+
+#[test]
+fn test_merkle_root_from_leaves() {
+    let fdb = MemoryDB::new();
+    let cs = Arc::new(RwLock::new(ChainState::new(fdb, "test_db".to_string(), 0)));
+    let mut state = State::new(cs, false);
+    let store = PrefixedStore::new("my_store", &mut state);
+    let mut mt = PersistentMerkleTree::new(store).unwrap();
+
+    let mut prng = test_rng();
+    let mut leaves = vec![];
+    assert_eq!(
+        AnemoiJive381::merkle_root_from_leaves(&leaves, 3, TREE_DEPTH).unwrap(),
+        mt.get_root().unwrap()
+    );
+
+    for _ in 0..40 {
+        let leaf = BLSScalar::random(&mut prng);
+        mt.add_commitment_hash(leaf).unwrap();
+        leaves.push(leaf);
+        assert_eq!(
+            AnemoiJive381::merkle_root_from_leaves(&leaves, 3, TREE_DEPTH).unwrap(),
+            mt.get_root().unwrap()
+        );
+    }
+
+    assert!(AnemoiJive381::merkle_root_from_leaves(&leaves, 2, TREE_DEPTH).is_err());
+    assert!(AnemoiJive381::merkle_root_from_leaves(&leaves, 3, 100).is_err());
+    assert!(AnemoiJive381::merkle_root_from_leaves(&leaves[..9], 3, 2).is_ok());
+    assert!(AnemoiJive381::merkle_root_from_leaves(&leaves[..10], 3, 2).is_err());
+}
+
+#[test]
+fn test_generate_proof_at_version() {
+    let fdb = MemoryDB::new();
+    let ver_window = 100;
+    let cs = Arc::new(RwLock::new(ChainState::new(
+        fdb,
+        "test_db".to_string(),
+        ver_window,
+    )));
+    let mut state = State::new(cs, false);
+    let store = PrefixedStore::new("my_store", &mut state);
+    let mut mt = PersistentMerkleTree::new(store).unwrap();
+
+    let mut prng = test_rng();
+    let leaf = BLSScalar::random(&mut prng);
+    let uid = mt.add_commitment_hash(leaf).unwrap();
+    let v1 = mt.commit().unwrap();
+    let root1 = mt.get_root().unwrap();
+
+    for _ in 0..5 {
+        mt.add_commitment_hash(BLSScalar::random(&mut prng))
+            .unwrap();
+    }
+    let v2 = mt.commit().unwrap();
+    assert_ne!(mt.get_root().unwrap(), root1);
+
+    let old_proof = mt.generate_proof_at_version(uid, v1).unwrap();
+    assert_eq!(old_proof.root, root1);
+    assert_eq!(old_proof.root_version, v1);
+    assert!(verify(leaf, &old_proof));
+
+    let new_proof = mt.generate_proof_at_version(uid, v2).unwrap();
+    assert_eq!(new_proof.root, mt.get_root().unwrap());
+    assert!(verify(leaf, &new_proof));
+
+    // a leaf that was not in the tree at this version
+    assert!(mt.generate_proof_at_version(uid + 1, v1).is_err());
+    // a version that is not committed yet
+    assert!(mt.generate_proof_at_version(uid, v2 + 1).is_err());
+}
+
+#[test]
+fn test_prune_before() {
+    let fdb = MemoryDB::new();
+    let ver_window = 100;
+    let cs = Arc::new(RwLock::new(ChainState::new(
+        fdb,
+        "test_db".to_string(),
+        ver_window,
+    )));
+    let mut state = State::new(cs, false);
+    let store = PrefixedStore::new("my_store", &mut state);
+    let mut mt = PersistentMerkleTree::new(store).unwrap();
+
+    let mut prng = test_rng();
+    let mut leaves = vec![];
+    let mut versions = vec![];
+    for _ in 0..4 {
+        let leaf = BLSScalar::random(&mut prng);
+        mt.add_commitment_hash(leaf).unwrap();
+        leaves.push(leaf);
+        versions.push(mt.commit().unwrap());
+    }
+
+    // cannot prune versions that are not committed yet
+    assert!(mt.prune_before(versions[3] + 1).is_err());
+
+    mt.prune_before(versions[2]).unwrap();
+    mt.commit().unwrap();
+    assert_eq!(mt.pruned_before(), versions[2]);
+
+    // pruning an older version is a no-op
+    mt.prune_before(versions[0]).unwrap();
+    assert_eq!(mt.pruned_before(), versions[2]);
+
+    for (uid, leaf) in leaves.iter().enumerate() {
+        let proof = mt.generate_proof(uid as u64).unwrap();
+        assert!(verify(*leaf, &proof));
+    }
+    let proof = mt.generate_proof_at_version(0, versions[2]).unwrap();
+    assert!(verify(leaves[0], &proof));
+
+    let err = mt.generate_proof_at_version(0, versions[1]).unwrap_err();
+    msg_eq!(NoahError::ParameterError, err);
+    assert!(mt
+        .get_root_with_depth_and_version(TREE_DEPTH, versions[0])
+        .is_err());
+}
@@ -329,6 +329,19 @@ impl BLSG1 {
         }
     }
 
+    /// Compress the points, concatenating their compressed bytes.
+    /// The conversion to affine coordinates shares a single field inversion for all the points,
+    /// and the output equals the concatenation of `to_compressed_bytes` of each point.
+    pub fn batch_compress(points: &[Self]) -> Vec<u8> {
+        let affines =
+            G1Projective::normalize_batch(&points.iter().map(|p| p.0).collect::<Vec<_>>());
+        let mut buf = Vec::with_capacity(points.len() * Self::COMPRESSED_LEN);
+        for affine in affines.iter() {
+            affine.serialize_with_mode(&mut buf, Compress::Yes).unwrap();
+        }
+        buf
+    }
+
     #[inline]
     fn common_multi_exp(scalars: &[&<Self as Group>::ScalarType], points: &[&Self]) -> Self {
         use ark_ec::VariableBaseMSM;
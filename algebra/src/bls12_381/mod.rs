@@ -38,7 +38,9 @@ mod bls12_381_groups_test {
         bls12_381::{BLSScalar, BLSG1},
         prelude::*,
         traits::{
-            group_tests::{test_scalar_operations, test_scalar_serialization},
+            group_tests::{
+                test_scalar_known_answer, test_scalar_operations, test_scalar_serialization,
+            },
             Pairing,
         },
     };
@@ -57,6 +59,24 @@ mod bls12_381_groups_test {
         test_scalar_serialization::<BLSFq>();
     }
 
+    #[test]
+    fn scalar_known_answer() {
+        // 5^e mod r, for e the little-endian u64 limbs of
+        // SHA-256("noah bls scalar known answer"), computed with Python's `pow`.
+        let exponent = [
+            0x13e7fc2adb7a7f76,
+            0xf9f1cc3690d8e569,
+            0x9bf853ed298689ae,
+            0x2494acc9de646870,
+        ];
+        let expected_bytes: [u8; 32] = [
+            0x59, 0xb8, 0xcc, 0x92, 0xff, 0x24, 0x0a, 0xff, 0x87, 0x92, 0x24, 0x81, 0xa6, 0x8e,
+            0x94, 0x3b, 0x48, 0x71, 0xec, 0x25, 0x40, 0x2a, 0x77, 0xa7, 0xec, 0x22, 0x9e, 0xc4,
+            0x39, 0x0b, 0x2b, 0x73,
+        ];
+        test_scalar_known_answer::<BLSScalar>(5, &exponent, &expected_bytes);
+    }
+
     #[test]
     fn scalar_from_to_bytes() {
         let small_value = BLSScalar::from(165747u32);
@@ -193,4 +213,20 @@ mod bls12_381_groups_test {
         let gt_recovered = BLSGt::from_compressed_bytes(&gt_bytes).unwrap();
         assert_eq!(gt, gt_recovered);
     }
+
+    #[test]
+    fn test_batch_compress() {
+        let mut prng = test_rng();
+
+        let mut points: Vec<BLSG1> = (0..5).map(|_| BLSG1::random(&mut prng)).collect();
+        points.push(BLSG1::get_identity());
+        let bytes = BLSG1::batch_compress(&points);
+        assert_eq!(bytes.len(), points.len() * BLSG1::COMPRESSED_LEN);
+        let expected: Vec<u8> = points
+            .iter()
+            .flat_map(|p| p.to_compressed_bytes())
+            .collect();
+        assert_eq!(bytes, expected);
+        assert!(BLSG1::batch_compress(&[]).is_empty());
+    }
 }
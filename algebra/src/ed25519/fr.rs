@@ -241,6 +241,11 @@ impl Scalar for Ed25519Scalar {
 }
 
 impl Ed25519Scalar {
+    /// Create a new scalar element from the arkworks-rs representation.
+    pub const fn new(is_positive: bool, limbs: &[u64]) -> Self {
+        Ed25519Scalar(Fr::from_sign_and_limbs(is_positive, &limbs))
+    }
+
     /// Return a tuple of (r, g^r)
     /// where r is a random `Scalar`, and g is the `BASEPOINT_POINT`
     #[inline]
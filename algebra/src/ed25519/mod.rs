@@ -13,6 +13,15 @@ pub use fq::*;
 mod g1;
 pub use g1::*;
 
+/// A convenient macro to initialize a scalar element over the Ed25519 curve.
+#[macro_export]
+macro_rules! new_ed25519_scalar {
+    ($c0:expr) => {{
+        let (is_positive, limbs) = ark_ff::ark_ff_macros::to_sign_and_limbs!($c0);
+        Ed25519Scalar::new(is_positive, &limbs)
+    }};
+}
+
 /// Obtain the d parameter of the ed25519 curve
 pub const fn get_ed25519_d() -> Fq {
     MontFp!("37095705934669439343138083508754565189542113879843219016388785533085940283555")
@@ -110,12 +110,157 @@ pub enum NoahError {
     MissingSRSError,
     MissingVerifierParamsError,
     AbarToBarParamsError,
+    CircuitTooLarge { needed: usize, available: usize },
+    LagrangeBaseMissing { size: usize, available: Vec<usize> },
+}
+
+impl NoahError {
+    /// Return the stable numeric code of this error, e.g., for RPC responses.
+    /// The codes are pinned: a new variant must take a new code, and codes are never reused.
+    pub fn code(&self) -> u32 {
+        use NoahError::*;
+        match self {
+            AXfrProverParamsError => 1,
+            AXfrVerifierParamsError => 2,
+            AXfrVerificationError => 3,
+            AXfrProofError => 4,
+            AnonFeeProofError => 5,
+            ArgumentVerificationError => 6,
+            CommitmentInputError => 7,
+            CommitmentVerificationError => 8,
+            DecompressElementError => 9,
+            EncryptionError => 10,
+            RangeProofProveError => 11,
+            RangeProofVerifyError => 12,
+            DeserializationError => 13,
+            SerializationError => 14,
+            DecryptionError => 15,
+            IndexError => 16,
+            ParameterError => 17,
+            InconsistentStructureError => 18,
+            SignatureError => 19,
+            XfrVerifyAssetAmountError => 20,
+            XfrVerifyConfidentialAssetError => 21,
+            XfrCreationAssetAmountError => 22,
+            XfrVerifyAssetTracingAssetAmountError => 23,
+            XfrVerifyAssetTracingIdentityError => 24,
+            XfrVerifyAssetTracingEmptyProofError => 25,
+            XfrVerifyConfidentialAmountError => 26,
+            ElGamalVerificationError => 27,
+            ElGamalDecryptionError => 28,
+            IdentityRevealVerifyError => 29,
+            AssetMixerVerificationError => 30,
+            XfrNotSupported => 31,
+            MerkleTreeVerificationError => 32,
+            WhitelistVerificationError => 33,
+            WhitelistProveError => 34,
+            SolvencyInputError => 35,
+            SolvencyProveError => 36,
+            SolvencyVerificationError => 37,
+            ZKProofVerificationError => 38,
+            ZKProofBatchVerificationError => 39,
+            GroupSignatureTraceError => 40,
+            AssetTracingExtractionError => 41,
+            IdentityTracingExtractionError => 42,
+            AnonymousCredentialSignError => 43,
+            R1CSProofError => 44,
+            NoMemoInAssetTracerMemo => 45,
+            BogusAssetTracerMemo => 46,
+            MissingURSError => 47,
+            MissingSRSError => 48,
+            MissingVerifierParamsError => 49,
+            AbarToBarParamsError => 50,
+            CircuitTooLarge { .. } => 51,
+            LagrangeBaseMissing { .. } => 52,
+        }
+    }
+
+    /// Return the error with the given numeric code, or `None` if the code is unknown.
+    /// The data carried by a variant, if any, is not part of the code and is zeroed.
+    pub fn from_code(code: u32) -> Option<Self> {
+        use NoahError::*;
+        Some(match code {
+            1 => AXfrProverParamsError,
+            2 => AXfrVerifierParamsError,
+            3 => AXfrVerificationError,
+            4 => AXfrProofError,
+            5 => AnonFeeProofError,
+            6 => ArgumentVerificationError,
+            7 => CommitmentInputError,
+            8 => CommitmentVerificationError,
+            9 => DecompressElementError,
+            10 => EncryptionError,
+            11 => RangeProofProveError,
+            12 => RangeProofVerifyError,
+            13 => DeserializationError,
+            14 => SerializationError,
+            15 => DecryptionError,
+            16 => IndexError,
+            17 => ParameterError,
+            18 => InconsistentStructureError,
+            19 => SignatureError,
+            20 => XfrVerifyAssetAmountError,
+            21 => XfrVerifyConfidentialAssetError,
+            22 => XfrCreationAssetAmountError,
+            23 => XfrVerifyAssetTracingAssetAmountError,
+            24 => XfrVerifyAssetTracingIdentityError,
+            25 => XfrVerifyAssetTracingEmptyProofError,
+            26 => XfrVerifyConfidentialAmountError,
+            27 => ElGamalVerificationError,
+            28 => ElGamalDecryptionError,
+            29 => IdentityRevealVerifyError,
+            30 => AssetMixerVerificationError,
+            31 => XfrNotSupported,
+            32 => MerkleTreeVerificationError,
+            33 => WhitelistVerificationError,
+            34 => WhitelistProveError,
+            35 => SolvencyInputError,
+            36 => SolvencyProveError,
+            37 => SolvencyVerificationError,
+            38 => ZKProofVerificationError,
+            39 => ZKProofBatchVerificationError,
+            40 => GroupSignatureTraceError,
+            41 => AssetTracingExtractionError,
+            42 => IdentityTracingExtractionError,
+            43 => AnonymousCredentialSignError,
+            44 => R1CSProofError,
+            45 => NoMemoInAssetTracerMemo,
+            46 => BogusAssetTracerMemo,
+            47 => MissingURSError,
+            48 => MissingSRSError,
+            49 => MissingVerifierParamsError,
+            50 => AbarToBarParamsError,
+            51 => CircuitTooLarge {
+                needed: 0,
+                available: 0,
+            },
+            52 => LagrangeBaseMissing {
+                size: 0,
+                available: vec![],
+            },
+            _ => return None,
+        })
+    }
 }
 
 impl fmt::Display for NoahError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use NoahError::*;
         f.write_str(match self {
+            CircuitTooLarge { needed, available } => {
+                return write!(
+                    f,
+                    "The circuit needs {} constraints, but the SRS only supports {}",
+                    needed, available
+                );
+            }
+            LagrangeBaseMissing { size, available } => {
+                return write!(
+                    f,
+                    "There is no Lagrange base for size {}, the available sizes are {:?}",
+                    size, available
+                );
+            }
             AXfrProverParamsError => "Could not preprocess anonymous transfer prover",
             AXfrVerifierParamsError => "Could not preprocess anonymous transfer verifier",
             AXfrVerificationError => "Invalid AXfrBody for merkle root",
@@ -171,3 +316,114 @@ impl fmt::Display for NoahError {
 }
 
 impl error::Error for NoahError {}
+
+#[cfg(test)]
+mod test {
+    use super::NoahError::{self, *};
+
+    #[test]
+    fn test_noah_error_codes() {
+        // These assignments are part of the public interface and must not change.
+        let pinned = [
+            (1, AXfrProverParamsError),
+            (2, AXfrVerifierParamsError),
+            (3, AXfrVerificationError),
+            (4, AXfrProofError),
+            (5, AnonFeeProofError),
+            (6, ArgumentVerificationError),
+            (7, CommitmentInputError),
+            (8, CommitmentVerificationError),
+            (9, DecompressElementError),
+            (10, EncryptionError),
+            (11, RangeProofProveError),
+            (12, RangeProofVerifyError),
+            (13, DeserializationError),
+            (14, SerializationError),
+            (15, DecryptionError),
+            (16, IndexError),
+            (17, ParameterError),
+            (18, InconsistentStructureError),
+            (19, SignatureError),
+            (20, XfrVerifyAssetAmountError),
+            (21, XfrVerifyConfidentialAssetError),
+            (22, XfrCreationAssetAmountError),
+            (23, XfrVerifyAssetTracingAssetAmountError),
+            (24, XfrVerifyAssetTracingIdentityError),
+            (25, XfrVerifyAssetTracingEmptyProofError),
+            (26, XfrVerifyConfidentialAmountError),
+            (27, ElGamalVerificationError),
+            (28, ElGamalDecryptionError),
+            (29, IdentityRevealVerifyError),
+            (30, AssetMixerVerificationError),
+            (31, XfrNotSupported),
+            (32, MerkleTreeVerificationError),
+            (33, WhitelistVerificationError),
+            (34, WhitelistProveError),
+            (35, SolvencyInputError),
+            (36, SolvencyProveError),
+            (37, SolvencyVerificationError),
+            (38, ZKProofVerificationError),
+            (39, ZKProofBatchVerificationError),
+            (40, GroupSignatureTraceError),
+            (41, AssetTracingExtractionError),
+            (42, IdentityTracingExtractionError),
+            (43, AnonymousCredentialSignError),
+            (44, R1CSProofError),
+            (45, NoMemoInAssetTracerMemo),
+            (46, BogusAssetTracerMemo),
+            (47, MissingURSError),
+            (48, MissingSRSError),
+            (49, MissingVerifierParamsError),
+            (50, AbarToBarParamsError),
+            (
+                51,
+                CircuitTooLarge {
+                    needed: 0,
+                    available: 0,
+                },
+            ),
+            (
+                52,
+                LagrangeBaseMissing {
+                    size: 0,
+                    available: vec![],
+                },
+            ),
+        ];
+        for (code, err) in pinned.iter() {
+            assert_eq!(err.code(), *code);
+            assert_eq!(NoahError::from_code(*code).as_ref(), Some(err));
+        }
+        assert_eq!(NoahError::from_code(0), None);
+        assert_eq!(NoahError::from_code(pinned.len() as u32 + 1), None);
+
+        let err = CircuitTooLarge {
+            needed: 16384,
+            available: 8192,
+        };
+        assert_eq!(err.code(), 51);
+    }
+
+    #[test]
+    fn test_noah_error_display() {
+        let err = CircuitTooLarge {
+            needed: 16384,
+            available: 8192,
+        };
+        assert_eq!(
+            err.to_string(),
+            "The circuit needs 16384 constraints, but the SRS only supports 8192"
+        );
+
+        let err = LagrangeBaseMissing {
+            size: 32,
+            available: vec![8192, 16384],
+        };
+        assert_eq!(
+            err.to_string(),
+            "There is no Lagrange base for size 32, the available sizes are [8192, 16384]"
+        );
+
+        assert_eq!(ZKProofVerificationError.to_string(), "Invalid proof");
+    }
+}
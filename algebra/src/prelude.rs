@@ -4,7 +4,7 @@ pub use crate::fmt::Formatter;
 pub use crate::iter::Sum;
 pub use crate::ops::*;
 pub use crate::rand::{CryptoRng, Rng, RngCore, SeedableRng};
-pub use crate::rand_helper::test_rng;
+pub use crate::rand_helper::{test_rng, test_rng_seeded};
 pub use crate::serialization::*;
 pub use crate::traits::{Group, Scalar};
 pub use crate::utils::*;
@@ -12,6 +12,13 @@ fn test_rng_helper() -> ChaChaRng {
     ChaChaRng::from_seed(seed)
 }
 
+/// Should be used only for tests, not for any real world usage.
+/// Return a deterministic rng for the given seed, so that a test can be run over many seeds
+/// while each run stays reproducible.
+pub fn test_rng_seeded(seed: u64) -> impl RngCore + CryptoRng {
+    ChaChaRng::seed_from_u64(seed)
+}
+
 /// Should be used only for tests, not for any real world usage.
 #[cfg(not(feature = "std"))]
 pub fn test_rng() -> impl rand::Rng {
@@ -19,11 +26,24 @@ pub fn test_rng() -> impl rand::Rng {
 }
 
 /// Should be used only for tests, not for any real world usage.
+/// If the `TEST_RNG_SEED` environment variable is set to a `u64`, the rng of
+/// `test_rng_seeded` for this seed is returned, so tests can be re-run over many seeds.
 #[cfg(feature = "std")]
 pub fn test_rng() -> impl RngCore + CryptoRng {
+    let seed = std::env::var("TEST_RNG_SEED").ok();
     let is_deterministic =
         std::env::vars().any(|(key, val)| key == "DETERMINISTIC_TEST_RNG" && val == "1");
-    if is_deterministic {
+    test_rng_from_env(seed.as_deref(), is_deterministic)
+}
+
+/// Return the rng of `test_rng` for the given values of `TEST_RNG_SEED` and
+/// `DETERMINISTIC_TEST_RNG`: a seed that parses as a `u64` takes precedence,
+/// then the fixed seed if the rng is deterministic, then a seed from entropy.
+#[cfg(feature = "std")]
+fn test_rng_from_env(seed: Option<&str>, is_deterministic: bool) -> ChaChaRng {
+    if let Some(seed) = seed.and_then(|seed| seed.parse::<u64>().ok()) {
+        ChaChaRng::seed_from_u64(seed)
+    } else if is_deterministic {
         test_rng_helper()
     } else {
         ChaChaRng::from_entropy()
@@ -34,24 +54,54 @@ pub fn test_rng() -> impl RngCore + CryptoRng {
 mod test {
     use ark_std::UniformRand;
 
+    #[test]
+    fn test_seeded_rng() {
+        let mut rng = super::test_rng_seeded(7);
+        let a = u128::rand(&mut rng);
+
+        let mut rng = super::test_rng_seeded(7);
+        let b = u128::rand(&mut rng);
+        assert_eq!(a, b);
+
+        let mut rng = super::test_rng_seeded(8);
+        let c = u128::rand(&mut rng);
+        assert_ne!(a, c); // should be unequal with high probability.
+    }
+
     #[test]
     fn test_deterministic_rng() {
-        let mut rng = super::test_rng();
+        let mut rng = super::test_rng_from_env(None, false);
         let a = u128::rand(&mut rng);
 
         // Reset the rng by sampling a new one.
-        let mut rng = super::test_rng();
+        let mut rng = super::test_rng_from_env(None, false);
         let b = u128::rand(&mut rng);
         assert_ne!(a, b); // should be unequal with high probability.
 
         // Let's make the rng deterministic.
-        std::env::set_var("DETERMINISTIC_TEST_RNG", "1");
-        let mut rng = super::test_rng();
+        let mut rng = super::test_rng_from_env(None, true);
         let a = u128::rand(&mut rng);
 
         // Reset the rng by sampling a new one.
-        let mut rng = super::test_rng();
+        let mut rng = super::test_rng_from_env(None, true);
         let b = u128::rand(&mut rng);
-        assert_eq!(a, b); // should be equal with high probability.
+        assert_eq!(a, b);
+
+        // A seed that does not parse falls back to the fixed seed.
+        let mut rng = super::test_rng_from_env(Some("not a seed"), true);
+        assert_eq!(u128::rand(&mut rng), a);
+    }
+
+    #[test]
+    fn test_rng_seed_from_env() {
+        let mut rng = super::test_rng_from_env(Some("7"), false);
+        let a = u128::rand(&mut rng);
+
+        let mut rng = super::test_rng_seeded(7);
+        assert_eq!(u128::rand(&mut rng), a);
+
+        // The seed takes precedence over the fixed seed.
+        let mut rng = super::test_rng_from_env(Some("7"), true);
+        assert_eq!(u128::rand(&mut rng), a);
     }
 }
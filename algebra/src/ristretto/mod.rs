@@ -510,7 +510,9 @@ impl From<&PedersenCommitmentRistretto> for bulletproofs::PedersenGens {
 
 #[cfg(test)]
 mod ristretto_group_test {
-    use crate::traits::group_tests::{test_scalar_operations, test_scalar_serialization};
+    use crate::traits::group_tests::{
+        test_scalar_known_answer, test_scalar_operations, test_scalar_serialization,
+    };
 
     #[test]
     fn scalar_ops() {
@@ -520,6 +522,25 @@ mod ristretto_group_test {
     fn scalar_serialization() {
         test_scalar_serialization::<super::RistrettoScalar>();
     }
+
+    #[test]
+    fn scalar_known_answer() {
+        // 5^e mod r, for e the little-endian u64 limbs of
+        // SHA-256("noah ristretto scalar known answer"), computed with Python's `pow`.
+        let exponent = [
+            0x4279e26582ccd5a0,
+            0x01860b41b100c8f8,
+            0x03168af65a6e7e8c,
+            0x6fae01f257391436,
+        ];
+        let expected_bytes: [u8; 32] = [
+            0x1f, 0xac, 0x53, 0x0c, 0x07, 0xfe, 0x9d, 0x34, 0xfd, 0x8b, 0x06, 0xd2, 0x3d, 0x5b,
+            0x0d, 0xb4, 0x5a, 0xc7, 0x2c, 0x08, 0x01, 0xa2, 0xb9, 0x99, 0xff, 0x86, 0xec, 0xc7,
+            0xb6, 0x7f, 0xb2, 0x05,
+        ];
+        test_scalar_known_answer::<super::RistrettoScalar>(5, &exponent, &expected_bytes);
+    }
+
     #[test]
     fn scalar_to_radix() {
         crate::traits::group_tests::test_to_radix::<super::RistrettoScalar>();
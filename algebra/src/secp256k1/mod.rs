@@ -15,7 +15,9 @@ mod secp256k1_groups_test {
     use crate::{
         prelude::*,
         secp256k1::{SECP256K1Scalar, SECP256K1G1},
-        traits::group_tests::{test_scalar_operations, test_scalar_serialization},
+        traits::group_tests::{
+            test_scalar_known_answer, test_scalar_operations, test_scalar_serialization,
+        },
     };
     use ark_ec::CurveGroup;
     use ark_secp256k1::Affine;
@@ -30,6 +32,24 @@ mod secp256k1_groups_test {
         test_scalar_serialization::<SECP256K1Scalar>();
     }
 
+    #[test]
+    fn scalar_known_answer() {
+        // 5^e mod r, for e the little-endian u64 limbs of
+        // SHA-256("noah secp scalar known answer"), computed with Python's `pow`.
+        let exponent = [
+            0x7d44b87265f8b693,
+            0xc117b498e8e12304,
+            0x3e10359ee3278178,
+            0x6992e27884036001,
+        ];
+        let expected_bytes: [u8; 32] = [
+            0x24, 0x25, 0xc6, 0xda, 0x29, 0x0a, 0xd2, 0x3f, 0x18, 0x20, 0x75, 0xe2, 0x27, 0x38,
+            0x65, 0xbd, 0xe4, 0xbb, 0xf8, 0xc2, 0x6d, 0xac, 0x69, 0xd9, 0xe3, 0x83, 0x06, 0x90,
+            0x25, 0x1a, 0x57, 0xbe,
+        ];
+        test_scalar_known_answer::<SECP256K1Scalar>(5, &exponent, &expected_bytes);
+    }
+
     #[test]
     fn scalar_from_to_bytes() {
         let small_value = SECP256K1Scalar::from(165747u32);
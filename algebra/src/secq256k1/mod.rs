@@ -69,7 +69,9 @@ mod secq256k1_groups_test {
     use crate::{
         prelude::*,
         secq256k1::{SECQ256K1Scalar, SECQ256K1G1},
-        traits::group_tests::{test_scalar_operations, test_scalar_serialization},
+        traits::group_tests::{
+            test_scalar_known_answer, test_scalar_operations, test_scalar_serialization,
+        },
     };
     use ark_ec::CurveGroup;
     use ark_secq256k1::Affine;
@@ -84,6 +86,24 @@ mod secq256k1_groups_test {
         test_scalar_serialization::<SECQ256K1Scalar>();
     }
 
+    #[test]
+    fn scalar_known_answer() {
+        // 5^e mod r, for e the little-endian u64 limbs of
+        // SHA-256("noah secq scalar known answer"), computed with Python's `pow`.
+        let exponent = [
+            0x4e886d6b1db81bda,
+            0xc70eeb7a5d030618,
+            0xc27de22e5872f5b5,
+            0xb001537bc891ea77,
+        ];
+        let expected_bytes: [u8; 32] = [
+            0x61, 0x4c, 0x69, 0x56, 0x4b, 0x9d, 0xad, 0x9d, 0x36, 0xd0, 0x12, 0x86, 0x7a, 0x8d,
+            0x94, 0x12, 0xf7, 0x8b, 0x1b, 0xde, 0x6a, 0xeb, 0x6a, 0x5a, 0xf5, 0xab, 0xcb, 0x6b,
+            0x27, 0x25, 0x4d, 0xef,
+        ];
+        test_scalar_known_answer::<SECQ256K1Scalar>(5, &exponent, &expected_bytes);
+    }
+
     #[test]
     fn scalar_from_to_bytes() {
         let small_value = SECQ256K1Scalar::from(165747u32);
@@ -432,6 +432,17 @@ pub(crate) mod group_tests {
         assert_eq!(a, b);
     }
 
+    pub(crate) fn test_scalar_known_answer<S: Scalar>(
+        base: u32,
+        exponent: &[u64],
+        expected_le_bytes: &[u8],
+    ) {
+        let a = S::from(base).pow(exponent);
+        assert_eq!(a.to_bytes(), expected_le_bytes);
+        let b = S::from_bytes(expected_le_bytes).unwrap();
+        assert_eq!(a, b);
+    }
+
     pub(crate) fn test_to_radix<S: Scalar>() {
         let int = S::from(41u32);
         let w = 2;
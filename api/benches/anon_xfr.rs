@@ -26,6 +26,7 @@ use noah_accumulators::merkle_tree::{PersistentMerkleTree, Proof, TreePath};
 use noah_algebra::ristretto::PedersenCommitmentRistretto;
 use noah_algebra::{bls12_381::BLSScalar, prelude::*};
 use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+use noah_plonk::plonk::constraint_system::ConstraintSystem;
 use parking_lot::RwLock;
 use sha2::Sha512;
 use std::sync::Arc;
@@ -46,6 +47,19 @@ fn bench_abar_to_abar(c: &mut Criterion) {
     abar_to_abar(c, inputs, outputs, 13);
 }
 
+// Measurement of the time to compute the copy constraint permutation of the 6-in-6-out
+// `abar_to_abar` circuit.
+fn bench_compute_permutation(c: &mut Criterion) {
+    let params = ProverParams::gen_abar_to_abar(6, 6, SECP256K1).unwrap();
+
+    let mut group = c.benchmark_group("abar_to_abar");
+    group.sample_size(10);
+    group.bench_function("compute permutation of 6-in-6-out".to_string(), |b| {
+        b.iter(|| params.cs.compute_permutation())
+    });
+    group.finish();
+}
+
 // Measurement of the verification time and batch verification time of `abar_to_bar`.
 fn bench_abar_to_bar(c: &mut Criterion) {
     abar_to_bar(c);
@@ -69,6 +83,7 @@ fn bench_ar_to_abar(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_abar_to_abar,
+    bench_compute_permutation,
     bench_abar_to_bar,
     bench_abar_to_ar,
     bench_bar_to_abar,
@@ -134,6 +149,18 @@ fn abar_to_abar(
             assert!(verify_anon_xfr_note(&verifier_params, &note, &root, hash.clone()).is_ok())
         });
     });
+    #[cfg(feature = "bench-unsound")]
+    single_group.bench_function("verify without pairing".to_string(), |b| {
+        b.iter(|| {
+            assert!(verify_anon_xfr_note_unsound_bench(
+                &verifier_params,
+                &note,
+                &root,
+                hash.clone()
+            )
+            .is_ok())
+        });
+    });
     single_group.finish();
 
     #[cfg(feature = "parallel")]
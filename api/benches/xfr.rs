@@ -1,4 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
+use merlin::Transcript;
 use noah::parameters::bulletproofs::BulletproofParams;
 use noah::parameters::AddressFormat::SECP256K1;
 use noah::{
@@ -11,6 +12,13 @@ use noah::{
     },
 };
 use noah_algebra::prelude::*;
+use noah_algebra::ristretto::{PedersenCommitmentRistretto, RistrettoPoint, RistrettoScalar};
+use noah_algebra::traits::PedersenCommitment;
+use noah_crypto::basic::elgamal::{elgamal_encrypt, elgamal_key_gen};
+use noah_crypto::basic::pedersen_elgamal::{
+    pedersen_elgamal_aggregate_eq_proof, pedersen_elgamal_batch_verify,
+    PedersenElGamalProofInstance,
+};
 use rand::{CryptoRng, RngCore};
 
 const BATCHSIZE: [usize; 7] = [1, 2, 3, 6, 10, 20, 30];
@@ -83,6 +91,63 @@ fn bench_nonconfidential_multi_asset(c: &mut Criterion) {
     }
 }
 
+// Measurement of the batch verification time of the Pedersen-ElGamal equality proofs of asset
+// tracing, for many traced transfers.
+fn bench_pedersen_elgamal_batch_verify(c: &mut Criterion) {
+    let mut prng = test_rng();
+    let pc_gens = PedersenCommitmentRistretto::default();
+    let n_instances = 100;
+
+    let keys: Vec<_> = (0..n_instances)
+        .map(|_| elgamal_key_gen::<_, RistrettoPoint>(&mut prng).1)
+        .collect();
+    let statements: Vec<_> = keys
+        .iter()
+        .map(|pk| {
+            let m = [RistrettoScalar::random(&mut prng)];
+            let r = [RistrettoScalar::random(&mut prng)];
+            let ctexts = vec![elgamal_encrypt(&m[0], &r[0], pk)];
+            let commitments = vec![pc_gens.commit(m[0], r[0])];
+            let proof = pedersen_elgamal_aggregate_eq_proof(
+                &mut Transcript::new(b"bench"),
+                &mut prng,
+                &m,
+                &r,
+                pk,
+                &ctexts,
+                &commitments,
+            );
+            (ctexts, commitments, proof)
+        })
+        .collect();
+    let instances: Vec<_> = keys
+        .iter()
+        .zip(statements.iter())
+        .map(
+            |(public_key, (cts, commitments, proof))| PedersenElGamalProofInstance {
+                public_key,
+                cts: cts.clone(),
+                commitments: commitments.clone(),
+                proof,
+            },
+        )
+        .collect();
+
+    let mut group = c.benchmark_group("pedersen_elgamal");
+    group.sample_size(10);
+    group.bench_function(format!("batch verify of {}", n_instances), |b| {
+        b.iter(|| {
+            assert!(pedersen_elgamal_batch_verify(
+                &mut Transcript::new(b"bench"),
+                &mut prng,
+                &instances
+            )
+            .is_ok())
+        });
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_nonconfidential_single_asset,
@@ -90,7 +155,8 @@ criterion_group!(
     bench_nonconfidential_amount_confidential_asset_type_single_asset,
     bench_confidential_single_asset,
     bench_confidential_multi_asset,
-    bench_nonconfidential_multi_asset
+    bench_nonconfidential_multi_asset,
+    bench_pedersen_elgamal_batch_verify
 );
 criterion_main!(benches);
 
@@ -158,6 +224,7 @@ fn verify_single_asset_transfer(
         b.iter(|| {
             assert!(gen_xfr_note(
                 &mut prng,
+                params,
                 inputs_record.as_slice(),
                 outputs_record.as_slice(),
                 &inkeys_ref,
@@ -168,6 +235,7 @@ fn verify_single_asset_transfer(
 
     let xfr_note = gen_xfr_note(
         &mut prng,
+        params,
         inputs_record.as_slice(),
         outputs_record.as_slice(),
         &inkeys_ref,
@@ -243,6 +311,7 @@ fn batch_verify_single_asset_transfer(
 
     let xfr_note = gen_xfr_note(
         &mut prng,
+        params,
         inputs_record.as_slice(),
         outputs_record.as_slice(),
         &inkeys_ref,
@@ -342,6 +411,7 @@ fn verify_multi_asset_transfer(c: &mut Criterion, asset_record_type: AssetRecord
         b.iter(|| {
             assert!(gen_xfr_note(
                 &mut prng,
+                &params,
                 inputs_record.as_slice(),
                 outputs_record.as_slice(),
                 &inkeys_ref,
@@ -352,6 +422,7 @@ fn verify_multi_asset_transfer(c: &mut Criterion, asset_record_type: AssetRecord
 
     let xfr_note = gen_xfr_note(
         &mut prng,
+        &params,
         inputs_record.as_slice(),
         outputs_record.as_slice(),
         &inkeys_ref,
@@ -440,6 +511,7 @@ fn batch_verify_multi_asset_transfer(
 
     let xfr_note = gen_xfr_note(
         &mut prng,
+        &params,
         inputs_record.as_slice(),
         outputs_record.as_slice(),
         &inkeys_ref,
@@ -0,0 +1,119 @@
+//! An end-to-end anonymous transfer with one input and two outputs.
+//!
+//! The input record is inserted into an in-memory Merkle tree, a transfer note
+//! is built against the tree root, and the note is verified. Sizes and timings
+//! of each step are printed.
+//!
+//! Run with `cargo run --release --example anon_transfer`.
+
+use digest::Digest;
+use noah::prelude::{
+    finish_anon_xfr_note, init_anon_xfr_note, verify_anon_xfr_note, AddressFormat::SECP256K1,
+    AnonAssetRecord, AssetType, KeyPair, MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecord,
+    OpenAnonAssetRecordBuilder, ProverParams, VerifierParams, FEE_TYPE,
+};
+use noah_accumulators::merkle_tree::{EphemeralMerkleTree, Proof, TreePath};
+use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+use rand_chacha::ChaChaRng;
+use sha2::Sha512;
+use std::time::Instant;
+
+const FEE: u32 = 9;
+
+fn build_oabar<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    amount: u64,
+    asset_type: AssetType,
+    keypair: &KeyPair,
+) -> Result<OpenAnonAssetRecord> {
+    OpenAnonAssetRecordBuilder::new()
+        .amount(amount)
+        .asset_type(asset_type)
+        .pub_key(&keypair.get_pk())
+        .finalize(prng)
+        .c(d!())?
+        .build()
+        .c(d!())
+}
+
+fn build_mt_leaf_info_from_proof(proof: Proof, uid: u64) -> MTLeafInfo {
+    MTLeafInfo {
+        path: MTPath {
+            nodes: proof
+                .nodes
+                .iter()
+                .map(|e| MTNode {
+                    left: e.left,
+                    mid: e.mid,
+                    right: e.right,
+                    is_left_child: (e.path == TreePath::Left) as u8,
+                    is_mid_child: (e.path == TreePath::Middle) as u8,
+                    is_right_child: (e.path == TreePath::Right) as u8,
+                })
+                .collect(),
+        },
+        root: proof.root,
+        root_version: proof.root_version,
+        uid,
+    }
+}
+
+fn random_hasher<R: CryptoRng + RngCore>(prng: &mut R) -> Sha512 {
+    let mut hasher = Sha512::new();
+    let mut random_bytes = [0u8; 32];
+    prng.fill_bytes(&mut random_bytes);
+    hasher.update(&random_bytes);
+    hasher
+}
+
+fn main() -> Result<()> {
+    let mut prng = ChaChaRng::from_seed([0u8; 32]);
+
+    let sender = KeyPair::sample(&mut prng, SECP256K1);
+    let receivers = [
+        KeyPair::sample(&mut prng, SECP256K1),
+        KeyPair::sample(&mut prng, SECP256K1),
+    ];
+
+    let start = Instant::now();
+    let params = ProverParams::gen_abar_to_abar(1, 2, SECP256K1).c(d!())?;
+    let verifier_params = VerifierParams::load_abar_to_abar(1, 2, SECP256K1).c(d!())?;
+    println!("load parameters:    {:?}", start.elapsed());
+
+    // The input record, inserted into the tree.
+    let mut input = build_oabar(&mut prng, 40 + FEE as u64, FEE_TYPE, &sender)?;
+    let abar = AnonAssetRecord::from_oabar(&input);
+
+    let mut mt = EphemeralMerkleTree::new().c(d!())?;
+    let uid = mt.entry_count();
+    let leaf = AnemoiJive381::eval_variable_length_hash(&[BLSScalar::from(uid), abar.commitment]);
+    mt.add_commitment_hash(leaf).c(d!())?;
+    let root = mt.get_root().c(d!())?;
+    let proof = mt.generate_proof(uid).c(d!())?;
+    input.update_mt_leaf_info(build_mt_leaf_info_from_proof(proof, uid));
+
+    let outputs = [
+        build_oabar(&mut prng, 15, FEE_TYPE, &receivers[0])?,
+        build_oabar(&mut prng, 25, FEE_TYPE, &receivers[1])?,
+    ];
+
+    let start = Instant::now();
+    let pre_note = init_anon_xfr_note(&[input], &outputs, FEE, &sender).c(d!())?;
+    let hash = random_hasher(&mut prng);
+    let note = finish_anon_xfr_note(&mut prng, &params, pre_note, hash.clone()).c(d!())?;
+    println!("prove:              {:?}", start.elapsed());
+
+    let start = Instant::now();
+    verify_anon_xfr_note(&verifier_params, &note, &root, hash).c(d!())?;
+    println!("verify:             {:?}", start.elapsed());
+
+    let note_size = bincode::serialize(&note).c(d!())?.len();
+    let proof_size = bincode::serialize(&note.proof).c(d!())?.len();
+    let compressed_proof_size = note.proof.to_compressed_bytes().c(d!())?.len();
+    println!("note size:          {} bytes", note_size);
+    println!("plonk proof size:   {} bytes", proof_size);
+    println!("compressed proof:   {} bytes", compressed_proof_size);
+
+    Ok(())
+}
@@ -7,7 +7,7 @@ use noah_algebra::{
 };
 use noah_crypto::{
     anon_creds::{Attribute, CommOutput},
-    basic::elgamal::elgamal_key_gen,
+    basic::elgamal::{elgamal_key_gen, elgamal_partial_decrypt},
 };
 
 type G1 = BLSG1;
@@ -40,6 +40,8 @@ pub type ACRevealProof = noah_crypto::anon_creds::CredentialCommOpenProof<G2, S>
 pub type ACConfidentialRevealProof = noah_crypto::confidential_anon_creds::CACPoK<G1, G2, S>;
 /// The attribute types.
 pub type Attr = u32;
+/// The baby-step giant-step table that recovers decrypted attributes below its bound.
+pub type AttributeRecoveryTable = noah_crypto::basic::elgamal::BsgsTable<G1>;
 
 /// Generate e key pair for a credential issuer.
 /// # Example
@@ -414,6 +416,28 @@ pub fn ac_confidential_open_commitment<R: CryptoRng + RngCore>(
     .c(d!())
 }
 
+/// Produce a confidential reveal proof that discloses, encrypted under `enc_key`, exactly the
+/// attributes at `reveal_indices`, with the empty message used by identity tracing in transfers.
+/// The returned ciphertexts are the ones to put in the tracer memo, and the proof verifies in
+/// `verify_identity_proofs` under a tracing policy whose reveal map selects the same indices.
+/// Returns Err(NoahError::ParameterError) if an index is out of range.
+pub fn ac_confidential_reveal<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    usk: &ACUserSecretKey,
+    credential: &Credential,
+    rand: &ACCommitmentKey,
+    enc_key: &AttributeEncKey,
+    reveal_indices: &[usize],
+) -> Result<ConfidentialAC> {
+    let mut reveal_map = vec![false; credential.attrs.len()];
+    for index in reveal_indices.iter() {
+        *reveal_map
+            .get_mut(*index)
+            .c(d!(NoahError::ParameterError))? = true;
+    }
+    ac_confidential_open_commitment(prng, usk, credential, rand, enc_key, &reveal_map, &[]).c(d!())
+}
+
 /// Verify a confidential anonymous credential reveal proof.
 pub fn ac_confidential_verify(
     issuer_pk: &ACIssuerPublicKey,
@@ -436,6 +460,95 @@ pub fn ac_confidential_verify(
     .c(d!())
 }
 
+/// Verify a confidential anonymous credential reveal proof, and return the `(index, value)`
+/// pairs of the attributes revealed according to `reveal_map`. The revealed attributes are
+/// decrypted with `dec_key` and recovered with `attr_table`, in time about the square root
+/// of its bound. Build the table once and reuse it across proofs.
+/// Returns Err(NoahError::IdentityTracingExtractionError) if a revealed attribute is not
+/// below the bound of the table.
+/// # Example
+/// ```
+/// use noah::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_commit};
+/// use noah::anon_creds::{ac_confidential_open_commitment, ac_confidential_verify_and_extract, ac_confidential_gen_encryption_keys, AttributeRecoveryTable};
+/// use rand_chacha::ChaChaRng;
+/// use rand_core::SeedableRng;
+/// use noah::anon_creds::Credential;
+/// let mut prng = ChaChaRng::from_seed([0u8;32]);
+/// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, 3);
+/// let (user_sk, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
+/// let (dec_key, enc_key) = ac_confidential_gen_encryption_keys::<ChaChaRng>(&mut prng);
+/// let attrs = vec![10, 20, 30];
+/// let bitmap = [true, false, true];
+/// let ac_sig = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, &attrs[..]).unwrap();
+/// let credential = Credential {
+///   sig: ac_sig,
+///   attrs: attrs,
+///   ipk: issuer_pk.clone(),
+/// };
+/// let (sig_commitment,_,key) = ac_commit::<ChaChaRng>(&mut prng, &user_sk, &credential, b"Address").unwrap();
+/// let conf_reveal_proof = ac_confidential_open_commitment::<ChaChaRng>(&mut prng, &user_sk, &credential, &key.unwrap(), &enc_key, &bitmap[..], b"Some Message").unwrap();
+/// let attr_table = AttributeRecoveryTable::new(1 << 16).unwrap();
+/// let revealed = ac_confidential_verify_and_extract(&issuer_pk, &enc_key, &dec_key, &bitmap[..], &sig_commitment, &conf_reveal_proof.cts, &conf_reveal_proof.pok, b"Some Message", &attr_table).unwrap();
+/// assert_eq!(revealed, vec![(0, 10), (2, 30)]);
+/// let small_table = AttributeRecoveryTable::new(20).unwrap();
+/// assert!(ac_confidential_verify_and_extract(&issuer_pk, &enc_key, &dec_key, &bitmap[..], &sig_commitment, &conf_reveal_proof.cts, &conf_reveal_proof.pok, b"Some Message", &small_table).is_err());
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn ac_confidential_verify_and_extract(
+    issuer_pk: &ACIssuerPublicKey,
+    enc_key: &AttributeEncKey,
+    dec_key: &AttributeDecKey,
+    reveal_map: &[bool],
+    sig_commitment: &ACCommitment,
+    attr_ctext: &[AttributeCiphertext],
+    cac_proof: &ACConfidentialRevealProof,
+    msg: &[u8],
+    attr_table: &AttributeRecoveryTable,
+) -> Result<Vec<(usize, Attr)>> {
+    ac_confidential_verify(
+        issuer_pk,
+        enc_key,
+        reveal_map,
+        sig_commitment,
+        attr_ctext,
+        cac_proof,
+        msg,
+    )
+    .c(d!())?;
+
+    // the verification checks that there is one ciphertext per revealed attribute
+    reveal_map
+        .iter()
+        .enumerate()
+        .filter(|(_, revealed)| **revealed)
+        .zip(attr_ctext.iter())
+        .map(|((index, _), ctext)| {
+            let elem = elgamal_partial_decrypt(ctext, dec_key);
+            attr_table
+                .recover(&elem)
+                .and_then(|attr| Attr::try_from(attr).ok())
+                .map(|attr| (index, attr))
+                .c(d!(NoahError::IdentityTracingExtractionError))
+        })
+        .collect()
+}
+
+/// An instance of confidential anonymous credential reveal proof verification.
+pub type ACVerifyInstance<'a> =
+    noah_crypto::confidential_anon_creds::CACVerifyInstance<'a, BLSPairingEngine>;
+
+/// Batch verify confidential anonymous credential reveal proofs, combining the pairing checks
+/// of all the instances with random weights. Fails if any of the instances is invalid.
+pub fn ac_confidential_batch_verify<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    instances: &[ACVerifyInstance<'_>],
+) -> Result<()> {
+    noah_crypto::confidential_anon_creds::confidential_batch_verify_open::<R, BLSPairingEngine>(
+        prng, instances,
+    )
+    .c(d!())
+}
+
 /// Generate encryptiion key for confidential anonymous credentials.
 pub fn ac_confidential_gen_encryption_keys<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -11,32 +11,43 @@ use crate::anon_xfr::{
     compute_merkle_root_variables, nullify, nullify_in_cs,
     structs::{
         AccElemVars, AnonAssetRecord, AxfrOwnerMemo, Commitment, MTNode, MTPath, Nullifier,
-        OpenAnonAssetRecord, PayeeWitness, PayeeWitnessVars, PayerWitness, PayerWitnessVars,
+        OpenAnonAssetRecord, OpenAnonAssetRecordBuilder, PayeeWitness, PayeeWitnessVars,
+        PayerWitness, PayerWitnessVars,
     },
-    AXfrAddressFoldingInstance, AXfrAddressFoldingWitness, AXfrPlonkPf, TurboPlonkCS, AMOUNT_LEN,
-    FEE_TYPE, MAX_AXFR_MEMO_SIZE, TREE_DEPTH,
+    AXfrAddressFoldingInstance, AXfrAddressFoldingWitness, AXfrPlonkPf, AddressFoldingKind,
+    TurboPlonkCS, AMOUNT_LEN, FEE_TYPE, MAX_AXFR_MEMO_SIZE, TREE_DEPTH,
 };
 use crate::errors::NoahError;
 use crate::keys::{KeyPair, PublicKey, PublicKeyInner, SecretKey};
 use crate::parameters::params::ProverParams;
 use crate::parameters::params::{AddressFormat, VerifierParams};
 use crate::parameters::{
-    MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_RECEIVER, MAX_ANONYMOUS_RECORD_NUMBER_ONE_INPUT,
-    MAX_ANONYMOUS_RECORD_NUMBER_STANDARD,
+    is_supported_anon_xfr_shape, MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_RECEIVER,
+    MAX_ANONYMOUS_RECORD_NUMBER_ONE_INPUT, MAX_ANONYMOUS_RECORD_NUMBER_STANDARD,
 };
 use digest::{consts::U64, Digest};
 use merlin::Transcript;
-use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+use noah_algebra::{
+    bls12_381::{BLSScalar, BLSG1},
+    prelude::*,
+};
 use noah_crypto::basic::anemoi_jive::{
     AnemoiJive, AnemoiJive381, AnemoiVLHTrace, ANEMOI_JIVE_381_SALTS,
 };
 use noah_plonk::plonk::{
-    constraint_system::{TurboCS, VarIndex},
+    constraint_system::{turbo::N_SELECTORS, ConstraintSystem, TurboCS, VarIndex},
     prover::prover_with_lagrange,
-    verifier::verifier,
+    verifier::{prepare_verifier, verifier},
 };
+use noah_plonk::poly_commit::kzg_poly_com::KZGCommitment;
+#[cfg(feature = "parallel")]
+use noah_plonk::poly_commit::{pcs::PolyComScheme, transcript::PolyComTranscript};
+use rand_chacha::ChaChaRng;
 #[cfg(feature = "parallel")]
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::ThreadPool;
+use sha2::Sha512;
 
 /// The domain separator for anonymous transfer, for the Plonk proof.
 const ANON_XFR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"Anon Xfr Plonk Proof";
@@ -46,18 +57,149 @@ const ANON_XFR_FOLDING_PROOF_TRANSCRIPT: &[u8] = b"Anon Xfr Folding Proof";
 const N_INPUTS_TRANSCRIPT: &[u8] = b"Number of input ABARs";
 /// The domain separator for the number of outputs.
 const N_OUTPUTS_TRANSCRIPT: &[u8] = b"Number of output ABARs";
+/// The domain separator for the batch verification of anonymous transfer notes.
+#[cfg(feature = "parallel")]
+const ANON_XFR_BATCH_VERIFICATION_TRANSCRIPT: &[u8] = b"Anon Xfr Batch Verification";
+/// The domain separator for the owner memo of a fee output.
+const FEE_OUTPUT_MEMO_DOMAIN: &[u8] = b"Anon Xfr Fee Output Memo";
+/// The domain separator for the anchor hash of a note.
+const ANCHOR_HASH_DOMAIN: &[u8] = b"Anon Xfr Anchor Hash";
+/// The number of bytes packed into each scalar of the anchor hash, below the capacity of BLSScalar.
+const ANCHOR_HASH_BYTES_PER_SCALAR: usize = 31;
 
 /// Anonymous transfer note.
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq)]
+///
+/// The note is serialized with the same versioned layout in every format: its version, and
+/// then its fields, including the explicit `folding_kind`. Notes serialized before the version
+/// was added only decode in self-describing formats such as JSON, and take the kind of their
+/// folding instance.
+#[derive(Debug, PartialEq, Clone, Eq)]
 pub struct AXfrNote {
     /// The anonymous transfer body.
     pub body: AXfrBody,
     /// The Plonk proof (assuming non-malleability).
     pub proof: AXfrPlonkPf,
+    /// The kind of address folding, which selects the verification of `folding_instance`.
+    pub folding_kind: AddressFoldingKind,
     /// The address folding instance.
     pub folding_instance: AXfrAddressFoldingInstance,
 }
 
+/// The version of the serialized layout of the notes.
+const AXFR_NOTE_VERSION: u32 = 1;
+
+/// The serialized layout of a note.
+#[derive(Serialize)]
+struct AXfrNoteRef<'a> {
+    version: u32,
+    body: &'a AXfrBody,
+    proof: &'a AXfrPlonkPf,
+    folding_kind: Option<AddressFoldingKind>,
+    folding_instance: &'a AXfrAddressFoldingInstance,
+}
+
+/// The serialized layout of a note. The notes serialized before the version was added have
+/// no version, and may have no kind.
+#[derive(Deserialize)]
+struct AXfrNoteVersioned {
+    #[serde(default)]
+    version: u32,
+    body: AXfrBody,
+    proof: AXfrPlonkPf,
+    #[serde(default)]
+    folding_kind: Option<AddressFoldingKind>,
+    folding_instance: AXfrAddressFoldingInstance,
+}
+
+impl Serialize for AXfrNote {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        AXfrNoteRef {
+            version: AXFR_NOTE_VERSION,
+            body: &self.body,
+            proof: &self.proof,
+            folding_kind: Some(self.folding_kind),
+            folding_instance: &self.folding_instance,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AXfrNote {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let note = AXfrNoteVersioned::deserialize(deserializer)?;
+        let folding_kind = match (note.version, note.folding_kind) {
+            (0, kind) => kind.unwrap_or_else(|| note.folding_instance.kind()),
+            (AXFR_NOTE_VERSION, Some(kind)) => kind,
+            (AXFR_NOTE_VERSION, None) => {
+                return Err(D::Error::custom("the note has no address folding kind"))
+            }
+            (version, _) => {
+                return Err(D::Error::custom(format!(
+                    "unsupported version {} of the note",
+                    version
+                )))
+            }
+        };
+        Ok(AXfrNote {
+            body: note.body,
+            proof: note.proof,
+            folding_kind,
+            folding_instance: note.folding_instance,
+        })
+    }
+}
+
+impl AXfrNote {
+    /// Return the nullifiers of the anonymous asset records spent by this note.
+    pub fn nullifiers(&self) -> &[Nullifier] {
+        &self.body.inputs
+    }
+
+    /// Return an Anemoi hash of the entire note, for chains that anchor one digest per
+    /// transaction. Unlike the signed bytes of the body, it also covers the proof and the
+    /// address folding instance.
+    ///
+    /// The hash absorbs, in order: the nullifiers, the output commitments, the Merkle root and
+    /// its version, the fee, and then the serialized owner memos, Plonk proof,
+    /// address folding kind and folding instance packed into scalars. Every variable-length part
+    /// is prefixed by its length, so that distinct notes give distinct inputs to the hash.
+    pub fn anchor_hash(&self) -> BLSScalar {
+        let mut elems = vec![BLSScalar::from_bytes(ANCHOR_HASH_DOMAIN).unwrap()];
+
+        elems.push(BLSScalar::from(self.body.inputs.len() as u64));
+        elems.extend_from_slice(&self.body.inputs);
+        elems.push(BLSScalar::from(self.body.outputs.len() as u64));
+        elems.extend(self.body.outputs.iter().map(|output| output.commitment));
+        elems.push(self.body.merkle_root);
+        elems.push(BLSScalar::from(self.body.merkle_root_version));
+        elems.push(BLSScalar::from(self.body.fee));
+
+        // The serialization of these in-memory structures cannot fail.
+        let bytes = bincode::serialize(&(
+            &self.body.owner_memos,
+            &self.proof,
+            &self.folding_kind,
+            &self.folding_instance,
+        ))
+        .unwrap();
+        elems.push(BLSScalar::from(bytes.len() as u64));
+        elems.extend(
+            bytes
+                .chunks(ANCHOR_HASH_BYTES_PER_SCALAR)
+                .map(|chunk| BLSScalar::from_bytes(chunk).unwrap()),
+        );
+
+        AnemoiJive381::eval_variable_length_hash(&elems)
+    }
+}
+
 /// Anonymous transfer pre-note without proofs and signatures.
 #[derive(Debug, Clone)]
 pub struct AXfrPreNote {
@@ -73,6 +215,8 @@ pub struct AXfrPreNote {
     pub nullifiers_traces: Vec<AnemoiVLHTrace<BLSScalar, 2, 12>>,
     /// Input key pair.
     pub input_keypair: KeyPair,
+    /// The hash of a public predicate that the transfer is bound to, interpreted by the chain.
+    pub predicate_hash: Option<BLSScalar>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq)]
@@ -99,12 +243,49 @@ pub fn init_anon_xfr_note(
     fee: u32,
     input_keypair: &KeyPair,
 ) -> Result<AXfrPreNote> {
+    build_anon_xfr_pre_note(inputs, outputs, fee, None, input_keypair)
+}
+
+/// Build an anonymous transfer note that pays its fee to `fee_recipient`, without generating
+/// the proof.
+///
+/// Instead of being burned, the fee is paid to an extra output of `FEE_TYPE`, owned by the
+/// given public key and committed with the given blinding factor, appended after `outputs`.
+/// This output is part of the balance constraint like any other output. The public `fee` of
+/// the body is still the fee paid, and the note must be verified with
+/// `verify_anon_xfr_note_with_fee_recipient`, which checks the fee output.
+pub fn init_anon_xfr_note_with_fee_recipient(
+    inputs: &[OpenAnonAssetRecord],
+    outputs: &[OpenAnonAssetRecord],
+    fee: u32,
+    fee_recipient: (PublicKey, BLSScalar),
+    input_keypair: &KeyPair,
+) -> Result<AXfrPreNote> {
+    build_anon_xfr_pre_note(inputs, outputs, fee, Some(fee_recipient), input_keypair)
+}
+
+fn build_anon_xfr_pre_note(
+    inputs: &[OpenAnonAssetRecord],
+    outputs: &[OpenAnonAssetRecord],
+    fee: u32,
+    fee_recipient: Option<(PublicKey, BLSScalar)>,
+    input_keypair: &KeyPair,
+) -> Result<AXfrPreNote> {
+    // The fee burned by the proof, which is zero if the fee is paid to an output.
+    let mut outputs = outputs.to_vec();
+    let mut burned_fee = fee;
+    if let Some((pub_key, blind)) = fee_recipient {
+        outputs.push(build_fee_output(&pub_key, blind, fee).c(d!())?);
+        burned_fee = 0;
+    }
+    let outputs = outputs.as_slice();
+
     // 1. check input correctness
     if inputs.is_empty() || outputs.is_empty() {
         return Err(eg!(NoahError::AXfrProverParamsError));
     }
     check_inputs(inputs, input_keypair).c(d!())?;
-    check_asset_amount(inputs, outputs, fee).c(d!())?;
+    check_asset_amount(inputs, outputs, burned_fee).c(d!())?;
     check_roots(inputs).c(d!())?;
 
     // 2. build input witness information
@@ -165,7 +346,7 @@ pub fn init_anon_xfr_note(
     let secret_inputs = AXfrWitness {
         payers_witnesses: payers_secrets,
         payees_witnesses: payees_secrets,
-        fee,
+        fee: burned_fee,
     };
     let out_abars = outputs
         .iter()
@@ -208,9 +389,60 @@ pub fn init_anon_xfr_note(
         output_commitments_traces,
         nullifiers_traces,
         input_keypair: input_keypair.clone(),
+        predicate_hash: None,
     })
 }
 
+/// Build an anonymous transfer note bound to a public predicate, without generating the proof.
+///
+/// The predicate is only identified by `predicate_hash`, which the chain interprets, e.g., as a
+/// timelock, and is not evaluated by the proof. The hash is an extra public input of the proof,
+/// so the note must be proven with `ProverParams::gen_abar_to_abar_with_predicate` and verified
+/// with `verify_anon_xfr_note_with_predicate`. It is not part of the note itself, whose encoding
+/// is the one of the transfers without a predicate: the chain carries it along with the note.
+pub fn init_anon_xfr_note_with_predicate(
+    inputs: &[OpenAnonAssetRecord],
+    outputs: &[OpenAnonAssetRecord],
+    fee: u32,
+    input_keypair: &KeyPair,
+    predicate_hash: BLSScalar,
+) -> Result<AXfrPreNote> {
+    let mut pre_note = init_anon_xfr_note(inputs, outputs, fee, input_keypair).c(d!())?;
+    pre_note.predicate_hash = Some(predicate_hash);
+    Ok(pre_note)
+}
+
+/// Build the output that pays `fee` to `pub_key`.
+/// The randomness of the owner memo is derived from the output, so that the note only depends
+/// on the inputs of `init_anon_xfr_note_with_fee_recipient`.
+fn build_fee_output(
+    pub_key: &PublicKey,
+    blind: BLSScalar,
+    fee: u32,
+) -> Result<OpenAnonAssetRecord> {
+    let amount = fee as u64;
+    let mut msg = vec![];
+    msg.extend_from_slice(&amount.to_le_bytes());
+    msg.extend_from_slice(&FEE_TYPE.0);
+    msg.extend_from_slice(&blind.to_bytes());
+
+    let mut hasher = Sha512::new();
+    hasher.update(FEE_OUTPUT_MEMO_DOMAIN);
+    hasher.update(pub_key.noah_to_bytes());
+    hasher.update(&msg);
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&hasher.finalize()[..32]);
+    let mut prng = ChaChaRng::from_seed(seed);
+
+    let mut builder = OpenAnonAssetRecordBuilder::new()
+        .pub_key(pub_key)
+        .amount(amount)
+        .asset_type(FEE_TYPE);
+    builder.oabar.blind = blind;
+    builder.oabar.owner_memo = Some(AxfrOwnerMemo::new(&mut prng, pub_key, &msg).c(d!())?);
+    builder.build().c(d!())
+}
+
 /// Build an anonymous transfer note without generating the proof.
 pub fn finish_anon_xfr_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64> + Default>(
     prng: &mut R,
@@ -225,6 +457,7 @@ pub fn finish_anon_xfr_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64>
         output_commitments_traces,
         nullifiers_traces,
         input_keypair,
+        predicate_hash,
     } = pre_note;
 
     let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
@@ -256,12 +489,14 @@ pub fn finish_anon_xfr_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64>
         &input_commitments_traces,
         &output_commitments_traces,
         &folding_witness,
+        predicate_hash.as_ref(),
     )
     .c(d!())?;
 
     Ok(AXfrNote {
         body,
         proof,
+        folding_kind: folding_instance.kind(),
         folding_instance,
     })
 }
@@ -273,6 +508,77 @@ pub fn verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default>(
     merkle_root: &BLSScalar,
     hash: D,
 ) -> Result<()> {
+    let (pub_inputs, address_folding_public_input) =
+        anon_xfr_note_public_inputs(params, note, merkle_root, None, hash).c(d!())?;
+
+    verify_xfr(
+        params,
+        &pub_inputs,
+        &note.proof,
+        &address_folding_public_input,
+        None,
+    )
+    .c(d!(NoahError::AXfrVerificationError))
+}
+
+/// Verify an anonymous transfer note as `verify_anon_xfr_note`, but skip the final pairing
+/// check of the Plonk proof.
+///
+/// NEVER use this function in production: a note that passes it may be invalid. It only exists
+/// to measure whether the processing of the public inputs and commitments, or the pairing check,
+/// dominates the verification time, and is only compiled with the `bench-unsound` feature.
+#[cfg(feature = "bench-unsound")]
+pub fn verify_anon_xfr_note_unsound_bench<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AXfrNote,
+    merkle_root: &BLSScalar,
+    hash: D,
+) -> Result<()> {
+    let (pub_inputs, address_folding_public_input) =
+        anon_xfr_note_public_inputs(params, note, merkle_root, None, hash).c(d!())?;
+
+    prepare_verify_xfr(
+        params,
+        &pub_inputs,
+        &note.proof,
+        &address_folding_public_input,
+    )
+    .map(|_| ())
+    .c(d!(NoahError::AXfrVerificationError))
+}
+
+/// Check an anonymous transfer note that pays its fee to `fee_recipient`, if any, up to the
+/// final check of its Plonk proof, and return the public inputs of the proof and the inputs of
+/// that check, e.g., to aggregate it with other proofs.
+pub(crate) fn prepare_verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AXfrNote,
+    merkle_root: &BLSScalar,
+    fee_recipient: Option<&(PublicKey, BLSScalar)>,
+    hash: D,
+) -> Result<(Vec<BLSScalar>, (KZGCommitment<BLSG1>, KZGCommitment<BLSG1>))> {
+    let (pub_inputs, address_folding_public_input) =
+        anon_xfr_note_public_inputs(params, note, merkle_root, fee_recipient, hash).c(d!())?;
+
+    prepare_verify_xfr(
+        params,
+        &pub_inputs,
+        &note.proof,
+        &address_folding_public_input,
+    )
+    .c(d!(NoahError::AXfrVerificationError))
+}
+
+/// Check an anonymous transfer note other than its Plonk proof, including the memos, the fee
+/// output if the fee is paid to `fee_recipient`, and the address folding, and return the public
+/// inputs of the Plonk proof.
+fn anon_xfr_note_public_inputs<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AXfrNote,
+    merkle_root: &BLSScalar,
+    fee_recipient: Option<&(PublicKey, BLSScalar)>,
+    hash: D,
+) -> Result<(AXfrPubInputs, Vec<BLSScalar>)> {
     if *merkle_root != note.body.merkle_root {
         return Err(eg!(NoahError::AXfrVerificationError));
     }
@@ -306,36 +612,208 @@ pub fn verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default>(
         .iter()
         .map(|output| output.commitment)
         .collect();
+
+    // A fee paid to a recipient is not burned, but must be the last output.
+    let burned_fee = match fee_recipient {
+        None => note.body.fee,
+        Some((pub_key, blind)) => {
+            let (fee_commitment, _) =
+                commit(pub_key, *blind, note.body.fee as u64, FEE_TYPE.as_scalar()).c(d!())?;
+            if note.body.outputs.last().map(|output| output.commitment) != Some(fee_commitment) {
+                return Err(eg!(NoahError::AXfrVerificationError));
+            }
+            0
+        }
+    };
+
     let pub_inputs = AXfrPubInputs {
         payers_inputs: note.body.inputs.clone(),
         payees_commitments,
         merkle_root: *merkle_root,
-        fee: note.body.fee,
+        fee: burned_fee,
     };
 
+    let address_folding_public_input = verify_note_address_folding(params, note, hash).c(d!())?;
+
+    Ok((pub_inputs, address_folding_public_input))
+}
+
+/// Verify the address folding of a note for the curve given by its `folding_kind`, which must
+/// match both the folding instance and the verifier parameters, and return the public inputs
+/// of the Plonk proof for the address folding.
+fn verify_note_address_folding<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AXfrNote,
+    hash: D,
+) -> Result<Vec<BLSScalar>> {
+    let kind = note.folding_kind;
+    if params.address_folding != Some(kind) {
+        return Err(eg!(format!(
+            "the note uses {:?} address folding, but the verifier parameters {} are for {:?}",
+            kind, params.label, params.address_folding
+        )));
+    }
+
     let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
 
-    let address_folding_public_input = match &note.folding_instance {
-        AXfrAddressFoldingInstance::Secp256k1(a) => {
+    match (kind, &note.folding_instance) {
+        (AddressFoldingKind::Secp256k1, AXfrAddressFoldingInstance::Secp256k1(a)) => {
             let (beta, lambda) = verify_address_folding_secp256k1(hash, &mut transcript, a)?;
-            prepare_verifier_input_secp256k1(&a, &beta, &lambda)
+            Ok(prepare_verifier_input_secp256k1(&a, &beta, &lambda))
         }
-        AXfrAddressFoldingInstance::Ed25519(a) => {
+        (AddressFoldingKind::Ed25519, AXfrAddressFoldingInstance::Ed25519(a)) => {
             let (beta, lambda) = verify_address_folding_ed25519(hash, &mut transcript, a)?;
-            prepare_verifier_input_ed25519(&a, &beta, &lambda)
+            Ok(prepare_verifier_input_ed25519(&a, &beta, &lambda))
         }
-    };
+        (kind, instance) => Err(eg!(format!(
+            "the note uses {:?} address folding, but carries a {:?} folding instance",
+            kind,
+            instance.kind()
+        ))),
+    }
+}
+
+/// Verify an anonymous transfer note, and that it is bound to the predicate `predicate_hash`.
+///
+/// The verifier parameters must be the ones of `VerifierParams::get_abar_to_abar_with_predicate`.
+pub fn verify_anon_xfr_note_with_predicate<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AXfrNote,
+    merkle_root: &BLSScalar,
+    predicate_hash: &BLSScalar,
+    hash: D,
+) -> Result<()> {
+    let (pub_inputs, address_folding_public_input) =
+        anon_xfr_note_public_inputs(params, note, merkle_root, None, hash).c(d!())?;
 
     verify_xfr(
         params,
         &pub_inputs,
         &note.proof,
         &address_folding_public_input,
+        Some(predicate_hash),
     )
     .c(d!(NoahError::AXfrVerificationError))
 }
 
+/// Verify an anonymous transfer note that pays its fee to `fee_recipient`, i.e., the public key
+/// and the blinding factor given to `init_anon_xfr_note_with_fee_recipient`.
+///
+/// Besides the proof, it checks that the last output of the note pays the public `fee` of the
+/// body to the recipient.
+pub fn verify_anon_xfr_note_with_fee_recipient<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AXfrNote,
+    merkle_root: &BLSScalar,
+    fee_recipient: &(PublicKey, BLSScalar),
+    hash: D,
+) -> Result<()> {
+    let (pub_inputs, address_folding_public_input) =
+        anon_xfr_note_public_inputs(params, note, merkle_root, Some(fee_recipient), hash)
+            .c(d!())?;
+
+    verify_xfr(
+        params,
+        &pub_inputs,
+        &note.proof,
+        &address_folding_public_input,
+        None,
+    )
+    .c(d!(NoahError::AXfrVerificationError))
+}
+
+/// Check that the public fee of an anonymous transfer note is `expected_fee`.
+///
+/// The fee is a public input of the proof, so this cheap check can reject a note whose
+/// declared fee is not the expected one before the proof is verified. It does not verify
+/// the proof: the fee is only bound to the proof once `verify_anon_xfr_note` succeeds.
+pub fn verify_fee_binding(note: &AXfrNote, expected_fee: u32) -> Result<()> {
+    if note.body.fee != expected_fee {
+        return Err(eg!(NoahError::AXfrVerificationError));
+    }
+    Ok(())
+}
+
+/// Verify an anonymous transfer note against a set of acceptable Merkle roots, e.g., the roots
+/// of a recent window. The proof is verified once, against the root of the note.
+/// A note that pays its fee to a recipient is verified as by
+/// `verify_anon_xfr_note_with_fee_recipient` if `fee_recipient` is given, and fails otherwise.
+/// Return the index of the root of the note in `merkle_roots`.
+pub fn verify_anon_xfr_note_multi_root<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AXfrNote,
+    merkle_roots: &[BLSScalar],
+    fee_recipient: Option<&(PublicKey, BLSScalar)>,
+    hash: D,
+) -> Result<usize> {
+    let index = merkle_roots
+        .iter()
+        .position(|root| *root == note.body.merkle_root)
+        .c(d!(NoahError::AXfrVerificationError))?;
+    match fee_recipient {
+        None => verify_anon_xfr_note(params, note, &merkle_roots[index], hash).c(d!())?,
+        Some(fee_recipient) => verify_anon_xfr_note_with_fee_recipient(
+            params,
+            note,
+            &merkle_roots[index],
+            fee_recipient,
+            hash,
+        )
+        .c(d!())?,
+    }
+    Ok(index)
+}
+
+/// The dominant operation counts of verifying anonymous transfer notes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VerifyCost {
+    /// The number of pairings.
+    pub n_pairings: usize,
+    /// The number of terms of the multi-scalar multiplications in BLS12-381 G1.
+    pub n_msm_terms: usize,
+}
+
+/// Estimate the cost of batch verifying `n_notes` anonymous transfer notes with `n_in` inputs
+/// and `n_out` outputs each, e.g., to choose the size of a batch.
+///
+/// The final check of each Plonk proof is prepared with scalar multiplications that do not
+/// depend on the shape: the number of inputs and outputs only changes the number of public
+/// inputs, whose evaluation uses field operations. The prepared checks are then combined into
+/// two multi-scalar multiplications with one term per note and a single product of two
+/// pairings, so the number of pairings does not grow with the batch. The verification of the
+/// address folding, a fixed cost per note on another curve, is not counted.
+pub fn estimate_batch_verify_cost(n_notes: usize, n_in: usize, n_out: usize) -> Result<VerifyCost> {
+    if !is_supported_anon_xfr_shape(n_in, n_out) {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    if n_notes == 0 {
+        return Ok(VerifyCost::default());
+    }
+
+    let n_wires = TurboCS::<BLSScalar>::n_wires_per_gate();
+    // The commitment of the linearization polynomial: the selectors, z, the last permutation,
+    // the boolean selector, two round key selectors, and the split t polynomials.
+    let r_terms = N_SELECTORS + 5 + n_wires;
+    // The opening at zeta: the witness polynomials, the other permutations, two round key
+    // selectors, and the linearization polynomial.
+    let zeta_terms = n_wires + (n_wires - 1) + 3;
+    // The opening at zeta * omega: z and the first three witness polynomials.
+    let zeta_omega_terms = 4;
+    // Combining the two openings into the two group elements of the final check.
+    let combine_terms = 5;
+    let prepare_terms = r_terms + zeta_terms + zeta_omega_terms + combine_terms;
+
+    Ok(VerifyCost {
+        n_pairings: 2,
+        n_msm_terms: n_notes * (prepare_terms + 2),
+    })
+}
+
 /// Batch verify the anonymous transfer notes.
+/// The final checks of the Plonk proofs are combined into one, with scalars derived from a
+/// transcript of the notes.
+/// A note that pays its fee to a recipient fails this verification, and must be verified with
+/// `batch_verify_anon_xfr_note_with_fee_recipients`.
 /// Note: this function assumes that the correctness of the Merkle roots has been checked outside.
 #[cfg(feature = "parallel")]
 pub fn batch_verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default + Sync + Send>(
@@ -344,86 +822,173 @@ pub fn batch_verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default + Sync +
     merkle_roots: &[&BLSScalar],
     hashes: Vec<D>,
 ) -> Result<()> {
-    if merkle_roots
-        .par_iter()
-        .zip(notes)
-        .any(|(x, y)| **x != y.body.merkle_root)
-    {
-        return Err(eg!(NoahError::AXfrVerificationError));
+    batch_verify_anon_xfr_note_with_fee_recipients(
+        params,
+        notes,
+        merkle_roots,
+        &vec![None; notes.len()],
+        hashes,
+    )
+}
+
+/// Batch verify the anonymous transfer notes as `batch_verify_anon_xfr_note`, where the note
+/// `i` pays its fee to `fee_recipients[i]` if it is given, as checked by
+/// `verify_anon_xfr_note_with_fee_recipient`, and burns it otherwise.
+/// Note: this function assumes that the correctness of the Merkle roots has been checked outside.
+#[cfg(feature = "parallel")]
+pub fn batch_verify_anon_xfr_note_with_fee_recipients<
+    D: Digest<OutputSize = U64> + Default + Sync + Send,
+>(
+    params: &[&VerifierParams],
+    notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    fee_recipients: &[Option<&(PublicKey, BLSScalar)>],
+    hashes: Vec<D>,
+) -> Result<()> {
+    let prepared =
+        prepare_batch_verify_anon_xfr_note(params, notes, merkle_roots, fee_recipients, hashes)
+            .c(d!())?;
+    if prepared.is_empty() {
+        return Ok(());
     }
 
-    // Check the memo size.
-    for note in notes.iter() {
-        let max_memo_len = if note.body.inputs.len() == 1 {
-            MAX_ANONYMOUS_RECORD_NUMBER_ONE_INPUT
-        } else if note.body.inputs.len() > 1
-            && note.body.inputs.len() <= MAX_ANONYMOUS_RECORD_NUMBER_STANDARD
-        {
-            MAX_ANONYMOUS_RECORD_NUMBER_STANDARD
-        } else {
-            MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_RECEIVER
-        };
+    let mut transcript = Transcript::new(ANON_XFR_BATCH_VERIFICATION_TRANSCRIPT);
+    for (left, right) in prepared.iter() {
+        transcript.append_commitment(left);
+        transcript.append_commitment(right);
+    }
+    let rhos: Vec<BLSScalar> = prepared
+        .iter()
+        .map(|_| transcript.get_challenge_field_elem(b"rho"))
+        .collect();
 
-        if note.body.owner_memos.len() != note.body.outputs.len()
-            || note.body.owner_memos.len() > max_memo_len
-        {
-            return Err(eg!(NoahError::AXfrVerificationError));
-        }
+    params[0]
+        .shrunk_vk
+        .verify_prepared(&prepared, &rhos)
+        .c(d!(NoahError::AXfrVerificationError))
+}
 
-        for memo in note.body.owner_memos.iter() {
-            if memo.size() > MAX_AXFR_MEMO_SIZE {
-                return Err(eg!(NoahError::AXfrVerificationError));
-            }
-        }
+/// Batch verify the anonymous transfer notes as `batch_verify_anon_xfr_note`, in the thread pool
+/// `pool` instead of the global one, to bound the number of threads used by the verification.
+#[cfg(feature = "parallel")]
+pub fn batch_verify_anon_xfr_note_in_pool<D: Digest<OutputSize = U64> + Default + Sync + Send>(
+    pool: &ThreadPool,
+    params: &[&VerifierParams],
+    notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+) -> Result<()> {
+    pool.install(|| batch_verify_anon_xfr_note(params, notes, merkle_roots, hashes))
+}
+
+/// Batch verify the anonymous transfer notes, combining the final checks of the Plonk proofs
+/// with the scalars `rhos`, one per note, instead of deriving them.
+///
+/// This lets a consensus protocol agree on the scalars, e.g., derived from the block hash, so
+/// that every validator performs the identical batch check. The scalars must be
+/// unpredictable to the creators of the notes, or a batch with invalid notes can pass.
+/// Note: this function assumes that the correctness of the Merkle roots has been checked outside.
+#[cfg(feature = "parallel")]
+pub fn batch_verify_anon_xfr_note_with_randomness<
+    D: Digest<OutputSize = U64> + Default + Sync + Send,
+>(
+    params: &[&VerifierParams],
+    notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+    rhos: &[BLSScalar],
+) -> Result<()> {
+    if rhos.len() != notes.len() {
+        return Err(eg!(NoahError::ParameterError));
     }
 
-    let is_ok = params
+    let prepared = prepare_batch_verify_anon_xfr_note(
+        params,
+        notes,
+        merkle_roots,
+        &vec![None; notes.len()],
+        hashes,
+    )
+    .c(d!())?;
+    if prepared.is_empty() {
+        return Ok(());
+    }
+
+    params[0]
+        .shrunk_vk
+        .verify_prepared(&prepared, rhos)
+        .c(d!(NoahError::AXfrVerificationError))
+}
+
+/// Check the anonymous transfer notes up to the final checks of their Plonk proofs, and
+/// return the inputs of those checks.
+#[cfg(feature = "parallel")]
+fn prepare_batch_verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default + Sync + Send>(
+    params: &[&VerifierParams],
+    notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    fee_recipients: &[Option<&(PublicKey, BLSScalar)>],
+    hashes: Vec<D>,
+) -> Result<Vec<(KZGCommitment<BLSG1>, KZGCommitment<BLSG1>)>> {
+    if params.len() != notes.len()
+        || merkle_roots.len() != notes.len()
+        || fee_recipients.len() != notes.len()
+        || hashes.len() != notes.len()
+    {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    // The shrunk polynomial commitment scheme is common to all the verifier parameters, so the
+    // final checks can be combined even if the notes have different shapes.
+    params
         .par_iter()
         .zip(notes)
         .zip(merkle_roots)
+        .zip(fee_recipients)
         .zip(hashes)
-        .map(|(((param, note), merkle_root), hash)| {
-            let payees_commitments = note
-                .body
-                .outputs
-                .iter()
-                .map(|output| output.commitment)
-                .collect();
-            let pub_inputs = AXfrPubInputs {
-                payers_inputs: note.body.inputs.clone(),
-                payees_commitments,
-                merkle_root: **merkle_root,
-                fee: note.body.fee,
-            };
-
-            let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
-
-            let address_folding_public_input = match &note.folding_instance {
-                AXfrAddressFoldingInstance::Secp256k1(a) => {
-                    let (beta, lambda) =
-                        verify_address_folding_secp256k1(hash, &mut transcript, a)?;
-                    prepare_verifier_input_secp256k1(&a, &beta, &lambda)
-                }
-                AXfrAddressFoldingInstance::Ed25519(a) => {
-                    let (beta, lambda) = verify_address_folding_ed25519(hash, &mut transcript, a)?;
-                    prepare_verifier_input_ed25519(&a, &beta, &lambda)
-                }
-            };
-
-            verify_xfr(
-                *param,
-                &pub_inputs,
-                &note.proof,
-                &address_folding_public_input,
-            )
+        .map(|((((param, note), merkle_root), fee_recipient), hash)| {
+            prepare_verify_anon_xfr_note(*param, note, merkle_root, *fee_recipient, hash)
+                .ok()
+                .map(|(_, prepared)| prepared)
         })
-        .all(|x| x.is_ok());
+        .collect::<Option<Vec<_>>>()
+        .c(d!(NoahError::AXfrVerificationError))
+}
 
-    if is_ok {
-        Ok(())
-    } else {
-        Err(eg!(NoahError::AXfrVerificationError))
+/// Batch verify the anonymous transfer notes, reporting `(verified, total)` to `progress`
+/// as the notes are verified, for long-running jobs over large batches.
+/// The notes are verified in chunks of the size of the thread pool, so that each chunk is still
+/// verified in parallel.
+/// Note: this function assumes that the correctness of the Merkle roots has been checked outside.
+#[cfg(feature = "parallel")]
+pub fn batch_verify_anon_xfr_note_with_progress<
+    D: Digest<OutputSize = U64> + Default + Sync + Send,
+>(
+    params: &[&VerifierParams],
+    notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<()> {
+    let total = notes.len();
+    if params.len() != total || merkle_roots.len() != total || hashes.len() != total {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    let chunk_size = core::cmp::max(rayon::current_num_threads(), 1);
+    let mut hashes = hashes.into_iter();
+    let mut verified = 0;
+    for ((params, notes), merkle_roots) in params
+        .chunks(chunk_size)
+        .zip(notes.chunks(chunk_size))
+        .zip(merkle_roots.chunks(chunk_size))
+    {
+        let hashes = hashes.by_ref().take(notes.len()).collect();
+        batch_verify_anon_xfr_note(params, notes, merkle_roots, hashes).c(d!())?;
+        verified += notes.len();
+        progress(verified, total);
     }
+    Ok(())
 }
 
 /// Generate a Plonk proof for anonymous transfer.
@@ -435,7 +1000,12 @@ pub(crate) fn prove_xfr<R: CryptoRng + RngCore>(
     input_commitments_traces: &[AnemoiVLHTrace<BLSScalar, 2, 12>],
     output_commitments_traces: &[AnemoiVLHTrace<BLSScalar, 2, 12>],
     folding_witness: &AXfrAddressFoldingWitness,
+    predicate_hash: Option<&BLSScalar>,
 ) -> Result<AXfrPlonkPf> {
+    debug_assert!(
+        !folding_witness.is_uninitialized_for_verifier(),
+        "the address folding witness is a placeholder and cannot be used for proving"
+    );
     let mut transcript = Transcript::new(ANON_XFR_PLONK_PROOF_TRANSCRIPT);
     transcript.append_u64(
         N_INPUTS_TRANSCRIPT,
@@ -454,7 +1024,13 @@ pub(crate) fn prove_xfr<R: CryptoRng + RngCore>(
         input_commitments_traces,
         output_commitments_traces,
         &folding_witness,
+        predicate_hash.copied(),
     );
+    // The parameters of the transfers with and without a predicate differ by the public input
+    // for the predicate hash.
+    if cs.public_vars_witness_indices.len() != params.cs.public_vars_witness_indices.len() {
+        return Err(eg!(NoahError::ParameterError));
+    }
     let witness = cs.get_and_clear_witness();
 
     prover_with_lagrange(
@@ -475,6 +1051,7 @@ pub(crate) fn verify_xfr(
     pub_inputs: &AXfrPubInputs,
     proof: &AXfrPlonkPf,
     address_folding_public_input: &Vec<BLSScalar>,
+    predicate_hash: Option<&BLSScalar>,
 ) -> Result<()> {
     let mut transcript = Transcript::new(ANON_XFR_PLONK_PROOF_TRANSCRIPT);
     transcript.append_u64(N_INPUTS_TRANSCRIPT, pub_inputs.payers_inputs.len() as u64);
@@ -485,6 +1062,7 @@ pub(crate) fn verify_xfr(
 
     let mut online_inputs = pub_inputs.to_vec();
     online_inputs.extend_from_slice(address_folding_public_input);
+    online_inputs.extend(predicate_hash);
 
     verifier(
         &mut transcript,
@@ -497,6 +1075,36 @@ pub(crate) fn verify_xfr(
     .c(d!(NoahError::ZKProofVerificationError))
 }
 
+/// Verify a Plonk proof for anonymous transfer up to its final check, and return its public
+/// inputs and the inputs of that check.
+fn prepare_verify_xfr(
+    params: &VerifierParams,
+    pub_inputs: &AXfrPubInputs,
+    proof: &AXfrPlonkPf,
+    address_folding_public_input: &Vec<BLSScalar>,
+) -> Result<(Vec<BLSScalar>, (KZGCommitment<BLSG1>, KZGCommitment<BLSG1>))> {
+    let mut transcript = Transcript::new(ANON_XFR_PLONK_PROOF_TRANSCRIPT);
+    transcript.append_u64(N_INPUTS_TRANSCRIPT, pub_inputs.payers_inputs.len() as u64);
+    transcript.append_u64(
+        N_OUTPUTS_TRANSCRIPT,
+        pub_inputs.payees_commitments.len() as u64,
+    );
+
+    let mut online_inputs = pub_inputs.to_vec();
+    online_inputs.extend_from_slice(address_folding_public_input);
+
+    let prepared = prepare_verifier(
+        &mut transcript,
+        &params.shrunk_vk,
+        &params.shrunk_cs,
+        &params.verifier_params,
+        &online_inputs,
+        proof,
+    )
+    .c(d!(NoahError::ZKProofVerificationError))?;
+    Ok((online_inputs, prepared))
+}
+
 /// The witness of an anonymous transfer.
 #[derive(Debug, Clone)]
 pub struct AXfrWitness {
@@ -634,6 +1242,7 @@ pub(crate) fn build_multi_xfr_cs(
     input_commitments_traces: &[AnemoiVLHTrace<BLSScalar, 2, 12>],
     output_commitments_traces: &[AnemoiVLHTrace<BLSScalar, 2, 12>],
     folding_witness: &AXfrAddressFoldingWitness,
+    predicate_hash: Option<BLSScalar>,
 ) -> (TurboPlonkCS, usize) {
     assert_ne!(witness.payers_witnesses.len(), 0);
     assert_ne!(witness.payees_witnesses.len(), 0);
@@ -813,6 +1422,12 @@ pub(crate) fn build_multi_xfr_cs(
         .unwrap(),
     }
 
+    // prepare the public input for the predicate hash, if any.
+    if let Some(predicate_hash) = predicate_hash {
+        let predicate_hash_var = cs.new_variable(predicate_hash);
+        cs.prepare_pi_variable(predicate_hash_var);
+    }
+
     if inputs.len() == 1 {
         asset_summing(&mut cs, &inputs, &outputs, fee_type, fee_var);
     } else {
@@ -1097,7 +1712,9 @@ pub(crate) fn add_payees_witnesses(
 
 #[cfg(test)]
 mod tests {
-    use crate::anon_xfr::abar_to_abar::ANON_XFR_FOLDING_PROOF_TRANSCRIPT;
+    use crate::anon_xfr::abar_to_abar::{
+        estimate_batch_verify_cost, VerifyCost, ANON_XFR_FOLDING_PROOF_TRANSCRIPT,
+    };
     use crate::anon_xfr::address_folding_secp256k1::{
         create_address_folding_secp256k1, prepare_verifier_input_secp256k1,
         verify_address_folding_secp256k1,
@@ -1110,15 +1727,31 @@ mod tests {
         AXfrAddressFoldingWitness,
     };
     use crate::keys::KeyPair;
+    use crate::parameters::params::VerifierParams;
     use crate::parameters::AddressFormat::SECP256K1;
     use digest::Digest;
     use merlin::Transcript;
-    use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+    use noah_algebra::{
+        bls12_381::{BLSScalar, BLSG1},
+        prelude::*,
+    };
     use noah_crypto::basic::anemoi_jive::{
         AnemoiJive, AnemoiJive381, AnemoiVLHTrace, ANEMOI_JIVE_381_SALTS,
     };
-    use noah_plonk::plonk::constraint_system::{TurboCS, VarIndex};
+    use noah_plonk::{
+        plonk::{
+            constraint_system::{ConstraintSystem, TurboCS, VarIndex},
+            indexer::{PlonkProof, PlonkVerifierParams},
+            verifier::prepare_verifier,
+        },
+        poly_commit::{
+            field_polynomial::FpPolynomial,
+            kzg_poly_com::{KZGCommitment, KZGCommitmentSchemeBLS},
+            pcs::{HomomorphicPolyComElem, PolyComScheme, ToBytes},
+        },
+    };
     use sha2::Sha512;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     fn new_multi_xfr_witness_for_test(
         inputs: Vec<(u64, BLSScalar)>,
@@ -1230,6 +1863,230 @@ mod tests {
         )
     }
 
+    static N_MSM_TERMS: AtomicUsize = AtomicUsize::new(0);
+    static N_PAIRINGS: AtomicUsize = AtomicUsize::new(0);
+
+    /// A KZG commitment that counts its scalar multiplications.
+    #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+    struct CountedCommitment(KZGCommitment<BLSG1>);
+
+    impl ToBytes for CountedCommitment {
+        fn to_bytes(&self) -> Vec<u8> {
+            self.0.to_bytes()
+        }
+    }
+
+    impl HomomorphicPolyComElem for CountedCommitment {
+        type Scalar = BLSScalar;
+
+        fn get_base() -> Self {
+            CountedCommitment(KZGCommitment::get_base())
+        }
+
+        fn get_identity() -> Self {
+            CountedCommitment(KZGCommitment::get_identity())
+        }
+
+        fn add(&self, other: &Self) -> Self {
+            CountedCommitment(self.0.add(&other.0))
+        }
+
+        fn add_assign(&mut self, other: &Self) {
+            self.0.add_assign(&other.0)
+        }
+
+        fn sub(&self, other: &Self) -> Self {
+            CountedCommitment(self.0.sub(&other.0))
+        }
+
+        fn sub_assign(&mut self, other: &Self) {
+            self.0.sub_assign(&other.0)
+        }
+
+        fn mul(&self, scalar: &BLSScalar) -> Self {
+            N_MSM_TERMS.fetch_add(1, Ordering::SeqCst);
+            CountedCommitment(self.0.mul(scalar))
+        }
+
+        fn mul_assign(&mut self, scalar: &BLSScalar) {
+            N_MSM_TERMS.fetch_add(1, Ordering::SeqCst);
+            self.0.mul_assign(scalar)
+        }
+    }
+
+    /// The KZG commitment scheme, for the verifier only, which forwards the final check to
+    /// `KZGCommitmentSchemeBLS` and counts its scalar multiplications and pairings.
+    struct CountingPCS(KZGCommitmentSchemeBLS);
+
+    impl PolyComScheme for CountingPCS {
+        type Field = BLSScalar;
+        type Commitment = CountedCommitment;
+
+        fn max_degree(&self) -> usize {
+            self.0.max_degree()
+        }
+
+        fn commit(&self, _: &FpPolynomial<BLSScalar>) -> Result<CountedCommitment> {
+            unimplemented!()
+        }
+
+        fn eval(&self, _: &FpPolynomial<BLSScalar>, _: &BLSScalar) -> BLSScalar {
+            unimplemented!()
+        }
+
+        fn prove(
+            &self,
+            _: &FpPolynomial<BLSScalar>,
+            _: &BLSScalar,
+            _: usize,
+        ) -> Result<CountedCommitment> {
+            unimplemented!()
+        }
+
+        fn verify(
+            &self,
+            _: &CountedCommitment,
+            _: usize,
+            _: &BLSScalar,
+            _: &BLSScalar,
+            _: &CountedCommitment,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+
+        fn apply_blind_factors(
+            &self,
+            _: &CountedCommitment,
+            _: &[BLSScalar],
+            _: usize,
+        ) -> CountedCommitment {
+            unimplemented!()
+        }
+
+        fn batch_verify_diff_points(
+            &self,
+            _: &mut Transcript,
+            _: &[CountedCommitment],
+            _: usize,
+            _: &[BLSScalar],
+            _: &[BLSScalar],
+            _: &[CountedCommitment],
+            _: &BLSScalar,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+
+        fn prepare_batch_verify_diff_points(
+            &self,
+            transcript: &mut Transcript,
+            cm_vec: &[CountedCommitment],
+            degree: usize,
+            point_vec: &[BLSScalar],
+            eval_vec: &[BLSScalar],
+            proofs: &[CountedCommitment],
+            challenge: &BLSScalar,
+        ) -> (CountedCommitment, CountedCommitment) {
+            // one term for the first proof, three for each other one, and one for the values
+            N_MSM_TERMS.fetch_add(3 * proofs.len() - 1, Ordering::SeqCst);
+
+            let cm_vec = cm_vec.iter().map(|cm| cm.0.clone()).collect::<Vec<_>>();
+            let proofs = proofs.iter().map(|pf| pf.0.clone()).collect::<Vec<_>>();
+            let (left, right) = self.0.prepare_batch_verify_diff_points(
+                transcript, &cm_vec, degree, point_vec, eval_vec, &proofs, challenge,
+            );
+            (CountedCommitment(left), CountedCommitment(right))
+        }
+
+        fn verify_prepared(
+            &self,
+            prepared: &[(CountedCommitment, CountedCommitment)],
+            rhos: &[BLSScalar],
+        ) -> Result<()> {
+            // two multi-scalar multiplications with one term per check, and two pairings
+            N_MSM_TERMS.fetch_add(2 * prepared.len(), Ordering::SeqCst);
+            N_PAIRINGS.fetch_add(2, Ordering::SeqCst);
+
+            let prepared = prepared
+                .iter()
+                .map(|(l, r)| (l.0.clone(), r.0.clone()))
+                .collect::<Vec<_>>();
+            self.0.verify_prepared(&prepared, rhos)
+        }
+
+        fn shrink_to_verifier_only(&self) -> Self {
+            CountingPCS(self.0.shrink_to_verifier_only())
+        }
+    }
+
+    #[test]
+    fn test_estimate_batch_verify_cost() {
+        let mut prng = test_rng();
+        let n_wires = TurboCS::<BLSScalar>::n_wires_per_gate();
+        let mut random_scalars = |n: usize| {
+            (0..n)
+                .map(|_| BLSScalar::random(&mut prng))
+                .collect::<Vec<_>>()
+        };
+
+        for (n_in, n_out) in [(1, 1), (2, 3), (3, 3)] {
+            let params = VerifierParams::load_abar_to_abar(n_in, n_out, SECP256K1).unwrap();
+            let pcs = CountingPCS(params.shrunk_vk.shrink_to_verifier_only());
+            let vk: PlonkVerifierParams<CountedCommitment, BLSScalar> =
+                bincode::deserialize(&bincode::serialize(&params.verifier_params).unwrap())
+                    .unwrap();
+
+            // The operations of the verifier do not depend on the values of the proof and of
+            // the public inputs, so the proofs are not valid.
+            let proof = PlonkProof {
+                cm_w_vec: vec![CountedCommitment::get_base(); n_wires],
+                cm_t_vec: vec![CountedCommitment::get_base(); n_wires],
+                cm_z: CountedCommitment::get_base(),
+                prk_3_poly_eval_zeta: random_scalars(1)[0],
+                prk_4_poly_eval_zeta: random_scalars(1)[0],
+                w_polys_eval_zeta: random_scalars(n_wires),
+                w_polys_eval_zeta_omega: random_scalars(3),
+                z_eval_zeta_omega: random_scalars(1)[0],
+                s_polys_eval_zeta: random_scalars(n_wires - 1),
+                opening_witness_zeta: CountedCommitment::get_base(),
+                opening_witness_zeta_omega: CountedCommitment::get_base(),
+                lookup: None,
+            };
+
+            for n_notes in [1, 10] {
+                N_MSM_TERMS.store(0, Ordering::SeqCst);
+                N_PAIRINGS.store(0, Ordering::SeqCst);
+
+                let prepared = (0..n_notes)
+                    .map(|_| {
+                        let pi = random_scalars(vk.public_vars_constraint_indices.len());
+                        prepare_verifier(
+                            &mut Transcript::new(b"Verify cost"),
+                            &pcs,
+                            &params.shrunk_cs,
+                            &vk,
+                            &pi,
+                            &proof,
+                        )
+                        .unwrap()
+                    })
+                    .collect::<Vec<_>>();
+                let rhos = random_scalars(n_notes);
+                assert!(pcs.verify_prepared(&prepared, &rhos).is_err());
+
+                let cost = estimate_batch_verify_cost(n_notes, n_in, n_out).unwrap();
+                assert_eq!(cost.n_pairings, N_PAIRINGS.load(Ordering::SeqCst));
+                assert_eq!(cost.n_msm_terms, N_MSM_TERMS.load(Ordering::SeqCst));
+            }
+        }
+
+        assert_eq!(
+            estimate_batch_verify_cost(0, 1, 1).unwrap(),
+            VerifyCost::default()
+        );
+        assert!(estimate_batch_verify_cost(1, 0, 1).is_err());
+        assert!(estimate_batch_verify_cost(1, 100, 100).is_err());
+    }
+
     #[test]
     fn test_asset_mixing() {
         // Fee type
@@ -2227,6 +3084,7 @@ mod tests {
             &input_commitments_traces,
             &output_commitments_traces,
             &AXfrAddressFoldingWitness::Secp256k1(folding_witness),
+            None,
         );
         let witness = cs.get_and_clear_witness();
 
@@ -23,17 +23,26 @@ use crate::xfr::{
 };
 use digest::{consts::U64, Digest};
 use merlin::Transcript;
-use noah_algebra::{bls12_381::BLSScalar, prelude::*, ristretto::PedersenCommitmentRistretto};
+use noah_algebra::{
+    bls12_381::{BLSScalar, BLSG1},
+    prelude::*,
+    ristretto::PedersenCommitmentRistretto,
+};
 use noah_crypto::basic::anemoi_jive::{
     AnemoiJive, AnemoiJive381, AnemoiVLHTrace, ANEMOI_JIVE_381_SALTS,
 };
-use noah_plonk::plonk::{
-    constraint_system::{TurboCS, VarIndex},
-    prover::prover_with_lagrange,
-    verifier::verifier,
+use noah_plonk::{
+    plonk::{
+        constraint_system::{TurboCS, VarIndex},
+        prover::prover_with_lagrange,
+        verifier::{prepare_verifier, verifier},
+    },
+    poly_commit::kzg_poly_com::KZGCommitment,
 };
 #[cfg(feature = "parallel")]
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::ThreadPool;
 
 /// The domain separator for anonymous-to-transparent, for the Plonk proof.
 const ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"ABAR to AR Plonk Proof";
@@ -52,6 +61,13 @@ pub struct AbarToArNote {
     pub folding_instance: AXfrAddressFoldingInstance,
 }
 
+impl AbarToArNote {
+    /// Return the nullifiers of the anonymous asset records spent by this note.
+    pub fn nullifiers(&self) -> &[Nullifier] {
+        core::slice::from_ref(&self.body.input)
+    }
+}
+
 /// The anonymous-to-transparent note without proof.
 #[derive(Clone, Debug)]
 pub struct AbarToArPreNote {
@@ -209,6 +225,51 @@ pub fn verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default>(
     merkle_root: &BLSScalar,
     hash: D,
 ) -> Result<()> {
+    let online_inputs = abar_to_ar_online_inputs(note, merkle_root, hash).c(d!())?;
+
+    let mut transcript = Transcript::new(ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT);
+    verifier(
+        &mut transcript,
+        &params.shrunk_vk,
+        &params.shrunk_cs,
+        &params.verifier_params,
+        &online_inputs,
+        &note.proof,
+    )
+    .c(d!(NoahError::AXfrVerificationError))
+}
+
+/// Check an anonymous-to-transparent note up to the final check of its Plonk proof, and return
+/// the public inputs of the proof together with the inputs of that check.
+pub(crate) fn prepare_verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AbarToArNote,
+    merkle_root: &BLSScalar,
+    hash: D,
+) -> Result<(Vec<BLSScalar>, (KZGCommitment<BLSG1>, KZGCommitment<BLSG1>))> {
+    let online_inputs = abar_to_ar_online_inputs(note, merkle_root, hash).c(d!())?;
+
+    let mut transcript = Transcript::new(ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT);
+    let prepared = prepare_verifier(
+        &mut transcript,
+        &params.shrunk_vk,
+        &params.shrunk_cs,
+        &params.verifier_params,
+        &online_inputs,
+        &note.proof,
+    )
+    .c(d!(NoahError::AXfrVerificationError))?;
+
+    Ok((online_inputs, prepared))
+}
+
+/// Check the parts of an anonymous-to-transparent note other than its Plonk proof, including
+/// the address folding, and return the public inputs of the Plonk proof.
+fn abar_to_ar_online_inputs<D: Digest<OutputSize = U64> + Default>(
+    note: &AbarToArNote,
+    merkle_root: &BLSScalar,
+    hash: D,
+) -> Result<Vec<BLSScalar>> {
     // require the output amount & asset type are non-confidential
     if note.body.output.amount.is_confidential() || note.body.output.asset_type.is_confidential() {
         return Err(eg!(NoahError::ParameterError));
@@ -239,7 +300,6 @@ pub fn verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default>(
         return Err(eg!(NoahError::AXfrVerificationError));
     }
 
-    let mut transcript = Transcript::new(ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT);
     let mut online_inputs = vec![];
     online_inputs.push(note.body.input.clone());
     online_inputs.push(merkle_root.clone());
@@ -247,15 +307,7 @@ pub fn verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default>(
     online_inputs.push(payer_asset_type.as_scalar());
     online_inputs.extend_from_slice(&address_folding_public_input);
 
-    verifier(
-        &mut transcript,
-        &params.shrunk_vk,
-        &params.shrunk_cs,
-        &params.verifier_params,
-        &online_inputs,
-        &note.proof,
-    )
-    .c(d!(NoahError::AXfrVerificationError))
+    Ok(online_inputs)
 }
 
 /// Batch verify the anonymous-to-transparent notes.
@@ -336,6 +388,19 @@ pub fn batch_verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default + Sync
         Err(eg!(NoahError::AXfrVerificationError))
     }
 }
+
+/// Batch verify the anonymous-to-transparent notes as `batch_verify_abar_to_ar_note`, in the thread
+/// pool `pool` instead of the global one, to bound the number of threads used by the verification.
+#[cfg(feature = "parallel")]
+pub fn batch_verify_abar_to_ar_note_in_pool<D: Digest<OutputSize = U64> + Default + Sync + Send>(
+    pool: &ThreadPool,
+    params: &VerifierParams,
+    notes: &[&AbarToArNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+) -> Result<()> {
+    pool.install(|| batch_verify_abar_to_ar_note(params, notes, merkle_roots, hashes))
+}
 fn prove_abar_to_ar<R: CryptoRng + RngCore>(
     rng: &mut R,
     params: &ProverParams,
@@ -344,6 +409,10 @@ fn prove_abar_to_ar<R: CryptoRng + RngCore>(
     input_commitment_trace: &AnemoiVLHTrace<BLSScalar, 2, 12>,
     folding_witness: &AXfrAddressFoldingWitness,
 ) -> Result<AXfrPlonkPf> {
+    debug_assert!(
+        !folding_witness.is_uninitialized_for_verifier(),
+        "the address folding witness is a placeholder and cannot be used for proving"
+    );
     let mut transcript = Transcript::new(ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT);
 
     let (mut cs, _) = build_abar_to_ar_cs(
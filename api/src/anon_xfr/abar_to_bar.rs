@@ -46,6 +46,8 @@ use noah_plonk::plonk::{
 use num_bigint::BigUint;
 #[cfg(feature = "parallel")]
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::ThreadPool;
 
 /// The domain separator for anonymous-to-confidential, for the Plonk proof.
 const ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"ABAR to BAR Plonk Proof";
@@ -63,6 +65,13 @@ pub struct AbarToBarNote {
     pub folding_instance: AXfrAddressFoldingInstance,
 }
 
+impl AbarToBarNote {
+    /// Return the nullifiers of the anonymous asset records spent by this note.
+    pub fn nullifiers(&self) -> &[Nullifier] {
+        core::slice::from_ref(&self.body.input)
+    }
+}
+
 /// An anonymous-to-confidential note without the proof.
 #[derive(Clone, Debug)]
 pub struct AbarToBarPreNote {
@@ -104,6 +113,9 @@ pub struct AbarToBarBody {
 }
 
 /// Generate the anonymous-to-confidential pre-note.
+///
+/// With `NonConfidentialAmount_NonConfidentialAssetType`, the output is a transparent record
+/// whose amount and asset type are revealed, and the proof uses trivial commitments to them.
 pub fn init_abar_to_bar_note<R: CryptoRng + RngCore>(
     prng: &mut R,
     oabar: &OpenAnonAssetRecord,
@@ -115,12 +127,6 @@ pub fn init_abar_to_bar_note<R: CryptoRng + RngCore>(
         return Err(eg!(NoahError::ParameterError));
     }
 
-    // Reject anonymous-to-confidential note that actually has transparent output.
-    // Should direct to AbarToAr.
-    if asset_record_type == AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType {
-        return Err(eg!(NoahError::ParameterError));
-    }
-
     let obar_amount = oabar.amount;
     let obar_type = oabar.asset_type;
 
@@ -288,13 +294,6 @@ pub fn verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default>(
     let bar = note.body.output.clone();
     let pc_gens = PedersenCommitmentRistretto::default();
 
-    // Reject anonymous-to-confidential notes whose outputs are transparent.
-    if note.body.output.get_record_type()
-        == AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType
-    {
-        return Err(eg!(NoahError::AXfrVerificationError));
-    }
-
     // 1. Get commitments.
     // 1.1 Reconstruct total amount commitment from bar.
     let (com_low, com_high) = match bar.amount {
@@ -418,14 +417,6 @@ pub fn batch_verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default + Syn
         check_memo_size(&note.body.output, &note.body.memo)?;
     }
 
-    // Reject anonymous-to-confidential notes whose outputs are transparent.
-    if notes.par_iter().any(|note| {
-        note.body.output.get_record_type()
-            == AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType
-    }) {
-        return Err(eg!(NoahError::AXfrVerificationError));
-    }
-
     let pc_gens = PedersenCommitmentRistretto::default();
 
     let is_ok = notes
@@ -546,6 +537,22 @@ pub fn batch_verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default + Syn
     }
 }
 
+/// Batch verify the anonymous-to-confidential notes as `batch_verify_abar_to_bar_note`, in the
+/// thread pool `pool` instead of the global one, to bound the number of threads used by the
+/// verification.
+#[cfg(feature = "parallel")]
+pub fn batch_verify_abar_to_bar_note_in_pool<
+    D: Digest<OutputSize = U64> + Default + Sync + Send,
+>(
+    pool: &ThreadPool,
+    params: &VerifierParams,
+    notes: &[&AbarToBarNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+) -> Result<()> {
+    pool.install(|| batch_verify_abar_to_bar_note(params, notes, merkle_roots, hashes))
+}
+
 fn prove_abar_to_bar<R: CryptoRng + RngCore>(
     rng: &mut R,
     params: &ProverParams,
@@ -558,6 +565,10 @@ fn prove_abar_to_bar<R: CryptoRng + RngCore>(
     lambda: &RistrettoScalar,
     folding_witness: &AXfrAddressFoldingWitness,
 ) -> Result<AXfrPlonkPf> {
+    debug_assert!(
+        !folding_witness.is_uninitialized_for_verifier(),
+        "the address folding witness is a placeholder and cannot be used for proving"
+    );
     let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
 
     let (mut cs, _) = build_abar_to_bar_cs(
@@ -749,30 +760,14 @@ pub fn build_abar_to_bar_cs(
     let s1_plus_lambda_s2_sim_fr_var = SimFrVar::alloc_input(&mut cs, &s1_plus_lambda_s2_sim_fr);
 
     // 3. Merge the limbs for x, y, a, b.
-    let mut all_limbs = Vec::with_capacity(4 * SimFrParamsRistretto::NUM_OF_LIMBS);
-    all_limbs.extend_from_slice(&x_sim_fr.limbs);
-    all_limbs.extend_from_slice(&y_sim_fr.limbs);
-    all_limbs.extend_from_slice(&a_sim_fr.limbs);
-    all_limbs.extend_from_slice(&b_sim_fr.limbs);
-
     let mut all_limbs_var = Vec::with_capacity(4 * SimFrParamsRistretto::NUM_OF_LIMBS);
     all_limbs_var.extend_from_slice(&x_sim_fr_var.var);
     all_limbs_var.extend_from_slice(&y_sim_fr_var.var);
     all_limbs_var.extend_from_slice(&a_sim_fr_var.var);
     all_limbs_var.extend_from_slice(&b_sim_fr_var.var);
 
-    let mut compressed_limbs = Vec::with_capacity(5);
     let mut compressed_limbs_var = Vec::with_capacity(5);
-    for (limbs, limbs_var) in all_limbs.chunks(5).zip(all_limbs_var.chunks(5)) {
-        let mut sum = BigUint::zero();
-        for (i, limb) in limbs.iter().enumerate() {
-            sum.add_assign(
-                <BLSScalar as Into<BigUint>>::into(*limb)
-                    .shl(SimFrParamsRistretto::BIT_PER_LIMB * i),
-            );
-        }
-        compressed_limbs.push(BLSScalar::from(&sum));
-
+    for limbs_var in all_limbs_var.chunks(5) {
         let mut sum_var = {
             let first_var = *limbs_var.get(0).unwrap_or(&zero_var);
             let second_var = *limbs_var.get(1).unwrap_or(&zero_var);
@@ -788,7 +783,7 @@ pub fn build_abar_to_bar_cs(
             )
         };
 
-        if limbs.len() == 5 {
+        if limbs_var.len() == 5 {
             let fifth_var = *limbs_var.get(4).unwrap_or(&zero_var);
             sum_var = cs.linear_combine(
                 &[sum_var, fifth_var, zero_var, zero_var],
@@ -803,29 +798,7 @@ pub fn build_abar_to_bar_cs(
     }
 
     // 4. Check the inspector's state commitment.
-    {
-        let trace = AnemoiJive381::eval_variable_length_hash_with_trace(&[
-            compressed_limbs[0],
-            compressed_limbs[1],
-            compressed_limbs[2],
-            compressed_limbs[3],
-            compressed_limbs[4],
-            r,
-        ]);
-
-        cs.anemoi_variable_length_hash(
-            &trace,
-            &[
-                compressed_limbs_var[0],
-                compressed_limbs_var[1],
-                compressed_limbs_var[2],
-                compressed_limbs_var[3],
-                compressed_limbs_var[4],
-                r_var,
-            ],
-            comm_var,
-        );
-    }
+    cs.prove_commitment_opening(comm_var, &compressed_limbs_var, r_var);
 
     // 5. Perform the check in field simulation.
     {
@@ -8,7 +8,6 @@ use noah_algebra::bls12_381::BLSScalar;
 use noah_algebra::ed25519::Ed25519Fq;
 use noah_algebra::prelude::*;
 use noah_algebra::zorro::{PedersenCommitmentZorro, ZorroBulletproofGens, ZorroG1, ZorroScalar};
-use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
 use noah_crypto::bulletproofs::scalar_mul_for_ed25519::ScalarMulProof;
 use noah_crypto::delegated_schnorr::{
     prove_delegated_schnorr, verify_delegated_schnorr, DelegatedSchnorrInspection,
@@ -287,29 +286,8 @@ pub fn prove_address_folding_in_cs_ed25519(
         } else {
             // Track if we have already met different bits.
             flag_meet_first_different_bit = flag_meet_first_different_bit || *secret_key_bit;
-
-            flag_meet_first_different_bit_var = {
-                let res = cs.new_variable(BLSScalar::from(flag_meet_first_different_bit as u32));
-
-                let zero = BLSScalar::zero();
-                let one = BLSScalar::one();
-                let zero_var = cs.zero_var();
-
-                cs.push_add_selectors(one, one, zero, zero);
-                cs.push_mul_selectors(one.neg(), zero);
-                cs.push_constant_selector(zero);
-                cs.push_ecc_selector(zero);
-                cs.push_out_selector(one);
-
-                cs.wiring[0].push(flag_meet_first_different_bit_var);
-                cs.wiring[1].push(*secret_key_bit_var);
-                cs.wiring[2].push(zero_var);
-                cs.wiring[3].push(zero_var);
-                cs.wiring[4].push(res);
-                cs.finish_new_gate();
-
-                res
-            };
+            flag_meet_first_different_bit_var =
+                cs.or(flag_meet_first_different_bit_var, *secret_key_bit_var);
         }
     }
 
@@ -445,24 +423,19 @@ pub fn prove_address_folding_in_cs_ed25519(
     res.enforce_zero(cs);
 
     // 6. merge limbs of the committed data as well as the randomizer scalars.
-    let mut all_limbs =
-        Vec::with_capacity(2 * query_vars.len() * SimFrParamsSecq256k1::NUM_OF_LIMBS);
     let mut all_limbs_var =
         Vec::with_capacity(2 * query_vars.len() * SimFrParamsSecq256k1::NUM_OF_LIMBS);
 
     // append all the data
     for (v, _) in query_vars.iter() {
-        all_limbs.extend_from_slice(&v.val.limbs);
         all_limbs_var.extend_from_slice(&v.var);
     }
 
     // append all the corresponding randomizers
     for (_, v) in query_vars.iter() {
-        all_limbs.extend_from_slice(&v.val.limbs);
         all_limbs_var.extend_from_slice(&v.var);
     }
 
-    let mut compressed_limbs = Vec::new();
     let mut compressed_limbs_var = Vec::new();
 
     let num_limbs_compressed = BLSScalar::capacity() / SimFrParamsZorro::BIT_PER_LIMB;
@@ -471,18 +444,7 @@ pub fn prove_address_folding_in_cs_ed25519(
         .map(|i| BLSScalar::from(&BigUint::one().shl(SimFrParamsZorro::BIT_PER_LIMB * i)))
         .collect::<Vec<BLSScalar>>();
 
-    for (limbs, limbs_var) in all_limbs
-        .chunks(num_limbs_compressed)
-        .zip(all_limbs_var.chunks(num_limbs_compressed))
-    {
-        let mut sum = BigUint::zero();
-        for (i, limb) in limbs.iter().enumerate() {
-            sum.add_assign(
-                <BLSScalar as Into<BigUint>>::into(*limb).shl(SimFrParamsZorro::BIT_PER_LIMB * i),
-            );
-        }
-        compressed_limbs.push(BLSScalar::from(&sum));
-
+    for limbs_var in all_limbs_var.chunks(num_limbs_compressed) {
         let one = BLSScalar::one();
         let zero = BLSScalar::zero();
         let zero_var = cs.zero_var();
@@ -502,7 +464,7 @@ pub fn prove_address_folding_in_cs_ed25519(
             )
         };
 
-        if limbs.len() == 5 {
+        if limbs_var.len() == 5 {
             let fifth_var = *limbs_var.get(4).unwrap_or(&zero_var);
             sum_var = cs.linear_combine(
                 &[sum_var, fifth_var, zero_var, zero_var],
@@ -521,16 +483,7 @@ pub fn prove_address_folding_in_cs_ed25519(
     let r_var = cs.new_variable(r);
     let comm_var = cs.new_variable(witness.delegated_schnorr_proof.inspection_comm);
 
-    {
-        let mut input_vars = compressed_limbs_var.clone();
-        input_vars.push(r_var);
-
-        let mut input = compressed_limbs.clone();
-        input.push(r);
-
-        let trace = AnemoiJive381::eval_variable_length_hash_with_trace(&input);
-        cs.anemoi_variable_length_hash(&trace, &input_vars, comm_var);
-    }
+    cs.prove_commitment_opening(comm_var, &compressed_limbs_var, r_var);
     cs.prepare_pi_variable(comm_var);
 
     for fr_var in lambda_series_vars_skip_first.iter() {
@@ -10,7 +10,6 @@ use noah_algebra::secp256k1::SECP256K1Scalar;
 use noah_algebra::secq256k1::{
     PedersenCommitmentSecq256k1, SECQ256K1Scalar, Secq256k1BulletproofGens, SECQ256K1G1,
 };
-use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
 use noah_crypto::bulletproofs::scalar_mul_for_secp256k1::ScalarMulProof;
 use noah_crypto::delegated_schnorr::{
     prove_delegated_schnorr, verify_delegated_schnorr, DelegatedSchnorrInspection,
@@ -54,7 +53,18 @@ pub struct AXfrAddressFoldingWitnessSecp256k1 {
 }
 
 impl Default for AXfrAddressFoldingWitnessSecp256k1 {
+    /// Produce a non-functional witness, see `new_uninitialized_for_verifier`.
+    /// Do not use it for proving; use `create_address_folding_secp256k1` instead.
     fn default() -> Self {
+        Self::new_uninitialized_for_verifier()
+    }
+}
+
+impl AXfrAddressFoldingWitnessSecp256k1 {
+    /// Create a placeholder witness, with the zero key pair and dummy proofs, that only serves to
+    /// build the constraint system on the verifier side. It is non-functional for proving: a
+    /// proving witness must be created by `create_address_folding_secp256k1`.
+    pub fn new_uninitialized_for_verifier() -> Self {
         let keypair = KeyPair::default(SECP256K1);
         let blinding_factors = vec![SECQ256K1Scalar::default(); 3];
 
@@ -92,6 +102,11 @@ impl Default for AXfrAddressFoldingWitnessSecp256k1 {
             lambda,
         }
     }
+
+    /// Return whether this is the placeholder witness from `new_uninitialized_for_verifier`.
+    pub fn is_uninitialized_for_verifier(&self) -> bool {
+        self.keypair == KeyPair::default(SECP256K1)
+    }
 }
 
 /// Create the folding instance and witness of address folding.
@@ -294,29 +309,8 @@ pub fn prove_address_folding_in_cs_secp256k1(
         } else {
             // Track if we have already met different bits.
             flag_meet_first_different_bit = flag_meet_first_different_bit || *secret_key_bit;
-
-            flag_meet_first_different_bit_var = {
-                let res = cs.new_variable(BLSScalar::from(flag_meet_first_different_bit as u32));
-
-                let zero = BLSScalar::zero();
-                let one = BLSScalar::one();
-                let zero_var = cs.zero_var();
-
-                cs.push_add_selectors(one, one, zero, zero);
-                cs.push_mul_selectors(one.neg(), zero);
-                cs.push_constant_selector(zero);
-                cs.push_ecc_selector(zero);
-                cs.push_out_selector(one);
-
-                cs.wiring[0].push(flag_meet_first_different_bit_var);
-                cs.wiring[1].push(*secret_key_bit_var);
-                cs.wiring[2].push(zero_var);
-                cs.wiring[3].push(zero_var);
-                cs.wiring[4].push(res);
-                cs.finish_new_gate();
-
-                res
-            };
+            flag_meet_first_different_bit_var =
+                cs.or(flag_meet_first_different_bit_var, *secret_key_bit_var);
         }
     }
 
@@ -455,24 +449,19 @@ pub fn prove_address_folding_in_cs_secp256k1(
     res.enforce_zero(cs);
 
     // 6. merge limbs of the committed data as well as the randomizer scalars.
-    let mut all_limbs =
-        Vec::with_capacity(2 * query_vars.len() * SimFrParamsSecq256k1::NUM_OF_LIMBS);
     let mut all_limbs_var =
         Vec::with_capacity(2 * query_vars.len() * SimFrParamsSecq256k1::NUM_OF_LIMBS);
 
     // append all the data
     for (v, _) in query_vars.iter() {
-        all_limbs.extend_from_slice(&v.val.limbs);
         all_limbs_var.extend_from_slice(&v.var);
     }
 
     // append all the corresponding randomizers
     for (_, v) in query_vars.iter() {
-        all_limbs.extend_from_slice(&v.val.limbs);
         all_limbs_var.extend_from_slice(&v.var);
     }
 
-    let mut compressed_limbs = Vec::new();
     let mut compressed_limbs_var = Vec::new();
 
     let num_limbs_compressed = BLSScalar::capacity() / SimFrParamsSecq256k1::BIT_PER_LIMB;
@@ -481,19 +470,7 @@ pub fn prove_address_folding_in_cs_secp256k1(
         .map(|i| BLSScalar::from(&BigUint::one().shl(SimFrParamsSecq256k1::BIT_PER_LIMB * i)))
         .collect::<Vec<BLSScalar>>();
 
-    for (limbs, limbs_var) in all_limbs
-        .chunks(num_limbs_compressed)
-        .zip(all_limbs_var.chunks(num_limbs_compressed))
-    {
-        let mut sum = BigUint::zero();
-        for (i, limb) in limbs.iter().enumerate() {
-            sum.add_assign(
-                <BLSScalar as Into<BigUint>>::into(*limb)
-                    .shl(SimFrParamsSecq256k1::BIT_PER_LIMB * i),
-            );
-        }
-        compressed_limbs.push(BLSScalar::from(&sum));
-
+    for limbs_var in all_limbs_var.chunks(num_limbs_compressed) {
         let one = BLSScalar::one();
         let zero = BLSScalar::zero();
         let zero_var = cs.zero_var();
@@ -513,7 +490,7 @@ pub fn prove_address_folding_in_cs_secp256k1(
             )
         };
 
-        if limbs.len() == 5 {
+        if limbs_var.len() == 5 {
             let fifth_var = *limbs_var.get(4).unwrap_or(&zero_var);
             sum_var = cs.linear_combine(
                 &[sum_var, fifth_var, zero_var, zero_var],
@@ -532,16 +509,7 @@ pub fn prove_address_folding_in_cs_secp256k1(
     let r_var = cs.new_variable(r);
     let comm_var = cs.new_variable(witness.delegated_schnorr_proof.inspection_comm);
 
-    {
-        let mut input_vars = compressed_limbs_var.clone();
-        input_vars.push(r_var);
-
-        let mut input = compressed_limbs.clone();
-        input.push(r);
-
-        let trace = AnemoiJive381::eval_variable_length_hash_with_trace(&input);
-        cs.anemoi_variable_length_hash(&trace, &input_vars, comm_var);
-    }
+    cs.prove_commitment_opening(comm_var, &compressed_limbs_var, r_var);
     cs.prepare_pi_variable(comm_var);
 
     for fr_var in lambda_series_vars_skip_first.iter() {
@@ -0,0 +1,148 @@
+use crate::anon_xfr::{
+    abar_to_abar::{prepare_verify_anon_xfr_note, AXfrNote},
+    abar_to_ar::{prepare_verify_abar_to_ar_note, AbarToArNote},
+    ar_to_abar::{prepare_verify_ar_to_abar_note, ArToAbarNote},
+};
+use crate::errors::NoahError;
+use crate::keys::PublicKey;
+use crate::parameters::params::VerifierParams;
+use digest::{consts::U64, Digest};
+use merlin::Transcript;
+use noah_algebra::{
+    bls12_381::{BLSScalar, BLSG1},
+    prelude::*,
+};
+use noah_plonk::poly_commit::{
+    kzg_poly_com::{KZGCommitment, KZGCommitmentSchemeBLS},
+    pcs::PolyComScheme,
+    transcript::PolyComTranscript,
+};
+
+/// The domain separator for the transcript of an aggregated verification.
+const AGGREGATION_TRANSCRIPT: &[u8] = b"Noah Aggregated Verification";
+
+/// An aggregator of the verification of notes of different kinds.
+///
+/// The public inputs and the prepared commitments of each added proof are absorbed into one
+/// transcript, from which a combination challenge is derived for every proof, so that the final
+/// checks of all the Plonk proofs are done with one multi-exponentiation and one pairing check.
+///
+/// All the verifier parameters given to the aggregator must come from the same SRS.
+pub struct NoahAggregator<'a> {
+    pcs: Option<&'a KZGCommitmentSchemeBLS>,
+    transcript: Transcript,
+    prepared: Vec<(KZGCommitment<BLSG1>, KZGCommitment<BLSG1>)>,
+}
+
+impl<'a> Default for NoahAggregator<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> NoahAggregator<'a> {
+    /// Create an empty aggregator.
+    pub fn new() -> Self {
+        Self {
+            pcs: None,
+            transcript: Transcript::new(AGGREGATION_TRANSCRIPT),
+            prepared: vec![],
+        }
+    }
+
+    /// Return the number of proofs added to the aggregator.
+    pub fn len(&self) -> usize {
+        self.prepared.len()
+    }
+
+    /// Return true if no proof has been added to the aggregator.
+    pub fn is_empty(&self) -> bool {
+        self.prepared.is_empty()
+    }
+
+    /// Check a transparent-to-anonymous note, and add its Plonk proof to the aggregator.
+    pub fn add_ar_to_abar_note(
+        &mut self,
+        params: &'a VerifierParams,
+        note: &ArToAbarNote,
+    ) -> Result<()> {
+        let (online_inputs, prepared) = prepare_verify_ar_to_abar_note(params, note).c(d!())?;
+        self.absorb(params, &online_inputs, prepared).c(d!())
+    }
+
+    /// Check an anonymous-to-transparent note, and add its Plonk proof to the aggregator.
+    pub fn add_abar_to_ar_note<D: Digest<OutputSize = U64> + Default>(
+        &mut self,
+        params: &'a VerifierParams,
+        note: &AbarToArNote,
+        merkle_root: &BLSScalar,
+        hash: D,
+    ) -> Result<()> {
+        let (online_inputs, prepared) =
+            prepare_verify_abar_to_ar_note(params, note, merkle_root, hash).c(d!())?;
+        self.absorb(params, &online_inputs, prepared).c(d!())
+    }
+
+    /// Check an anonymous transfer note, and add its Plonk proof to the aggregator.
+    /// If the note pays its fee to a recipient, `fee_recipient` must be given, as for
+    /// `verify_anon_xfr_note_with_fee_recipient`; otherwise the fee is burned.
+    pub fn add_anon_xfr_note<D: Digest<OutputSize = U64> + Default>(
+        &mut self,
+        params: &'a VerifierParams,
+        note: &AXfrNote,
+        merkle_root: &BLSScalar,
+        fee_recipient: Option<&(PublicKey, BLSScalar)>,
+        hash: D,
+    ) -> Result<()> {
+        let (online_inputs, prepared) =
+            prepare_verify_anon_xfr_note(params, note, merkle_root, fee_recipient, hash).c(d!())?;
+        self.absorb(params, &online_inputs, prepared).c(d!())
+    }
+
+    /// Run the combined check of all the Plonk proofs added to the aggregator.
+    pub fn verify(mut self) -> Result<()> {
+        let pcs = match self.pcs {
+            Some(pcs) => pcs,
+            None => return Ok(()),
+        };
+
+        let transcript = &mut self.transcript;
+        let rhos: Vec<BLSScalar> = self
+            .prepared
+            .iter()
+            .map(|_| transcript.get_challenge_field_elem(b"rho"))
+            .collect();
+
+        pcs.verify_prepared(&self.prepared, &rhos)
+            .c(d!(NoahError::AXfrVerificationError))
+    }
+
+    fn absorb(
+        &mut self,
+        params: &'a VerifierParams,
+        online_inputs: &[BLSScalar],
+        prepared: (KZGCommitment<BLSG1>, KZGCommitment<BLSG1>),
+    ) -> Result<()> {
+        match self.pcs {
+            Some(pcs) => {
+                if pcs.public_parameter_group_2 != params.shrunk_vk.public_parameter_group_2 {
+                    return Err(eg!(NoahError::ParameterError));
+                }
+            }
+            None => self.pcs = Some(&params.shrunk_vk),
+        }
+
+        self.transcript
+            .append_message(b"label", params.label.as_bytes());
+        self.transcript
+            .append_u64(b"n_inputs", online_inputs.len() as u64);
+        for input in online_inputs.iter() {
+            self.transcript.append_field_elem(input);
+        }
+        self.transcript.append_commitment(&prepared.0);
+        self.transcript.append_commitment(&prepared.1);
+
+        self.prepared.push(prepared);
+        Ok(())
+    }
+}
@@ -1,22 +1,33 @@
 use crate::anon_xfr::{
-    commit, commit_in_cs,
+    commit, commit_in_cs, sign_note_body,
     structs::{
         AnonAssetRecord, AxfrOwnerMemo, OpenAnonAssetRecordBuilder, PayeeWitness, PayeeWitnessVars,
     },
-    AXfrPlonkPf, TurboPlonkCS, MAX_AXFR_MEMO_SIZE,
+    verify_note_body, AXfrPlonkPf, TurboPlonkCS, MAX_AXFR_MEMO_SIZE,
 };
 use crate::keys::{KeyPair, PublicKey, PublicKeyInner, Signature};
 use crate::parameters::params::ProverParams;
 use crate::parameters::params::VerifierParams;
 use crate::xfr::structs::{BlindAssetRecord, OpenAssetRecord};
 use merlin::Transcript;
-use noah_algebra::{bls12_381::BLSScalar, errors::NoahError, prelude::*};
+use noah_algebra::{
+    bls12_381::{BLSScalar, BLSG1},
+    errors::NoahError,
+    prelude::*,
+};
 use noah_crypto::basic::anemoi_jive::{AnemoiJive381, AnemoiVLHTrace};
-use noah_plonk::plonk::{
-    constraint_system::TurboCS, prover::prover_with_lagrange, verifier::verifier,
+use noah_plonk::{
+    plonk::{
+        constraint_system::TurboCS,
+        prover::prover_with_lagrange,
+        verifier::{prepare_verifier, verifier},
+    },
+    poly_commit::kzg_poly_com::KZGCommitment,
 };
 #[cfg(feature = "parallel")]
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::ThreadPool;
 
 /// The domain separator for transparent-to-anonymous, for the Plonk proof.
 const AR_TO_ABAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"AR to ABAR Plonk Proof";
@@ -30,6 +41,14 @@ pub struct ArToAbarNote {
     pub signature: Signature,
 }
 
+impl ArToAbarNote {
+    /// Return the transparent asset record spent by this note.
+    /// There is no nullifier, since the input is not an anonymous asset record.
+    pub fn spent_record(&self) -> &BlindAssetRecord {
+        &self.body.input
+    }
+}
+
 /// The transparent-to-anonymous body.
 #[derive(Debug, Serialize, Deserialize, Eq, Clone, PartialEq)]
 pub struct ArToAbarBody {
@@ -54,10 +73,7 @@ pub fn gen_ar_to_abar_note<R: CryptoRng + RngCore>(
     // generate body
     let body = gen_ar_to_abar_body(prng, params, record, &abar_pubkey).c(d!())?;
 
-    let msg = bincode::serialize(&body)
-        .map_err(|_| NoahError::SerializationError)
-        .c(d!())?;
-    let signature = bar_keypair.sign(&msg)?;
+    let signature = sign_note_body(bar_keypair, &body)?;
 
     let note = ArToAbarNote { body, signature };
     Ok(note)
@@ -70,12 +86,7 @@ pub fn verify_ar_to_abar_note(params: &VerifierParams, note: &ArToAbarNote) -> R
         return Err(eg!(NoahError::AXfrVerificationError));
     }
 
-    let msg = bincode::serialize(&note.body).c(d!(NoahError::SerializationError))?;
-    note.body
-        .input
-        .public_key
-        .verify(&msg, &note.signature)
-        .c(d!())?;
+    verify_note_body(&note.body.input.public_key, &note.body, &note.signature)?;
 
     verify_ar_to_abar_body(params, &note.body).c(d!())
 }
@@ -96,12 +107,7 @@ pub fn batch_verify_ar_to_abar_note(
     let is_ok = notes
         .par_iter()
         .map(|note| {
-            let msg = bincode::serialize(&note.body).c(d!(NoahError::SerializationError))?;
-            note.body
-                .input
-                .public_key
-                .verify(&msg, &note.signature)
-                .c(d!())?;
+            verify_note_body(&note.body.input.public_key, &note.body, &note.signature)?;
 
             verify_ar_to_abar_body(params, &note.body).c(d!())
         })
@@ -114,6 +120,17 @@ pub fn batch_verify_ar_to_abar_note(
     }
 }
 
+/// Batch verify the transparent-to-anonymous notes as `batch_verify_ar_to_abar_note`, in the thread
+/// pool `pool` instead of the global one, to bound the number of threads used by the verification.
+#[cfg(feature = "parallel")]
+pub fn batch_verify_ar_to_abar_note_in_pool(
+    pool: &ThreadPool,
+    params: &VerifierParams,
+    notes: &[&ArToAbarNote],
+) -> Result<()> {
+    pool.install(|| batch_verify_ar_to_abar_note(params, notes))
+}
+
 /// Generate the transparent-to-anonymous body.
 pub fn gen_ar_to_abar_body<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -174,19 +191,9 @@ pub fn gen_ar_to_abar_body<R: CryptoRng + RngCore>(
 
 /// Verify the transparent-to-anonymous body.
 pub fn verify_ar_to_abar_body(params: &VerifierParams, body: &ArToAbarBody) -> Result<()> {
-    if body.input.amount.is_confidential() || body.input.asset_type.is_confidential() {
-        return Err(eg!(NoahError::ParameterError));
-    }
-
-    let amount = body.input.amount.get_amount().unwrap();
-    let asset_type = body.input.asset_type.get_asset_type().unwrap();
+    let online_inputs = ar_to_abar_online_inputs(body).c(d!())?;
 
     let mut transcript = Transcript::new(AR_TO_ABAR_PLONK_PROOF_TRANSCRIPT);
-    let mut online_inputs: Vec<BLSScalar> = vec![];
-    online_inputs.push(BLSScalar::from(amount));
-    online_inputs.push(asset_type.as_scalar());
-    online_inputs.push(body.output.commitment);
-
     verifier(
         &mut transcript,
         &params.shrunk_vk,
@@ -198,6 +205,51 @@ pub fn verify_ar_to_abar_body(params: &VerifierParams, body: &ArToAbarBody) -> R
     .c(d!(NoahError::AXfrVerificationError))
 }
 
+/// Check a transparent-to-anonymous note up to the final check of its Plonk proof, and return
+/// the public inputs of the proof together with the inputs of that check.
+pub(crate) fn prepare_verify_ar_to_abar_note(
+    params: &VerifierParams,
+    note: &ArToAbarNote,
+) -> Result<(Vec<BLSScalar>, (KZGCommitment<BLSG1>, KZGCommitment<BLSG1>))> {
+    // Check the memo size.
+    if note.body.memo.size() > MAX_AXFR_MEMO_SIZE {
+        return Err(eg!(NoahError::AXfrVerificationError));
+    }
+
+    verify_note_body(&note.body.input.public_key, &note.body, &note.signature)?;
+
+    let online_inputs = ar_to_abar_online_inputs(&note.body).c(d!())?;
+
+    let mut transcript = Transcript::new(AR_TO_ABAR_PLONK_PROOF_TRANSCRIPT);
+    let prepared = prepare_verifier(
+        &mut transcript,
+        &params.shrunk_vk,
+        &params.shrunk_cs,
+        &params.verifier_params,
+        &online_inputs,
+        &note.body.proof,
+    )
+    .c(d!(NoahError::AXfrVerificationError))?;
+
+    Ok((online_inputs, prepared))
+}
+
+/// Return the public inputs of the Plonk proof of a transparent-to-anonymous body.
+fn ar_to_abar_online_inputs(body: &ArToAbarBody) -> Result<Vec<BLSScalar>> {
+    if body.input.amount.is_confidential() || body.input.asset_type.is_confidential() {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    let amount = body.input.amount.get_amount().unwrap();
+    let asset_type = body.input.asset_type.get_asset_type().unwrap();
+
+    let mut online_inputs: Vec<BLSScalar> = vec![];
+    online_inputs.push(BLSScalar::from(amount));
+    online_inputs.push(asset_type.as_scalar());
+    online_inputs.push(body.output.commitment);
+    Ok(online_inputs)
+}
+
 /// Construct the transparent-to-anonymous constraint system.
 pub fn build_ar_to_abar_cs(
     payee_data: PayeeWitness,
@@ -1,7 +1,7 @@
 use crate::anon_xfr::{
-    commit, commit_in_cs,
+    commit, commit_in_cs, sign_note_body,
     structs::{AnonAssetRecord, AxfrOwnerMemo, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
-    AXfrPlonkPf, TurboPlonkCS, MAX_AXFR_MEMO_SIZE, TWO_POW_32,
+    verify_note_body, AXfrPlonkPf, TurboPlonkCS, MAX_AXFR_MEMO_SIZE, TWO_POW_32,
 };
 use crate::keys::{KeyPair, PublicKey, PublicKeyInner, Signature};
 use crate::parameters::params::ProverParams;
@@ -17,7 +17,7 @@ use noah_algebra::{
     ristretto::{PedersenCommitmentRistretto, RistrettoPoint, RistrettoScalar},
     traits::PedersenCommitment,
 };
-use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381, AnemoiVLHTrace};
+use noah_crypto::basic::anemoi_jive::{AnemoiJive381, AnemoiVLHTrace};
 use noah_crypto::{
     delegated_schnorr::{
         prove_delegated_schnorr, verify_delegated_schnorr, DelegatedSchnorrInspection,
@@ -33,6 +33,8 @@ use noah_plonk::plonk::{
 use num_bigint::BigUint;
 #[cfg(feature = "parallel")]
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::ThreadPool;
 
 const BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"BAR to ABAR Plonk Proof";
 
@@ -61,6 +63,117 @@ pub struct BarToAbarBody {
     pub memo: AxfrOwnerMemo,
 }
 
+/// A proof that an anonymous asset record commits to the same amount and asset type as a
+/// confidential asset record, which can be verified independently of a note.
+#[derive(Debug, Serialize, Deserialize, Eq, Clone, PartialEq)]
+pub struct BarAbarConsistencyProof {
+    /// The delegated Schnorr proof of the amount and the asset type of the confidential record.
+    pub delegated_schnorr_proof:
+        DelegatedSchnorrProof<RistrettoScalar, RistrettoPoint, SimFrParamsRistretto>,
+    /// The inspector's proof that they match the commitment of the anonymous record.
+    pub inspector_proof: AXfrPlonkPf,
+}
+
+impl BarAbarConsistencyProof {
+    /// Prove that the anonymous asset record `oabar` has the amount and the asset type of the
+    /// confidential asset record `obar`.
+    pub fn prove<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        params: &ProverParams,
+        obar: &OpenAssetRecord,
+        oabar: &OpenAnonAssetRecord,
+    ) -> Result<Self> {
+        if obar.amount != oabar.get_amount() || obar.asset_type != oabar.get_asset_type() {
+            return Err(eg!(NoahError::ParameterError));
+        }
+
+        let pc_gens = PedersenCommitmentRistretto::default();
+
+        // 1. Reconstruct the points.
+        let x = RistrettoScalar::from(obar.amount);
+        let y: RistrettoScalar = obar.asset_type.as_scalar();
+        let gamma = obar
+            .amount_blinds
+            .0
+            .add(&obar.amount_blinds.1.mul(&RistrettoScalar::from(TWO_POW_32)));
+        let delta = obar.type_blind;
+        let point_p = pc_gens.commit(x, gamma);
+        let point_q = pc_gens.commit(y, delta);
+
+        let x_in_bls12_381 = BLSScalar::from(&BigUint::from_bytes_le(&x.to_bytes()));
+        let y_in_bls12_381 = BLSScalar::from(&BigUint::from_bytes_le(&y.to_bytes()));
+
+        let (comm, comm_trace) = commit(
+            oabar.pub_key_ref(),
+            oabar.get_blind(),
+            obar.amount,
+            obar.asset_type.as_scalar(),
+        )?;
+
+        let mut transcript = Transcript::new(BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT);
+        // important: address folding relies significantly on the Fiat-Shamir transform.
+        transcript.append_message(b"commitment", &comm.to_bytes());
+
+        // 2. Compute the delegated Schnorr proof.
+        let (delegated_schnorr_proof, inspection, beta, lambda) = prove_delegated_schnorr(
+            prng,
+            &vec![(x, gamma), (y, delta)],
+            &pc_gens,
+            &vec![point_p, point_q],
+            &mut transcript,
+        )
+        .c(d!())?;
+
+        // 3. Compute the inspector's proof.
+        let inspector_proof = prove_bar_to_abar_cs(
+            prng,
+            params,
+            x_in_bls12_381,
+            y_in_bls12_381,
+            oabar.get_blind(),
+            oabar.pub_key_ref(),
+            &delegated_schnorr_proof,
+            &inspection,
+            &beta,
+            &lambda,
+            &comm_trace,
+        )
+        .c(d!())?;
+
+        Ok(BarAbarConsistencyProof {
+            delegated_schnorr_proof,
+            inspector_proof,
+        })
+    }
+
+    /// Verify that the anonymous asset record `abar` has the amount and the asset type of the
+    /// confidential asset record `bar`.
+    pub fn verify(
+        &self,
+        params: &VerifierParams,
+        bar: &BlindAssetRecord,
+        abar: &AnonAssetRecord,
+    ) -> Result<()> {
+        verify_bar_to_abar(
+            params,
+            bar,
+            abar,
+            &self.delegated_schnorr_proof,
+            &self.inspector_proof,
+        )
+    }
+}
+
+impl BarToAbarBody {
+    /// Return the proof that the output has the amount and the asset type of the input.
+    pub fn consistency_proof(&self) -> BarAbarConsistencyProof {
+        BarAbarConsistencyProof {
+            delegated_schnorr_proof: self.proof.0.clone(),
+            inspector_proof: self.proof.1.clone(),
+        }
+    }
+}
+
 /// Generate confidential-to-anonymous note.
 pub fn gen_bar_to_abar_note<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -84,10 +197,7 @@ pub fn gen_bar_to_abar_note<R: CryptoRng + RngCore>(
         memo: open_abar.owner_memo.unwrap(),
     };
 
-    let msg = bincode::serialize(&body)
-        .map_err(|_| NoahError::SerializationError)
-        .c(d!())?;
-    let signature = bar_keypair.sign(&msg)?;
+    let signature = sign_note_body(bar_keypair, &body)?;
 
     let note = BarToAbarNote { body, signature };
     Ok(note)
@@ -108,12 +218,12 @@ pub fn verify_bar_to_abar_note(
         params,
         &note.body.input,
         &note.body.output,
-        &note.body.proof,
+        &note.body.proof.0,
+        &note.body.proof.1,
     )
     .c(d!())?;
 
-    let msg = bincode::serialize(&note.body).c(d!(NoahError::SerializationError))?;
-    bar_pub_key.verify(&msg, &note.signature).c(d!())
+    verify_note_body(bar_pub_key, &note.body, &note.signature)
 }
 
 /// Batch verify the confidential-to-anonymous notes.
@@ -138,12 +248,12 @@ pub fn batch_verify_bar_to_abar_note(
                 params,
                 &note.body.input,
                 &note.body.output,
-                &note.body.proof,
+                &note.body.proof.0,
+                &note.body.proof.1,
             )
             .c(d!())?;
 
-            let msg = bincode::serialize(&note.body).c(d!(NoahError::SerializationError))?;
-            bar_pub_key.verify(&msg, &note.signature)
+            verify_note_body(bar_pub_key, &note.body, &note.signature)
         })
         .all(|x| x.is_ok());
 
@@ -154,6 +264,19 @@ pub fn batch_verify_bar_to_abar_note(
     }
 }
 
+/// Batch verify the confidential-to-anonymous notes as `batch_verify_bar_to_abar_note`, in the
+/// thread pool `pool` instead of the global one, to bound the number of threads used by the
+/// verification.
+#[cfg(feature = "parallel")]
+pub fn batch_verify_bar_to_abar_note_in_pool(
+    pool: &ThreadPool,
+    params: &VerifierParams,
+    notes: &[&BarToAbarNote],
+    bar_pub_keys: &[&PublicKey],
+) -> Result<()> {
+    pool.install(|| batch_verify_bar_to_abar_note(params, notes, bar_pub_keys))
+}
+
 pub(crate) fn prove_bar_to_abar<R: CryptoRng + RngCore>(
     prng: &mut R,
     params: &ProverParams,
@@ -164,13 +287,9 @@ pub(crate) fn prove_bar_to_abar<R: CryptoRng + RngCore>(
     DelegatedSchnorrProof<RistrettoScalar, RistrettoPoint, SimFrParamsRistretto>,
     AXfrPlonkPf,
 )> {
-    let oabar_amount = obar.amount;
-
-    let pc_gens = PedersenCommitmentRistretto::default();
-
     // 1. Construct ABAR.
     let oabar = OpenAnonAssetRecordBuilder::new()
-        .amount(oabar_amount)
+        .amount(obar.amount)
         .asset_type(obar.asset_type)
         .pub_key(abar_pubkey)
         .finalize(prng)
@@ -178,56 +297,11 @@ pub(crate) fn prove_bar_to_abar<R: CryptoRng + RngCore>(
         .build()
         .c(d!())?;
 
-    // 2. Reconstruct the points.
-    let x = RistrettoScalar::from(oabar_amount);
-    let y: RistrettoScalar = obar.asset_type.as_scalar();
-    let gamma = obar
-        .amount_blinds
-        .0
-        .add(&obar.amount_blinds.1.mul(&RistrettoScalar::from(TWO_POW_32)));
-    let delta = obar.type_blind;
-    let point_p = pc_gens.commit(x, gamma);
-    let point_q = pc_gens.commit(y, delta);
-
-    let x_in_bls12_381 = BLSScalar::from(&BigUint::from_bytes_le(&x.to_bytes()));
-    let y_in_bls12_381 = BLSScalar::from(&BigUint::from_bytes_le(&y.to_bytes()));
-
-    let (comm, comm_trace) = commit(
-        abar_pubkey,
-        oabar.blind,
-        oabar_amount,
-        obar.asset_type.as_scalar(),
-    )?;
-
-    let mut transcript = Transcript::new(BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT);
-    // important: address folding relies significantly on the Fiat-Shamir transform.
-    transcript.append_message(b"commitment", &comm.to_bytes());
-
-    // 3. Compute the delegated Schnorr proof.
-    let (delegated_schnorr_proof, inspection, beta, lambda) = prove_delegated_schnorr(
-        prng,
-        &vec![(x, gamma), (y, delta)],
-        &pc_gens,
-        &vec![point_p, point_q],
-        &mut transcript,
-    )
-    .c(d!())?;
-
-    // 4. Compute the inspector's proof.
-    let inspector_proof = prove_bar_to_abar_cs(
-        prng,
-        params,
-        x_in_bls12_381,
-        y_in_bls12_381,
-        oabar.blind,
-        abar_pubkey,
-        &delegated_schnorr_proof,
-        &inspection,
-        &beta,
-        &lambda,
-        &comm_trace,
-    )
-    .c(d!())?;
+    // 2. Prove that it matches the confidential asset record.
+    let BarAbarConsistencyProof {
+        delegated_schnorr_proof,
+        inspector_proof,
+    } = BarAbarConsistencyProof::prove(prng, params, obar, &oabar).c(d!())?;
 
     Ok((oabar, delegated_schnorr_proof, inspector_proof))
 }
@@ -236,10 +310,12 @@ pub(crate) fn verify_bar_to_abar(
     params: &VerifierParams,
     bar: &BlindAssetRecord,
     abar: &AnonAssetRecord,
-    proof: &(
-        DelegatedSchnorrProof<RistrettoScalar, RistrettoPoint, SimFrParamsRistretto>,
-        AXfrPlonkPf,
-    ),
+    delegated_schnorr_proof: &DelegatedSchnorrProof<
+        RistrettoScalar,
+        RistrettoPoint,
+        SimFrParamsRistretto,
+    >,
+    inspector_proof: &AXfrPlonkPf,
 ) -> Result<()> {
     let pc_gens = PedersenCommitmentRistretto::default();
 
@@ -291,13 +367,21 @@ pub(crate) fn verify_bar_to_abar(
     let (beta, lambda) = verify_delegated_schnorr(
         &pc_gens,
         &vec![com_amount, com_asset_type],
-        &proof.0,
+        delegated_schnorr_proof,
         &mut transcript,
     )
     .c(d!())?;
 
     // 3. Verify the inspector's proof.
-    verify_inspection(params, abar.commitment, &proof.0, &proof.1, &beta, &lambda).c(d!())
+    verify_inspection(
+        params,
+        abar.commitment,
+        delegated_schnorr_proof,
+        inspector_proof,
+        &beta,
+        &lambda,
+    )
+    .c(d!())
 }
 
 /// Generate the inspector's proof.
@@ -466,30 +550,14 @@ pub(crate) fn build_bar_to_abar_cs(
     let s1_plus_lambda_s2_sim_fr_var = SimFrVar::alloc_input(&mut cs, &s1_plus_lambda_s2_sim_fr);
 
     // 3. Merge the limbs for x, y, a, b.
-    let mut all_limbs = Vec::with_capacity(4 * SimFrParamsRistretto::NUM_OF_LIMBS);
-    all_limbs.extend_from_slice(&x_sim_fr.limbs);
-    all_limbs.extend_from_slice(&y_sim_fr.limbs);
-    all_limbs.extend_from_slice(&a_sim_fr.limbs);
-    all_limbs.extend_from_slice(&b_sim_fr.limbs);
-
     let mut all_limbs_var = Vec::with_capacity(4 * SimFrParamsRistretto::NUM_OF_LIMBS);
     all_limbs_var.extend_from_slice(&x_sim_fr_var.var);
     all_limbs_var.extend_from_slice(&y_sim_fr_var.var);
     all_limbs_var.extend_from_slice(&a_sim_fr_var.var);
     all_limbs_var.extend_from_slice(&b_sim_fr_var.var);
 
-    let mut compressed_limbs = Vec::with_capacity(5);
     let mut compressed_limbs_var = Vec::with_capacity(5);
-    for (limbs, limbs_var) in all_limbs.chunks(5).zip(all_limbs_var.chunks(5)) {
-        let mut sum = BigUint::zero();
-        for (i, limb) in limbs.iter().enumerate() {
-            sum.add_assign(
-                <BLSScalar as Into<BigUint>>::into(*limb)
-                    .shl(SimFrParamsRistretto::BIT_PER_LIMB * i),
-            );
-        }
-        compressed_limbs.push(BLSScalar::from(&sum));
-
+    for limbs_var in all_limbs_var.chunks(5) {
         let mut sum_var = {
             let first_var = *limbs_var.get(0).unwrap_or(&zero_var);
             let second_var = *limbs_var.get(1).unwrap_or(&zero_var);
@@ -505,7 +573,7 @@ pub(crate) fn build_bar_to_abar_cs(
             )
         };
 
-        if limbs.len() == 5 {
+        if limbs_var.len() == 5 {
             let fifth_var = *limbs_var.get(4).unwrap_or(&zero_var);
             sum_var = cs.linear_combine(
                 &[sum_var, fifth_var, zero_var, zero_var],
@@ -520,29 +588,7 @@ pub(crate) fn build_bar_to_abar_cs(
     }
 
     // 4. Open the inspector's state commitment.
-    {
-        let trace = AnemoiJive381::eval_variable_length_hash_with_trace(&[
-            compressed_limbs[0],
-            compressed_limbs[1],
-            compressed_limbs[2],
-            compressed_limbs[3],
-            compressed_limbs[4],
-            r,
-        ]);
-
-        cs.anemoi_variable_length_hash(
-            &trace,
-            &[
-                compressed_limbs_var[0],
-                compressed_limbs_var[1],
-                compressed_limbs_var[2],
-                compressed_limbs_var[3],
-                compressed_limbs_var[4],
-                r_var,
-            ],
-            comm_var,
-        );
-    }
+    cs.prove_commitment_opening(comm_var, &compressed_limbs_var, r_var);
 
     // 5. Perform the check in field simulation.
     {
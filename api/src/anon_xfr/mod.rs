@@ -1,9 +1,11 @@
+use crate::anon_xfr::abar_to_abar::AXfrNote;
+use crate::anon_xfr::address_folding_secp256k1::AXfrAddressFoldingWitnessSecp256k1;
 use crate::anon_xfr::structs::Commitment;
-use crate::keys::{KeyPair, PublicKey, PublicKeyInner, SecretKey};
+use crate::keys::{KeyPair, PublicKey, PublicKeyInner, SecretKey, Signature};
 use crate::{
     anon_xfr::structs::{
         AccElemVars, AnonAssetRecord, AxfrOwnerMemo, MTPath, MerkleNodeVars, MerklePathVars,
-        OpenAnonAssetRecord,
+        OpenAnonAssetRecord, OpenAnonAssetRecordBuilder,
     },
     xfr::structs::{AssetType, ASSET_TYPE_LENGTH},
 };
@@ -11,7 +13,7 @@ use aes_gcm::aead::Aead;
 use digest::{generic_array::GenericArray, Digest, KeyInit};
 use noah_algebra::{
     bls12_381::{BLSScalar, BLS12_381_SCALAR_LEN},
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     prelude::*,
 };
 use noah_crypto::basic::anemoi_jive::{
@@ -30,6 +32,9 @@ use noah_algebra::{
     secp256k1::{SECP256K1Scalar, SECP256K1G1},
 };
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
 #[cfg(target_arch = "wasm32")]
 use {noah_plonk::plonk::prover::init_prover, wasm_bindgen::prelude::*};
 
@@ -43,10 +48,14 @@ pub mod abar_to_bar;
 pub mod address_folding_ed25519;
 /// Module for designs related to address folding for secp256k1.
 pub mod address_folding_secp256k1;
+/// Module for aggregating the verification of notes of different kinds.
+pub mod aggregation;
 /// Module for converting transparent assets to anonymous assets.
 pub mod ar_to_abar;
 /// Module for converting confidential assets to anonymous assets.
 pub mod bar_to_abar;
+/// Module for proving the ownership of anonymous assets without spending them.
+pub mod ownership;
 /// Module for shared structures.
 pub mod structs;
 
@@ -61,7 +70,10 @@ pub const MAX_AXFR_MEMO_SIZE: usize = 121;
 
 pub(crate) type TurboPlonkCS = TurboCS<BLSScalar>;
 
-use crate::parameters::params::AddressFormat;
+use crate::parameters::params::{
+    is_supported_anon_xfr_shape, AddressFormat, MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_RECEIVER,
+    MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_SENDER,
+};
 
 /// The Plonk proof type.
 pub(crate) type AXfrPlonkPf = PlonkPf<KZGCommitmentSchemeBLS>;
@@ -75,6 +87,47 @@ pub enum AXfrAddressFoldingInstance {
     Ed25519(address_folding_ed25519::AXfrAddressFoldingInstanceEd25519),
 }
 
+impl AXfrAddressFoldingInstance {
+    /// Get the kind of the address folding instance.
+    pub fn kind(&self) -> AddressFoldingKind {
+        match self {
+            Self::Secp256k1(_) => AddressFoldingKind::Secp256k1,
+            Self::Ed25519(_) => AddressFoldingKind::Ed25519,
+        }
+    }
+}
+
+/// The kind of address folding used by a note, i.e., the curve of the key that spends the inputs.
+///
+/// A note carries it explicitly in its serialized form, so that a verifier can dispatch to the
+/// address folding verification of the right curve without inspecting the folding instance.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddressFoldingKind {
+    /// Address folding for secp256k1 keys.
+    Secp256k1,
+    /// Address folding for ed25519 keys.
+    Ed25519,
+}
+
+impl AddressFoldingKind {
+    /// Get the format of the addresses of this kind.
+    pub fn get_address_format(&self) -> AddressFormat {
+        match self {
+            Self::Secp256k1 => AddressFormat::SECP256K1,
+            Self::Ed25519 => AddressFormat::ED25519,
+        }
+    }
+}
+
+impl From<AddressFormat> for AddressFoldingKind {
+    fn from(address_format: AddressFormat) -> Self {
+        match address_format {
+            AddressFormat::SECP256K1 => Self::Secp256k1,
+            AddressFormat::ED25519 => Self::Ed25519,
+        }
+    }
+}
+
 /// The witness for address folding.
 pub enum AXfrAddressFoldingWitness {
     /// The Secp256k1 witness for address folding.
@@ -85,10 +138,11 @@ pub enum AXfrAddressFoldingWitness {
 
 impl AXfrAddressFoldingWitness {
     /// Get the default folding witness.
+    /// It is a placeholder for building the constraint system, and must not be used for proving.
     pub fn default(address_format: AddressFormat) -> Self {
         match address_format {
             AddressFormat::SECP256K1 => Self::Secp256k1(
-                address_folding_secp256k1::AXfrAddressFoldingWitnessSecp256k1::default(),
+                AXfrAddressFoldingWitnessSecp256k1::new_uninitialized_for_verifier(),
             ),
             AddressFormat::ED25519 => {
                 Self::Ed25519(address_folding_ed25519::AXfrAddressFoldingWitnessEd25519::default())
@@ -104,6 +158,14 @@ impl AXfrAddressFoldingWitness {
         }
     }
 
+    /// Return whether this is a placeholder witness that cannot be used for proving.
+    pub(crate) fn is_uninitialized_for_verifier(&self) -> bool {
+        match self {
+            AXfrAddressFoldingWitness::Secp256k1(a) => a.is_uninitialized_for_verifier(),
+            AXfrAddressFoldingWitness::Ed25519(_) => false,
+        }
+    }
+
     pub(crate) fn keypair(&self) -> KeyPair {
         match self {
             AXfrAddressFoldingWitness::Secp256k1(a) => a.keypair.clone(),
@@ -112,6 +174,24 @@ impl AXfrAddressFoldingWitness {
     }
 }
 
+/// Sign the serialized body of a note, with the sender's key pair.
+pub(crate) fn sign_note_body<B: Serialize>(keypair: &KeyPair, body: &B) -> Result<Signature> {
+    let msg = bincode::serialize(body)
+        .map_err(|_| NoahError::SerializationError)
+        .c(d!())?;
+    keypair.sign(&msg)
+}
+
+/// Verify the signature of the serialized body of a note, by the sender's public key.
+pub(crate) fn verify_note_body<B: Serialize>(
+    pub_key: &PublicKey,
+    body: &B,
+    signature: &Signature,
+) -> Result<()> {
+    let msg = bincode::serialize(body).c(d!(NoahError::SerializationError))?;
+    pub_key.verify(&msg, signature).c(d!())
+}
+
 /// Check that inputs have Merkle tree witness and matching key pair.
 fn check_inputs(inputs: &[OpenAnonAssetRecord], keypair: &KeyPair) -> Result<()> {
     for input in inputs.iter() {
@@ -163,6 +243,250 @@ fn check_asset_amount(
     Ok(())
 }
 
+/// Compute the change of each asset type, i.e., the amount that remains after paying the explicit
+/// outputs and, for `fee_type`, the fee. The change can then be added as outputs to the sender,
+/// so that the transfer balances. Asset types without change are omitted.
+/// Return an error if the inputs cannot cover the outputs and the fee for some asset type, or if
+/// the change of an asset type does not fit in a `u64`.
+pub fn compute_change(
+    inputs: &[OpenAnonAssetRecord],
+    explicit_outputs: &[(u64, AssetType)],
+    fee: u32,
+    fee_type: AssetType,
+) -> Result<BTreeMap<AssetType, u64>> {
+    compute_change_of_amounts(
+        inputs
+            .iter()
+            .map(|record| (record.amount, record.asset_type)),
+        explicit_outputs,
+        fee,
+        fee_type,
+    )
+}
+
+/// Return the distinct asset types of the records, sorted, e.g., to know how many asset types
+/// a multi-asset transfer involves.
+pub fn distinct_asset_types(records: &[OpenAnonAssetRecord]) -> Vec<AssetType> {
+    records
+        .iter()
+        .map(|record| record.asset_type)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn compute_change_of_amounts<I: Iterator<Item = (u64, AssetType)>>(
+    inputs: I,
+    explicit_outputs: &[(u64, AssetType)],
+    fee: u32,
+    fee_type: AssetType,
+) -> Result<BTreeMap<AssetType, u64>> {
+    let mut balances: BTreeMap<AssetType, i128> = BTreeMap::new();
+
+    for (amount, asset_type) in inputs {
+        *balances.entry(asset_type).or_insert(0) += amount as i128;
+    }
+    for (amount, asset_type) in explicit_outputs.iter() {
+        *balances.entry(*asset_type).or_insert(0) -= *amount as i128;
+    }
+    *balances.entry(fee_type).or_insert(0) -= fee as i128;
+
+    let mut change = BTreeMap::new();
+    for (asset_type, sum) in balances.into_iter() {
+        let amount = u64::try_from(sum).c(d!(NoahError::XfrCreationAssetAmountError))?;
+        if amount > 0 {
+            change.insert(asset_type, amount);
+        }
+    }
+    Ok(change)
+}
+
+/// The source of an input of a [`TransferPlan`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlannedInput {
+    /// The record at this index of the inputs given to [`plan_consolidation`].
+    Record(usize),
+    /// An output created by an earlier transfer of the plan.
+    Output {
+        /// The index of the earlier transfer.
+        stage: usize,
+        /// The index of the output in that transfer.
+        output: usize,
+    },
+}
+
+/// An anonymous transfer of a supported shape, as part of a consolidation plan.
+/// The outputs of all but the last transfer go back to the sender.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransferPlan {
+    /// The inputs of the transfer.
+    pub inputs: Vec<PlannedInput>,
+    /// The amount and asset type of each output.
+    pub outputs: Vec<(u64, AssetType)>,
+    /// The fee of the transfer, paid in `FEE_TYPE`.
+    pub fee: u32,
+}
+
+/// Plan a sequence of anonymous transfers of supported shapes that spends all the `inputs`
+/// and ends with `target_outputs`, followed by the change of each asset type.
+///
+/// While the inputs do not fit in a single transfer, records are merged by consolidation
+/// transfers that have at most `MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_SENDER` inputs and
+/// one output per asset type. `fee_fn` gives the fee for a given number of inputs and outputs.
+pub fn plan_consolidation<F: Fn(usize, usize) -> u32>(
+    inputs: &[OpenAnonAssetRecord],
+    target_outputs: &[(u64, AssetType)],
+    fee_fn: F,
+) -> Result<Vec<TransferPlan>> {
+    if inputs.is_empty() {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    let mut pool: Vec<(PlannedInput, u64, AssetType)> = inputs
+        .iter()
+        .enumerate()
+        .map(|(i, record)| (PlannedInput::Record(i), record.amount, record.asset_type))
+        .collect();
+    let mut plans = vec![];
+
+    loop {
+        let final_plan = plan_final_transfer(&pool, target_outputs, &fee_fn).c(d!())?;
+        if is_supported_anon_xfr_shape(final_plan.inputs.len(), final_plan.outputs.len()) {
+            plans.push(final_plan);
+            return Ok(plans);
+        }
+
+        let plan = plan_consolidation_transfer(&mut pool, &fee_fn).c(d!())?;
+        let stage = plans.len();
+        for (output, (amount, asset_type)) in plan.outputs.iter().enumerate() {
+            pool.push((PlannedInput::Output { stage, output }, *amount, *asset_type));
+        }
+        plans.push(plan);
+    }
+}
+
+/// Plan the transfer that spends the whole `pool`, pays `target_outputs` and returns the change.
+fn plan_final_transfer<F: Fn(usize, usize) -> u32>(
+    pool: &[(PlannedInput, u64, AssetType)],
+    target_outputs: &[(u64, AssetType)],
+    fee_fn: &F,
+) -> Result<TransferPlan> {
+    // The fee depends on the number of outputs, so the change outputs are decided before the fee.
+    let before_fee = compute_change_of_amounts(
+        pool.iter()
+            .map(|(_, amount, asset_type)| (*amount, *asset_type)),
+        target_outputs,
+        0,
+        FEE_TYPE,
+    )
+    .c(d!())?;
+    let change_types: Vec<AssetType> = before_fee
+        .iter()
+        .filter(|(_, amount)| **amount > 0)
+        .map(|(asset_type, _)| *asset_type)
+        .collect();
+
+    let fee = fee_fn(pool.len(), target_outputs.len() + change_types.len());
+    let change = compute_change_of_amounts(
+        pool.iter()
+            .map(|(_, amount, asset_type)| (*amount, *asset_type)),
+        target_outputs,
+        fee,
+        FEE_TYPE,
+    )
+    .c(d!())?;
+
+    let mut outputs = target_outputs.to_vec();
+    for asset_type in change_types.iter() {
+        let amount = change.get(asset_type).copied().unwrap_or(0);
+        outputs.push((amount, *asset_type));
+    }
+
+    Ok(TransferPlan {
+        inputs: pool.iter().map(|(input, _, _)| *input).collect(),
+        outputs,
+        fee,
+    })
+}
+
+/// Plan a consolidation transfer, removing its inputs from `pool`.
+/// The inputs are the largest record of `FEE_TYPE`, which pays the fee, and the records of the
+/// asset types with the most records, within the consolidation shape limits.
+fn plan_consolidation_transfer<F: Fn(usize, usize) -> u32>(
+    pool: &mut Vec<(PlannedInput, u64, AssetType)>,
+    fee_fn: &F,
+) -> Result<TransferPlan> {
+    let fee_record = pool
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, _, asset_type))| *asset_type == FEE_TYPE)
+        .max_by_key(|(_, (_, amount, _))| *amount)
+        .map(|(i, _)| i)
+        .c(d!(NoahError::XfrCreationAssetAmountError))?;
+
+    let mut counts: BTreeMap<AssetType, usize> = BTreeMap::new();
+    for (_, _, asset_type) in pool.iter() {
+        *counts.entry(*asset_type).or_insert(0) += 1;
+    }
+    let mut asset_types: Vec<AssetType> = counts
+        .keys()
+        .filter(|asset_type| **asset_type != FEE_TYPE)
+        .copied()
+        .collect();
+    asset_types.sort_by(|a, b| counts[b].cmp(&counts[a]));
+    asset_types.insert(0, FEE_TYPE);
+    asset_types.truncate(MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_RECEIVER);
+
+    let mut selected = vec![fee_record];
+    for asset_type in asset_types.iter() {
+        for (i, (_, _, t)) in pool.iter().enumerate() {
+            if selected.len() == MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_SENDER {
+                break;
+            }
+            if t == asset_type && i != fee_record {
+                selected.push(i);
+            }
+        }
+    }
+
+    let mut chunk = vec![];
+    selected.sort_unstable();
+    for i in selected.into_iter().rev() {
+        chunk.push(pool.remove(i));
+    }
+    chunk.reverse();
+
+    let chunk_types: Vec<AssetType> = asset_types
+        .into_iter()
+        .filter(|asset_type| chunk.iter().any(|(_, _, t)| t == asset_type))
+        .collect();
+
+    // A consolidation that does not reduce the number of records cannot make progress.
+    if chunk.len() <= chunk_types.len() {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    let fee = fee_fn(chunk.len(), chunk_types.len());
+    let change = compute_change_of_amounts(
+        chunk
+            .iter()
+            .map(|(_, amount, asset_type)| (*amount, *asset_type)),
+        &[],
+        fee,
+        FEE_TYPE,
+    )
+    .c(d!())?;
+
+    Ok(TransferPlan {
+        inputs: chunk.iter().map(|(input, _, _)| *input).collect(),
+        outputs: chunk_types
+            .iter()
+            .map(|asset_type| (change.get(asset_type).copied().unwrap_or(0), *asset_type))
+            .collect(),
+        fee,
+    })
+}
+
 /// Check that the Merkle roots in input asset records are the same
 /// `inputs` is guaranteed to have at least one asset record.
 fn check_roots(inputs: &[OpenAnonAssetRecord]) -> Result<()> {
@@ -232,6 +556,50 @@ pub fn decrypt_memo(
     parse_memo(&plaintext, key_pair, abar)
 }
 
+/// Try to open each output `(record, owner memo)` with the key pair, e.g., to find the records
+/// of a wallet in a block. Return the opened record of each output owned by the key pair,
+/// and `None` for the other outputs.
+pub fn scan_outputs(
+    outputs: &[(AnonAssetRecord, AxfrOwnerMemo)],
+    key_pair: &KeyPair,
+) -> Vec<Option<OpenAnonAssetRecord>> {
+    outputs
+        .iter()
+        .map(|(record, memo)| open_output(record, memo, key_pair))
+        .collect()
+}
+
+/// Try to open each output `(record, owner memo)` with the key pair, in parallel.
+/// The result is the same as the one of `scan_outputs`.
+#[cfg(feature = "parallel")]
+pub fn scan_outputs_par(
+    outputs: &[(AnonAssetRecord, AxfrOwnerMemo)],
+    key_pair: &KeyPair,
+) -> Vec<Option<OpenAnonAssetRecord>> {
+    outputs
+        .par_iter()
+        .map(|(record, memo)| open_output(record, memo, key_pair))
+        .collect()
+}
+
+fn open_output(
+    record: &AnonAssetRecord,
+    memo: &AxfrOwnerMemo,
+    key_pair: &KeyPair,
+) -> Option<OpenAnonAssetRecord> {
+    OpenAnonAssetRecordBuilder::from_abar(record, memo.clone(), key_pair)
+        .and_then(|builder| builder.build())
+        .ok()
+}
+
+/// Check whether two anonymous transfer notes spend a common input, i.e., share a nullifier,
+/// so that at most one of them can be accepted, e.g., in a mempool.
+pub fn notes_conflict(a: &AXfrNote, b: &AXfrNote) -> bool {
+    a.nullifiers()
+        .iter()
+        .any(|nullifier| b.nullifiers().contains(nullifier))
+}
+
 /// Compute the nullifier.
 pub fn nullify(
     key_pair: &KeyPair,
@@ -243,7 +611,7 @@ pub fn nullify(
 
     let pow_2_64 = BLSScalar::from(u64::MAX).add(&BLSScalar::from(1u32));
     let uid_shifted = BLSScalar::from(uid).mul(&pow_2_64);
-    let uid_amount = uid_shifted.add(&BLSScalar::from(amount));
+    let uid_amount = uid_shifted.add(&amount_to_field_checked(amount)?);
 
     let public_key_scalars = pub_key.to_bls_scalars()?;
     let secret_key_scalars = key_pair.get_sk().to_bls_scalars()?;
@@ -273,6 +641,22 @@ pub fn nullify(
 /// Length of the amount allowed in anonymous assets.
 pub(crate) const AMOUNT_LEN: usize = 64;
 
+/// Convert an amount into the field of the circuit, checking that it is represented exactly
+/// and fits in the `AMOUNT_LEN` bits that the balance gates range-check, so that the amount
+/// in the circuit cannot diverge from the plaintext one.
+pub fn amount_to_field_checked(amount: u64) -> Result<BLSScalar> {
+    if amount.checked_shr(AMOUNT_LEN as u32).unwrap_or(0) != 0 {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    let scalar = BLSScalar::from(amount);
+    let bytes = scalar.to_bytes();
+    if bytes[..8] != amount.to_le_bytes() || bytes[8..].iter().any(|b| *b != 0) {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    Ok(scalar)
+}
+
 /// Depth of the Merkle Tree circuit.
 pub const TREE_DEPTH: usize = 30;
 
@@ -313,34 +697,54 @@ pub fn commit(
     amount: u64,
     asset_type_scalar: BLSScalar,
 ) -> Result<(Commitment, AnemoiVLHTrace<BLSScalar, 2, 12>)> {
-    let address_format_number: BLSScalar;
-    match public_key.0 {
-        PublicKeyInner::Ed25519(_) => {
-            address_format_number = BLSScalar::one();
-        }
-        PublicKeyInner::Secp256k1(_) => {
-            address_format_number = BLSScalar::zero();
-        }
+    let public_key_scalars = public_key_commitment_scalars(public_key)?;
+    let trace = AnemoiJive381::eval_variable_length_hash_with_trace(&commitment_input(
+        &public_key_scalars,
+        blind,
+        amount,
+        asset_type_scalar,
+    )?);
+
+    Ok((trace.output, trace))
+}
+
+/// Compute the address format number and the scalars of the public key, as committed
+/// in the record's commitment.
+pub(crate) fn public_key_commitment_scalars(public_key: &PublicKey) -> Result<[BLSScalar; 4]> {
+    let address_format_number = match public_key.0 {
+        PublicKeyInner::Ed25519(_) => BLSScalar::one(),
+        PublicKeyInner::Secp256k1(_) => BLSScalar::zero(),
         PublicKeyInner::EthAddress(_) => {
             return Err(eg!(NoahError::ParameterError));
         }
     };
-
-    let zero = BLSScalar::zero();
     let public_key_scalars = public_key.to_bls_scalars()?;
 
-    let trace = AnemoiJive381::eval_variable_length_hash_with_trace(&[
-        zero, /* protocol version number */
+    Ok([
+        address_format_number,
+        public_key_scalars[0],
+        public_key_scalars[1],
+        public_key_scalars[2],
+    ])
+}
+
+/// Build the input of the hash for the record's commitment.
+pub(crate) fn commitment_input(
+    public_key_scalars: &[BLSScalar; 4],
+    blind: BLSScalar,
+    amount: u64,
+    asset_type_scalar: BLSScalar,
+) -> Result<[BLSScalar; 8]> {
+    Ok([
+        BLSScalar::zero(), /* protocol version number */
         blind,
-        BLSScalar::from(amount),
+        amount_to_field_checked(amount)?,
         asset_type_scalar,
-        address_format_number, /* address format number */
-        public_key_scalars[0], /* public key */
+        public_key_scalars[0], /* address format number */
         public_key_scalars[1], /* public key */
         public_key_scalars[2], /* public key */
-    ]);
-
-    Ok((trace.output, trace))
+        public_key_scalars[3], /* public key */
+    ])
 }
 
 /// Add the nullifier constraints to the constraint system.
@@ -633,3 +1037,231 @@ pub fn axfr_hybrid_decrypt(sk: &SecretKey, ctext: &[u8]) -> Result<Vec<u8>> {
     };
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::anon_xfr::{
+        amount_to_field_checked, compute_change, distinct_asset_types, plan_consolidation,
+        scan_outputs,
+        structs::{AnonAssetRecord, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
+        PlannedInput, AMOUNT_LEN, FEE_TYPE,
+    };
+    use crate::keys::KeyPair;
+    use crate::parameters::params::is_supported_anon_xfr_shape;
+    use crate::parameters::AddressFormat::{ED25519, SECP256K1};
+    use crate::xfr::structs::AssetType;
+    use noah_algebra::{bls12_381::BLSScalar, collections::BTreeMap, prelude::*};
+    use noah_plonk::plonk::constraint_system::{ConstraintSystem, TurboCS};
+
+    fn build_oabar(amount: u64, asset_type: AssetType, keypair: &KeyPair) -> OpenAnonAssetRecord {
+        let mut prng = test_rng();
+        OpenAnonAssetRecordBuilder::new()
+            .amount(amount)
+            .asset_type(asset_type)
+            .pub_key(&keypair.get_pk())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_distinct_asset_types() {
+        let mut prng = test_rng();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_1 = AssetType::from_identical_byte(1);
+        let asset_2 = AssetType::from_identical_byte(2);
+
+        let records = vec![
+            build_oabar(10, asset_2, &keypair),
+            build_oabar(20, FEE_TYPE, &keypair),
+            build_oabar(30, asset_2, &keypair),
+            build_oabar(40, asset_1, &keypair),
+        ];
+        assert_eq!(
+            distinct_asset_types(&records),
+            vec![FEE_TYPE, asset_1, asset_2]
+        );
+        assert!(distinct_asset_types(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_amount_to_field_checked() {
+        for amount in [0, 1, u32::MAX as u64, u64::MAX - 1, u64::MAX] {
+            let scalar = amount_to_field_checked(amount).unwrap();
+            assert_eq!(scalar, BLSScalar::from(amount));
+        }
+
+        // u64::MAX fills the low limb of the scalar, and one more carries into the next limb
+        let max = amount_to_field_checked(u64::MAX).unwrap();
+        let mut expected = [0u8; 32];
+        expected[..8].copy_from_slice(&[0xff; 8]);
+        assert_eq!(max.to_bytes(), expected);
+        let mut expected = [0u8; 32];
+        expected[8] = 1;
+        assert_eq!(max.add(&BLSScalar::one()).to_bytes(), expected);
+
+        // the balance gates accept u64::MAX, but not the next amount
+        for (amount, is_ok) in [(max, true), (max.add(&BLSScalar::one()), false)] {
+            let mut cs = TurboCS::new();
+            let var = cs.new_variable(amount);
+            cs.range_check(var, AMOUNT_LEN);
+            let witness = cs.get_and_clear_witness();
+            assert_eq!(cs.verify_witness(&witness, &[]).is_ok(), is_ok);
+        }
+
+        let mut prng = test_rng();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let oabar = build_oabar(u64::MAX, FEE_TYPE, &keypair);
+        assert_eq!(oabar.get_amount(), u64::MAX);
+    }
+
+    #[test]
+    fn test_scan_outputs() {
+        let mut prng = test_rng();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let other_keypair = KeyPair::sample(&mut prng, ED25519);
+
+        let oabars = (0..8u64)
+            .map(|i| {
+                let owner = if i % 3 == 0 { &keypair } else { &other_keypair };
+                build_oabar(i, FEE_TYPE, owner)
+            })
+            .collect::<Vec<_>>();
+        let mut outputs = oabars
+            .iter()
+            .map(|oabar| {
+                (
+                    AnonAssetRecord::from_oabar(oabar),
+                    oabar.get_owner_memo().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        // a memo that does not open the record
+        outputs[6].1 = outputs[0].1.clone();
+
+        let scanned = scan_outputs(&outputs, &keypair);
+        assert_eq!(scanned.len(), outputs.len());
+        for (i, (oabar, scanned)) in oabars.iter().zip(scanned.iter()).enumerate() {
+            if i % 3 == 0 && i != 6 {
+                assert_eq!(scanned.as_ref(), Some(oabar));
+            } else {
+                assert!(scanned.is_none());
+            }
+        }
+
+        #[cfg(feature = "parallel")]
+        assert_eq!(super::scan_outputs_par(&outputs, &keypair), scanned);
+    }
+
+    #[test]
+    fn test_compute_change() {
+        let mut prng = test_rng();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_1 = AssetType::from_identical_byte(1);
+        let asset_2 = AssetType::from_identical_byte(2);
+
+        let inputs = vec![
+            build_oabar(30, FEE_TYPE, &keypair),
+            build_oabar(50, asset_1, &keypair),
+            build_oabar(20, asset_1, &keypair),
+            build_oabar(5, asset_2, &keypair),
+        ];
+        let outputs = vec![(10, FEE_TYPE), (60, asset_1), (5, asset_2)];
+
+        let change = compute_change(&inputs, &outputs, 7, FEE_TYPE).unwrap();
+        assert_eq!(change.len(), 2);
+        assert_eq!(change[&FEE_TYPE], 13);
+        assert_eq!(change[&asset_1], 10);
+        assert!(!change.contains_key(&asset_2));
+
+        // insufficient funds for an asset
+        let outputs = vec![(10, FEE_TYPE), (71, asset_1)];
+        assert!(compute_change(&inputs, &outputs, 7, FEE_TYPE).is_err());
+
+        // insufficient funds for the fee
+        let outputs = vec![(25, FEE_TYPE)];
+        assert!(compute_change(&inputs, &outputs, 7, FEE_TYPE).is_err());
+
+        // an output of an asset type without inputs
+        let outputs = vec![(1, AssetType::from_identical_byte(3))];
+        assert!(compute_change(&inputs, &outputs, 0, FEE_TYPE).is_err());
+
+        // a change that does not fit in a u64
+        let inputs = vec![
+            build_oabar(u64::MAX, asset_1, &keypair),
+            build_oabar(1, asset_1, &keypair),
+            build_oabar(7, FEE_TYPE, &keypair),
+        ];
+        assert!(compute_change(&inputs, &[], 7, FEE_TYPE).is_err());
+        let change = compute_change(&inputs, &[(1, asset_1)], 7, FEE_TYPE).unwrap();
+        assert_eq!(change.len(), 1);
+        assert_eq!(change[&asset_1], u64::MAX);
+    }
+
+    #[test]
+    fn test_plan_consolidation() {
+        let mut prng = test_rng();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_1 = AssetType::from_identical_byte(1);
+        let fee_fn = |x: usize, y: usize| 5 + (x as u32) + 2 * (y as u32);
+
+        let mut inputs = vec![];
+        for _ in 0..12 {
+            inputs.push(build_oabar(100, FEE_TYPE, &keypair));
+        }
+        for _ in 0..9 {
+            inputs.push(build_oabar(10, asset_1, &keypair));
+        }
+        let targets = vec![(500, FEE_TYPE), (85, asset_1)];
+
+        let plans = plan_consolidation(&inputs, &targets, fee_fn).unwrap();
+        assert!(plans.len() > 1);
+
+        // Replay the plan: every input and intermediate output is spent exactly once,
+        // every transfer has a supported shape and balances.
+        let mut spent_records = vec![false; inputs.len()];
+        let mut unspent_outputs = BTreeMap::new();
+        for (stage, plan) in plans.iter().enumerate() {
+            assert!(is_supported_anon_xfr_shape(
+                plan.inputs.len(),
+                plan.outputs.len()
+            ));
+            assert_eq!(plan.fee, fee_fn(plan.inputs.len(), plan.outputs.len()));
+
+            let mut balances: BTreeMap<AssetType, i128> = BTreeMap::new();
+            for input in plan.inputs.iter() {
+                let (amount, asset_type) = match input {
+                    PlannedInput::Record(i) => {
+                        assert!(!spent_records[*i]);
+                        spent_records[*i] = true;
+                        (inputs[*i].amount, inputs[*i].asset_type)
+                    }
+                    PlannedInput::Output { stage, output } => {
+                        unspent_outputs.remove(&(*stage, *output)).unwrap()
+                    }
+                };
+                *balances.entry(asset_type).or_insert(0) += amount as i128;
+            }
+            for (output, (amount, asset_type)) in plan.outputs.iter().enumerate() {
+                *balances.entry(*asset_type).or_insert(0) -= *amount as i128;
+                unspent_outputs.insert((stage, output), (*amount, *asset_type));
+            }
+            *balances.entry(FEE_TYPE).or_insert(0) -= plan.fee as i128;
+            assert!(balances.values().all(|v| *v == 0));
+        }
+        assert!(spent_records.iter().all(|x| *x));
+
+        let last = plans.last().unwrap();
+        assert_eq!(&last.outputs[..targets.len()], &targets[..]);
+        assert_eq!(unspent_outputs.len(), last.outputs.len());
+
+        // A single transfer is planned when the inputs fit.
+        let plans = plan_consolidation(&inputs[..3], &[(150, FEE_TYPE)], fee_fn).unwrap();
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].outputs.len(), 2);
+
+        // The inputs cannot cover the outputs and the fees.
+        assert!(plan_consolidation(&inputs, &[(1200, FEE_TYPE)], fee_fn).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::anon_xfr::address_folding_ed25519::{
+    create_address_folding_ed25519, prepare_verifier_input_ed25519,
+    prove_address_folding_in_cs_ed25519, verify_address_folding_ed25519,
+};
+use crate::anon_xfr::address_folding_secp256k1::{
+    create_address_folding_secp256k1, prepare_verifier_input_secp256k1,
+    prove_address_folding_in_cs_secp256k1, verify_address_folding_secp256k1,
+};
+use crate::anon_xfr::{
+    abar_to_abar::add_payers_witnesses,
+    commit, commit_in_cs, compute_merkle_root_variables,
+    structs::{AccElemVars, OpenAnonAssetRecord, PayerWitness},
+    AXfrAddressFoldingInstance, AXfrAddressFoldingWitness, AXfrPlonkPf, TurboPlonkCS,
+};
+use crate::keys::{KeyPair, SecretKey};
+use crate::parameters::params::{ProverParams, VerifierParams};
+use crate::xfr::structs::AssetType;
+use digest::{consts::U64, Digest};
+use merlin::Transcript;
+use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+use noah_crypto::basic::anemoi_jive::{
+    AnemoiJive, AnemoiJive381, AnemoiVLHTrace, ANEMOI_JIVE_381_SALTS,
+};
+use noah_plonk::plonk::{
+    constraint_system::TurboCS, prover::prover_with_lagrange, verifier::verifier,
+};
+
+/// The domain separator for the proof of ownership, for the Plonk proof.
+const OWNERSHIP_PLONK_PROOF_TRANSCRIPT: &[u8] = b"ABAR Ownership Plonk Proof";
+
+/// The domain separator for the proof of ownership, for address folding.
+const OWNERSHIP_FOLDING_PROOF_TRANSCRIPT: &[u8] = b"ABAR Ownership Folding Proof";
+
+/// The label of the nonce of the proof of ownership in the transcripts.
+const OWNERSHIP_NONCE_TRANSCRIPT: &[u8] = b"Ownership nonce";
+
+/// A proof that the prover owns an anonymous asset record in the Merkle tree, without spending it.
+///
+/// The amount and the asset type of the record are revealed, but neither its position
+/// in the tree nor a nullifier is, so the record stays spendable and unlinkable.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OwnershipProof {
+    /// The Merkle root hash.
+    pub merkle_root: BLSScalar,
+    /// The Merkle root version.
+    pub merkle_root_version: u64,
+    /// The amount of the owned record.
+    pub amount: u64,
+    /// The asset type of the owned record.
+    pub asset_type: AssetType,
+    /// The Plonk proof (assuming non-malleability).
+    pub proof: AXfrPlonkPf,
+    /// The address folding instance.
+    pub folding_instance: AXfrAddressFoldingInstance,
+}
+
+/// Prove the ownership of an anonymous asset record, without creating a nullifier for it.
+///
+/// The proof is bound to `nonce`, e.g., a challenge chosen by the verifier, so that it cannot
+/// be replayed in another context.
+pub fn prove_ownership<R: CryptoRng + RngCore, D: Digest<OutputSize = U64> + Default>(
+    prng: &mut R,
+    params: &ProverParams,
+    oabar: &OpenAnonAssetRecord,
+    keypair: &KeyPair,
+    nonce: &[u8],
+    hash: D,
+) -> Result<OwnershipProof> {
+    if oabar.mt_leaf_info.is_none() || keypair.get_pk() != oabar.pub_key {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    let mt_leaf_info = oabar.mt_leaf_info.as_ref().unwrap();
+
+    let (_, input_commitment_trace) = commit(
+        &keypair.get_pk(),
+        oabar.blind,
+        oabar.amount,
+        oabar.asset_type.as_scalar(),
+    )
+    .c(d!())?;
+
+    let witness = PayerWitness {
+        secret_key: keypair.get_sk(),
+        uid: mt_leaf_info.uid,
+        amount: oabar.amount,
+        asset_type: oabar.asset_type.as_scalar(),
+        path: mt_leaf_info.path.clone(),
+        blind: oabar.blind,
+    };
+
+    let mut transcript = Transcript::new(OWNERSHIP_FOLDING_PROOF_TRANSCRIPT);
+    transcript.append_message(OWNERSHIP_NONCE_TRANSCRIPT, nonce);
+
+    let (folding_instance, folding_witness) = match keypair.get_sk_ref() {
+        SecretKey::Secp256k1(_) => {
+            let (folding_instance, folding_witness) =
+                create_address_folding_secp256k1(prng, hash, &mut transcript, keypair)?;
+            (
+                AXfrAddressFoldingInstance::Secp256k1(folding_instance),
+                AXfrAddressFoldingWitness::Secp256k1(folding_witness),
+            )
+        }
+        SecretKey::Ed25519(_) => {
+            let (folding_instance, folding_witness) =
+                create_address_folding_ed25519(prng, hash, &mut transcript, keypair)?;
+            (
+                AXfrAddressFoldingInstance::Ed25519(folding_instance),
+                AXfrAddressFoldingWitness::Ed25519(folding_witness),
+            )
+        }
+    };
+
+    let mut transcript = Transcript::new(OWNERSHIP_PLONK_PROOF_TRANSCRIPT);
+    transcript.append_message(OWNERSHIP_NONCE_TRANSCRIPT, nonce);
+    let (mut cs, _) = build_ownership_cs(&witness, &input_commitment_trace, &folding_witness);
+    let cs_witness = cs.get_and_clear_witness();
+
+    let proof = prover_with_lagrange(
+        prng,
+        &mut transcript,
+        &params.pcs,
+        params.lagrange_pcs.as_ref(),
+        &params.cs,
+        &params.prover_params,
+        &cs_witness,
+    )
+    .c(d!(NoahError::AXfrProofError))?;
+
+    Ok(OwnershipProof {
+        merkle_root: mt_leaf_info.root,
+        merkle_root_version: mt_leaf_info.root_version,
+        amount: oabar.amount,
+        asset_type: oabar.asset_type,
+        proof,
+        folding_instance,
+    })
+}
+
+/// Verify a proof of ownership against the given Merkle root and nonce.
+pub fn verify_ownership<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    proof: &OwnershipProof,
+    merkle_root: &BLSScalar,
+    nonce: &[u8],
+    hash: D,
+) -> Result<()> {
+    if *merkle_root != proof.merkle_root {
+        return Err(eg!(NoahError::AXfrVerificationError));
+    }
+
+    let mut transcript = Transcript::new(OWNERSHIP_FOLDING_PROOF_TRANSCRIPT);
+    transcript.append_message(OWNERSHIP_NONCE_TRANSCRIPT, nonce);
+
+    let address_folding_public_input = match &proof.folding_instance {
+        AXfrAddressFoldingInstance::Secp256k1(a) => {
+            let (beta, lambda) = verify_address_folding_secp256k1(hash, &mut transcript, a)?;
+            prepare_verifier_input_secp256k1(&a, &beta, &lambda)
+        }
+        AXfrAddressFoldingInstance::Ed25519(a) => {
+            let (beta, lambda) = verify_address_folding_ed25519(hash, &mut transcript, a)?;
+            prepare_verifier_input_ed25519(&a, &beta, &lambda)
+        }
+    };
+
+    let mut transcript = Transcript::new(OWNERSHIP_PLONK_PROOF_TRANSCRIPT);
+    transcript.append_message(OWNERSHIP_NONCE_TRANSCRIPT, nonce);
+    let mut online_inputs = vec![];
+    online_inputs.push(merkle_root.clone());
+    online_inputs.push(BLSScalar::from(proof.amount));
+    online_inputs.push(proof.asset_type.as_scalar());
+    online_inputs.extend_from_slice(&address_folding_public_input);
+
+    verifier(
+        &mut transcript,
+        &params.shrunk_vk,
+        &params.shrunk_cs,
+        &params.verifier_params,
+        &online_inputs,
+        &proof.proof,
+    )
+    .c(d!(NoahError::AXfrVerificationError))
+}
+
+/// Construct the constraint system for the proof of ownership.
+///
+/// This is the anonymous-to-transparent circuit without the nullifier: it opens the
+/// record commitment, authenticates its Merkle path, and binds the public key to the
+/// address folding instance.
+pub fn build_ownership_cs(
+    payer_witness: &PayerWitness,
+    input_commitment_trace: &AnemoiVLHTrace<BLSScalar, 2, 12>,
+    folding_witness: &AXfrAddressFoldingWitness,
+) -> (TurboPlonkCS, usize) {
+    let mut cs = TurboCS::new();
+
+    cs.load_anemoi_jive_parameters::<AnemoiJive381>();
+
+    let payers_witnesses_vars = add_payers_witnesses(&mut cs, &[payer_witness]);
+    let payer_witness_var = &payers_witnesses_vars[0];
+
+    let keypair = folding_witness.keypair();
+    let public_key_scalars = keypair.get_pk().to_bls_scalars().unwrap();
+    let secret_key_scalars = keypair.get_sk().to_bls_scalars().unwrap();
+
+    let public_key_scalars_vars = [
+        cs.new_variable(public_key_scalars[0]),
+        cs.new_variable(public_key_scalars[1]),
+        cs.new_variable(public_key_scalars[2]),
+    ];
+    let secret_key_scalars_vars = [
+        cs.new_variable(secret_key_scalars[0]),
+        cs.new_variable(secret_key_scalars[1]),
+    ];
+
+    let key_type = match keypair.get_sk() {
+        SecretKey::Ed25519(_) => cs.new_variable(BLSScalar::one()),
+        SecretKey::Secp256k1(_) => cs.new_variable(BLSScalar::zero()),
+    };
+
+    // commitments
+    let com_abar_in_var = commit_in_cs(
+        &mut cs,
+        payer_witness_var.blind,
+        payer_witness_var.amount,
+        payer_witness_var.asset_type,
+        key_type,
+        &public_key_scalars_vars,
+        input_commitment_trace,
+    );
+
+    // Merkle path authentication
+    let acc_elem = AccElemVars {
+        uid: payer_witness_var.uid,
+        commitment: com_abar_in_var,
+    };
+
+    let mut path_traces = Vec::new();
+    let (commitment, _) = commit(
+        &keypair.get_pk(),
+        payer_witness.blind,
+        payer_witness.amount,
+        payer_witness.asset_type,
+    )
+    .unwrap();
+    let leaf_trace = AnemoiJive381::eval_variable_length_hash_with_trace(&[
+        BLSScalar::from(payer_witness.uid),
+        commitment,
+    ]);
+    for (i, mt_node) in payer_witness.path.nodes.iter().enumerate() {
+        let trace = AnemoiJive381::eval_jive_with_trace(
+            &[mt_node.left, mt_node.mid],
+            &[mt_node.right, ANEMOI_JIVE_381_SALTS[i]],
+        );
+        path_traces.push(trace);
+    }
+
+    let root_var = compute_merkle_root_variables(
+        &mut cs,
+        acc_elem,
+        &payer_witness_var.path,
+        &leaf_trace,
+        &path_traces,
+    );
+
+    // prepare public inputs variables
+    cs.prepare_pi_variable(root_var);
+    cs.prepare_pi_variable(payer_witness_var.amount);
+    cs.prepare_pi_variable(payer_witness_var.asset_type);
+
+    match folding_witness {
+        AXfrAddressFoldingWitness::Secp256k1(a) => prove_address_folding_in_cs_secp256k1(
+            &mut cs,
+            &public_key_scalars_vars,
+            &secret_key_scalars_vars,
+            &a,
+        )
+        .unwrap(),
+        AXfrAddressFoldingWitness::Ed25519(a) => prove_address_folding_in_cs_ed25519(
+            &mut cs,
+            &public_key_scalars_vars,
+            &secret_key_scalars_vars,
+            &a,
+        )
+        .unwrap(),
+    }
+
+    // pad the number of constraints to power of two
+    cs.pad();
+
+    let n_constraints = cs.size;
+    (cs, n_constraints)
+}
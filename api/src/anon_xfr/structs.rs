@@ -1,10 +1,18 @@
-use crate::anon_xfr::{axfr_hybrid_decrypt, axfr_hybrid_encrypt, commit, decrypt_memo};
+use crate::anon_xfr::{
+    amount_to_field_checked, axfr_hybrid_decrypt, axfr_hybrid_encrypt, commit, commitment_input,
+    decrypt_memo, public_key_commitment_scalars,
+};
 use crate::keys::{KeyPair, PublicKey, SecretKey};
 use crate::parameters::params::AddressFormat::{ED25519, SECP256K1};
 use crate::xfr::structs::AssetType;
-use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+use noah_algebra::{
+    bls12_381::{BLSScalar, BLS12_381_SCALAR_LEN},
+    prelude::*,
+};
+use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
 use noah_plonk::plonk::constraint_system::VarIndex;
 use serde::Serialize;
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
 /// The nullifier.
@@ -53,6 +61,61 @@ impl AnonAssetRecord {
 
         AnonAssetRecord { commitment }
     }
+
+    /// Compute the commitments of many records, given as `(amount, asset type, public key, blind)`.
+    /// The scalars of each distinct public key and asset type are computed only once, and no
+    /// hash trace is kept, so this is faster than calling `commit` for each record.
+    /// The outputs are the same as those of `commit`.
+    pub fn commit_batch(
+        records: &[(u64, AssetType, &PublicKey, BLSScalar)],
+    ) -> Result<Vec<Commitment>> {
+        let mut public_keys_scalars = HashMap::new();
+        let mut asset_types_scalars = HashMap::new();
+
+        records
+            .iter()
+            .map(|(amount, asset_type, public_key, blind)| {
+                let public_key_scalars = match public_keys_scalars.get(public_key) {
+                    Some(scalars) => *scalars,
+                    None => {
+                        let scalars = public_key_commitment_scalars(public_key).c(d!())?;
+                        public_keys_scalars.insert(*public_key, scalars);
+                        scalars
+                    }
+                };
+                let asset_type_scalar = *asset_types_scalars
+                    .entry(asset_type)
+                    .or_insert_with(|| asset_type.as_scalar());
+
+                Ok(AnemoiJive381::eval_variable_length_hash(
+                    &commitment_input(&public_key_scalars, *blind, *amount, asset_type_scalar)
+                        .c(d!())?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Return the commitment in the canonical big-endian encoding, used to store the leaf.
+    pub fn commitment_bytes(&self) -> [u8; BLS12_381_SCALAR_LEN] {
+        let mut bytes = [0u8; BLS12_381_SCALAR_LEN];
+        bytes.copy_from_slice(&self.commitment.to_bytes());
+        bytes.reverse();
+        bytes
+    }
+
+    /// Parse the anonymous asset record from the big-endian encoding of its commitment.
+    /// Return an error if the encoding is not canonical.
+    pub fn from_commitment_bytes(bytes: &[u8; BLS12_381_SCALAR_LEN]) -> Result<Self> {
+        let mut le_bytes = *bytes;
+        le_bytes.reverse();
+        let commitment = BLSScalar::from_bytes(&le_bytes).c(d!(NoahError::DeserializationError))?;
+
+        let record = AnonAssetRecord { commitment };
+        if record.commitment_bytes() != *bytes {
+            return Err(eg!(NoahError::DeserializationError));
+        }
+        Ok(record)
+    }
 }
 
 /// A Merkle tree leaf.
@@ -232,10 +295,93 @@ impl OpenAnonAssetRecordBuilder {
         if self.oabar.owner_memo.is_none() {
             return Err(eg!(NoahError::InconsistentStructureError));
         }
+
+        // 3. the amount is represented exactly in the circuit
+        amount_to_field_checked(self.oabar.amount).c(d!())?;
         Ok(())
     }
 }
 
+#[derive(Default)]
+/// The outputs of an anonymous transfer to one or several recipients, which build the opened
+/// output records for `init_anon_xfr_note`.
+/// An output of zero amount is rejected as dust, unless explicitly allowed.
+pub struct AnonXfrOutputs {
+    outputs: Vec<(u64, AssetType, PublicKey)>,
+    allow_zero_amount: bool,
+}
+
+impl AnonXfrOutputs {
+    /// Create an empty list of outputs.
+    pub fn new() -> Self {
+        AnonXfrOutputs {
+            ..Default::default()
+        }
+    }
+
+    /// Allow outputs of zero amount.
+    pub fn allow_zero_amount(mut self) -> Self {
+        self.allow_zero_amount = true;
+        self
+    }
+
+    /// Add an output paying `amount` of `asset_type` to `recipient`.
+    pub fn add(mut self, amount: u64, asset_type: AssetType, recipient: &PublicKey) -> Self {
+        self.outputs.push((amount, asset_type, *recipient));
+        self
+    }
+
+    /// Return the number of outputs.
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Return true if there is no output.
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    /// Return the recipients, in the order of their first output, each with the indices of
+    /// the outputs paid to it.
+    pub fn by_recipient(&self) -> Vec<(PublicKey, Vec<usize>)> {
+        let mut groups: Vec<(PublicKey, Vec<usize>)> = vec![];
+        for (i, (_, _, recipient)) in self.outputs.iter().enumerate() {
+            match groups.iter_mut().find(|(pk, _)| pk == recipient) {
+                Some((_, indices)) => indices.push(i),
+                None => groups.push((*recipient, vec![i])),
+            }
+        }
+        groups
+    }
+
+    /// Check the outputs, and build the opened output records, in the order they were added.
+    pub fn build<R: CryptoRng + RngCore>(&self, prng: &mut R) -> Result<Vec<OpenAnonAssetRecord>> {
+        if self.outputs.is_empty() {
+            return Err(eg!(NoahError::ParameterError));
+        }
+
+        let mut oabars = Vec::with_capacity(self.outputs.len());
+        for (i, (amount, asset_type, recipient)) in self.outputs.iter().enumerate() {
+            if *amount == 0 && !self.allow_zero_amount {
+                return Err(eg!(format!(
+                    "output {} pays a zero amount, which is not allowed",
+                    i
+                )));
+            }
+            let oabar = OpenAnonAssetRecordBuilder::new()
+                .amount(*amount)
+                .asset_type(*asset_type)
+                .pub_key(recipient)
+                .finalize(prng)
+                .c(d!())?
+                .build()
+                .c(d!())?;
+            oabars.push(oabar);
+        }
+        Ok(oabars)
+    }
+}
+
 /// An authentication path of a ternary Merkle tree.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MTPath {
@@ -354,10 +500,111 @@ impl AxfrOwnerMemo {
 
 #[cfg(test)]
 mod test {
-    use crate::anon_xfr::structs::PublicKey;
+    use crate::anon_xfr::commit;
+    use crate::anon_xfr::structs::{AnonAssetRecord, OpenAnonAssetRecordBuilder, PublicKey};
     use crate::keys::KeyPair;
-    use crate::parameters::AddressFormat::SECP256K1;
-    use noah_algebra::prelude::*;
+    use crate::parameters::AddressFormat::{ED25519, SECP256K1};
+    use crate::xfr::structs::AssetType;
+    use noah_algebra::{
+        bls12_381::{BLSScalar, BLS12_381_SCALAR_LEN},
+        prelude::*,
+    };
+    use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+
+    #[test]
+    fn test_commit_batch() {
+        let mut prng = test_rng();
+        let keypairs = [
+            KeyPair::sample(&mut prng, SECP256K1),
+            KeyPair::sample(&mut prng, ED25519),
+        ];
+        let public_keys = keypairs.iter().map(|k| k.get_pk()).collect::<Vec<_>>();
+
+        let records = (0..6u64)
+            .map(|i| {
+                (
+                    i * 7,
+                    AssetType::from_identical_byte((i % 2) as u8),
+                    &public_keys[(i % 3 == 0) as usize],
+                    BLSScalar::random(&mut prng),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let commitments = AnonAssetRecord::commit_batch(&records).unwrap();
+        assert_eq!(commitments.len(), records.len());
+        for ((amount, asset_type, public_key, blind), commitment) in
+            records.iter().zip(commitments.iter())
+        {
+            let (expected, _) =
+                commit(public_key, *blind, *amount, asset_type.as_scalar()).unwrap();
+            assert_eq!(*commitment, expected);
+        }
+
+        assert!(AnonAssetRecord::commit_batch(&[]).unwrap().is_empty());
+
+        let address = KeyPair::sample_address(&mut prng).get_pk();
+        let records = [(
+            1,
+            AssetType::from_identical_byte(1),
+            &address,
+            BLSScalar::one(),
+        )];
+        assert!(AnonAssetRecord::commit_batch(&records).is_err());
+    }
+
+    #[test]
+    fn test_abar_commitment_bytes() {
+        let mut prng = test_rng();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(10)
+            .asset_type(AssetType::from_identical_byte(1))
+            .pub_key(&keypair.get_pk())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        let abar = AnonAssetRecord::from_oabar(&oabar);
+
+        // the big-endian encoding of known commitments
+        let expected = [
+            (
+                BLSScalar::from(0x0102030405060708u64),
+                "0000000000000000000000000000000000000000000000000102030405060708",
+            ),
+            (
+                BLSScalar::zero() - BLSScalar::one(),
+                "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000",
+            ),
+        ];
+        for (commitment, hex_bytes) in expected {
+            let record = AnonAssetRecord { commitment };
+            assert_eq!(hex::encode(record.commitment_bytes()), hex_bytes);
+            let bytes: [u8; BLS12_381_SCALAR_LEN] =
+                hex::decode(hex_bytes).unwrap().try_into().unwrap();
+            assert_eq!(
+                AnonAssetRecord::from_commitment_bytes(&bytes).unwrap(),
+                record
+            );
+        }
+
+        let bytes = abar.commitment_bytes();
+        let abar_de = AnonAssetRecord::from_commitment_bytes(&bytes).unwrap();
+        assert_eq!(abar, abar_de);
+
+        // the leaf of the tree is the same for the parsed record
+        let hash_abar = |uid: u64, abar: &AnonAssetRecord| {
+            AnemoiJive381::eval_variable_length_hash(&[BLSScalar::from(uid), abar.commitment])
+        };
+        assert_eq!(hash_abar(5, &abar), hash_abar(5, &abar_de));
+
+        // non-canonical encodings, such as the field modulus, are rejected
+        assert!(AnonAssetRecord::from_commitment_bytes(&[0xff; 32]).is_err());
+        let modulus = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+        let bytes: [u8; BLS12_381_SCALAR_LEN] = hex::decode(modulus).unwrap().try_into().unwrap();
+        assert!(AnonAssetRecord::from_commitment_bytes(&bytes).is_err());
+    }
 
     #[test]
     fn test_axfr_pub_key_serialization() {
@@ -271,19 +271,11 @@ impl PublicKey {
     /// Verify a signature.
     pub fn verify(&self, message: &[u8], signature: &Signature) -> Result<()> {
         match (self.0, signature) {
-            (PublicKeyInner::Ed25519(pk), Signature::Ed25519(sign)) => {
-                pk.verify(message, sign).c(d!(NoahError::SignatureError))
+            (PublicKeyInner::Ed25519(pk), Signature::Ed25519(_)) => {
+                Ed25519SecretKey::verify_body(&pk, message, signature)
             }
-            (PublicKeyInner::Secp256k1(pk), Signature::Secp256k1(sign, _)) => {
-                let mut hasher = Keccak256::new();
-                hasher.update(message);
-                let res = hasher.finalize();
-                let msg = Message::parse_slice(&res[..]).c(d!(NoahError::SignatureError))?;
-                if secp256k1_verify(&msg, sign, &pk) {
-                    Ok(())
-                } else {
-                    Err(eg!(NoahError::SignatureError))
-                }
+            (PublicKeyInner::Secp256k1(pk), Signature::Secp256k1(_, _)) => {
+                Secp256k1SecretKey::verify_body(&pk, message, signature)
             }
             (PublicKeyInner::EthAddress(hash), Signature::Secp256k1(sign, rec)) => {
                 let mut hasher = Keccak256::new();
@@ -312,6 +304,70 @@ impl PublicKey {
     }
 }
 
+/// Signing and verification of serialized note bodies over one signature curve.
+/// A new curve is supported by implementing this trait for its secret key type.
+pub trait NoteSigner {
+    /// The public key type of the curve.
+    type PublicKey;
+
+    /// Sign a serialized note body.
+    fn sign_body(&self, body: &[u8]) -> Result<Signature>;
+
+    /// Verify the signature of a serialized note body.
+    fn verify_body(pub_key: &Self::PublicKey, body: &[u8], signature: &Signature) -> Result<()>;
+}
+
+impl NoteSigner for Ed25519SecretKey {
+    type PublicKey = Ed25519PublicKey;
+
+    fn sign_body(&self, body: &[u8]) -> Result<Signature> {
+        let sign = ed25519_dalek::Keypair::from(
+            ed25519_dalek::SecretKey::from_bytes(&self.to_bytes()).unwrap(),
+        )
+        .sign(body);
+        Ok(Signature::Ed25519(sign))
+    }
+
+    fn verify_body(pub_key: &Self::PublicKey, body: &[u8], signature: &Signature) -> Result<()> {
+        match signature {
+            Signature::Ed25519(sign) => pub_key.verify(body, sign).c(d!(NoahError::SignatureError)),
+            _ => Err(eg!(NoahError::SignatureError)),
+        }
+    }
+}
+
+impl NoteSigner for Secp256k1SecretKey {
+    type PublicKey = Secp256k1PublicKey;
+
+    fn sign_body(&self, body: &[u8]) -> Result<Signature> {
+        // If the Ethereum sign is used outside,
+        // it needs to be dealt with first, only hash in Noah.
+        let mut hasher = Keccak256::new();
+        hasher.update(body);
+        let res = hasher.finalize();
+        let msg = Message::parse_slice(&res[..]).c(d!(NoahError::SignatureError))?;
+        let (sign, rec) = secp256k1_sign(&msg, self);
+        Ok(Signature::Secp256k1(sign, rec))
+    }
+
+    fn verify_body(pub_key: &Self::PublicKey, body: &[u8], signature: &Signature) -> Result<()> {
+        match signature {
+            Signature::Secp256k1(sign, _) => {
+                let mut hasher = Keccak256::new();
+                hasher.update(body);
+                let res = hasher.finalize();
+                let msg = Message::parse_slice(&res[..]).c(d!(NoahError::SignatureError))?;
+                if secp256k1_verify(&msg, sign, pub_key) {
+                    Ok(())
+                } else {
+                    Err(eg!(NoahError::SignatureError))
+                }
+            }
+            _ => Err(eg!(NoahError::SignatureError)),
+        }
+    }
+}
+
 #[derive(Debug)]
 /// The secret key for confidential transfer.
 pub enum SecretKey {
@@ -466,23 +522,8 @@ impl SecretKey {
     /// Sign a message.
     pub fn sign(&self, message: &[u8]) -> Result<Signature> {
         match self {
-            SecretKey::Ed25519(sk) => {
-                let sign = ed25519_dalek::Keypair::from(
-                    ed25519_dalek::SecretKey::from_bytes(&sk.to_bytes()).unwrap(),
-                )
-                .sign(message);
-                Ok(Signature::Ed25519(sign))
-            }
-            SecretKey::Secp256k1(sk) => {
-                // If the Ethereum sign is used outside,
-                // it needs to be dealt with first, only hash in Noah.
-                let mut hasher = Keccak256::new();
-                hasher.update(message);
-                let res = hasher.finalize();
-                let msg = Message::parse_slice(&res[..]).c(d!(NoahError::SignatureError))?;
-                let (sign, rec) = secp256k1_sign(&msg, sk);
-                Ok(Signature::Secp256k1(sign, rec))
-            }
+            SecretKey::Ed25519(sk) => sk.sign_body(message),
+            SecretKey::Secp256k1(sk) => sk.sign_body(message),
         }
     }
 
@@ -556,6 +597,14 @@ impl KeyPair {
         SecretKey::default(address_format).into_keypair()
     }
 
+    /// Get the address format of the keypair, determined by its secret key.
+    pub fn address_format(&self) -> AddressFormat {
+        match self.sec_key {
+            SecretKey::Ed25519(_) => ED25519,
+            SecretKey::Secp256k1(_) => SECP256K1,
+        }
+    }
+
     /// Change to algebra Secp256k1 keypair
     pub fn to_secp256k1(&self) -> Result<(SECP256K1Scalar, SECP256K1G1)> {
         match (&self.sec_key, &self.pub_key) {
@@ -644,6 +693,61 @@ impl KeyPair {
         self.sec_key.sign(msg)
     }
 
+    /// Convert into bytes prefixed by a one-byte curve tag, so that the curve can be
+    /// determined without schema knowledge. The tag is `KeyType::to_byte` of the public key,
+    /// followed by the raw 32-byte secret key and the raw public key:
+    /// - `0` (Ed25519): 32-byte public key.
+    /// - `1` (Secp256k1): 33-byte compressed public key.
+    /// - `2` (EthAddress): 20-byte address.
+    pub fn to_tagged_bytes(&self) -> Vec<u8> {
+        let key_type = match self.pub_key.0 {
+            PublicKeyInner::Ed25519(_) => KeyType::Ed25519,
+            PublicKeyInner::Secp256k1(_) => KeyType::Secp256k1,
+            PublicKeyInner::EthAddress(_) => KeyType::EthAddress,
+        };
+
+        let mut bytes = vec![key_type.to_byte()];
+        bytes.extend_from_slice(&self.sec_key.noah_to_bytes()[1..]);
+        match self.pub_key.0 {
+            PublicKeyInner::Ed25519(pk) => bytes.extend_from_slice(pk.as_bytes()),
+            PublicKeyInner::Secp256k1(pk) => bytes.extend_from_slice(&pk.serialize_compressed()),
+            PublicKeyInner::EthAddress(hash) => bytes.extend_from_slice(&hash),
+        }
+        bytes
+    }
+
+    /// Convert from bytes produced by `to_tagged_bytes`.
+    /// Unknown tags, lengths that do not match the tag, and public keys that
+    /// do not match the secret key are rejected.
+    pub fn from_tagged_bytes(bytes: &[u8]) -> Result<Self> {
+        let (key_type, pk_len) = match bytes.first() {
+            Some(0u8) => (KeyType::Ed25519, PUBLIC_KEY_LENGTH - 2),
+            Some(1u8) => (KeyType::Secp256k1, PUBLIC_KEY_LENGTH - 1),
+            Some(2u8) => (KeyType::EthAddress, 20),
+            _ => return Err(eg!(NoahError::DeserializationError)),
+        };
+        if bytes.len() != SECRET_KEY_LENGTH + pk_len {
+            return Err(eg!(NoahError::DeserializationError));
+        }
+
+        let sk_bytes = &bytes[1..SECRET_KEY_LENGTH];
+        let keypair = match key_type {
+            KeyType::Ed25519 => SecretKey::Ed25519(
+                Ed25519SecretKey::from_bytes(sk_bytes).c(d!(NoahError::DeserializationError))?,
+            )
+            .into_keypair(),
+            KeyType::Secp256k1 => SecretKey::from_secp256k1_with_address(sk_bytes)?.into_keypair(),
+            KeyType::EthAddress => SecretKey::from_secp256k1_with_address(sk_bytes)?
+                .into_keypair()
+                .to_eth_address()?,
+        };
+
+        if keypair.to_tagged_bytes() != bytes {
+            return Err(eg!(NoahError::DeserializationError));
+        }
+        Ok(keypair)
+    }
+
     #[inline(always)]
     /// Return the public key.
     pub fn get_pk(&self) -> PublicKey {
@@ -842,6 +946,25 @@ mod test {
     use ark_ec::{AffineRepr, CurveGroup};
     use ark_std::env;
 
+    #[test]
+    fn address_format() {
+        let mut prng = test_rng();
+        for address_format in [SECP256K1, ED25519] {
+            let keypair = KeyPair::sample(&mut prng, address_format);
+            assert_eq!(keypair.address_format(), address_format);
+
+            let key_type = keypair.to_tagged_bytes()[0];
+            assert_eq!(
+                AddressFormat::from_key_type(KeyType::from_byte(key_type)).unwrap(),
+                address_format
+            );
+        }
+        assert!(AddressFormat::from_key_type(KeyType::EthAddress).is_err());
+
+        let keypair = KeyPair::sample_address(&mut prng);
+        assert_eq!(keypair.address_format(), SECP256K1);
+    }
+
     #[test]
     fn signatures() {
         env::set_var("DETERMINISTIC_TEST_RNG", "1");
@@ -890,6 +1013,64 @@ mod test {
         );
     }
 
+    fn note_signer<S: NoteSigner>(sk: &S, pk: &S::PublicKey, expected: &str) {
+        let body = [7u8; 100];
+        let sig = sk.sign_body(&body).unwrap();
+        assert_eq!(hex::encode(sig.noah_to_bytes()), expected);
+        pnk!(S::verify_body(pk, &body, &sig));
+        msg_eq!(
+            NoahError::SignatureError,
+            S::verify_body(pk, &body[1..], &sig).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn note_signers() {
+        // the secret keys are SHA-256("noah note signer ed25519") and
+        // SHA-256("noah note signer secp256k1"), and the signatures are over 100 bytes 0x07,
+        // computed with RFC 8032 for ed25519 and RFC 6979 ECDSA over Keccak-256 for secp256k1.
+        let sk = Ed25519SecretKey::from_bytes(
+            &hex::decode("4f9464f6cb2265a5fb9647507012bd44d3672e501b9dddbbce4993637f5ea780")
+                .unwrap(),
+        )
+        .unwrap();
+        let pk = Ed25519PublicKey::from(&sk);
+        assert_eq!(
+            hex::encode(pk.to_bytes()),
+            "7e3dcc3e7bd50bc0f9f096a892fa6d2a1ab0e797c6e7a7fb8ceafe3779f189ba"
+        );
+        note_signer(
+            &sk,
+            &pk,
+            "00c7b448b802b69f22610e566db5a9f28510812eee75cdc51db8c250a23abc66\
+             79207163e7a17930abc89c96072ae9c3e3fe425417c0ae23c92dcb7e7af69f770d00",
+        );
+
+        let sk = Secp256k1SecretKey::parse_slice(
+            &hex::decode("df497a4722d852b3691cb899f3f7de493d9dc2c8b40495a19d73fe4d559699ea")
+                .unwrap(),
+        )
+        .unwrap();
+        let pk = Secp256k1PublicKey::from_secret_key(&sk);
+        assert_eq!(
+            hex::encode(pk.serialize_compressed()),
+            "025d1c422b20ce953967e604aa883bfb3baf6bc60b60457f961c29e2e5b50d5011"
+        );
+        note_signer(
+            &sk,
+            &pk,
+            "0182e62cc524f9541faf7c6841bb928277e827781e9e919fcaecf5dfb71dde5532\
+             69d2ea646e67546e4bbc5f72fc84013bec836757ffa94f46809fe569a4067f0801",
+        );
+
+        // a signature over another curve is rejected.
+        let ed25519_sig = Ed25519SecretKey::from_bytes(&[1u8; 32])
+            .unwrap()
+            .sign_body(&[7u8; 100])
+            .unwrap();
+        assert!(Secp256k1SecretKey::verify_body(&pk, &[7u8; 100], &ed25519_sig).is_err());
+    }
+
     fn generate_keypairs<R: CryptoRng + RngCore>(prng: &mut R, n: usize) -> Vec<KeyPair> {
         let mut v = vec![];
         for _ in 0..n {
@@ -948,6 +1129,45 @@ mod test {
         assert_eq!(new_kp.pub_key, new_pk);
     }
 
+    #[test]
+    fn tagged_keypair_bytes() {
+        let mut prng = test_rng();
+        let secp256k1 = KeyPair::sample(&mut prng, SECP256K1);
+        let ed25519 = KeyPair::sample(&mut prng, ED25519);
+        let address = KeyPair::sample_address(&mut prng);
+
+        for kp in [&secp256k1, &ed25519, &address] {
+            let bytes = kp.to_tagged_bytes();
+            assert_eq!(&KeyPair::from_tagged_bytes(&bytes).unwrap(), kp);
+        }
+
+        let secp256k1_bytes = secp256k1.to_tagged_bytes();
+        let ed25519_bytes = ed25519.to_tagged_bytes();
+        assert_eq!(secp256k1_bytes[0], KeyType::Secp256k1.to_byte());
+        assert_eq!(ed25519_bytes[0], KeyType::Ed25519.to_byte());
+
+        // A secp256k1 keypair never parses as ed25519, and vice versa.
+        let mut bytes = secp256k1_bytes.clone();
+        bytes[0] = KeyType::Ed25519.to_byte();
+        assert!(KeyPair::from_tagged_bytes(&bytes).is_err());
+        assert!(KeyPair::from_tagged_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut bytes = ed25519_bytes.clone();
+        bytes[0] = KeyType::Secp256k1.to_byte();
+        assert!(KeyPair::from_tagged_bytes(&bytes).is_err());
+        bytes.push(0);
+        assert!(KeyPair::from_tagged_bytes(&bytes).is_err());
+
+        // Mismatched public keys and unknown tags are rejected.
+        let other = KeyPair::sample(&mut prng, SECP256K1).to_tagged_bytes();
+        let mut bytes = secp256k1_bytes;
+        bytes[SECRET_KEY_LENGTH..].copy_from_slice(&other[SECRET_KEY_LENGTH..]);
+        assert!(KeyPair::from_tagged_bytes(&bytes).is_err());
+        let mut bytes = ed25519_bytes;
+        bytes[0] = 3;
+        assert!(KeyPair::from_tagged_bytes(&bytes).is_err());
+    }
+
     #[test]
     fn multisig() {
         let mut prng = test_rng();
@@ -9,7 +9,8 @@
 #![deny(unused_comparisons, bare_trait_objects, unused_must_use)]
 #![doc(html_logo_url = "https://avatars.githubusercontent.com/u/74745723?s=200&v=4")]
 #![doc(html_playground_url = "https://play.rust-lang.org")]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 #![warn(
     unused,
     future_incompatible,
@@ -39,10 +40,44 @@ pub mod keys;
 pub mod nextgen;
 /// The wrapper of the parameters.
 pub mod parameters;
+/// The stable public API, re-exporting the common entry points.
+pub mod prelude;
 /// Module for serialization.
 pub mod serialization;
+/// Helpers to set up anonymous asset records in tests.
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
 /// Module for confidential transfer.
 pub mod xfr;
 
 pub use noah_algebra::errors;
 pub use noah_algebra::ristretto;
+
+/// The algebra crate under its former name, for downstream crates migrating from `zei`.
+/// This alias is transitional and will be removed: use `noah_algebra` instead.
+#[cfg(feature = "zei-compat")]
+pub use noah_algebra as zei_algebra;
+/// The crypto crate under its former name, for downstream crates migrating from `zei`.
+/// This alias is transitional and will be removed: use `noah_crypto` instead.
+#[cfg(feature = "zei-compat")]
+pub use noah_crypto as zei_crypto;
+
+#[cfg(all(test, feature = "zei-compat"))]
+mod test {
+    use crate::zei_algebra::{bls12_381::BLSScalar, prelude::*};
+    use crate::zei_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+    use noah_algebra::bls12_381::BLSScalar as NoahBLSScalar;
+    use noah_crypto::basic::anemoi_jive::AnemoiJive381 as NoahAnemoiJive381;
+
+    #[test]
+    fn test_zei_compat_aliases() {
+        // The aliased paths resolve to the very same types.
+        let x = BLSScalar::from(7u32);
+        let y: NoahBLSScalar = x;
+        assert_eq!(
+            AnemoiJive381::eval_variable_length_hash(&[x]),
+            NoahAnemoiJive381::eval_variable_length_hash(&[y])
+        );
+        assert_eq!(x.add(&BLSScalar::one()), BLSScalar::from(8u32));
+    }
+}
@@ -1,4 +1,6 @@
 use noah_algebra::collections::BTreeMap;
+use noah_algebra::prelude::*;
+use sha2::{Digest, Sha256};
 
 /// Parameters for Bulletproofs.
 pub mod bulletproofs;
@@ -154,3 +156,90 @@ lazy_static! {
         m
     };
 }
+
+/// The expected SHA-256 digests of the parameter files, in the format of `sha256sum`.
+/// Generated by `gen-params digests`.
+static PARAMETER_DIGESTS: &str = include_str!("../../parameters/sha256sums.txt");
+
+/// Return the hex-encoded SHA-256 digest of a parameter file.
+pub fn parameter_digest(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Check that the embedded parameter files match their expected digests, to detect a
+/// corrupted build. The parameter files that are not embedded, due to the features, are skipped.
+pub fn verify_embedded_parameters() -> Result<()> {
+    let mut files = [
+        ("bulletproof-curve25519-urs.bin", BULLETPROOF_CURVE25519_URS),
+        ("bulletproof-secq256k1-urs.bin", BULLETPROOF_SECQ256K1_URS),
+        ("bulletproof-zorro-urs.bin", BULLETPROOF_ZORRO_URS),
+        ("srs-padding.bin", SRS),
+        (
+            "transfer-vk-common.bin",
+            ABAR_TO_ABAR_VERIFIER_COMMON_PARAMS,
+        ),
+        (
+            "transfer-vk-ed25519-specific.bin",
+            ABAR_TO_ABAR_VERIFIER_ED25519_SPECIFIC_PARAMS,
+        ),
+        (
+            "transfer-vk-secp256k1-specific.bin",
+            ABAR_TO_ABAR_VERIFIER_SECP256K1_SPECIFIC_PARAMS,
+        ),
+        (
+            "abar-to-bar-vk-ed25519.bin",
+            ABAR_TO_BAR_ED25519_VERIFIER_PARAMS,
+        ),
+        (
+            "abar-to-bar-vk-secp256k1.bin",
+            ABAR_TO_BAR_SECP256K1_VERIFIER_PARAMS,
+        ),
+        ("bar-to-abar-vk.bin", BAR_TO_ABAR_VERIFIER_PARAMS),
+        ("ar-to-abar-vk.bin", AR_TO_ABAR_VERIFIER_PARAMS),
+        (
+            "abar-to-ar-vk-ed25519.bin",
+            ABAR_TO_AR_ED25519_VERIFIER_PARAMS,
+        ),
+        (
+            "abar-to-ar-vk-secp256k1.bin",
+            ABAR_TO_AR_SECP256K1_VERIFIER_PARAMS,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(name, bytes)| bytes.map(|bytes| (name.to_string(), bytes)))
+    .collect::<Vec<_>>();
+    for (size, bytes) in LAGRANGE_BASES.iter() {
+        files.push((format!("lagrange-srs-{}.bin", size), *bytes));
+    }
+    verify_parameter_digests(&files, PARAMETER_DIGESTS)
+}
+
+/// Check that each of the named parameter files has the digest listed for it in `digests`.
+fn verify_parameter_digests(files: &[(String, &[u8])], digests: &str) -> Result<()> {
+    for (name, bytes) in files {
+        let expected = digests
+            .lines()
+            .filter_map(|line| line.split_once("  "))
+            .find(|(_, file)| *file == name.as_str())
+            .map(|(digest, _)| digest);
+        match expected {
+            Some(digest) if digest == parameter_digest(bytes) => {}
+            Some(_) => {
+                return Err(eg!(format!(
+                    "the parameter file {} does not match its expected digest",
+                    name
+                )))
+            }
+            None => {
+                return Err(eg!(format!(
+                    "the parameter file {} has no expected digest",
+                    name
+                )))
+            }
+        }
+    }
+    Ok(())
+}
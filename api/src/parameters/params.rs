@@ -3,11 +3,13 @@ use crate::anon_xfr::abar_to_ar::build_abar_to_ar_cs;
 use crate::anon_xfr::abar_to_bar::build_abar_to_bar_cs;
 use crate::anon_xfr::ar_to_abar::build_ar_to_abar_cs;
 use crate::anon_xfr::bar_to_abar::build_bar_to_abar_cs;
+use crate::anon_xfr::ownership::build_ownership_cs;
 use crate::anon_xfr::structs::{MTNode, MTPath, PayeeWitness, PayerWitness};
 use crate::anon_xfr::{
-    commit, nullify, AXfrAddressFoldingWitness, TurboPlonkCS, FEE_TYPE, TREE_DEPTH,
+    commit, nullify, AXfrAddressFoldingWitness, AddressFoldingKind, TurboPlonkCS, FEE_TYPE,
+    TREE_DEPTH,
 };
-use crate::keys::KeyPair;
+use crate::keys::{KeyPair, KeyType};
 use crate::parameters::AddressFormat::{ED25519, SECP256K1};
 use crate::parameters::{
     ABAR_TO_ABAR_VERIFIER_COMMON_PARAMS, ABAR_TO_ABAR_VERIFIER_ED25519_SPECIFIC_PARAMS,
@@ -25,11 +27,21 @@ use noah_crypto::delegated_schnorr::{
 use noah_plonk::plonk::constraint_system::ConstraintSystem;
 use noah_plonk::plonk::indexer::{indexer_with_lagrange, PlonkPK, PlonkVK};
 use noah_plonk::poly_commit::kzg_poly_com::KZGCommitmentSchemeBLS;
-use noah_plonk::poly_commit::pcs::PolyComScheme;
+use noah_plonk::poly_commit::pcs::{PolyComScheme, ToBytes};
 use num_traits::Zero;
 use rand_chacha::ChaChaRng;
 use rand_core::SeedableRng;
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+#[cfg(feature = "mmap")]
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 /// The range in the Bulletproofs range check.
 pub const BULLET_PROOF_RANGE: usize = 32;
@@ -47,12 +59,26 @@ pub const MAX_ANONYMOUS_RECORD_NUMBER_ONE_INPUT: usize = 20;
 pub const DEFAULT_BP_NUM_GENS: usize = 256;
 /// The number of the Bulletproofs(over the Secq256k1 curve) generators needed for anonymous transfer.
 pub const ANON_XFR_BP_GENS_LEN: usize = 2048;
+/// The maximal circuit size supported by the SRS.
+pub const MAX_CIRCUIT_SIZE: usize = 8192;
+
+lazy_static! {
+    /// The serialized verifier parameters for anonymous transfer bound to a public predicate,
+    /// which are not precomputed, by number of inputs, number of outputs and address format.
+    static ref ABAR_TO_ABAR_WITH_PREDICATE_VERIFIER_PARAMS:
+        Mutex<BTreeMap<(usize, usize, AddressFormat), Vec<u8>>> = Mutex::new(BTreeMap::new());
+}
 
 #[derive(Serialize, Deserialize)]
 /// The verifier parameters.
 pub struct VerifierParams {
     /// A label that describes the prover parameters.
     pub label: String,
+    /// The kind of address folding of the circuit, for the anonymous transfer parameters.
+    /// It is set when the parameters are generated or loaded, and is not serialized, so that
+    /// the precomputed parameters keep their layout.
+    #[serde(skip)]
+    pub address_folding: Option<AddressFoldingKind>,
     /// The shrunk version of the polynomial commitment scheme.
     pub shrunk_vk: KZGCommitmentSchemeBLS,
     /// The shrunk version of the constraint system.
@@ -79,8 +105,23 @@ pub struct VerifierParamsSplitSpecific {
     pub verifier_params: PlonkVK<KZGCommitmentSchemeBLS>,
 }
 
+#[derive(Serialize, Deserialize)]
+/// The parts of the verifier parameters used by verification.
+struct VerifierParamsPublic {
+    /// A label that describes the prover parameters.
+    label: String,
+    /// The kind of address folding of the circuit, for the anonymous transfer parameters.
+    address_folding: Option<AddressFoldingKind>,
+    /// The shrunk version of the polynomial commitment scheme.
+    shrunk_pcs: KZGCommitmentSchemeBLS,
+    /// The number of variables of the constraint system.
+    num_vars: usize,
+    /// The TurboPlonk verifying key, which contains the size of the constraint system.
+    verifier_params: PlonkVK<KZGCommitmentSchemeBLS>,
+}
+
 /// The address format.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum AddressFormat {
     /// Secp256k1 address
     SECP256K1,
@@ -88,6 +129,18 @@ pub enum AddressFormat {
     ED25519,
 }
 
+impl AddressFormat {
+    /// Get the address format of a key type.
+    /// Key types without an address format, such as `EthAddress`, are rejected.
+    pub fn from_key_type(key_type: KeyType) -> Result<AddressFormat> {
+        match key_type {
+            KeyType::Ed25519 => Ok(ED25519),
+            KeyType::Secp256k1 => Ok(SECP256K1),
+            _ => Err(eg!(NoahError::ParameterError)),
+        }
+    }
+}
+
 impl ProverParams {
     /// Obtain the parameters for anonymous transfer for a given number of inputs and a given number of outputs.
     pub fn gen_abar_to_abar(
@@ -95,9 +148,34 @@ impl ProverParams {
         n_payees: usize,
         address_format: AddressFormat,
     ) -> Result<ProverParams> {
+        Self::gen_abar_to_abar_circuit(n_payers, n_payees, address_format, false)
+    }
+
+    /// Obtain the parameters for anonymous transfer bound to a public predicate, for a given
+    /// number of inputs and a given number of outputs.
+    /// The hash of the predicate is an extra public input of the circuit.
+    pub fn gen_abar_to_abar_with_predicate(
+        n_payers: usize,
+        n_payees: usize,
+        address_format: AddressFormat,
+    ) -> Result<ProverParams> {
+        Self::gen_abar_to_abar_circuit(n_payers, n_payees, address_format, true)
+    }
+
+    fn gen_abar_to_abar_circuit(
+        n_payers: usize,
+        n_payees: usize,
+        address_format: AddressFormat,
+        with_predicate: bool,
+    ) -> Result<ProverParams> {
+        let prefix = if with_predicate {
+            "abar_to_abar_predicate"
+        } else {
+            "abar_to_abar"
+        };
         let label = match address_format {
-            SECP256K1 => format!("abar_to_abar_{}_to_{}_secp256k1", n_payees, n_payers),
-            ED25519 => format!("abar_to_abar_{}_to_{}_ed25519", n_payees, n_payers),
+            SECP256K1 => format!("{}_{}_to_{}_secp256k1", prefix, n_payees, n_payers),
+            ED25519 => format!("{}_{}_to_{}_ed25519", prefix, n_payees, n_payers),
         };
 
         let fake_witness = AXfrWitness::fake(n_payers, n_payees, 0, address_format);
@@ -140,24 +218,34 @@ impl ProverParams {
             &input_commitments_traces,
             &output_commitments_traces,
             &AXfrAddressFoldingWitness::default(address_format),
+            if with_predicate {
+                Some(BLSScalar::zero())
+            } else {
+                None
+            },
         );
 
         let cs_size = cs.size();
+        check_circuit_size(cs_size).c(d!())?;
         let pcs = load_srs_params(cs_size)?;
         let lagrange_pcs = load_lagrange_params(cs_size);
 
-        let verifier_params =
-            if let Ok(v) = VerifierParams::load_abar_to_abar(n_payers, n_payees, address_format) {
-                Some(v.verifier_params)
-            } else {
-                None
-            };
+        // The precomputed verifier parameters are only available without a predicate.
+        let verifier_params = if with_predicate {
+            None
+        } else if let Ok(v) = VerifierParams::load_abar_to_abar(n_payers, n_payees, address_format)
+        {
+            Some(v.verifier_params)
+        } else {
+            None
+        };
 
         let prover_params =
             indexer_with_lagrange(&cs, &pcs, lagrange_pcs.as_ref(), verifier_params).unwrap();
 
         Ok(ProverParams {
             label,
+            address_folding: Some(AddressFoldingKind::from(address_format)),
             pcs,
             lagrange_pcs,
             cs,
@@ -227,6 +315,7 @@ impl ProverParams {
 
         Ok(ProverParams {
             label,
+            address_folding: None,
             pcs,
             lagrange_pcs,
             cs,
@@ -327,6 +416,7 @@ impl ProverParams {
 
         Ok(ProverParams {
             label,
+            address_folding: None,
             pcs,
             lagrange_pcs,
             cs,
@@ -374,6 +464,7 @@ impl ProverParams {
 
         Ok(ProverParams {
             label,
+            address_folding: None,
             pcs,
             lagrange_pcs,
             cs,
@@ -446,6 +537,68 @@ impl ProverParams {
 
         Ok(ProverParams {
             label,
+            address_folding: None,
+            pcs,
+            lagrange_pcs,
+            cs,
+            prover_params,
+        })
+    }
+
+    /// Obtain the parameters for proving the ownership of an anonymous asset record.
+    /// No verifier parameters are precomputed for this circuit; use `VerifierParams::from`.
+    pub fn gen_ownership(address_format: AddressFormat) -> Result<ProverParams> {
+        let label = match address_format {
+            SECP256K1 => String::from("ownership_secp256k1"),
+            ED25519 => String::from("ownership_ed25519"),
+        };
+
+        let bls_zero = BLSScalar::zero();
+
+        // It's okay to choose a fixed seed to build CS.
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+
+        let node = MTNode {
+            left: bls_zero,
+            mid: bls_zero,
+            right: bls_zero,
+            is_left_child: 0,
+            is_mid_child: 0,
+            is_right_child: 0,
+        };
+
+        let keypair = KeyPair::sample(&mut prng, address_format);
+        let payer_secret = PayerWitness {
+            secret_key: keypair.get_sk(),
+            uid: 0,
+            amount: 0,
+            asset_type: bls_zero,
+            path: MTPath::new(vec![node.clone(); TREE_DEPTH]),
+            blind: bls_zero,
+        };
+
+        let (_, input_commitment_trace) = commit(
+            &keypair.get_pk(),
+            payer_secret.blind,
+            payer_secret.amount,
+            payer_secret.asset_type,
+        )?;
+
+        let (cs, _) = build_ownership_cs(
+            &payer_secret,
+            &input_commitment_trace,
+            &AXfrAddressFoldingWitness::default(address_format),
+        );
+
+        let cs_size = cs.size();
+        let pcs = load_srs_params(cs_size)?;
+        let lagrange_pcs = load_lagrange_params(cs_size);
+
+        let prover_params = indexer_with_lagrange(&cs, &pcs, lagrange_pcs.as_ref(), None).unwrap();
+
+        Ok(ProverParams {
+            label,
+            address_folding: None,
             pcs,
             lagrange_pcs,
             cs,
@@ -461,12 +614,7 @@ impl VerifierParams {
         n_payees: usize,
         address_format: AddressFormat,
     ) -> Result<VerifierParams> {
-        if (!(n_payees <= MAX_ANONYMOUS_RECORD_NUMBER_STANDARD
-            && n_payers <= MAX_ANONYMOUS_RECORD_NUMBER_STANDARD))
-            && (!(n_payers <= MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_SENDER
-                && n_payees <= MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_RECEIVER))
-            && (!(n_payers == 1 && n_payees <= MAX_ANONYMOUS_RECORD_NUMBER_ONE_INPUT))
-        {
+        if !is_supported_anon_xfr_shape(n_payers, n_payees) {
             Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
         } else {
             match Self::load_abar_to_abar(n_payers, n_payees, address_format) {
@@ -480,6 +628,38 @@ impl VerifierParams {
         }
     }
 
+    /// Obtain the verifier parameters for anonymous transfer bound to a public predicate, for a
+    /// given number of inputs and a given number of outputs.
+    /// They are not precomputed: they are generated from the prover parameters the first time
+    /// they are requested, and then kept in memory.
+    pub fn get_abar_to_abar_with_predicate(
+        n_payers: usize,
+        n_payees: usize,
+        address_format: AddressFormat,
+    ) -> Result<VerifierParams> {
+        if !is_supported_anon_xfr_shape(n_payers, n_payees) {
+            return Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into());
+        }
+
+        let mut cache = ABAR_TO_ABAR_WITH_PREDICATE_VERIFIER_PARAMS.lock().unwrap();
+        let key = (n_payers, n_payees, address_format);
+        if !cache.contains_key(&key) {
+            let params = Self::from(ProverParams::gen_abar_to_abar_with_predicate(
+                n_payers,
+                n_payees,
+                address_format,
+            )?);
+            let bytes = bincode::serialize(&params).c(d!(NoahError::SerializationError))?;
+            cache.insert(key, bytes);
+            return Ok(params);
+        }
+
+        let mut params: VerifierParams =
+            bincode::deserialize(&cache[&key]).c(d!(NoahError::DeserializationError))?;
+        params.address_folding = Some(AddressFoldingKind::from(address_format));
+        Ok(params)
+    }
+
     /// Load the verifier parameters from prepare.
     pub fn load_abar_to_abar(
         n_payers: usize,
@@ -491,15 +671,28 @@ impl VerifierParams {
             ED25519 => ABAR_TO_ABAR_VERIFIER_ED25519_SPECIFIC_PARAMS,
         };
 
+        Self::abar_to_abar_from_bytes(
+            ABAR_TO_ABAR_VERIFIER_COMMON_PARAMS,
+            verifier_specific_params,
+            n_payers,
+            n_payees,
+            address_format,
+        )
+    }
+
+    fn abar_to_abar_from_bytes(
+        common_bytes: Option<&[u8]>,
+        specific_bytes: Option<&[u8]>,
+        n_payers: usize,
+        n_payees: usize,
+        address_format: AddressFormat,
+    ) -> Result<VerifierParams> {
         let label = match address_format {
             SECP256K1 => format!("abar_to_abar_{}_to_{}_secp256k1", n_payees, n_payers),
             ED25519 => format!("abar_to_abar_{}_to_{}_ed25519", n_payees, n_payers),
         };
 
-        match (
-            ABAR_TO_ABAR_VERIFIER_COMMON_PARAMS,
-            verifier_specific_params,
-        ) {
+        match (common_bytes, specific_bytes) {
             (Some(c_bytes), Some(s_bytes)) => {
                 let common: VerifierParamsSplitCommon =
                     bincode::deserialize(c_bytes).c(d!(NoahError::DeserializationError))?;
@@ -521,6 +714,7 @@ impl VerifierParams {
 
                 Ok(VerifierParams {
                     label,
+                    address_folding: Some(AddressFoldingKind::from(address_format)),
                     shrunk_vk: common.shrunk_pcs,
                     shrunk_cs: special.shrunk_cs,
                     verifier_params: special.verifier_params,
@@ -530,6 +724,24 @@ impl VerifierParams {
         }
     }
 
+    /// Deserialize the verifier parameters and check that they carry the expected label.
+    fn from_labeled_bytes(bytes: Option<&[u8]>, label: &str) -> Result<VerifierParams> {
+        if let Some(bytes) = bytes {
+            let verifier_params = bincode::deserialize::<VerifierParams>(bytes);
+            if let Ok(verifier_params) = verifier_params {
+                if verifier_params.label != label {
+                    Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
+                } else {
+                    Ok(verifier_params)
+                }
+            } else {
+                Err(SimpleError::new(d!(NoahError::DeserializationError), None).into())
+            }
+        } else {
+            Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
+        }
+    }
+
     /// Obtain the parameters for anonymous to confidential.
     pub fn get_abar_to_bar(address_format: AddressFormat) -> Result<VerifierParams> {
         match Self::load_abar_to_bar(address_format) {
@@ -543,29 +755,14 @@ impl VerifierParams {
 
     /// Obtain the parameters for anonymous to confidential from prepare.
     pub fn load_abar_to_bar(address_format: AddressFormat) -> Result<VerifierParams> {
-        let bytes = match address_format {
-            SECP256K1 => ABAR_TO_BAR_SECP256K1_VERIFIER_PARAMS,
-            ED25519 => ABAR_TO_BAR_ED25519_VERIFIER_PARAMS,
-        };
-
-        if let Some(bytes) = bytes {
-            let verifier_params = bincode::deserialize::<VerifierParams>(bytes);
-            if let Ok(verifier_params) = verifier_params {
-                let label = match address_format {
-                    SECP256K1 => String::from("abar_to_bar_secp256k1"),
-                    ED25519 => String::from("abar_to_bar_ed25519"),
-                };
-
-                if verifier_params.label != label {
-                    Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
-                } else {
-                    Ok(verifier_params)
-                }
-            } else {
-                Err(SimpleError::new(d!(NoahError::DeserializationError), None).into())
+        match address_format {
+            SECP256K1 => Self::from_labeled_bytes(
+                ABAR_TO_BAR_SECP256K1_VERIFIER_PARAMS,
+                "abar_to_bar_secp256k1",
+            ),
+            ED25519 => {
+                Self::from_labeled_bytes(ABAR_TO_BAR_ED25519_VERIFIER_PARAMS, "abar_to_bar_ed25519")
             }
-        } else {
-            Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
         }
     }
 
@@ -582,20 +779,7 @@ impl VerifierParams {
 
     /// Obtain the parameters for confidential to anonymous from prepare.
     pub fn load_bar_to_abar() -> Result<VerifierParams> {
-        if let Some(bytes) = BAR_TO_ABAR_VERIFIER_PARAMS {
-            let verifier_params = bincode::deserialize::<VerifierParams>(bytes);
-            if let Ok(verifier_params) = verifier_params {
-                if verifier_params.label != String::from("bar_to_abar") {
-                    Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
-                } else {
-                    Ok(verifier_params)
-                }
-            } else {
-                Err(SimpleError::new(d!(NoahError::DeserializationError), None).into())
-            }
-        } else {
-            Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
-        }
+        Self::from_labeled_bytes(BAR_TO_ABAR_VERIFIER_PARAMS, "bar_to_abar")
     }
 
     /// Obtain the parameters for transparent to anonymous.
@@ -611,20 +795,7 @@ impl VerifierParams {
 
     /// Obtain the parameters for transparent to anonymous from prepare.
     pub fn load_ar_to_abar() -> Result<VerifierParams> {
-        if let Some(bytes) = AR_TO_ABAR_VERIFIER_PARAMS {
-            let verifier_params = bincode::deserialize::<VerifierParams>(bytes);
-            if let Ok(verifier_params) = verifier_params {
-                if verifier_params.label != String::from("ar_to_abar") {
-                    Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
-                } else {
-                    Ok(verifier_params)
-                }
-            } else {
-                Err(SimpleError::new(d!(NoahError::DeserializationError), None).into())
-            }
-        } else {
-            Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
-        }
+        Self::from_labeled_bytes(AR_TO_ABAR_VERIFIER_PARAMS, "ar_to_abar")
     }
 
     /// Obtain the parameters for anonymous to transparent.
@@ -640,30 +811,51 @@ impl VerifierParams {
 
     /// Obtain the parameters for anonymous to transparent from prepare.
     pub fn load_abar_to_ar(address_format: AddressFormat) -> Result<VerifierParams> {
-        let bytes = match address_format {
-            SECP256K1 => ABAR_TO_AR_SECP256K1_VERIFIER_PARAMS,
-            ED25519 => ABAR_TO_AR_ED25519_VERIFIER_PARAMS,
-        };
+        match address_format {
+            SECP256K1 => Self::from_labeled_bytes(
+                ABAR_TO_AR_SECP256K1_VERIFIER_PARAMS,
+                "abar_to_ar_secp256k1",
+            ),
+            ED25519 => {
+                Self::from_labeled_bytes(ABAR_TO_AR_ED25519_VERIFIER_PARAMS, "abar_to_ar_ed25519")
+            }
+        }
+    }
 
-        if let Some(bytes) = bytes {
-            let verifier_params = bincode::deserialize::<VerifierParams>(bytes);
-            if let Ok(verifier_params) = verifier_params {
-                let label = match address_format {
-                    SECP256K1 => String::from("abar_to_ar_secp256k1"),
-                    ED25519 => String::from("abar_to_ar_ed25519"),
-                };
+    /// Return the coset separators `k` of the verifier parameters, which distinguish the wire
+    /// columns in the permutation argument.
+    pub fn coset_separators(&self) -> &[BLSScalar] {
+        &self.verifier_params.k
+    }
 
-                if verifier_params.label != label {
-                    Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
-                } else {
-                    Ok(verifier_params)
-                }
-            } else {
-                Err(SimpleError::new(d!(NoahError::DeserializationError), None).into())
-            }
-        } else {
-            Err(SimpleError::new(d!(NoahError::MissingVerifierParamsError), None).into())
+    /// Return a digest of the commitments of the selectors and of the permutation, which
+    /// identifies the circuit, e.g., to check at startup that the parameters are the expected ones.
+    /// The commitments are hashed in the order of the verifying key: the selectors, the
+    /// permutation, the boolean selector, and the round key selectors.
+    pub fn selectors_digest(&self) -> [u8; 32] {
+        let vk = &self.verifier_params;
+        let mut hasher = Sha256::new();
+        for cm in vk
+            .cm_q_vec
+            .iter()
+            .chain(vk.cm_s_vec.iter())
+            .chain(core::iter::once(&vk.cm_qb))
+            .chain(vk.cm_prk_vec.iter())
+        {
+            hasher.update(cm.to_bytes());
         }
+
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&hasher.finalize());
+        digest
+    }
+
+    /// Check that the coset separators are pairwise distinct quadratic non-residues (besides the
+    /// first one, which is one), as required for the soundness of the permutation argument.
+    pub fn validate_coset_separators(&self) -> Result<()> {
+        self.verifier_params
+            .validate_coset_separators()
+            .c(d!(NoahError::AXfrVerifierParamsError))
     }
 
     /// Split the verifier parameters to the common part and the sspecific part.
@@ -679,12 +871,121 @@ impl VerifierParams {
             },
         ))
     }
+
+    /// Serialize the parts of the verifier parameters used by verification, for distribution to
+    /// light clients. Unlike `bincode::serialize`, the placeholders of the shrunk constraint
+    /// system are omitted, and only its size and number of variables are kept.
+    pub fn to_public_bytes(&self) -> Result<Vec<u8>> {
+        let public = VerifierParamsPublic {
+            label: self.label.clone(),
+            address_folding: self.address_folding,
+            shrunk_pcs: self.shrunk_vk.shrink_to_verifier_only(),
+            num_vars: self.shrunk_cs.num_vars,
+            verifier_params: self.verifier_params.clone(),
+        };
+        bincode::serialize(&public).c(d!(NoahError::SerializationError))
+    }
+
+    /// Deserialize verifier parameters serialized by `to_public_bytes`.
+    pub fn from_public_bytes(bytes: &[u8]) -> Result<VerifierParams> {
+        let public: VerifierParamsPublic =
+            bincode::deserialize(bytes).c(d!(NoahError::DeserializationError))?;
+
+        let mut cs = TurboPlonkCS::new();
+        cs.size = public.verifier_params.cs_size;
+        cs.num_vars = public.num_vars;
+
+        Ok(VerifierParams {
+            label: public.label,
+            address_folding: public.address_folding,
+            shrunk_vk: public.shrunk_pcs,
+            shrunk_cs: cs.shrink_to_verifier_only(),
+            verifier_params: public.verifier_params,
+        })
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl VerifierParams {
+    /// Load the verifier parameters for anonymous transfer by memory-mapping the parameter
+    /// files in `directory`, which must follow the layout written by `gen-params`.
+    /// The result is identical to `load_abar_to_abar`, without embedding the bytes in the binary.
+    pub fn load_abar_to_abar_mmap<P: AsRef<Path>>(
+        directory: P,
+        n_payers: usize,
+        n_payees: usize,
+        address_format: AddressFormat,
+    ) -> Result<VerifierParams> {
+        let specific_file = match address_format {
+            SECP256K1 => "transfer-vk-secp256k1-specific.bin",
+            ED25519 => "transfer-vk-ed25519-specific.bin",
+        };
+        let common = map_parameter_file(directory.as_ref().join("transfer-vk-common.bin"))?;
+        let specific = map_parameter_file(directory.as_ref().join(specific_file))?;
+
+        Self::abar_to_abar_from_bytes(
+            Some(&common[..]),
+            Some(&specific[..]),
+            n_payers,
+            n_payees,
+            address_format,
+        )
+    }
+
+    /// Load the parameters for anonymous to confidential by memory-mapping the parameter file in `directory`.
+    pub fn load_abar_to_bar_mmap<P: AsRef<Path>>(
+        directory: P,
+        address_format: AddressFormat,
+    ) -> Result<VerifierParams> {
+        let (file, label) = match address_format {
+            SECP256K1 => ("abar-to-bar-vk-secp256k1.bin", "abar_to_bar_secp256k1"),
+            ED25519 => ("abar-to-bar-vk-ed25519.bin", "abar_to_bar_ed25519"),
+        };
+        let bytes = map_parameter_file(directory.as_ref().join(file))?;
+        Self::from_labeled_bytes(Some(&bytes[..]), label)
+    }
+
+    /// Load the parameters for confidential to anonymous by memory-mapping the parameter file in `directory`.
+    pub fn load_bar_to_abar_mmap<P: AsRef<Path>>(directory: P) -> Result<VerifierParams> {
+        let bytes = map_parameter_file(directory.as_ref().join("bar-to-abar-vk.bin"))?;
+        Self::from_labeled_bytes(Some(&bytes[..]), "bar_to_abar")
+    }
+
+    /// Load the parameters for transparent to anonymous by memory-mapping the parameter file in `directory`.
+    pub fn load_ar_to_abar_mmap<P: AsRef<Path>>(directory: P) -> Result<VerifierParams> {
+        let bytes = map_parameter_file(directory.as_ref().join("ar-to-abar-vk.bin"))?;
+        Self::from_labeled_bytes(Some(&bytes[..]), "ar_to_abar")
+    }
+
+    /// Load the parameters for anonymous to transparent by memory-mapping the parameter file in `directory`.
+    pub fn load_abar_to_ar_mmap<P: AsRef<Path>>(
+        directory: P,
+        address_format: AddressFormat,
+    ) -> Result<VerifierParams> {
+        let (file, label) = match address_format {
+            SECP256K1 => ("abar-to-ar-vk-secp256k1.bin", "abar_to_ar_secp256k1"),
+            ED25519 => ("abar-to-ar-vk-ed25519.bin", "abar_to_ar_ed25519"),
+        };
+        let bytes = map_parameter_file(directory.as_ref().join(file))?;
+        Self::from_labeled_bytes(Some(&bytes[..]), label)
+    }
+}
+
+/// Map a parameter file into memory as read-only.
+#[cfg(feature = "mmap")]
+#[allow(unsafe_code)]
+fn map_parameter_file(path: PathBuf) -> Result<Mmap> {
+    let file = File::open(path).c(d!(NoahError::MissingVerifierParamsError))?;
+    // The parameter files are read-only artifacts, so they are not expected to be
+    // modified or truncated while mapped.
+    unsafe { Mmap::map(&file) }.c(d!(NoahError::MissingVerifierParamsError))
 }
 
 impl From<ProverParams> for VerifierParams {
     fn from(params: ProverParams) -> Self {
         VerifierParams {
             label: params.label,
+            address_folding: params.address_folding,
             shrunk_vk: params.pcs.shrink_to_verifier_only(),
             shrunk_cs: params.cs.shrink_to_verifier_only(),
             verifier_params: params.prover_params.get_verifier_params(),
@@ -697,6 +998,11 @@ impl From<ProverParams> for VerifierParams {
 pub struct ProverParams {
     /// A label that describes the prover parameters.
     pub label: String,
+    /// The kind of address folding of the circuit, for the anonymous transfer parameters.
+    /// It is set when the parameters are generated or loaded, and is not serialized, so that
+    /// the precomputed parameters keep their layout.
+    #[serde(skip)]
+    pub address_folding: Option<AddressFoldingKind>,
     /// The full SRS for the polynomial commitment scheme.
     pub pcs: KZGCommitmentSchemeBLS,
     /// The Lagrange basis format of SRS.
@@ -707,14 +1013,50 @@ pub struct ProverParams {
     pub prover_params: PlonkPK<KZGCommitmentSchemeBLS>,
 }
 
+/// Check whether the setup program supports anonymous transfers with the given numbers
+/// of inputs and outputs: the standard shapes, the consolidation shapes, and the
+/// one-input shapes.
+pub fn is_supported_anon_xfr_shape(n_payers: usize, n_payees: usize) -> bool {
+    if n_payers == 0 || n_payees == 0 {
+        return false;
+    }
+    (n_payers <= MAX_ANONYMOUS_RECORD_NUMBER_STANDARD
+        && n_payees <= MAX_ANONYMOUS_RECORD_NUMBER_STANDARD)
+        || (n_payers <= MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_SENDER
+            && n_payees <= MAX_ANONYMOUS_RECORD_NUMBER_CONSOLIDATION_RECEIVER)
+        || (n_payers == 1 && n_payees <= MAX_ANONYMOUS_RECORD_NUMBER_ONE_INPUT)
+}
+
 fn load_lagrange_params(size: usize) -> Option<KZGCommitmentSchemeBLS> {
+    lagrange_base_for(size).ok()
+}
+
+/// Load the Lagrange base of the SRS for circuits of the given size.
+/// If there is none, the error lists the sizes for which a Lagrange base is available.
+pub fn lagrange_base_for(size: usize) -> Result<KZGCommitmentSchemeBLS> {
     match LAGRANGE_BASES.get(&size) {
-        None => None,
-        Some(bytes) => KZGCommitmentSchemeBLS::from_unchecked_bytes(&bytes).ok(),
+        None => Err(eg!(NoahError::LagrangeBaseMissing {
+            size,
+            available: LAGRANGE_BASES.keys().copied().collect(),
+        })),
+        Some(bytes) => KZGCommitmentSchemeBLS::from_unchecked_bytes(&bytes)
+            .c(d!(NoahError::DeserializationError)),
     }
 }
 
+/// Check that a circuit of the given size fits in the SRS, before loading it.
+pub fn check_circuit_size(size: usize) -> Result<()> {
+    if size > MAX_CIRCUIT_SIZE {
+        return Err(eg!(NoahError::CircuitTooLarge {
+            needed: size,
+            available: MAX_CIRCUIT_SIZE,
+        }));
+    }
+    Ok(())
+}
+
 fn load_srs_params(size: usize) -> Result<KZGCommitmentSchemeBLS> {
+    check_circuit_size(size).c(d!())?;
     let srs = SRS.c(d!(NoahError::MissingSRSError))?;
 
     let KZGCommitmentSchemeBLS {
@@ -734,10 +1076,6 @@ fn load_srs_params(size: usize) -> Result<KZGCommitmentSchemeBLS> {
         new_group_1[8192..8195].copy_from_slice(&public_parameter_group_1[2054..2057]);
     }
 
-    if size > 8192 {
-        return Err(SimpleError::new(d!(NoahError::ParameterError), None).into());
-    }
-
     Ok(KZGCommitmentSchemeBLS {
         public_parameter_group_2,
         public_parameter_group_1: new_group_1,
@@ -746,10 +1084,14 @@ fn load_srs_params(size: usize) -> Result<KZGCommitmentSchemeBLS> {
 
 #[cfg(test)]
 mod test {
-    use crate::parameters::params::load_srs_params;
     use crate::parameters::params::AddressFormat::{ED25519, SECP256K1};
     use crate::parameters::params::ProverParams;
     use crate::parameters::params::VerifierParams;
+    use crate::parameters::params::{check_circuit_size, load_srs_params, MAX_CIRCUIT_SIZE};
+    use crate::parameters::{
+        verify_embedded_parameters, verify_parameter_digests, AR_TO_ABAR_VERIFIER_PARAMS,
+        PARAMETER_DIGESTS,
+    };
     use noah_algebra::{
         bls12_381::{BLSScalar, BLSG1},
         prelude::*,
@@ -788,6 +1130,99 @@ mod test {
         assert_eq!(v, v2);
     }
 
+    #[test]
+    fn test_vk_params_public_bytes() {
+        let params = VerifierParams::get_abar_to_ar(SECP256K1).unwrap();
+        let bytes = params.to_public_bytes().unwrap();
+        assert!(bytes.len() < bincode::serialize(&params).unwrap().len());
+
+        let params_de = VerifierParams::from_public_bytes(&bytes).unwrap();
+        assert_eq!(params_de.label, params.label);
+        assert_eq!(params_de.shrunk_cs.size, params.shrunk_cs.size);
+        assert_eq!(params_de.shrunk_cs.num_vars, params.shrunk_cs.num_vars);
+        assert_eq!(
+            bincode::serialize(&params_de).unwrap(),
+            bincode::serialize(&params).unwrap()
+        );
+
+        assert!(VerifierParams::from_public_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_selectors_digest() {
+        let params = VerifierParams::load_abar_to_abar(1, 2, SECP256K1).unwrap();
+        let same_params = VerifierParams::load_abar_to_abar(1, 2, SECP256K1).unwrap();
+        assert_eq!(params.selectors_digest(), same_params.selectors_digest());
+
+        for other_params in [
+            VerifierParams::load_abar_to_abar(2, 1, SECP256K1).unwrap(),
+            VerifierParams::load_abar_to_abar(1, 2, ED25519).unwrap(),
+        ] {
+            assert_ne!(params.selectors_digest(), other_params.selectors_digest());
+        }
+    }
+
+    #[test]
+    fn test_embedded_parameters() {
+        pnk!(verify_embedded_parameters());
+
+        let name = "ar-to-abar-vk.bin".to_string();
+        let mut bytes = AR_TO_ABAR_VERIFIER_PARAMS.unwrap().to_vec();
+        pnk!(verify_parameter_digests(
+            &[(name.clone(), bytes.as_slice())],
+            PARAMETER_DIGESTS
+        ));
+
+        bytes[0] ^= 1;
+        let err = verify_parameter_digests(&[(name.clone(), bytes.as_slice())], PARAMETER_DIGESTS)
+            .unwrap_err();
+        assert!(err.to_string().contains(&name));
+
+        let name = "unknown.bin".to_string();
+        assert!(verify_parameter_digests(&[(name, bytes.as_slice())], PARAMETER_DIGESTS).is_err());
+    }
+
+    #[test]
+    fn test_coset_separators() {
+        let mut params = VerifierParams::get_abar_to_ar(SECP256K1).unwrap();
+        assert_eq!(params.coset_separators().len(), 5);
+        assert!(params.validate_coset_separators().is_ok());
+
+        params.verifier_params.k[2] = params.verifier_params.k[1];
+        assert!(params.validate_coset_separators().is_err());
+    }
+
+    #[test]
+    fn test_circuit_size_guard() {
+        assert!(check_circuit_size(MAX_CIRCUIT_SIZE).is_ok());
+
+        let err = load_srs_params(2 * MAX_CIRCUIT_SIZE).unwrap_err();
+        msg_eq!(
+            NoahError::CircuitTooLarge {
+                needed: 2 * MAX_CIRCUIT_SIZE,
+                available: MAX_CIRCUIT_SIZE,
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn test_lagrange_base_for() {
+        let available: Vec<usize> = LAGRANGE_BASES.keys().copied().collect();
+        for size in available.iter() {
+            assert!(lagrange_base_for(*size).is_ok());
+        }
+
+        let err = lagrange_base_for(3000).unwrap_err();
+        msg_eq!(
+            NoahError::LagrangeBaseMissing {
+                size: 3000,
+                available,
+            },
+            err
+        );
+    }
+
     #[test]
     fn test_crs_commit() {
         let pcs = load_srs_params(16).unwrap();
@@ -810,4 +1245,36 @@ mod test {
         }
         assert_eq!(expected_committed_value, commitment.0);
     }
+
+    #[test]
+    #[cfg(all(feature = "mmap", not(feature = "no_vk")))]
+    fn test_mmap_params_match_embedded() {
+        let directory = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("parameters");
+
+        for address_format in [SECP256K1, ED25519] {
+            let embedded = VerifierParams::load_abar_to_abar(2, 2, address_format).unwrap();
+            let mapped =
+                VerifierParams::load_abar_to_abar_mmap(&directory, 2, 2, address_format).unwrap();
+            assert_eq!(
+                bincode::serialize(&embedded).unwrap(),
+                bincode::serialize(&mapped).unwrap()
+            );
+
+            let embedded = VerifierParams::load_abar_to_ar(address_format).unwrap();
+            let mapped = VerifierParams::load_abar_to_ar_mmap(&directory, address_format).unwrap();
+            assert_eq!(
+                bincode::serialize(&embedded).unwrap(),
+                bincode::serialize(&mapped).unwrap()
+            );
+        }
+
+        let embedded = VerifierParams::load_ar_to_abar().unwrap();
+        let mapped = VerifierParams::load_ar_to_abar_mmap(&directory).unwrap();
+        assert_eq!(
+            bincode::serialize(&embedded).unwrap(),
+            bincode::serialize(&mapped).unwrap()
+        );
+
+        assert!(VerifierParams::load_bar_to_abar_mmap(directory.join("missing")).is_err());
+    }
 }
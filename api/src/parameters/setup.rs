@@ -8,7 +8,7 @@
 use ark_bulletproofs::BulletproofGens as BulletproofGensOverSecq256k1;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 use bulletproofs::BulletproofGens;
-use noah::parameters::{AddressFormat, SRS};
+use noah::parameters::{parameter_digest, AddressFormat, SRS};
 use noah_algebra::bls12_381::BLSG1;
 use noah_algebra::secq256k1::{PedersenCommitmentSecq256k1, Secq256k1BulletproofGens};
 use noah_algebra::utils::save_to_file;
@@ -66,6 +66,9 @@ enum Actions {
     /// Cut the SRS, adapt to Lagrange, and only save the minimum 2^11, 2^12, and 2^13 padding
     CUT_SRS { directory: PathBuf },
 
+    /// Generates the digests of the parameter files, checked by `verify_embedded_parameters`
+    DIGESTS { directory: PathBuf },
+
     /// Generates all necessary parameters
     ALL { directory: PathBuf },
 }
@@ -103,6 +106,8 @@ fn main() {
 
         CUT_SRS { directory } => cut_srs(directory),
 
+        DIGESTS { directory } => gen_digests(directory),
+
         ALL { directory } => gen_all(directory),
     };
 }
@@ -388,6 +393,24 @@ fn cut_srs(mut path: PathBuf) {
     save_to_file(&bytes, path);
 }
 
+// cargo run --release --features="gen" --bin gen-params digests "./parameters"
+fn gen_digests(mut path: PathBuf) {
+    let mut names = std::fs::read_dir(&path)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".bin"))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    let mut digests = String::new();
+    for name in names.iter() {
+        let bytes = std::fs::read(path.join(name)).unwrap();
+        digests.push_str(&format!("{}  {}\n", parameter_digest(&bytes), name));
+    }
+    path.push("sha256sums.txt");
+    save_to_file(digests.as_bytes(), path);
+}
+
 // cargo run --release --features="gen no_vk" --bin gen-params all "./parameters"
 fn gen_all(directory: PathBuf) {
     gen_transfer_vk(directory.clone(), SECP256K1);
@@ -399,5 +422,6 @@ fn gen_all(directory: PathBuf) {
     gen_bulletproof_curve25519_urs(directory.clone());
     gen_bulletproof_secq256k1_urs(directory.clone());
     gen_bulletproof_zorro_urs(directory.clone());
-    cut_srs(directory)
+    cut_srs(directory.clone());
+    gen_digests(directory)
 }
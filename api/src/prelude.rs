@@ -0,0 +1,38 @@
+//! The stable public API of Noah.
+//!
+//! This module re-exports the high-level entry points to build and verify transfers, together
+//! with the parameters, keys, and record types they use. Items re-exported here are considered
+//! the stable API: they stay available under `noah::prelude` even if the modules that define
+//! them are reorganized. Items only reachable through their defining modules, such as the
+//! constraint systems or the address folding, are internal and may move between versions.
+
+pub use crate::anon_xfr::{
+    abar_to_abar::{
+        finish_anon_xfr_note, init_anon_xfr_note, verify_anon_xfr_note, AXfrNote, AXfrPreNote,
+    },
+    abar_to_ar::{
+        finish_abar_to_ar_note, init_abar_to_ar_note, verify_abar_to_ar_note, AbarToArNote,
+    },
+    abar_to_bar::{
+        finish_abar_to_bar_note, init_abar_to_bar_note, verify_abar_to_bar_note, AbarToBarNote,
+    },
+    ar_to_abar::{gen_ar_to_abar_note, verify_ar_to_abar_note, ArToAbarNote},
+    bar_to_abar::{gen_bar_to_abar_note, verify_bar_to_abar_note, BarToAbarNote},
+    ownership::{prove_ownership, verify_ownership, OwnershipProof},
+    structs::{
+        AnonAssetRecord, AxfrOwnerMemo, MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecord,
+        OpenAnonAssetRecordBuilder,
+    },
+    FEE_TYPE,
+};
+pub use crate::keys::{KeyPair, KeyType, PublicKey, SecretKey, Signature};
+pub use crate::parameters::params::{AddressFormat, ProverParams, VerifierParams};
+pub use crate::xfr::{
+    asset_record::{build_blind_asset_record, open_blind_asset_record, AssetRecordType},
+    gen_xfr_note,
+    structs::{
+        AssetRecord, AssetRecordTemplate, AssetType, BlindAssetRecord, OpenAssetRecord, OwnerMemo,
+        TracingPolicies, XfrAmount, XfrAssetType, XfrNote,
+    },
+    verify_xfr_note, XfrNotePolicies,
+};
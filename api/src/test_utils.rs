@@ -0,0 +1,69 @@
+use crate::anon_xfr::structs::{
+    AnonAssetRecord, MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder,
+};
+use crate::keys::KeyPair;
+use crate::xfr::structs::AssetType;
+use noah_accumulators::merkle_tree::{EphemeralMerkleTree, Proof, TreePath};
+use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+
+/// Build an open anonymous asset record of `amount` and `asset_type` for each of `records`,
+/// owned by `owner`, and insert them into a new Merkle tree.
+/// Return the tree, the records with their Merkle paths, ready to be spent, and the root.
+pub fn setup_tree_with_records<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    records: &[(u64, AssetType)],
+    owner: &KeyPair,
+) -> Result<(EphemeralMerkleTree, Vec<OpenAnonAssetRecord>, BLSScalar)> {
+    let mut mt = EphemeralMerkleTree::new().c(d!())?;
+
+    let mut oabars = vec![];
+    let mut uids = vec![];
+    for (amount, asset_type) in records.iter() {
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(*amount)
+            .asset_type(*asset_type)
+            .pub_key(&owner.get_pk())
+            .finalize(prng)
+            .c(d!())?
+            .build()
+            .c(d!())?;
+        let abar = AnonAssetRecord::from_oabar(&oabar);
+
+        let uid = mt.entry_count();
+        let leaf =
+            AnemoiJive381::eval_variable_length_hash(&[BLSScalar::from(uid), abar.commitment]);
+        uids.push(mt.add_commitment_hash(leaf).c(d!())?);
+        oabars.push(oabar);
+    }
+
+    for (oabar, uid) in oabars.iter_mut().zip(uids) {
+        let proof = mt.generate_proof(uid).c(d!())?;
+        oabar.update_mt_leaf_info(mt_leaf_info_from_proof(proof, uid));
+    }
+
+    let root = mt.get_root().c(d!())?;
+    Ok((mt, oabars, root))
+}
+
+fn mt_leaf_info_from_proof(proof: Proof, uid: u64) -> MTLeafInfo {
+    MTLeafInfo {
+        path: MTPath {
+            nodes: proof
+                .nodes
+                .iter()
+                .map(|e| MTNode {
+                    left: e.left,
+                    mid: e.mid,
+                    right: e.right,
+                    is_left_child: (e.path == TreePath::Left) as u8,
+                    is_mid_child: (e.path == TreePath::Middle) as u8,
+                    is_right_child: (e.path == TreePath::Right) as u8,
+                })
+                .collect(),
+        },
+        root: proof.root,
+        root_version: proof.root_version,
+        uid,
+    }
+}
@@ -7,10 +7,18 @@ use crate::xfr::structs::{
     AssetRecord, AssetRecordTemplate, AssetType, BlindAssetRecord, OpenAssetRecord, OwnerMemo,
     TracerMemo, TracingPolicies, XfrAmount, XfrAssetType,
 };
+use merlin::Transcript;
 use noah_algebra::{
     prelude::*,
     ristretto::{PedersenCommitmentRistretto, RistrettoScalar},
+    traits::PedersenCommitment,
 };
+use noah_crypto::basic::chaum_pedersen::{
+    chaum_pedersen_prove_eq, chaum_pedersen_verify_eq, ChaumPedersenProof,
+};
+
+/// The domain separator for the proof of a revealed asset type.
+const ASSET_TYPE_REVEAL_TRANSCRIPT: &[u8] = b"Asset Type Reveal";
 
 /// AssetRecord confidentiality flags. Indicated if amount and/or asset type should be confidential.
 #[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -91,14 +99,56 @@ impl AssetRecord {
         }
     }
 
+    /// Build a record input from OpenAssetRecord with tracing policies and the asset tracer memos
+    /// already computed for them, without identity tracing.
+    /// Check that there is exactly one memo per policy, encrypted under the keys of that policy,
+    /// as the proofs of asset tracing pair each policy with the memo at the same position.
+    pub fn new_checked(
+        oar: OpenAssetRecord,
+        policies: TracingPolicies,
+        memos: Vec<TracerMemo>,
+    ) -> Result<AssetRecord> {
+        if policies.len() != memos.len() {
+            return Err(eg!(format!(
+                "{} tracing policies but {} asset tracer memos",
+                policies.len(),
+                memos.len()
+            )));
+        }
+        for (i, (policy, memo)) in policies.get_policies().iter().zip(memos.iter()).enumerate() {
+            if policy.identity_tracing.is_some() {
+                return Err(eg!(format!(
+                    "tracing policy {} requires identity tracing, which needs identity proofs",
+                    i
+                )));
+            }
+            if policy.enc_keys != memo.enc_key {
+                return Err(eg!(format!(
+                    "asset tracer memo {} is not encrypted under the keys of tracing policy {}",
+                    i, i
+                )));
+            }
+        }
+
+        Ok(AssetRecord {
+            open_asset_record: oar,
+            identity_proofs: vec![None; policies.len()],
+            tracing_policies: policies,
+            asset_tracers_memos: memos,
+            owner_memo: None,
+        })
+    }
+
     /// Build a record input from OpenAssetRecord with an associated policy that has no identity tracing
     /// Important: It assumes that RecordInput will be used as an input and not as an output
     /// since OpenAsset record was recovered from a BlindAsset record. This means owner_memo field is be None.
+    /// The policies are normalized, see `TracingPolicies::normalized`.
     pub fn from_open_asset_record_with_asset_tracing_but_no_identity<R: CryptoRng + RngCore>(
         prng: &mut R,
         oar: OpenAssetRecord,
         asset_tracing_policies: TracingPolicies,
     ) -> Result<AssetRecord> {
+        let asset_tracing_policies = asset_tracing_policies.normalized();
         let mut memos = vec![];
         let mut identity_proofs = vec![];
         for asset_tracing_policy in asset_tracing_policies.get_policies().iter() {
@@ -152,6 +202,7 @@ impl AssetRecord {
     /// Build a record input from OpenAssetRecord with associated policies for asset *and* identity tracing
     /// Important: It assumes that RecordInput will be used as an input and not as an output
     /// since OpenAsset record was recovered from a BlindAsset record. This means owner_memo field is None.
+    /// The policies are normalized, see `TracingPolicies::normalized`.
     pub fn from_open_asset_record_with_tracing<R: CryptoRng + RngCore>(
         prng: &mut R,
         oar: OpenAssetRecord,
@@ -160,6 +211,7 @@ impl AssetRecord {
         credential: &Credential,
         credential_commitment_key: &ACCommitmentKey,
     ) -> Result<AssetRecord> {
+        let asset_tracing_policies = asset_tracing_policies.normalized();
         let mut memos = vec![];
         let mut identity_proofs = vec![];
         for asset_tracing_policy in asset_tracing_policies.get_policies().iter() {
@@ -236,10 +288,12 @@ impl AssetRecord {
     }
 
     /// Create the asset record using a template, without identity tracing.
+    /// The policies of the template are normalized, see `TracingPolicies::normalized`.
     pub fn from_template_no_identity_tracing<R: CryptoRng + RngCore>(
         prng: &mut R,
         template: &AssetRecordTemplate,
     ) -> Result<AssetRecord> {
+        let template = template.with_normalized_policies();
         let empty_id_proofs_and_ctext = vec![(None, vec![]); template.asset_tracing_policies.len()];
         for policy in template.asset_tracing_policies.get_policies().iter() {
             if policy.identity_tracing.is_some() {
@@ -251,6 +305,7 @@ impl AssetRecord {
     }
 
     /// Create the asset record using a template, with identity tracing.
+    /// The policies of the template are normalized, see `TracingPolicies::normalized`.
     pub fn from_template_with_identity_tracing<R: CryptoRng + RngCore>(
         prng: &mut R,
         template: &AssetRecordTemplate,
@@ -258,6 +313,7 @@ impl AssetRecord {
         credential: &Credential,
         credential_key: &ACCommitmentKey,
     ) -> Result<AssetRecord> {
+        let template = template.with_normalized_policies();
         let mut id_proofs_and_attrs = Vec::with_capacity(template.asset_tracing_policies.len());
         for policy in template.asset_tracing_policies.get_policies().iter() {
             let (conf_id, attrs) = if let Some(reveal_policy) = policy.identity_tracing.as_ref() {
@@ -321,6 +377,17 @@ impl AssetRecordTemplate {
         template.asset_tracing_policies = policies;
         template
     }
+
+    /// Return a copy of the template with normalized tracing policies.
+    fn with_normalized_policies(&self) -> AssetRecordTemplate {
+        AssetRecordTemplate {
+            amount: self.amount,
+            asset_type: self.asset_type,
+            public_key: self.public_key,
+            asset_record_type: self.asset_record_type,
+            asset_tracing_policies: self.asset_tracing_policies.clone().normalized(),
+        }
+    }
 }
 
 fn sample_blind_asset_record<R: CryptoRng + RngCore>(
@@ -570,6 +637,97 @@ pub fn open_blind_asset_record(
     })
 }
 
+impl OpenAssetRecord {
+    /// Reveal the asset type of a record with confidential amount and confidential asset type.
+    /// Return a new record for the same owner, with the same amount commitments but the asset
+    /// type in the clear, its owner memo, and a Chaum-Pedersen proof that the revealed asset type
+    /// is the one committed in this record. `owner_memo` is the owner memo of this record, from
+    /// which the new one keeps the amount blinds.
+    pub fn reveal_asset_type<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        owner_memo: &OwnerMemo,
+    ) -> Result<(BlindAssetRecord, OwnerMemo, ChaumPedersenProof)> {
+        if self.get_record_type() != AssetRecordType::ConfidentialAmount_ConfidentialAssetType {
+            return Err(eg!(NoahError::ParameterError));
+        }
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let old_commitment = self
+            .blind_asset_record
+            .asset_type
+            .get_commitment()
+            .c(d!(NoahError::ParameterError))?
+            .decompress()
+            .c(d!(NoahError::DecompressElementError))?;
+        let zero = RistrettoScalar::zero();
+        let new_commitment = pc_gens.commit(self.asset_type.as_scalar(), zero);
+
+        let public_key = self.blind_asset_record.public_key;
+        let blind_asset_record = BlindAssetRecord {
+            amount: self.blind_asset_record.amount.clone(),
+            asset_type: XfrAssetType::NonConfidential(self.asset_type),
+            public_key,
+        };
+        let owner_memo = owner_memo
+            .with_amount_only(prng, self.amount, &public_key)
+            .c(d!())?;
+
+        let mut transcript = Transcript::new(ASSET_TYPE_REVEAL_TRANSCRIPT);
+        let proof = chaum_pedersen_prove_eq(
+            &mut transcript,
+            prng,
+            &self.asset_type.as_scalar(),
+            (&old_commitment, &self.type_blind),
+            (&new_commitment, &zero),
+        );
+
+        Ok((blind_asset_record, owner_memo, proof))
+    }
+}
+
+/// Verify that `new_record` is `old_record` with its asset type revealed, as produced by
+/// `OpenAssetRecord::reveal_asset_type`: it has the same owner and amount commitments, and
+/// the revealed asset type is the committed one.
+pub fn verify_asset_type_reveal<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    old_record: &BlindAssetRecord,
+    new_record: &BlindAssetRecord,
+    proof: &ChaumPedersenProof,
+) -> Result<()> {
+    if old_record.get_record_type() != AssetRecordType::ConfidentialAmount_ConfidentialAssetType
+        || new_record.get_record_type()
+            != AssetRecordType::ConfidentialAmount_NonConfidentialAssetType
+        || old_record.public_key != new_record.public_key
+    {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    if old_record.amount != new_record.amount {
+        return Err(eg!(NoahError::XfrVerifyConfidentialAmountError));
+    }
+    let pc_gens = PedersenCommitmentRistretto::default();
+    let old_commitment = old_record
+        .asset_type
+        .get_commitment()
+        .c(d!(NoahError::ParameterError))?
+        .decompress()
+        .c(d!(NoahError::DecompressElementError))?;
+    let asset_type = new_record
+        .asset_type
+        .get_asset_type()
+        .c(d!(NoahError::ParameterError))?;
+    let new_commitment = pc_gens.commit(asset_type.as_scalar(), RistrettoScalar::zero());
+
+    let mut transcript = Transcript::new(ASSET_TYPE_REVEAL_TRANSCRIPT);
+    chaum_pedersen_verify_eq(
+        &mut transcript,
+        prng,
+        &old_commitment,
+        &new_commitment,
+        proof,
+    )
+    .c(d!(NoahError::XfrVerifyConfidentialAssetError))
+}
+
 /// Helper function to generate assemble asset record from templates.
 fn build_record_input_from_template<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -616,8 +774,12 @@ fn build_record_input_from_template<R: CryptoRng + RngCore>(
 #[cfg(feature = "xfr-tracing")]
 #[cfg(test)]
 mod test {
-    use super::{build_blind_asset_record, build_open_asset_record, open_blind_asset_record};
+    use super::{
+        build_blind_asset_record, build_open_asset_record, open_blind_asset_record,
+        verify_asset_type_reveal,
+    };
     use crate::keys::KeyPair;
+    use crate::parameters::bulletproofs::BulletproofParams;
     use crate::parameters::AddressFormat::SECP256K1;
     use crate::xfr::{
         asset_record::AssetRecordType,
@@ -769,6 +931,7 @@ mod test {
 
     fn do_test_open_asset_record(record_type: AssetRecordType) {
         let mut prng = test_rng();
+        let params = BulletproofParams::default();
         let pc_gens = PedersenCommitmentRistretto::default();
 
         let asset_type = AssetType::from_identical_byte(1u8);
@@ -800,6 +963,7 @@ mod test {
 
         let (xfr_note, _, _) = create_xfr(
             &mut prng,
+            &params,
             &input_templates,
             &output_templates,
             inkeys.iter().collect_vec().as_slice(),
@@ -911,6 +1075,59 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_reveal_asset_type() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_type: AssetType = AssetType(prng.gen());
+        let amount: u64 = prng.gen();
+        let ar = AssetRecordTemplate::with_no_asset_tracing(
+            amount,
+            asset_type,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            keypair.pub_key,
+        );
+        let (blind_rec, _, owner_memo) = build_blind_asset_record(&mut prng, &pc_gens, &ar, vec![]);
+        let open_rec = open_blind_asset_record(&blind_rec, &owner_memo, &keypair).unwrap();
+
+        let owner_memo = owner_memo.unwrap();
+        let (new_rec, new_memo, proof) =
+            open_rec.reveal_asset_type(&mut prng, &owner_memo).unwrap();
+        assert_eq!(
+            new_rec.get_record_type(),
+            AssetRecordType::ConfidentialAmount_NonConfidentialAssetType
+        );
+        assert_eq!(new_rec.public_key, keypair.pub_key);
+        assert_eq!(new_rec.amount, blind_rec.amount);
+        assert!(verify_asset_type_reveal(&mut prng, &blind_rec, &new_rec, &proof).is_ok());
+
+        let new_open_rec = open_blind_asset_record(&new_rec, &Some(new_memo), &keypair).unwrap();
+        assert_eq!(*new_open_rec.get_amount(), amount);
+        assert_eq!(*new_open_rec.get_asset_type(), asset_type);
+
+        // a different asset type does not match the old commitment
+        let mut bad_rec = new_rec.clone();
+        bad_rec.asset_type = XfrAssetType::NonConfidential(AssetType(prng.gen()));
+        assert!(verify_asset_type_reveal(&mut prng, &blind_rec, &bad_rec, &proof).is_err());
+
+        // the amount cannot be re-committed, e.g. to a larger amount
+        let inflated = AssetRecordTemplate::with_no_asset_tracing(
+            amount.wrapping_add(1),
+            asset_type,
+            AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+            keypair.pub_key,
+        );
+        let (inflated_rec, _, _) = build_blind_asset_record(&mut prng, &pc_gens, &inflated, vec![]);
+        assert!(verify_asset_type_reveal(&mut prng, &blind_rec, &inflated_rec, &proof).is_err());
+
+        // only records with confidential amount and asset type can be revealed
+        assert!(new_open_rec
+            .reveal_asset_type(&mut prng, &owner_memo)
+            .is_err());
+    }
+
     #[test]
     fn open_blind_asset_record_error() {
         let mut prng = test_rng();
@@ -1,7 +1,9 @@
-use crate::anon_creds::{Attr, AttributeCiphertext};
+use crate::anon_creds::{Attr, AttributeCiphertext, AttributeEncKey};
 use crate::xfr::structs::{
     AssetTracerDecKeys, AssetTracerEncKeys, AssetType, TracerMemo, ASSET_TYPE_LENGTH,
 };
+use digest::Digest;
+use merlin::Transcript;
 use noah_algebra::{
     bls12_381::{BLSScalar, BLSG1},
     prelude::*,
@@ -9,10 +11,13 @@ use noah_algebra::{
 };
 use noah_crypto::basic::{
     elgamal::{
-        elgamal_encrypt, elgamal_partial_decrypt, ElGamalCiphertext, ElGamalDecKey, ElGamalEncKey,
+        elgamal_batch_verify, elgamal_encrypt, elgamal_partial_decrypt, ElGamalCiphertext,
+        ElGamalDecKey, ElGamalEncKey,
     },
     hybrid_encryption::{hybrid_decrypt_with_x25519_secret_key, hybrid_encrypt_x25519},
+    matrix_sigma::SigmaTranscript,
 };
+use rand_chacha::ChaChaRng;
 
 /// The encryption key for the record data.
 pub type RecordDataEncKey = ElGamalEncKey<RistrettoPoint>;
@@ -24,6 +29,138 @@ type DecryptedAssetMemo = (Option<u64>, Option<AssetType>, Vec<Attr>);
 
 const U32_BYTES: usize = 4;
 
+/// A proof that an attribute ciphertext encrypts one of the values of a public set,
+/// without revealing which one.
+/// It is an OR-composition of Chaum-Pedersen proofs, one per allowed value.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AttrSetProof {
+    /// The proof commitments `(A_i, B_i)` for each allowed value.
+    pub commitments: Vec<(BLSG1, BLSG1)>,
+    /// The challenge share for each allowed value.
+    pub challenges: Vec<BLSScalar>,
+    /// The response for each allowed value.
+    pub responses: Vec<BLSScalar>,
+}
+
+fn init_attr_set_transcript(
+    transcript: &mut Transcript,
+    enc_key: &AttributeEncKey,
+    ctext: &AttributeCiphertext,
+    allowed: &[Attr],
+) {
+    let allowed_scalars = allowed.iter().map(|x| BLSScalar::from(*x)).collect_vec();
+    transcript.init_sigma::<BLSG1>(
+        b"Attribute in set",
+        &allowed_scalars.iter().collect_vec(),
+        &[BLSG1::get_base(), enc_key.0, ctext.e1, ctext.e2],
+    );
+}
+
+/// Prove that `ctext`, an ElGamal encryption under `enc_key` with randomness `blind`,
+/// encrypts one of the values in `allowed`.
+/// Returns Err(NoahError::ParameterError) if `allowed` is empty or if `ctext` does not
+/// encrypt any of its values with `blind`.
+pub fn prove_attr_in_set<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    enc_key: &AttributeEncKey,
+    ctext: &AttributeCiphertext,
+    blind: &BLSScalar,
+    allowed: &[Attr],
+) -> Result<AttrSetProof> {
+    let base = BLSG1::get_base();
+    if ctext.e1 != base.mul(blind) {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    // m * G = e2 - r * pk
+    let msg_point = ctext.e2.sub(&enc_key.0.mul(blind));
+    let index = allowed
+        .iter()
+        .position(|x| base.mul(&BLSScalar::from(*x)) == msg_point)
+        .c(d!(NoahError::ParameterError))?;
+
+    let mut transcript = Transcript::new(b"AttrSetProof");
+    init_attr_set_transcript(&mut transcript, enc_key, ctext, allowed);
+
+    let n = allowed.len();
+    let mut commitments = Vec::with_capacity(n);
+    let mut challenges = vec![BLSScalar::zero(); n];
+    let mut responses = vec![BLSScalar::zero(); n];
+    let w = BLSScalar::random(prng);
+    for (i, attr) in allowed.iter().enumerate() {
+        let (a, b) = if i == index {
+            (base.mul(&w), enc_key.0.mul(&w))
+        } else {
+            // simulate the branch: A = z * G - c * e1, B = z * pk - c * (e2 - attr * G)
+            challenges[i] = BLSScalar::random(prng);
+            responses[i] = BLSScalar::random(prng);
+            let shifted = ctext.e2.sub(&base.mul(&BLSScalar::from(*attr)));
+            (
+                base.mul(&responses[i]).sub(&ctext.e1.mul(&challenges[i])),
+                enc_key
+                    .0
+                    .mul(&responses[i])
+                    .sub(&shifted.mul(&challenges[i])),
+            )
+        };
+        transcript.append_proof_commitment(&a);
+        transcript.append_proof_commitment(&b);
+        commitments.push((a, b));
+    }
+
+    let challenge: BLSScalar = transcript.get_challenge();
+    let others: BLSScalar = challenges.iter().sum();
+    challenges[index] = challenge - &others;
+    responses[index] = w + &(challenges[index] * blind);
+
+    Ok(AttrSetProof {
+        commitments,
+        challenges,
+        responses,
+    })
+}
+
+/// Verify an `AttrSetProof` that `ctext` encrypts one of the values in `allowed` under `enc_key`.
+/// Returns Err(NoahError::ZKProofVerificationError) in case of verification failure.
+pub fn verify_attr_in_set(
+    enc_key: &AttributeEncKey,
+    ctext: &AttributeCiphertext,
+    allowed: &[Attr],
+    proof: &AttrSetProof,
+) -> Result<()> {
+    let n = allowed.len();
+    if n == 0
+        || proof.commitments.len() != n
+        || proof.challenges.len() != n
+        || proof.responses.len() != n
+    {
+        return Err(eg!(NoahError::ZKProofVerificationError));
+    }
+
+    let mut transcript = Transcript::new(b"AttrSetProof");
+    init_attr_set_transcript(&mut transcript, enc_key, ctext, allowed);
+    for (a, b) in proof.commitments.iter() {
+        transcript.append_proof_commitment(a);
+        transcript.append_proof_commitment(b);
+    }
+    let challenge: BLSScalar = transcript.get_challenge();
+    let sum: BLSScalar = proof.challenges.iter().sum();
+    if sum != challenge {
+        return Err(eg!(NoahError::ZKProofVerificationError));
+    }
+
+    let base = BLSG1::get_base();
+    for (i, attr) in allowed.iter().enumerate() {
+        let (a, b) = &proof.commitments[i];
+        let c = &proof.challenges[i];
+        let z = &proof.responses[i];
+        let shifted = ctext.e2.sub(&base.mul(&BLSScalar::from(*attr)));
+        if base.mul(z) != a.add(&ctext.e1.mul(c)) || enc_key.0.mul(z) != b.add(&shifted.mul(c)) {
+            return Err(eg!(NoahError::ZKProofVerificationError));
+        }
+    }
+    Ok(())
+}
+
 impl TracerMemo {
     /// Sample a new TracerMemo.
     /// amount_info is (amount_low, amount_high, amount_blind_low, amount_blind_high) tuple
@@ -75,41 +212,118 @@ impl TracerMemo {
         }
     }
 
+    /// Build a TracerMemo whose randomness is entirely derived from `seed`.
+    /// The ElGamal randomizers of the amount, the asset type and the attributes,
+    /// as well as the ephemeral key of the hybrid encryption, are drawn from a
+    /// ChaCha PRNG keyed by SHA-512 of the seed, so identical seeds produce identical memos.
+    /// This is meant for test fixtures and reproducible audits only: the blinds are not
+    /// the ones of the record commitments, and a reused seed leaks the plaintexts.
+    pub fn new_deterministic(
+        seed: &[u8],
+        tracer_enc_key: &AssetTracerEncKeys,
+        amount: Option<u64>,
+        asset_type: Option<&AssetType>,
+        attrs: &[Attr],
+    ) -> Self {
+        let mut hasher = sha2::Sha512::new();
+        hasher.update(b"Noah deterministic tracer memo");
+        hasher.update(seed);
+        let mut prng_seed = [0u8; 32];
+        prng_seed.copy_from_slice(&hasher.finalize()[0..32]);
+        let mut prng = ChaChaRng::from_seed(prng_seed);
+
+        let amount_blinds = amount.map(|amount| {
+            let (low, high) = u64_to_u32_pair(amount);
+            (
+                low,
+                high,
+                RistrettoScalar::random(&mut prng),
+                RistrettoScalar::random(&mut prng),
+            )
+        });
+        let asset_type_blind = asset_type.map(|asset_type| {
+            let blind = RistrettoScalar::random(&mut prng);
+            (asset_type, blind)
+        });
+        let attrs_info = attrs
+            .iter()
+            .map(|attr| {
+                let ctext = elgamal_encrypt(
+                    &BLSScalar::from(*attr),
+                    &BLSScalar::random(&mut prng),
+                    &tracer_enc_key.attrs_enc_key,
+                );
+                (*attr, ctext)
+            })
+            .collect_vec();
+
+        Self::new(
+            &mut prng,
+            tracer_enc_key,
+            amount_blinds
+                .as_ref()
+                .map(|(low, high, blind_low, blind_high)| (*low, *high, blind_low, blind_high)),
+            asset_type_blind
+                .as_ref()
+                .map(|(asset_type, blind)| (*asset_type, blind)),
+            &attrs_info,
+        )
+    }
+
     /// Decrypts the asset tracer memo:
     /// Returns NoahError:BogusAssetTracerMemo in case decrypted values are inconsistents
     pub fn decrypt(&self, dec_key: &AssetTracerDecKeys) -> Result<DecryptedAssetMemo> {
+        let (amount, asset_type, attrs) = self.decrypt_lock_info(dec_key)?;
+
+        // sanitize amount
+        if let Some(amount) = amount {
+            self.verify_amount(&dec_key.record_data_dec_key, amount)
+                .c(d!(NoahError::BogusAssetTracerMemo))?;
+        }
+
+        // sanitize asset type
+        if let Some(asset_type) = asset_type.as_ref() {
+            self.verify_asset_type(&dec_key.record_data_dec_key, asset_type)
+                .c(d!(NoahError::BogusAssetTracerMemo))?;
+        }
+
+        if !self
+            .verify_identity_attributes(&dec_key.attrs_dec_key, &attrs)
+            .c(d!(NoahError::BogusAssetTracerMemo))?
+            .iter()
+            .all(|&x| x)
+        {
+            return Err(eg!(NoahError::BogusAssetTracerMemo));
+        }
+        Ok((amount, asset_type, attrs))
+    }
+
+    /// Decrypts the hybrid ciphertext `lock_info` and splits it into amount, asset type and attributes,
+    /// without checking them against the ElGamal ciphertexts.
+    fn decrypt_lock_info(&self, dec_key: &AssetTracerDecKeys) -> Result<DecryptedAssetMemo> {
         let mut plaintext =
             hybrid_decrypt_with_x25519_secret_key(&self.lock_info, &dec_key.lock_info_dec_key);
 
-        // decrypt and sanitize amount
         let amount = if self.lock_amount.is_some() {
             if plaintext.len() < 2 * U32_BYTES {
                 return Err(eg!(NoahError::BogusAssetTracerMemo));
             }
             let amount_low = u8_be_slice_to_u32(&plaintext[0..U32_BYTES]);
             let amount_high = u8_be_slice_to_u32(&plaintext[U32_BYTES..2 * U32_BYTES]);
-            let amount = (amount_low as u64) + ((amount_high as u64) << 32);
-            self.verify_amount(&dec_key.record_data_dec_key, amount)
-                .c(d!(NoahError::BogusAssetTracerMemo))?;
             plaintext = plaintext.split_off(2 * U32_BYTES);
-            Some(amount)
+            Some((amount_low as u64) + ((amount_high as u64) << 32))
         } else {
             None
         };
 
-        // decrypt and sanitize asset type
         let asset_type = if self.lock_asset_type.is_some() {
             if plaintext.len() < ASSET_TYPE_LENGTH {
                 return Err(eg!(NoahError::BogusAssetTracerMemo));
             }
             let mut asset_type = [0u8; ASSET_TYPE_LENGTH];
             asset_type.copy_from_slice(&plaintext[0..ASSET_TYPE_LENGTH]);
-            let asset_type = AssetType(asset_type);
-
-            self.verify_asset_type(&dec_key.record_data_dec_key, &asset_type)
-                .c(d!(NoahError::BogusAssetTracerMemo))?;
             plaintext = plaintext.split_off(ASSET_TYPE_LENGTH);
-            Some(asset_type)
+            Some(AssetType(asset_type))
         } else {
             None
         };
@@ -121,15 +335,6 @@ impl TracerMemo {
         for attr_byte in plaintext.chunks(U32_BYTES) {
             attrs.push(u8_be_slice_to_u32(attr_byte));
         }
-
-        if !self
-            .verify_identity_attributes(&dec_key.attrs_dec_key, &attrs)
-            .c(d!(NoahError::BogusAssetTracerMemo))?
-            .iter()
-            .all(|&x| x)
-        {
-            return Err(eg!(NoahError::BogusAssetTracerMemo));
-        }
         Ok((amount, asset_type, attrs))
     }
 
@@ -221,12 +426,196 @@ impl TracerMemo {
     }
 }
 
+impl AssetTracerDecKeys {
+    /// Decrypts a batch of asset tracer memos.
+    /// The hybrid ciphertexts are decrypted one by one, while the consistency checks against
+    /// the ElGamal ciphertexts of all the memos are done with one multi-exponentiation per group.
+    /// Memos that cannot be parsed, or all of them if the batch check fails, go through
+    /// `TracerMemo::decrypt`, so the output always matches decrypting each memo individually.
+    pub fn decrypt_batch(&self, memos: &[TracerMemo]) -> Vec<Result<DecryptedAssetMemo>> {
+        let decrypted = memos
+            .iter()
+            .map(|memo| {
+                memo.decrypt_lock_info(self)
+                    .ok()
+                    .filter(|(_, _, attrs)| attrs.len() == memo.lock_attributes.len())
+            })
+            .collect_vec();
+
+        let mut record_msgs = vec![];
+        let mut record_ctexts = vec![];
+        let mut attrs_msgs = vec![];
+        let mut attrs_ctexts = vec![];
+        for (memo, info) in memos.iter().zip(decrypted.iter()) {
+            if let Some((amount, asset_type, attrs)) = info {
+                if let (Some(amount), Some((ctext_low, ctext_high))) =
+                    (amount, memo.lock_amount.as_ref())
+                {
+                    let (low, high) = u64_to_u32_pair(*amount);
+                    record_msgs.push(RistrettoScalar::from(low));
+                    record_ctexts.push(ctext_low);
+                    record_msgs.push(RistrettoScalar::from(high));
+                    record_ctexts.push(ctext_high);
+                }
+                if let (Some(asset_type), Some(ctext)) = (asset_type, memo.lock_asset_type.as_ref())
+                {
+                    record_msgs.push(asset_type.as_scalar());
+                    record_ctexts.push(ctext);
+                }
+                for (attr, ctext) in attrs.iter().zip(memo.lock_attributes.iter()) {
+                    attrs_msgs.push(BLSScalar::from(*attr));
+                    attrs_ctexts.push(ctext);
+                }
+            }
+        }
+
+        let batch_ok =
+            elgamal_batch_verify(&record_msgs, &record_ctexts, &self.record_data_dec_key).is_ok()
+                && elgamal_batch_verify(&attrs_msgs, &attrs_ctexts, &self.attrs_dec_key).is_ok();
+
+        memos
+            .iter()
+            .zip(decrypted.into_iter())
+            .map(|(memo, info)| match info {
+                Some(info) if batch_ok => Ok(info),
+                _ => memo.decrypt(self),
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{prove_attr_in_set, verify_attr_in_set};
     use crate::xfr::structs::{AssetTracerKeyPair, AssetType, TracerMemo};
     use noah_algebra::{bls12_381::BLSScalar, prelude::*, ristretto::RistrettoScalar};
     use noah_crypto::basic::elgamal::elgamal_encrypt;
 
+    #[test]
+    fn deterministic_tracer_memo() {
+        let mut prng = test_rng();
+        let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
+        let amount = (1u64 << 40) + 500;
+        let asset_type = AssetType::from_identical_byte(7u8);
+        let attrs = [1u32, 2, 3];
+
+        let memo = TracerMemo::new_deterministic(
+            b"fixture",
+            &tracer_keys.enc_key,
+            Some(amount),
+            Some(&asset_type),
+            &attrs,
+        );
+        let same = TracerMemo::new_deterministic(
+            b"fixture",
+            &tracer_keys.enc_key,
+            Some(amount),
+            Some(&asset_type),
+            &attrs,
+        );
+        let other = TracerMemo::new_deterministic(
+            b"fixture2",
+            &tracer_keys.enc_key,
+            Some(amount),
+            Some(&asset_type),
+            &attrs,
+        );
+        assert_eq!(memo, same);
+        assert_ne!(memo, other);
+
+        let (dec_amount, dec_asset_type, dec_attrs) = memo.decrypt(&tracer_keys.dec_key).unwrap();
+        assert_eq!(dec_amount, Some(amount));
+        assert_eq!(dec_asset_type, Some(asset_type));
+        assert_eq!(dec_attrs, attrs.to_vec());
+        assert_eq!(
+            other.decrypt(&tracer_keys.dec_key).unwrap(),
+            (Some(amount), Some(asset_type), attrs.to_vec())
+        );
+    }
+
+    #[test]
+    fn batch_decrypt_tracer_memos() {
+        let mut prng = test_rng();
+        let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
+        let asset_type = AssetType::from_identical_byte(3u8);
+
+        let mut memos = vec![
+            TracerMemo::new_deterministic(b"0", &tracer_keys.enc_key, None, None, &[]),
+            TracerMemo::new_deterministic(b"1", &tracer_keys.enc_key, Some(10), None, &[]),
+            TracerMemo::new_deterministic(
+                b"2",
+                &tracer_keys.enc_key,
+                Some(1u64 << 40),
+                Some(&asset_type),
+                &[1u32, 2],
+            ),
+            TracerMemo::new_deterministic(b"3", &tracer_keys.enc_key, None, None, &[7u32]),
+        ];
+
+        let check = |memos: &[TracerMemo]| {
+            let batch = tracer_keys.dec_key.decrypt_batch(memos);
+            assert_eq!(batch.len(), memos.len());
+            for (memo, result) in memos.iter().zip(batch.iter()) {
+                match memo.decrypt(&tracer_keys.dec_key) {
+                    Ok(expected) => assert_eq!(result.as_ref().unwrap(), &expected),
+                    Err(_) => assert!(result.is_err()),
+                }
+            }
+            batch
+        };
+
+        assert!(check(&memos).iter().all(|r| r.is_ok()));
+        assert!(tracer_keys.dec_key.decrypt_batch(&[]).is_empty());
+
+        // the amount ciphertexts do not match the hybrid ciphertext anymore
+        memos[1].lock_amount = memos[2].lock_amount.clone();
+        // the hybrid ciphertext is too short for the attributes
+        memos[3].lock_attributes = memos[2].lock_attributes.clone();
+        let batch = check(&memos);
+        assert!(batch[0].is_ok());
+        assert!(batch[1].is_err());
+        assert!(batch[2].is_ok());
+        assert!(batch[3].is_err());
+    }
+
+    #[test]
+    fn attribute_in_set_proof() {
+        let mut prng = test_rng();
+        let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
+        let enc_key = &tracer_keys.enc_key.attrs_enc_key;
+        let allowed = [3u32, 14, 15, 92];
+
+        let blind = BLSScalar::random(&mut prng);
+        let ctext = elgamal_encrypt(&BLSScalar::from(15u32), &blind, enc_key);
+        let proof = prove_attr_in_set(&mut prng, enc_key, &ctext, &blind, &allowed).unwrap();
+        pnk!(verify_attr_in_set(enc_key, &ctext, &allowed, &proof));
+
+        // wrong set or wrong ciphertext
+        msg_eq!(
+            NoahError::ZKProofVerificationError,
+            verify_attr_in_set(enc_key, &ctext, &[3u32, 14, 16, 92], &proof).unwrap_err(),
+        );
+        msg_eq!(
+            NoahError::ZKProofVerificationError,
+            verify_attr_in_set(enc_key, &ctext, &allowed[1..], &proof).unwrap_err(),
+        );
+        let other = elgamal_encrypt(&BLSScalar::from(16u32), &blind, enc_key);
+        msg_eq!(
+            NoahError::ZKProofVerificationError,
+            verify_attr_in_set(enc_key, &other, &allowed, &proof).unwrap_err(),
+        );
+
+        // the prover cannot prove a value outside of the set
+        msg_eq!(
+            NoahError::ParameterError,
+            prove_attr_in_set(&mut prng, enc_key, &other, &blind, &allowed).unwrap_err(),
+        );
+        msg_eq!(
+            NoahError::ParameterError,
+            prove_attr_in_set(&mut prng, enc_key, &ctext, &blind, &[]).unwrap_err(),
+        );
+    }
+
     #[test]
     fn extract_amount_from_tracer_memo() {
         let mut prng = test_rng();
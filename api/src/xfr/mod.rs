@@ -2,7 +2,7 @@ use aes_gcm::aead::Aead;
 use digest::{generic_array::GenericArray, Digest, KeyInit};
 use noah_algebra::secp256k1::{SECP256K1Scalar, SECP256K1G1};
 use noah_algebra::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     prelude::*,
     ristretto::{CompressedRistretto, PedersenCommitmentRistretto, RistrettoScalar},
     traits::PedersenCommitment,
@@ -37,7 +37,8 @@ use self::{
     },
     proofs::{
         asset_amount_tracing_proofs, asset_proof, batch_verify_confidential_amount,
-        batch_verify_confidential_asset, batch_verify_tracer_tracing_proof, gen_range_proof,
+        batch_verify_confidential_asset, batch_verify_tracer_tracing_proof, check_bp_gens,
+        gen_range_proof,
     },
     structs::*,
 };
@@ -119,7 +120,68 @@ impl XfrType {
     }
 }
 
+/// The kind of a confidential transfer, used to route it to the right handling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferKind {
+    /// All the amounts and asset types are revealed.
+    NonConfidential,
+    /// Some amounts are confidential, all the asset types are revealed.
+    ConfidentialAmount,
+    /// Some asset types are confidential, all the amounts are revealed.
+    ConfidentialType,
+    /// Some amounts and some asset types are confidential.
+    FullyConfidential,
+}
+
+impl TransferKind {
+    /// Return true if some amounts are confidential, so that the transfer has a range proof.
+    pub fn has_confidential_amount(self) -> bool {
+        matches!(
+            self,
+            TransferKind::ConfidentialAmount | TransferKind::FullyConfidential
+        )
+    }
+}
+
+/// Return the distinct asset types of the open records, sorted, e.g., to know how many asset
+/// types a multi-asset transfer involves.
+pub fn distinct_asset_types(records: &[OpenAssetRecord]) -> Vec<AssetType> {
+    records
+        .iter()
+        .map(|record| record.asset_type)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Classify a transfer body by the confidentiality of its input and output records.
+pub fn classify_transfer(body: &XfrBody) -> TransferKind {
+    classify_records(body.inputs.iter().chain(body.outputs.iter()))
+}
+
+/// Classify a set of records by their confidentiality, as `classify_transfer` does for the
+/// records of a transfer body.
+pub fn classify_records<'a>(
+    records: impl IntoIterator<Item = &'a BlindAssetRecord>,
+) -> TransferKind {
+    let (confidential_amount, confidential_asset_type) = records
+        .into_iter()
+        .map(|record| record.get_record_type().get_flags())
+        .fold((false, false), |acc, flags| {
+            (acc.0 || flags.0, acc.1 || flags.1)
+        });
+
+    match (confidential_amount, confidential_asset_type) {
+        (false, false) => TransferKind::NonConfidential,
+        (true, false) => TransferKind::ConfidentialAmount,
+        (false, true) => TransferKind::ConfidentialType,
+        (true, true) => TransferKind::FullyConfidential,
+    }
+}
+
 /// Generate a confidential transfer note.
+/// The range proofs of confidential amounts use the generators of `params`, which must be
+/// long enough for the outputs, see `check_bp_gens`.
 /// # Example
 /// ```
 /// use rand_chacha::ChaChaRng;
@@ -173,6 +235,7 @@ impl XfrType {
 /// }
 ///
 /// let xfr_note = gen_xfr_note( &mut prng,
+///                              &params,
 ///                              inputs.as_slice(),
 ///                              outputs.as_slice(),
 ///                              inkeys.iter().map(|x| x).collect_vec().as_slice()
@@ -182,6 +245,7 @@ impl XfrType {
 /// ```
 pub fn gen_xfr_note<R: CryptoRng + RngCore>(
     prng: &mut R,
+    params: &BulletproofParams,
     inputs: &[AssetRecord],
     outputs: &[AssetRecord],
     input_key_pairs: &[&KeyPair],
@@ -192,13 +256,14 @@ pub fn gen_xfr_note<R: CryptoRng + RngCore>(
 
     check_keys(inputs, input_key_pairs).c(d!())?;
 
-    let body = gen_xfr_body(prng, inputs, outputs).c(d!())?;
+    let body = gen_xfr_body(prng, params, inputs, outputs).c(d!())?;
     let multisig = compute_transfer_multisig(&body, input_key_pairs).c(d!())?;
 
     Ok(XfrNote { body, multisig })
 }
 
 /// Generate the confidential transfer body.
+/// The range proofs of confidential amounts use the generators of `params`, see `gen_xfr_note`.
 /// # Example
 /// ```
 /// use rand_chacha::ChaChaRng;
@@ -243,12 +308,13 @@ pub fn gen_xfr_note<R: CryptoRng + RngCore>(
 ///     let ar = AssetRecordTemplate::with_no_asset_tracing(x.0, x.1, asset_record_type, keypair.get_pk());
 ///     outputs.push(AssetRecord::from_template_no_identity_tracing(&mut prng, &ar).unwrap());
 /// }
-/// let body = gen_xfr_body(&mut prng, &inputs, &outputs).unwrap();
+/// let body = gen_xfr_body(&mut prng, &params, &inputs, &outputs).unwrap();
 /// let policies = XfrNotePolicies::empty_policies(inputs.len(), outputs.len());
 /// pnk!(verify_xfr_body(&mut prng, &mut params, &body, &policies.to_ref()));
 /// ```
 pub fn gen_xfr_body<R: CryptoRng + RngCore>(
     prng: &mut R,
+    params: &BulletproofParams,
     inputs: &[AssetRecord],
     outputs: &[AssetRecord],
 ) -> Result<XfrBody> {
@@ -274,6 +340,7 @@ pub fn gen_xfr_body<R: CryptoRng + RngCore>(
     let asset_amount_proof = if single_asset {
         gen_xfr_proofs_single_asset(
             prng,
+            params,
             open_inputs.as_slice(),
             open_outputs.as_slice(),
             xfr_type,
@@ -384,16 +451,25 @@ fn gen_xfr_proofs_multi_asset(
 
 fn gen_xfr_proofs_single_asset<R: CryptoRng + RngCore>(
     prng: &mut R,
+    params: &BulletproofParams,
     inputs: &[&OpenAssetRecord],
     outputs: &[&OpenAssetRecord],
     xfr_type: XfrType,
 ) -> Result<AssetTypeAndAmountProof> {
     let pc_gens = PedersenCommitmentRistretto::default();
 
+    if matches!(
+        xfr_type,
+        XfrType::ConfidentialAmount_NonConfidentialAssetType_SingleAsset
+            | XfrType::Confidential_SingleAsset
+    ) {
+        check_bp_gens(&params.bp_gens, outputs.len()).c(d!(NoahError::RangeProofProveError))?;
+    }
+
     match xfr_type {
         XfrType::NonConfidential_SingleAsset => Ok(AssetTypeAndAmountProof::NoProof),
         XfrType::ConfidentialAmount_NonConfidentialAssetType_SingleAsset => Ok(
-            AssetTypeAndAmountProof::ConfAmount(gen_range_proof(inputs, outputs).c(d!())?),
+            AssetTypeAndAmountProof::ConfAmount(gen_range_proof(params, inputs, outputs).c(d!())?),
         ),
         XfrType::NonConfidentialAmount_ConfidentialAssetType_SingleAsset => {
             Ok(AssetTypeAndAmountProof::ConfAsset(Box::new(
@@ -401,7 +477,7 @@ fn gen_xfr_proofs_single_asset<R: CryptoRng + RngCore>(
             )))
         }
         XfrType::Confidential_SingleAsset => Ok(AssetTypeAndAmountProof::ConfAll(Box::new((
-            gen_range_proof(inputs, outputs).c(d!())?,
+            gen_range_proof(params, inputs, outputs).c(d!())?,
             asset_proof(prng, &pc_gens, inputs, outputs).c(d!())?,
         )))),
         _ => Err(eg!(NoahError::XfrCreationAssetAmountError)), // Type cannot be multi asset
@@ -604,6 +680,101 @@ impl<'b> XfrNotePoliciesRef<'b> {
             outputs_sig_commitments,
         }
     }
+
+    /// Return the normalized tracing policies of the inputs and of the outputs, in the order
+    /// the record builders use for the tracer memos and proofs, see `TracingPolicies::normalized`.
+    pub(crate) fn normalized_tracing_policies(
+        &self,
+    ) -> (Vec<TracingPolicies>, Vec<TracingPolicies>) {
+        let normalize = |policies: &[&TracingPolicies]| {
+            policies
+                .iter()
+                .map(|policies| (*policies).clone().normalized())
+                .collect_vec()
+        };
+        (
+            normalize(&self.inputs_tracing_policies),
+            normalize(&self.outputs_tracing_policies),
+        )
+    }
+}
+
+/// A builder of `XfrNotePoliciesRef` for a transfer with a given number of inputs and outputs,
+/// which checks that the tracing policies and the attribute commitments are aligned as they
+/// are added, so that the built policies are consistent.
+pub struct XfrNotePoliciesBuilder<'b> {
+    n_inputs: usize,
+    n_outputs: usize,
+    policies: XfrNotePoliciesRef<'b>,
+}
+
+impl<'b> XfrNotePoliciesBuilder<'b> {
+    /// Create a builder for a transfer with `n_inputs` inputs and `n_outputs` outputs.
+    pub fn new(n_inputs: usize, n_outputs: usize) -> Self {
+        XfrNotePoliciesBuilder {
+            n_inputs,
+            n_outputs,
+            policies: XfrNotePoliciesRef::default(),
+        }
+    }
+
+    /// Add the tracing policies and the attribute commitment of the next input.
+    pub fn add_input(
+        mut self,
+        tracing_policies: &'b TracingPolicies,
+        sig_commitment: Option<&'b ACCommitment>,
+    ) -> Result<Self> {
+        if self.policies.inputs_tracing_policies.len() == self.n_inputs {
+            return Err(eg!(NoahError::ParameterError));
+        }
+        check_sig_commitment(tracing_policies, sig_commitment).c(d!())?;
+        self.policies.inputs_tracing_policies.push(tracing_policies);
+        self.policies.inputs_sig_commitments.push(sig_commitment);
+        Ok(self)
+    }
+
+    /// Add the tracing policies and the attribute commitment of the next output.
+    pub fn add_output(
+        mut self,
+        tracing_policies: &'b TracingPolicies,
+        sig_commitment: Option<&'b ACCommitment>,
+    ) -> Result<Self> {
+        if self.policies.outputs_tracing_policies.len() == self.n_outputs {
+            return Err(eg!(NoahError::ParameterError));
+        }
+        check_sig_commitment(tracing_policies, sig_commitment).c(d!())?;
+        self.policies
+            .outputs_tracing_policies
+            .push(tracing_policies);
+        self.policies.outputs_sig_commitments.push(sig_commitment);
+        Ok(self)
+    }
+
+    /// Build the policies, checking that the policies of all the inputs and outputs were added.
+    pub fn build(mut self) -> Result<XfrNotePoliciesRef<'b>> {
+        if self.policies.inputs_tracing_policies.len() != self.n_inputs
+            || self.policies.outputs_tracing_policies.len() != self.n_outputs
+        {
+            return Err(eg!(NoahError::ParameterError));
+        }
+        self.policies.valid = true;
+        Ok(self.policies)
+    }
+}
+
+/// Check that an attribute commitment is given if one of the policies traces the identity.
+fn check_sig_commitment(
+    tracing_policies: &TracingPolicies,
+    sig_commitment: Option<&ACCommitment>,
+) -> Result<()> {
+    let traces_identity = tracing_policies
+        .get_policies()
+        .iter()
+        .any(|policy| policy.identity_tracing.is_some());
+    if traces_identity && sig_commitment.is_none() {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    Ok(())
 }
 
 pub(crate) fn if_some_closure(x: &Option<ACCommitment>) -> Option<&ACCommitment> {
@@ -1,16 +1,19 @@
-use crate::anon_creds::{ac_confidential_verify, ACCommitment, ACConfidentialRevealProof};
+use crate::anon_creds::{
+    ac_confidential_batch_verify, ACCommitment, ACConfidentialRevealProof, ACVerifyInstance,
+};
 use crate::parameters::bulletproofs::BulletproofParams;
 use crate::parameters::params::{BULLET_PROOF_RANGE, MAX_CONFIDENTIAL_RECORD_NUMBER};
 use crate::xfr::{
     asset_record::AssetRecordType,
     asset_tracer::RecordDataEncKey,
+    classify_records,
     structs::{
-        AssetRecord, BlindAssetRecord, OpenAssetRecord, TracerMemo, TracingPolicies, XfrAmount,
-        XfrAssetType, XfrBody, XfrRangeProof,
+        AssetRecord, BlindAssetRecord, OpenAssetRecord, TracerMemo, TracingPolicies, TracingPolicy,
+        XfrAmount, XfrAssetType, XfrBody, XfrRangeProof,
     },
-    XfrNotePoliciesRef,
+    TransferKind, XfrNotePoliciesRef,
 };
-use bulletproofs::RangeProof;
+use bulletproofs::{BulletproofGens, RangeProof};
 use linear_map::LinearMap;
 use merlin::Transcript;
 use noah_algebra::{
@@ -25,7 +28,7 @@ use noah_crypto::{
     basic::{
         chaum_pedersen::{
             chaum_pedersen_batch_verify_multiple_eq, chaum_pedersen_prove_multiple_eq,
-            ChaumPedersenProofX,
+            chaum_pedersen_verify_multiple_eq, ChaumPedersenProofX,
         },
         elgamal::ElGamalCiphertext,
         pedersen_elgamal::{
@@ -206,9 +209,7 @@ fn collect_bars_and_memos_by_keys<'a>(
 
     for ((tracing_policies_i, bar_i), memos_i) in bmp.range_over() {
         // If the bar is non-confidential skip memo and bar, since there is no tracing proof.
-        if bar_i.get_record_type()
-            == AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType
-        {
+        if classify_records([bar_i]) == TransferKind::NonConfidential {
             continue;
         }
 
@@ -233,57 +234,67 @@ pub(crate) fn batch_verify_tracer_tracing_proof<R: CryptoRng + RngCore>(
     if xfr_bodies.len() != instances_policies.len() {
         return Err(eg!(NoahError::ParameterError));
     }
+    if instances_policies.iter().any(|policies| !policies.valid) {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    // The memos and proofs of the records follow their normalized tracing policies.
+    let normalized = instances_policies
+        .iter()
+        .map(|policies| policies.normalized_tracing_policies())
+        .collect_vec();
+    let normalized_refs = normalized
+        .iter()
+        .map(|(inputs, outputs)| (inputs.iter().collect_vec(), outputs.iter().collect_vec()))
+        .collect_vec();
 
     // 1. Batch asset_type and amount tracing.
-    let input_reveal_policies: Result<Vec<&[&TracingPolicies]>> = instances_policies
+    let input_reveal_policies = normalized_refs
         .iter()
-        .map(|policies| {
-            if policies.valid {
-                Ok(policies.inputs_tracing_policies.as_slice())
-            } else {
-                Err(eg!(NoahError::ParameterError))
-            }
-        })
-        .collect();
-    let output_reveal_policies: Result<Vec<&[&TracingPolicies]>> = instances_policies
+        .map(|(inputs, _)| inputs.as_slice())
+        .collect_vec();
+    let output_reveal_policies = normalized_refs
         .iter()
-        .map(|policies| {
-            if policies.valid {
-                Ok(policies.outputs_tracing_policies.as_slice())
-            } else {
-                Err(eg!(NoahError::ParameterError))
-            }
-        })
-        .collect();
+        .map(|(_, outputs)| outputs.as_slice())
+        .collect_vec();
     batch_verify_asset_tracing_proofs(
         prng,
         xfr_bodies,
-        &input_reveal_policies.c(d!())?,
-        &output_reveal_policies.c(d!())?,
+        &input_reveal_policies,
+        &output_reveal_policies,
     )
     .c(d!(NoahError::XfrVerifyAssetTracingAssetAmountError))?;
 
-    // 2. Check the identity proof individually for now.
-    for (xfr_body, policies) in xfr_bodies.iter().zip(instances_policies.iter()) {
-        // 2. do identity tracing proof
+    // 2. Batch the identity proofs.
+    let mut identity_tracing = Vec::with_capacity(2 * xfr_bodies.len());
+    for ((xfr_body, policies), (inputs_policies, outputs_policies)) in xfr_bodies
+        .iter()
+        .zip(instances_policies.iter())
+        .zip(normalized_refs.iter())
+    {
         let inputs_len = xfr_body.inputs.len();
-        verify_identity_proofs(
-            &policies.inputs_tracing_policies,
+        identity_tracing.push((
+            inputs_policies.as_slice(),
             &xfr_body.asset_tracing_memos[..inputs_len],
-            &xfr_body.proofs.asset_tracing_proof.inputs_identity_proofs,
-            &policies.inputs_sig_commitments,
-        )
-        .c(d!())?;
-        verify_identity_proofs(
-            &policies.outputs_tracing_policies,
+            xfr_body
+                .proofs
+                .asset_tracing_proof
+                .inputs_identity_proofs
+                .as_slice(),
+            policies.inputs_sig_commitments.as_slice(),
+        ));
+        identity_tracing.push((
+            outputs_policies.as_slice(),
             &xfr_body.asset_tracing_memos[inputs_len..],
-            &xfr_body.proofs.asset_tracing_proof.outputs_identity_proofs,
-            &policies.outputs_sig_commitments,
-        )
-        .c(d!())?;
+            xfr_body
+                .proofs
+                .asset_tracing_proof
+                .outputs_identity_proofs
+                .as_slice(),
+            policies.outputs_sig_commitments.as_slice(),
+        ));
     }
-
-    Ok(())
+    batch_verify_identity_proofs(prng, &identity_tracing)
 }
 
 fn batch_verify_asset_tracing_proofs<R: CryptoRng + RngCore>(
@@ -382,12 +393,39 @@ fn collect_records_memos_by_key<'a>(
     Ok(map)
 }
 
-fn verify_identity_proofs(
-    reveal_policies: &[&TracingPolicies],
-    memos: &[Vec<TracerMemo>],
-    proofs: &[Vec<Option<ACConfidentialRevealProof>>],
-    sig_commitments: &[Option<&ACCommitment>],
+/// The identity tracing of a list of records: for each record, its tracing policies, its tracer
+/// memos, its credential reveal proofs, and the commitment of its credential.
+type IdentityTracing<'a> = (
+    &'a [&'a TracingPolicies],
+    &'a [Vec<TracerMemo>],
+    &'a [Vec<Option<ACConfidentialRevealProof>>],
+    &'a [Option<&'a ACCommitment>],
+);
+
+/// Check the structure of the identity tracing of the lists of records against their policies,
+/// and batch-verify all their credential reveal proofs.
+fn batch_verify_identity_proofs<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    identity_tracing: &[IdentityTracing<'_>],
 ) -> Result<()> {
+    let mut instances = vec![];
+    for &(reveal_policies, memos, proofs, sig_commitments) in identity_tracing {
+        instances.extend(
+            collect_identity_instances(reveal_policies, memos, proofs, sig_commitments).c(d!())?,
+        );
+    }
+    ac_confidential_batch_verify(prng, &instances)
+        .c(d!(NoahError::XfrVerifyAssetTracingIdentityError))
+}
+
+/// Check the structure of the identity tracing memos and proofs against the policies,
+/// and collect the credential reveal proofs to verify.
+fn collect_identity_instances<'a>(
+    reveal_policies: &[&'a TracingPolicies],
+    memos: &'a [Vec<TracerMemo>],
+    proofs: &'a [Vec<Option<ACConfidentialRevealProof>>],
+    sig_commitments: &[Option<&'a ACCommitment>],
+) -> Result<Vec<ACVerifyInstance<'a>>> {
     // 1. Check structures.
     let n = reveal_policies.len();
 
@@ -404,7 +442,8 @@ fn verify_identity_proofs(
         return Err(eg!(NoahError::XfrVerifyAssetTracingIdentityError));
     }
 
-    // 2. Check proofs.
+    // 2. Collect proofs.
+    let mut instances = vec![];
     for (policies, (memos, (proofs, sig_commitment))) in reveal_policies
         .iter()
         .zip(memos.iter().zip(proofs.iter().zip(sig_commitments.iter())))
@@ -414,23 +453,22 @@ fn verify_identity_proofs(
             return Err(eg!(NoahError::XfrVerifyAssetTracingIdentityError));
         }
         // for each policy memo and proof
-        let policies = policies.get_policies();
+        let policies: &'a [TracingPolicy] = (*policies).get_policies();
         for (policy, (memo, proof)) in policies.iter().zip(memos.iter().zip(proofs)) {
             let enc_keys = &policy.enc_keys.attrs_enc_key;
             match (&policy.identity_tracing, proof) {
                 (Some(policy), Some(proof)) => {
                     let sig_com =
                         sig_commitment.c(d!(NoahError::XfrVerifyAssetTracingIdentityError))?;
-                    ac_confidential_verify(
-                        &policy.cred_issuer_pub_key,
-                        enc_keys,
-                        &policy.reveal_map.as_slice(),
-                        sig_com,
-                        &memo.lock_attributes[..],
-                        proof,
-                        &[],
-                    )
-                    .c(d!(NoahError::XfrVerifyAssetTracingIdentityError))?
+                    instances.push(ACVerifyInstance {
+                        ipk: &policy.cred_issuer_pub_key,
+                        ek: enc_keys,
+                        reveal_map: policy.reveal_map.as_slice(),
+                        cm: sig_com,
+                        cts: &memo.lock_attributes[..],
+                        pok: proof,
+                        m: &[],
+                    });
                 }
                 (None, None) => {}
                 _ => {
@@ -439,7 +477,7 @@ fn verify_identity_proofs(
             }
         }
     }
-    Ok(())
+    Ok(instances)
 }
 
 fn extract_ciphertext_and_commitments(
@@ -491,20 +529,69 @@ fn extract_ciphertext_and_commitments(
     Ok((ctexts, coms))
 }
 
+/// Return the maximal number of outputs of a transfer with confidential amounts.
+/// The range proof covers two 32-bit limbs for each output and for the balance difference,
+/// padded to a power of two, which must not exceed `MAX_CONFIDENTIAL_RECORD_NUMBER`.
+pub fn max_confidential_outputs() -> usize {
+    MAX_CONFIDENTIAL_RECORD_NUMBER / 2 - 1
+}
+
+/// Check that a transfer with confidential amounts, `n_inputs` inputs and `n_outputs` outputs
+/// is supported, so that it does not fail later at proof generation.
+pub fn validate_transfer_shape(n_inputs: usize, n_outputs: usize) -> Result<()> {
+    if n_inputs == 0 || n_outputs == 0 {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    if n_outputs > max_confidential_outputs() {
+        return Err(eg!(format!(
+            "confidential transfer with {} outputs exceeds the limit of {} outputs (MAX_CONFIDENTIAL_RECORD_NUMBER = {})",
+            n_outputs,
+            max_confidential_outputs(),
+            MAX_CONFIDENTIAL_RECORD_NUMBER
+        )));
+    }
+    Ok(())
+}
+
+/// Return the number of party generators of `BulletproofParams::bp_gens` needed to prove
+/// the range of a transfer with `num_outputs` confidential outputs: two 32-bit limbs for each
+/// output and for the balance difference, padded to a power of two.
+pub fn required_bp_gens_len(num_outputs: usize) -> usize {
+    min_greater_equal_power_of_two((2 * (num_outputs + 1)) as u32) as usize
+}
+
+/// Check that the Bulletproofs generators are large enough for the range proof of a transfer
+/// with `num_outputs` confidential outputs.
+pub fn check_bp_gens(bp_gens: &BulletproofGens, num_outputs: usize) -> Result<()> {
+    let required = required_bp_gens_len(num_outputs);
+    if bp_gens.gens_capacity < BULLET_PROOF_RANGE || bp_gens.party_capacity < required {
+        return Err(eg!(format!(
+            "the Bulletproofs generators ({} gens, {} parties) are too short for {} outputs, which need {} gens and {} parties",
+            bp_gens.gens_capacity,
+            bp_gens.party_capacity,
+            num_outputs,
+            BULLET_PROOF_RANGE,
+            required
+        )));
+    }
+    Ok(())
+}
+
 /// Compute a range proof for confidential amount non-confidential asset type transfers.
 /// The proof guarantees that output amounts and difference between total input,
 /// and total output are in the range [0,2^{64} - 1].
 pub(crate) fn gen_range_proof(
+    params: &BulletproofParams,
     inputs: &[&OpenAssetRecord],
     outputs: &[&OpenAssetRecord],
 ) -> Result<XfrRangeProof> {
     let num_output = outputs.len();
-    let upper_power2 = min_greater_equal_power_of_two((2 * (num_output + 1)) as u32) as usize;
+    let upper_power2 = required_bp_gens_len(num_output);
     if upper_power2 > MAX_CONFIDENTIAL_RECORD_NUMBER {
         return Err(eg!(NoahError::RangeProofProveError));
     }
 
-    let params = BulletproofParams::default();
+    check_bp_gens(&params.bp_gens, num_output).c(d!(NoahError::RangeProofProveError))?;
 
     // Build values vector (out amounts + amount difference).
     let in_total = inputs.iter().fold(0u64, |accum, x| accum + x.amount);
@@ -584,6 +671,10 @@ pub(crate) fn batch_verify_confidential_amount<R: CryptoRng + RngCore>(
     let mut transcripts = vec![Transcript::new(b"Zei Range Proof"); instances.len()];
     let proofs: Vec<&RangeProof> = instances.iter().map(|(_, _, pf)| &pf.range_proof).collect();
     let mut commitments = vec![];
+    for (_, output, _) in instances {
+        check_bp_gens(&params.bp_gens, output.len())
+            .c(d!(NoahError::XfrVerifyConfidentialAmountError))?;
+    }
     for (input, output, proof) in instances {
         commitments
             .push(extract_value_commitments(input.as_slice(), output.as_slice(), proof).c(d!())?);
@@ -600,70 +691,69 @@ pub(crate) fn batch_verify_confidential_amount<R: CryptoRng + RngCore>(
     .c(d!(NoahError::XfrVerifyConfidentialAmountError))
 }
 
+/// Batch-verify the range proofs of confidential amounts in chunks of at most `chunk_size`
+/// instances, so that the memory used does not grow with the number of instances.
+/// Each chunk is verified as a batch on its own, and the verification fails if any chunk fails,
+/// in which case the error reports the chunk and the index of the first failing instance.
+pub fn batch_verify_confidential_amount_chunked<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &BulletproofParams,
+    instances: &[(
+        &Vec<BlindAssetRecord>,
+        &Vec<BlindAssetRecord>,
+        &XfrRangeProof,
+    )],
+    chunk_size: usize,
+) -> Result<()> {
+    if chunk_size == 0 {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    for (chunk_index, chunk) in instances.chunks(chunk_size).enumerate() {
+        if let Err(e) = batch_verify_confidential_amount(prng, params, chunk) {
+            // Find the failing instance by verifying the instances of the chunk one by one.
+            let offset = chunk
+                .iter()
+                .position(|instance| {
+                    batch_verify_confidential_amount(prng, params, &[*instance]).is_err()
+                })
+                .unwrap_or(0);
+            return Err(e).c(d!(format!(
+                "range proof verification failed in chunk {} at instance {}",
+                chunk_index,
+                chunk_index * chunk_size + offset
+            )));
+        }
+    }
+    Ok(())
+}
+
 fn extract_value_commitments(
     inputs: &[BlindAssetRecord],
     outputs: &[BlindAssetRecord],
     proof: &XfrRangeProof,
 ) -> Result<Vec<CompressedRistretto>> {
+    // Only a transfer with confidential amounts has a range proof.
+    if !classify_records(inputs.iter().chain(outputs.iter())).has_confidential_amount() {
+        return Err(eg!(NoahError::XfrVerifyConfidentialAmountError));
+    }
+
     let num_output = outputs.len();
     let upper_power2 = min_greater_equal_power_of_two((2 * num_output + 2) as u32) as usize;
     let pow2_32 = RistrettoScalar::from(POW_2_32);
 
     let mut commitments = Vec::with_capacity(upper_power2);
 
-    // 1. Verify proof commitment to transfer's input - output amounts match proof commitments.
-    let mut total_input_com_low = RistrettoPoint::get_identity();
-    let mut total_input_com_high = RistrettoPoint::get_identity();
-    for input in inputs.iter() {
-        let (com_low, com_high) = match input.amount {
-            XfrAmount::Confidential((com_low, com_high)) => (
-                com_low
-                    .decompress()
-                    .c(d!(NoahError::XfrVerifyConfidentialAmountError))?,
-                com_high
-                    .decompress()
-                    .c(d!(NoahError::XfrVerifyConfidentialAmountError))?,
-            ),
-            XfrAmount::NonConfidential(amount) => {
-                let (low, high) = u64_to_u32_pair(amount);
-                let pc_gens = PedersenCommitmentRistretto::default();
-                let com_low = pc_gens.commit(RistrettoScalar::from(low), RistrettoScalar::zero());
-                let com_high = pc_gens.commit(RistrettoScalar::from(high), RistrettoScalar::zero());
-                (com_low, com_high)
-            }
-        };
-        total_input_com_low = total_input_com_low.add(&com_low);
-        total_input_com_high = total_input_com_high.add(&com_high);
-    }
-    let mut total_output_com_low = RistrettoPoint::get_identity();
-    let mut total_output_com_high = RistrettoPoint::get_identity();
+    // 1. Collect the output amount commitments.
+    let mut output_coms = Vec::with_capacity(num_output);
     for output in outputs.iter() {
-        let (com_low, com_high) = match output.amount {
-            XfrAmount::Confidential((com_low, com_high)) => (
-                com_low.decompress().c(d!(NoahError::ParameterError))?,
-                com_high.decompress().c(d!(NoahError::ParameterError))?,
-            ),
-            XfrAmount::NonConfidential(amount) => {
-                let (low, high) = u64_to_u32_pair(amount);
-                let pc_gens = PedersenCommitmentRistretto::default();
-                let com_low = pc_gens.commit(RistrettoScalar::from(low), RistrettoScalar::zero());
-                let com_high = pc_gens.commit(RistrettoScalar::from(high), RistrettoScalar::zero());
-                (com_low, com_high)
-            }
-        };
-        total_output_com_low = total_output_com_low.add(&com_low);
-        total_output_com_high = total_output_com_high.add(&com_high);
-
+        let (com_low, com_high) = amount_commitments(output, NoahError::ParameterError)?;
         commitments.push(com_low.compress());
         commitments.push(com_high.compress());
+        output_coms.push((com_low, com_high));
     }
 
     // 2. Derive input - output commitment, compare with proof struct low and high commitments
-    let derived_xfr_diff_com = total_input_com_low.sub(&total_output_com_low).add(
-        &total_input_com_high
-            .sub(&total_output_com_high)
-            .mul(&pow2_32),
-    );
+    let derived_xfr_diff_com = derive_xfr_diff_commitment(inputs, &output_coms)?;
     let proof_xfr_com_low = proof
         .xfr_diff_commitment_low
         .decompress()
@@ -690,6 +780,137 @@ fn extract_value_commitments(
     Ok(commitments)
 }
 
+/// Compute the commitment to the difference between the total input amount and the
+/// total output amount, `(in_low - out_low) + 2^32 * (in_high - out_high)`, which the range
+/// proof of a transfer must match. Non-confidential amounts are committed with a zero blind.
+pub fn compute_xfr_diff_commitment(
+    inputs: &[BlindAssetRecord],
+    outputs: &[BlindAssetRecord],
+) -> Result<CompressedRistretto> {
+    let output_coms = outputs
+        .iter()
+        .map(|output| amount_commitments(output, NoahError::ParameterError))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(derive_xfr_diff_commitment(inputs, &output_coms)?.compress())
+}
+
+fn derive_xfr_diff_commitment(
+    inputs: &[BlindAssetRecord],
+    output_coms: &[(RistrettoPoint, RistrettoPoint)],
+) -> Result<RistrettoPoint> {
+    let pow2_32 = RistrettoScalar::from(POW_2_32);
+
+    let mut total_input_com_low = RistrettoPoint::get_identity();
+    let mut total_input_com_high = RistrettoPoint::get_identity();
+    for input in inputs.iter() {
+        let (com_low, com_high) =
+            amount_commitments(input, NoahError::XfrVerifyConfidentialAmountError)?;
+        total_input_com_low = total_input_com_low.add(&com_low);
+        total_input_com_high = total_input_com_high.add(&com_high);
+    }
+    let mut total_output_com_low = RistrettoPoint::get_identity();
+    let mut total_output_com_high = RistrettoPoint::get_identity();
+    for (com_low, com_high) in output_coms.iter() {
+        total_output_com_low = total_output_com_low.add(com_low);
+        total_output_com_high = total_output_com_high.add(com_high);
+    }
+
+    Ok(total_input_com_low.sub(&total_output_com_low).add(
+        &total_input_com_high
+            .sub(&total_output_com_high)
+            .mul(&pow2_32),
+    ))
+}
+
+/// Return the commitments to the low and high 32 bits of the amount of a record,
+/// using a zero blind for a non-confidential amount.
+fn amount_commitments(
+    record: &BlindAssetRecord,
+    decompress_error: NoahError,
+) -> Result<(RistrettoPoint, RistrettoPoint)> {
+    match record.amount {
+        XfrAmount::Confidential((com_low, com_high)) => Ok((
+            com_low.decompress().c(d!(decompress_error))?,
+            com_high.decompress().c(d!(decompress_error))?,
+        )),
+        XfrAmount::NonConfidential(amount) => {
+            let (low, high) = u64_to_u32_pair(amount);
+            let pc_gens = PedersenCommitmentRistretto::default();
+            let com_low = pc_gens.commit(RistrettoScalar::from(low), RistrettoScalar::zero());
+            let com_high = pc_gens.commit(RistrettoScalar::from(high), RistrettoScalar::zero());
+            Ok((com_low, com_high))
+        }
+    }
+}
+
+/// Verify a range proof against commitments supplied by the caller, rather than
+/// deriving them from the records of a transfer.
+///
+/// `expected_commitments` is the full list of value commitments the proof is over:
+/// the low and high commitments of each output, then the proof's difference commitments,
+/// padded with the identity to a power of two. The generators of `params` must cover
+/// that many commitments.
+pub fn verify_range_proof_against<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &BulletproofParams,
+    proof: &XfrRangeProof,
+    expected_commitments: &[CompressedRistretto],
+) -> Result<()> {
+    let n = expected_commitments.len();
+    if n < 2 || !n.is_power_of_two() || n > MAX_CONFIDENTIAL_RECORD_NUMBER {
+        return Err(eg!(NoahError::XfrVerifyConfidentialAmountError));
+    }
+    // `n` commitments are the outputs and the difference, padded to a power of two.
+    check_bp_gens(&params.bp_gens, n / 2 - 1).c(d!(NoahError::XfrVerifyConfidentialAmountError))?;
+
+    // The difference commitments of the proof must be a pair followed only by padding.
+    let identity = CompressedRistretto::identity();
+    let diff_pair = [
+        proof.xfr_diff_commitment_low,
+        proof.xfr_diff_commitment_high,
+    ];
+    let binds_diff = (0..n).step_by(2).rev().any(|i| {
+        expected_commitments[i..i + 2] == diff_pair
+            && expected_commitments[i + 2..]
+                .iter()
+                .all(|com| *com == identity)
+    });
+    if !binds_diff {
+        return Err(eg!(NoahError::XfrVerifyConfidentialAmountError));
+    }
+
+    // The transcript header is unchanged for compatibility.
+    let mut transcripts = [Transcript::new(b"Zei Range Proof")];
+    batch_verify_ranges(
+        prng,
+        &params.bp_gens,
+        &[&proof.range_proof],
+        &mut transcripts,
+        &[expected_commitments],
+        BULLET_PROOF_RANGE,
+    )
+    .c(d!(NoahError::XfrVerifyConfidentialAmountError))
+}
+
+/// Check that all the records have the same asset type, as required by the asset proof.
+/// The error names the first record, among the inputs then the outputs, whose asset type
+/// differs from the one of the first record.
+pub fn assert_all_same_asset_type(records: &[&OpenAssetRecord]) -> Result<()> {
+    if let Some(first) = records.first() {
+        if let Some((i, record)) = records
+            .iter()
+            .enumerate()
+            .find(|(_, record)| record.asset_type != first.asset_type)
+        {
+            return Err(eg!(format!(
+                "the asset type of record {} ({:?}) differs from the asset type of record 0 ({:?})",
+                i, record.asset_type, first.asset_type
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Compute an asset proof for confidential asset transfers
 pub(crate) fn asset_proof<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -697,6 +918,13 @@ pub(crate) fn asset_proof<R: CryptoRng + RngCore>(
     open_inputs: &[&OpenAssetRecord],
     open_outputs: &[&OpenAssetRecord],
 ) -> Result<ChaumPedersenProofX> {
+    let records = open_inputs
+        .iter()
+        .chain(open_outputs)
+        .copied()
+        .collect::<Vec<_>>();
+    assert_all_same_asset_type(&records).c(d!())?;
+
     let mut asset_coms = vec![];
     let mut asset_blinds = vec![];
 
@@ -722,6 +950,21 @@ pub(crate) fn asset_proof<R: CryptoRng + RngCore>(
     .c(d!())
 }
 
+/// Verify an asset equality proof against commitments supplied by the caller, rather than
+/// deriving them from the records of a transfer.
+///
+/// `commitments` are the asset type commitments of the inputs then of the outputs. The
+/// commitment of a non-confidential asset type is the commitment with a zero blinding factor.
+pub fn verify_asset_proof_against<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    proof: &ChaumPedersenProofX,
+    commitments: &[RistrettoPoint],
+) -> Result<()> {
+    let mut transcript = Transcript::new(b"AssetEquality");
+    chaum_pedersen_verify_multiple_eq(&mut transcript, prng, commitments, proof)
+        .c(d!(NoahError::XfrVerifyConfidentialAssetError))
+}
+
 pub(crate) fn batch_verify_confidential_asset<R: CryptoRng + RngCore>(
     prng: &mut R,
     instances: &[(
@@ -754,14 +997,151 @@ pub(crate) fn batch_verify_confidential_asset<R: CryptoRng + RngCore>(
 
 #[cfg(test)]
 mod tests {
+    use crate::anon_creds::{
+        ac_commit, ac_confidential_reveal, ac_keygen_issuer, ac_keygen_user, ac_sign, Credential,
+    };
+    use crate::keys::KeyPair;
+    use crate::parameters::bulletproofs::BulletproofParams;
+    use crate::parameters::AddressFormat::SECP256K1;
     use crate::xfr::{
-        proofs::verify_identity_proofs,
-        structs::{AssetTracerKeyPair, TracerMemo, TracingPolicies, TracingPolicy},
+        asset_record::{build_open_asset_record, AssetRecordType},
+        proofs::{
+            assert_all_same_asset_type, asset_amount_tracing_proofs, asset_proof,
+            batch_verify_confidential_amount_chunked, batch_verify_identity_proofs, check_bp_gens,
+            compute_xfr_diff_commitment, extract_value_commitments, gen_range_proof,
+            max_confidential_outputs, required_bp_gens_len, validate_transfer_shape,
+            verify_asset_proof_against, verify_range_proof_against, POW_2_32,
+        },
+        structs::{
+            AssetRecord, AssetRecordTemplate, AssetTracerKeyPair, AssetType, BlindAssetRecord,
+            IdentityRevealPolicy, OpenAssetRecord, TracerMemo, TracingPolicies, TracingPolicy,
+            XfrAmount, XfrAssetType,
+        },
+    };
+    use bulletproofs::BulletproofGens;
+    use noah_algebra::{
+        prelude::*,
+        ristretto::{CompressedRistretto, PedersenCommitmentRistretto, RistrettoScalar},
+        traits::PedersenCommitment,
     };
-    use noah_algebra::prelude::*;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn batch_verify_identity_proofs_partial_reveal() {
+        let mut prng = test_rng();
+        let (issuer_sk, issuer_pk) = ac_keygen_issuer(&mut prng, 3);
+        let (user_sk, user_pk) = ac_keygen_user(&mut prng, &issuer_pk);
+        let attrs = vec![10u32, 20, 30];
+        let sig = ac_sign(&mut prng, &issuer_sk, &user_pk, &attrs).unwrap();
+        let credential = Credential {
+            sig,
+            attrs,
+            ipk: issuer_pk.clone(),
+        };
+        let (sig_commitment, _, key) = ac_commit(&mut prng, &user_sk, &credential, b"").unwrap();
+        let key = key.unwrap();
+
+        let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
+        let policy = |reveal_map: Vec<bool>| {
+            TracingPolicies(vec![TracingPolicy {
+                enc_keys: tracer_keys.enc_key.clone(),
+                asset_tracing: false,
+                identity_tracing: Some(IdentityRevealPolicy {
+                    cred_issuer_pub_key: issuer_pk.clone(),
+                    reveal_map,
+                }),
+            }])
+        };
+
+        let open = ac_confidential_reveal(
+            &mut prng,
+            &user_sk,
+            &credential,
+            &key,
+            &tracer_keys.enc_key.attrs_enc_key,
+            &[0, 2],
+        )
+        .unwrap();
+        let attrs_and_ctexts = vec![(10u32, open.cts[0].clone()), (30u32, open.cts[1].clone())];
+        let memos = vec![vec![TracerMemo::new(
+            &mut prng,
+            &tracer_keys.enc_key,
+            None,
+            None,
+            &attrs_and_ctexts,
+        )]];
+        let proofs = vec![vec![Some(open.pok)]];
+        let sig_commitments = vec![Some(&sig_commitment)];
+
+        let policies = policy(vec![true, false, true]);
+        let reveal_policies = [&policies];
+        pnk!(batch_verify_identity_proofs(
+            &mut prng,
+            &[(
+                &reveal_policies[..],
+                &memos[..],
+                &proofs[..],
+                &sig_commitments[..]
+            )]
+        ));
+
+        let policies = policy(vec![true, true, false]);
+        let reveal_policies = [&policies];
+        msg_eq!(
+            NoahError::XfrVerifyAssetTracingIdentityError,
+            batch_verify_identity_proofs(
+                &mut prng,
+                &[(
+                    &reveal_policies[..],
+                    &memos[..],
+                    &proofs[..],
+                    &sig_commitments[..]
+                )]
+            )
+            .unwrap_err()
+        );
+
+        assert!(ac_confidential_reveal(
+            &mut prng,
+            &user_sk,
+            &credential,
+            &key,
+            &tracer_keys.enc_key.attrs_enc_key,
+            &[3],
+        )
+        .is_err());
+    }
 
     #[test]
-    fn verify_identity_proofs_structure() {
+    fn transfer_shape() {
+        let max = max_confidential_outputs();
+        assert_eq!(max, 63);
+        pnk!(validate_transfer_shape(1, 1));
+        pnk!(validate_transfer_shape(10, max));
+        assert!(validate_transfer_shape(1, max + 1).is_err());
+        assert!(validate_transfer_shape(0, 1).is_err());
+        assert!(validate_transfer_shape(1, 0).is_err());
+    }
+
+    #[test]
+    fn bp_gens_len() {
+        assert_eq!(required_bp_gens_len(1), 4);
+        assert_eq!(required_bp_gens_len(2), 8);
+        assert_eq!(required_bp_gens_len(3), 8);
+        assert_eq!(required_bp_gens_len(max_confidential_outputs()), 128);
+
+        let params = BulletproofParams::default();
+        pnk!(check_bp_gens(&params.bp_gens, max_confidential_outputs()));
+
+        let small_gens = BulletproofGens::new(32, 4);
+        pnk!(check_bp_gens(&small_gens, 1));
+        let err = check_bp_gens(&small_gens, 2).unwrap_err();
+        assert!(err.to_string().contains("too short for 2 outputs"));
+        assert!(check_bp_gens(&BulletproofGens::new(16, 4), 1).is_err());
+    }
+
+    #[test]
+    fn batch_verify_identity_proofs_structure() {
         let mut prng = test_rng();
 
         // Case where the number of asset tracing policies is 0
@@ -771,11 +1151,14 @@ mod tests {
         let sig_commitments = vec![];
 
         // 1. no policies => correct verification
-        let res = verify_identity_proofs(
-            reveal_policies.as_slice(),
-            memos.as_slice(),
-            proofs.as_slice(),
-            sig_commitments.as_slice(),
+        let res = batch_verify_identity_proofs(
+            &mut prng,
+            &[(
+                reveal_policies.as_slice(),
+                memos.as_slice(),
+                proofs.as_slice(),
+                sig_commitments.as_slice(),
+            )],
         );
         pnk!(res);
 
@@ -785,11 +1168,14 @@ mod tests {
 
         // 2. sig commitments length doesn't match memos length
         let sig_commitments = vec![Some(&sig_commitment)];
-        let res = verify_identity_proofs(
-            reveal_policies.as_slice(),
-            memos.as_slice(),
-            proofs.as_slice(),
-            sig_commitments.as_slice(),
+        let res = batch_verify_identity_proofs(
+            &mut prng,
+            &[(
+                reveal_policies.as_slice(),
+                memos.as_slice(),
+                proofs.as_slice(),
+                sig_commitments.as_slice(),
+            )],
         );
 
         msg_eq!(
@@ -807,11 +1193,14 @@ mod tests {
         let asset_tracing_policies = TracingPolicies(vec![policy]);
         let reveal_policies = vec![&asset_tracing_policies];
 
-        let res = verify_identity_proofs(
-            reveal_policies.as_slice(),
-            memos.as_slice(),
-            proofs.as_slice(),
-            sig_commitments.as_slice(),
+        let res = batch_verify_identity_proofs(
+            &mut prng,
+            &[(
+                reveal_policies.as_slice(),
+                memos.as_slice(),
+                proofs.as_slice(),
+                sig_commitments.as_slice(),
+            )],
         );
 
         msg_eq!(
@@ -830,11 +1219,14 @@ mod tests {
         )]];
         let reveal_policies = vec![&asset_tracing_policies];
 
-        let res = verify_identity_proofs(
-            reveal_policies.as_slice(),
-            memos.as_slice(),
-            proofs.as_slice(),
-            sig_commitments.as_slice(),
+        let res = batch_verify_identity_proofs(
+            &mut prng,
+            &[(
+                reveal_policies.as_slice(),
+                memos.as_slice(),
+                proofs.as_slice(),
+                sig_commitments.as_slice(),
+            )],
         );
 
         msg_eq!(
@@ -842,4 +1234,413 @@ mod tests {
             res.unwrap_err()
         );
     }
+
+    #[test]
+    fn range_proof_against_commitments() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_type = AssetType::from_identical_byte(0u8);
+
+        let mut open_record = |amount: u64| -> OpenAssetRecord {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+                keypair.get_pk(),
+            );
+            build_open_asset_record(&mut prng, &pc_gens, &template, vec![]).0
+        };
+        let inputs = vec![open_record(10), open_record(20)];
+        let outputs = vec![open_record(5), open_record(15), open_record(7)];
+
+        let input_refs = inputs.iter().collect_vec();
+        let output_refs = outputs.iter().collect_vec();
+        let params = BulletproofParams::default();
+        let proof = pnk!(gen_range_proof(&params, &input_refs, &output_refs));
+
+        let input_bars = inputs
+            .iter()
+            .map(|x| x.blind_asset_record.clone())
+            .collect_vec();
+        let output_bars = outputs
+            .iter()
+            .map(|x| x.blind_asset_record.clone())
+            .collect_vec();
+        let expected = pnk!(extract_value_commitments(&input_bars, &output_bars, &proof));
+        pnk!(verify_range_proof_against(
+            &mut prng, &params, &proof, &expected
+        ));
+
+        // the records of a transfer without confidential amounts have no range proof
+        let reveal = |bars: &[BlindAssetRecord], records: &[OpenAssetRecord]| {
+            bars.iter()
+                .zip(records.iter())
+                .map(|(bar, record)| BlindAssetRecord {
+                    amount: XfrAmount::NonConfidential(record.amount),
+                    ..bar.clone()
+                })
+                .collect_vec()
+        };
+        let plain_inputs = reveal(&input_bars, &inputs);
+        let plain_outputs = reveal(&output_bars, &outputs);
+        assert!(extract_value_commitments(&plain_inputs, &plain_outputs, &proof).is_err());
+
+        // a commitment the proof does not bind to
+        let mut wrong = expected.clone();
+        wrong.swap(0, 1);
+        assert!(verify_range_proof_against(&mut prng, &params, &proof, &wrong).is_err());
+
+        // difference commitments at the wrong position
+        let mut wrong = expected.clone();
+        wrong.swap(6, 7);
+        assert!(verify_range_proof_against(&mut prng, &params, &proof, &wrong).is_err());
+
+        // not padded to a power of two
+        assert!(verify_range_proof_against(&mut prng, &params, &proof, &expected[..6]).is_err());
+        let mut wrong = expected.clone();
+        wrong.push(CompressedRistretto::identity());
+        assert!(verify_range_proof_against(&mut prng, &params, &proof, &wrong).is_err());
+
+        // generators too short for the eight commitments
+        let small_params = BulletproofParams {
+            bp_gens: BulletproofGens::new(32, 4),
+            ..BulletproofParams::default()
+        };
+        assert!(verify_range_proof_against(&mut prng, &small_params, &proof, &expected).is_err());
+    }
+
+    #[test]
+    fn range_proof_rejects_undersized_gens() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_type = AssetType::from_identical_byte(0u8);
+
+        let mut open_record = |amount: u64| -> OpenAssetRecord {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+                keypair.get_pk(),
+            );
+            build_open_asset_record(&mut prng, &pc_gens, &template, vec![]).0
+        };
+        let inputs = vec![open_record(10), open_record(20)];
+        let outputs = vec![open_record(5), open_record(15)];
+        let input_refs = inputs.iter().collect_vec();
+        let output_refs = outputs.iter().collect_vec();
+
+        // two outputs need 8 parties, these generators only have 4
+        let small_params = BulletproofParams {
+            bp_gens: BulletproofGens::new(32, 4),
+            ..BulletproofParams::default()
+        };
+        assert!(gen_range_proof(&small_params, &input_refs, &output_refs).is_err());
+
+        let params = BulletproofParams::default();
+        let proof = pnk!(gen_range_proof(&params, &input_refs, &output_refs));
+        let input_bars = inputs
+            .iter()
+            .map(|x| x.blind_asset_record.clone())
+            .collect_vec();
+        let output_bars = outputs
+            .iter()
+            .map(|x| x.blind_asset_record.clone())
+            .collect_vec();
+        let instances = [(&input_bars, &output_bars, &proof)];
+        pnk!(batch_verify_confidential_amount_chunked(
+            &mut prng, &params, &instances, 1
+        ));
+        assert!(
+            batch_verify_confidential_amount_chunked(&mut prng, &small_params, &instances, 1)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn chunked_range_proof_batch_verification() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let params = BulletproofParams::default();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_type = AssetType::from_identical_byte(0u8);
+
+        let mut bars = |amounts: &[u64]| -> (Vec<OpenAssetRecord>, Vec<BlindAssetRecord>) {
+            let records = amounts
+                .iter()
+                .map(|amount| {
+                    let template = AssetRecordTemplate::with_no_asset_tracing(
+                        *amount,
+                        asset_type,
+                        AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+                        keypair.get_pk(),
+                    );
+                    build_open_asset_record(&mut prng, &pc_gens, &template, vec![]).0
+                })
+                .collect_vec();
+            let bars = records
+                .iter()
+                .map(|x| x.blind_asset_record.clone())
+                .collect_vec();
+            (records, bars)
+        };
+
+        let mut transfers = vec![];
+        for i in 0..5u64 {
+            let (inputs, input_bars) = bars(&[10 + i, 20]);
+            let (outputs, output_bars) = bars(&[5, 15 + i]);
+            let proof = pnk!(gen_range_proof(
+                &BulletproofParams::default(),
+                &inputs.iter().collect_vec(),
+                &outputs.iter().collect_vec()
+            ));
+            transfers.push((input_bars, output_bars, proof));
+        }
+        let instances = transfers
+            .iter()
+            .map(|(inputs, outputs, proof)| (inputs, outputs, proof))
+            .collect_vec();
+
+        for chunk_size in [1, 2, 5, 10] {
+            pnk!(batch_verify_confidential_amount_chunked(
+                &mut prng, &params, &instances, chunk_size
+            ));
+        }
+        assert!(
+            batch_verify_confidential_amount_chunked(&mut prng, &params, &instances, 0).is_err()
+        );
+
+        // a range proof that does not match the commitments of its transfer
+        let mut wrong_proof = transfers[3].2.clone();
+        wrong_proof.range_proof = transfers[0].2.range_proof.clone();
+        let mut wrong_instances = instances.clone();
+        wrong_instances[3].2 = &wrong_proof;
+
+        let err = batch_verify_confidential_amount_chunked(&mut prng, &params, &wrong_instances, 2)
+            .unwrap_err();
+        msg_eq!(NoahError::XfrVerifyConfidentialAmountError, err);
+        assert!(err.to_string().contains("chunk 1 at instance 3"));
+    }
+
+    #[test]
+    fn asset_proof_against_commitments() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_type = AssetType::from_identical_byte(1u8);
+
+        let mut open_record = |asset_type: AssetType, record_type| -> OpenAssetRecord {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                10,
+                asset_type,
+                record_type,
+                keypair.get_pk(),
+            );
+            build_open_asset_record(&mut prng, &pc_gens, &template, vec![]).0
+        };
+        let inputs = vec![
+            open_record(
+                asset_type,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            ),
+            open_record(
+                asset_type,
+                AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+            ),
+        ];
+        let outputs = vec![
+            open_record(
+                asset_type,
+                AssetRecordType::NonConfidentialAmount_ConfidentialAssetType,
+            ),
+            open_record(
+                asset_type,
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+            ),
+        ];
+
+        let input_refs = inputs.iter().collect_vec();
+        let output_refs = outputs.iter().collect_vec();
+        let proof = pnk!(asset_proof(&mut prng, &pc_gens, &input_refs, &output_refs));
+
+        let commitments = inputs
+            .iter()
+            .chain(outputs.iter())
+            .map(|x| match x.blind_asset_record.asset_type {
+                XfrAssetType::Confidential(com) => com.decompress().unwrap(),
+                XfrAssetType::NonConfidential(asset_type) => {
+                    pc_gens.commit(asset_type.as_scalar(), RistrettoScalar::zero())
+                }
+            })
+            .collect_vec();
+        pnk!(verify_asset_proof_against(&mut prng, &proof, &commitments));
+
+        // a commitment to another asset type
+        let mut wrong = commitments.clone();
+        wrong[1] = pc_gens.commit(
+            AssetType::from_identical_byte(2u8).as_scalar(),
+            RistrettoScalar::zero(),
+        );
+        assert!(verify_asset_proof_against(&mut prng, &proof, &wrong).is_err());
+
+        // a missing commitment
+        assert!(verify_asset_proof_against(&mut prng, &proof, &commitments[..3]).is_err());
+    }
+
+    #[test]
+    fn asset_proof_mismatched_asset_types() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+
+        let mut open_record = |asset_type: u8| -> OpenAssetRecord {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                10,
+                AssetType::from_identical_byte(asset_type),
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                keypair.get_pk(),
+            );
+            build_open_asset_record(&mut prng, &pc_gens, &template, vec![]).0
+        };
+        let inputs = vec![open_record(1), open_record(1)];
+        let outputs = vec![open_record(2), open_record(1)];
+
+        let input_refs = inputs.iter().collect_vec();
+        let output_refs = outputs.iter().collect_vec();
+        pnk!(assert_all_same_asset_type(&input_refs));
+        pnk!(assert_all_same_asset_type(&[]));
+
+        let records = input_refs
+            .iter()
+            .chain(output_refs.iter())
+            .copied()
+            .collect_vec();
+        let err = assert_all_same_asset_type(&records).unwrap_err();
+        assert!(err.to_string().contains("record 2"));
+
+        assert!(asset_proof(&mut prng, &pc_gens, &input_refs, &output_refs).is_err());
+    }
+
+    #[test]
+    fn normalized_tracing_policies() {
+        let mut prng = test_rng();
+        let policy = |enc_keys| TracingPolicy {
+            enc_keys,
+            asset_tracing: true,
+            identity_tracing: None,
+        };
+        let policy1 = policy(AssetTracerKeyPair::generate(&mut prng).enc_key);
+        let policy2 = policy(AssetTracerKeyPair::generate(&mut prng).enc_key);
+        let policy3 = policy(AssetTracerKeyPair::generate(&mut prng).enc_key);
+
+        let policies_a = TracingPolicies(vec![policy1.clone(), policy2.clone(), policy3.clone()]);
+        let policies_b = TracingPolicies(vec![
+            policy3.clone(),
+            policy1.clone(),
+            policy3,
+            policy2,
+            policy1,
+        ]);
+        assert_ne!(policies_a, policies_b);
+        let normalized_a = policies_a.clone().normalized();
+        assert_eq!(normalized_a, policies_b.clone().normalized());
+        assert_eq!(normalized_a.len(), 3);
+        assert_eq!(normalized_a.clone().normalized(), normalized_a);
+
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let tracing_proofs = |policies: TracingPolicies| {
+            let mut prng = ChaChaRng::from_seed([0u8; 32]);
+            let records = [10u64, 20u64]
+                .iter()
+                .map(|amount| {
+                    let template = AssetRecordTemplate::with_asset_tracing(
+                        *amount,
+                        AssetType::from_identical_byte(0u8),
+                        AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                        keypair.get_pk(),
+                        policies.clone(),
+                    );
+                    pnk!(AssetRecord::from_template_no_identity_tracing(
+                        &mut prng, &template
+                    ))
+                })
+                .collect_vec();
+            pnk!(asset_amount_tracing_proofs(
+                &mut prng,
+                &records[..1],
+                &records[1..]
+            ))
+        };
+        let proofs = tracing_proofs(policies_a);
+        assert_eq!(proofs.len(), 3);
+        assert_eq!(proofs, tracing_proofs(policies_b));
+    }
+
+    #[test]
+    fn xfr_diff_commitment() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+
+        let mut open_record = |amount: u64, confidential: bool| -> OpenAssetRecord {
+            let asset_record_type = if confidential {
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType
+            } else {
+                AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType
+            };
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                AssetType::from_identical_byte(0u8),
+                asset_record_type,
+                keypair.get_pk(),
+            );
+            build_open_asset_record(&mut prng, &pc_gens, &template, vec![]).0
+        };
+        let bars = |records: &[OpenAssetRecord]| {
+            records
+                .iter()
+                .map(|x| x.blind_asset_record.clone())
+                .collect_vec()
+        };
+
+        // mixed confidential and non-confidential amounts
+        let inputs = vec![open_record(10, true), open_record(20, false)];
+        let outputs = vec![
+            open_record(5, false),
+            open_record(15, true),
+            open_record(7, true),
+        ];
+        let proof = pnk!(gen_range_proof(
+            &BulletproofParams::default(),
+            &inputs.iter().collect_vec(),
+            &outputs.iter().collect_vec()
+        ));
+        let pow2_32 = RistrettoScalar::from(POW_2_32);
+        let expected = proof.xfr_diff_commitment_low.decompress().unwrap().add(
+            &proof
+                .xfr_diff_commitment_high
+                .decompress()
+                .unwrap()
+                .mul(&pow2_32),
+        );
+        let diff_com = pnk!(compute_xfr_diff_commitment(&bars(&inputs), &bars(&outputs)));
+        assert_eq!(diff_com, expected.compress());
+
+        // non-confidential amounts only, committed with a zero blind
+        let inputs = vec![open_record(10, false), open_record(20, false)];
+        let outputs = vec![open_record(25, false)];
+        let diff_com = pnk!(compute_xfr_diff_commitment(&bars(&inputs), &bars(&outputs)));
+        assert_eq!(
+            diff_com,
+            pc_gens
+                .commit(RistrettoScalar::from(5u32), RistrettoScalar::zero())
+                .compress()
+        );
+        let other_diff_com = pnk!(compute_xfr_diff_commitment(
+            &bars(&inputs),
+            &bars(&outputs[..0])
+        ));
+        assert_ne!(diff_com, other_diff_com);
+    }
 }
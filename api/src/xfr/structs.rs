@@ -85,6 +85,25 @@ impl AssetType {
         v[0..ASSET_TYPE_NOAH_REPR_LENGTH].copy_from_slice(&noah_repr);
         S::from_bytes(&v).unwrap()
     }
+
+    /// Recover the asset type whose scalar representation is `s`, via a linear scan over
+    /// `candidate_asset_types`, since the representation is a hash of the asset type and
+    /// cannot be inverted. Return Err(NoahError::ParameterError) if there is no candidate,
+    /// and Err(NoahError::AssetTracingExtractionError) if no candidate matches.
+    pub fn try_from_scalar<S: Scalar>(
+        s: &S,
+        candidate_asset_types: &[AssetType],
+    ) -> Result<AssetType> {
+        if candidate_asset_types.is_empty() {
+            return Err(eg!(NoahError::ParameterError));
+        }
+        for candidate in candidate_asset_types.iter() {
+            if candidate.as_scalar::<S>() == *s {
+                return Ok(*candidate);
+            }
+        }
+        Err(eg!(NoahError::AssetTracingExtractionError))
+    }
 }
 
 /// A confidential transfer note.
@@ -111,6 +130,134 @@ pub struct XfrBody {
     pub owners_memos: Vec<Option<OwnerMemo>>, // If confidential amount or asset type, lock the amount and/or asset type to the public key in asset_record
 }
 
+impl XfrBody {
+    /// Return the number of bytes of the bincode encoding of the body, including the proofs
+    /// and the memos, which can be used to estimate the fee of a transfer before submitting it.
+    /// The sizes are computed field by field without allocating the encoding.
+    pub fn serialized_size(&self) -> usize {
+        fn size_of<T: Serialize>(value: &T) -> usize {
+            bincode::serialized_size(value).unwrap_or_default() as usize
+        }
+
+        size_of(&self.inputs)
+            + size_of(&self.outputs)
+            + size_of(&self.proofs.asset_type_and_amount_proof)
+            + size_of(&self.proofs.asset_tracing_proof)
+            + size_of(&self.asset_tracing_memos)
+            + size_of(&self.owners_memos)
+    }
+
+    /// Check the structural invariants of the body, which do not require any cryptographic
+    /// verification, so that a malformed body can be rejected early. Return an error
+    /// describing the first invariant that is violated.
+    pub fn validate_structure(&self) -> Result<()> {
+        let n_inputs = self.inputs.len();
+        let n_outputs = self.outputs.len();
+        if n_inputs == 0 || n_outputs == 0 {
+            return Err(eg!(format!(
+                "the body has {} inputs and {} outputs, but needs at least one of each",
+                n_inputs, n_outputs
+            )));
+        }
+
+        // 1. The owner memos, one per output, present exactly for the confidential outputs.
+        if self.owners_memos.len() != n_outputs {
+            return Err(eg!(format!(
+                "the body has {} owner memos for {} outputs",
+                self.owners_memos.len(),
+                n_outputs
+            )));
+        }
+        for (i, (output, memo)) in self.outputs.iter().zip(&self.owners_memos).enumerate() {
+            let is_confidential =
+                output.amount.is_confidential() || output.asset_type.is_confidential();
+            if is_confidential != memo.is_some() {
+                return Err(eg!(format!(
+                    "output {} is {}confidential but has {} owner memo",
+                    i,
+                    if is_confidential { "" } else { "not " },
+                    if memo.is_some() { "an" } else { "no" }
+                )));
+            }
+        }
+
+        // 2. The tracing memos and the identity proofs, one list per input and output.
+        if self.asset_tracing_memos.len() != n_inputs + n_outputs {
+            return Err(eg!(format!(
+                "the body has {} lists of tracing memos for {} inputs and {} outputs",
+                self.asset_tracing_memos.len(),
+                n_inputs,
+                n_outputs
+            )));
+        }
+        let tracing_proof = &self.proofs.asset_tracing_proof;
+        if tracing_proof.inputs_identity_proofs.len() != n_inputs
+            || tracing_proof.outputs_identity_proofs.len() != n_outputs
+        {
+            return Err(eg!(format!(
+                "the body has {} and {} lists of identity proofs for {} inputs and {} outputs",
+                tracing_proof.inputs_identity_proofs.len(),
+                tracing_proof.outputs_identity_proofs.len(),
+                n_inputs,
+                n_outputs
+            )));
+        }
+        for (i, (memos, proofs)) in self
+            .asset_tracing_memos
+            .iter()
+            .zip(
+                tracing_proof
+                    .inputs_identity_proofs
+                    .iter()
+                    .chain(&tracing_proof.outputs_identity_proofs),
+            )
+            .enumerate()
+        {
+            if memos.len() != proofs.len() {
+                return Err(eg!(format!(
+                    "record {} has {} tracing memos but {} identity proofs",
+                    i,
+                    memos.len(),
+                    proofs.len()
+                )));
+            }
+        }
+
+        // 3. The kind of the amount and asset type proof, given the confidentiality of the records.
+        let records = || self.inputs.iter().chain(&self.outputs);
+        let any_confidential_amount = records().any(|x| x.amount.is_confidential());
+        let any_confidential_asset_type = records().any(|x| x.asset_type.is_confidential());
+        let inconsistency = match self.proofs.asset_type_and_amount_proof {
+            AssetTypeAndAmountProof::NoProof
+                if any_confidential_amount && any_confidential_asset_type =>
+            {
+                Some(("no proof", "amounts and asset types"))
+            }
+            AssetTypeAndAmountProof::NoProof if any_confidential_amount => {
+                Some(("no proof", "amounts"))
+            }
+            AssetTypeAndAmountProof::NoProof if any_confidential_asset_type => {
+                Some(("no proof", "asset types"))
+            }
+            AssetTypeAndAmountProof::ConfAmount(_) if any_confidential_asset_type => {
+                Some(("a confidential amount proof", "asset types"))
+            }
+            AssetTypeAndAmountProof::ConfAsset(_) if any_confidential_amount => {
+                Some(("a confidential asset type proof", "amounts"))
+            }
+            _ => None,
+        };
+        if let Some((kind, confidential)) = inconsistency {
+            return Err(eg!(format!(
+                "the body has {} but its records have confidential {}",
+                kind, confidential
+            )));
+        }
+
+        Ok(())
+    }
+}
+
 /// A transfer input or output record as seen in the ledger.
 /// Amount and asset type can be confidential or non confidential.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -366,6 +513,19 @@ impl TracingPolicies {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+    /// Sort the policies by the bytes of their encryption keys and remove exact duplicates,
+    /// so that the tracing memos and proofs do not depend on how the list was assembled.
+    pub fn normalized(self) -> TracingPolicies {
+        let mut policies = self.0;
+        policies.sort_by_cached_key(|policy| {
+            (
+                bincode::serialize(&policy.enc_keys).unwrap(),
+                bincode::serialize(policy).unwrap(),
+            )
+        });
+        policies.dedup();
+        TracingPolicies(policies)
+    }
 }
 
 /// An asset and identity tracing policy for an asset.
@@ -379,6 +539,18 @@ pub struct TracingPolicy {
     pub identity_tracing: Option<IdentityRevealPolicy>,
 }
 
+impl TracingPolicy {
+    /// Construct a policy that traces the amount and asset type but not the identity.
+    /// Records under such a policy carry no identity memo, and their identity proofs are `None`.
+    pub fn asset_only(enc_keys: AssetTracerEncKeys) -> Self {
+        TracingPolicy {
+            enc_keys,
+            asset_tracing: true,
+            identity_tracing: None,
+        }
+    }
+}
+
 /// An identity reveal policy.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct IdentityRevealPolicy {
@@ -487,6 +659,23 @@ impl OwnerMemo {
         ))
     }
 
+    /// Construct an `OwnerMemo` for an asset record with only confidential amount, which
+    /// derives the same blinds as this memo, so that the amount commitments of the record
+    /// can be kept when its asset type is revealed.
+    pub fn with_amount_only<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        amount: u64,
+        pub_key: &PublicKey,
+    ) -> Result<Self> {
+        let lock_bytes = xfr_hybrid_encrypt(pub_key, prng, &amount.to_be_bytes())?;
+        Ok(OwnerMemo {
+            key_type: self.key_type.clone(),
+            blind_share_bytes: self.blind_share_bytes.clone(),
+            lock_bytes,
+        })
+    }
+
     /// Decrypt the `OwnerMemo.lock` which encrypts only the confidential amount
     /// returns error if the decrypted bytes length doesn't match.
     pub fn decrypt_amount(&self, keypair: &KeyPair) -> Result<u64> {
@@ -546,6 +735,48 @@ impl OwnerMemo {
         let shared_point = OwnerMemo::derive_shared_point(&key_type, &s, &self.blind_share_bytes)?;
         Ok(OwnerMemo::calc_asset_type_blind(&shared_point))
     }
+
+    /// Check that the memo is well-formed without decrypting it: the random point is a valid
+    /// point of the curve of the key type, and the ciphertext has the length of an encrypted
+    /// amount, asset type, or both.
+    pub fn validate(&self) -> Result<()> {
+        let lock_lens = match self.key_type {
+            KeyType::Ed25519 => {
+                if self.blind_share_bytes.len() != Ed25519Point::COMPRESSED_LEN {
+                    return Err(eg!(NoahError::DecompressElementError));
+                }
+                Ed25519Point::from_compressed_bytes(&self.blind_share_bytes)
+                    .c(d!(NoahError::DecompressElementError))?;
+                [
+                    MAX_LOCK_BYTES_CON_NON_ED25519,
+                    MAX_LOCK_BYTES_NON_CON_ED25519,
+                    MAX_LOCK_BYTES_CON_CON_ED25519,
+                ]
+            }
+            KeyType::Secp256k1 => {
+                if self.blind_share_bytes.len() != SECP256K1G1::COMPRESSED_LEN
+                    || self.lock_bytes.len() < SECP256K1G1::COMPRESSED_LEN
+                {
+                    return Err(eg!(NoahError::DecompressElementError));
+                }
+                SECP256K1G1::from_compressed_bytes(&self.blind_share_bytes)
+                    .c(d!(NoahError::DecompressElementError))?;
+                // The ciphertext starts with its own ephemeral point.
+                SECP256K1G1::from_compressed_bytes(&self.lock_bytes[..SECP256K1G1::COMPRESSED_LEN])
+                    .c(d!(NoahError::DecompressElementError))?;
+                [
+                    MAX_LOCK_BYTES_CON_NON_SECP256K1,
+                    MAX_LOCK_BYTES_NON_CON_SECP256K1,
+                    MAX_LOCK_BYTES_CON_CON_SECP256K1,
+                ]
+            }
+            KeyType::EthAddress => return Err(eg!(NoahError::ParameterError)),
+        };
+        if !lock_lens.contains(&self.lock_bytes.len()) {
+            return Err(eg!(NoahError::InconsistentStructureError));
+        }
+        Ok(())
+    }
 }
 
 impl OwnerMemo {
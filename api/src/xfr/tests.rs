@@ -4,14 +4,14 @@ use crate::parameters::bulletproofs::BulletproofParams;
 use crate::parameters::AddressFormat::SECP256K1;
 use crate::xfr::{
     asset_record::AssetRecordType,
-    batch_verify_xfr_body_asset_records, batch_verify_xfr_notes, compute_transfer_multisig,
-    gen_xfr_note,
+    batch_verify_xfr_body_asset_records, batch_verify_xfr_notes, classify_records,
+    classify_transfer, compute_transfer_multisig, gen_xfr_note,
     structs::{
         AssetRecord, AssetRecordTemplate, AssetTracerEncKeys, AssetTracerKeyPair, AssetType,
         IdentityRevealPolicy, TracerMemo, TracingPolicy, XfrAmount, XfrAssetType, XfrBody, XfrNote,
         ASSET_TYPE_LENGTH,
     },
-    verify_xfr_body, verify_xfr_note, XfrNotePolicies,
+    verify_xfr_body, verify_xfr_note, TransferKind, XfrNotePolicies,
 };
 use merlin::Transcript;
 use noah_algebra::{
@@ -28,6 +28,7 @@ use serde::{Deserialize, Serialize};
 
 pub(crate) fn create_xfr<R: CryptoRng + RngCore>(
     prng: &mut R,
+    params: &BulletproofParams,
     input_templates: &[AssetRecordTemplate],
     output_templates: &[AssetRecordTemplate],
     inkeys: &[&KeyPair],
@@ -41,7 +42,8 @@ pub(crate) fn create_xfr<R: CryptoRng + RngCore>(
         .map(|template| AssetRecord::from_template_no_identity_tracing(prng, &template).unwrap())
         .collect_vec();
 
-    let xfr_note = gen_xfr_note(prng, inputs.as_slice(), outputs.as_slice(), inkeys).unwrap();
+    let xfr_note =
+        gen_xfr_note(prng, params, inputs.as_slice(), outputs.as_slice(), inkeys).unwrap();
 
     (xfr_note, inputs, outputs)
 }
@@ -104,6 +106,7 @@ fn do_transfer_tests_single_asset(
 
     let tuple = create_xfr(
         &mut prng,
+        params,
         inputs.as_slice(),
         outputs.as_slice(),
         inkeys_ref.as_slice(),
@@ -113,6 +116,11 @@ fn do_transfer_tests_single_asset(
     let mut inputs = tuple.1;
     let mut outputs = tuple.2;
 
+    assert_eq!(
+        xfr_note.body.serialized_size(),
+        bincode::serialize(&xfr_note.body).unwrap().len()
+    );
+
     let policies = XfrNotePolicies::empty_policies(inputs.len(), outputs.len());
     // test 1: simple transfer
     pnk!(verify_xfr_note(
@@ -141,6 +149,7 @@ fn do_transfer_tests_single_asset(
     outputs[3] = AssetRecord::from_template_no_identity_tracing(&mut prng, &asset_record).unwrap();
     let xfr_note = gen_xfr_note(
         &mut prng,
+        params,
         inputs.as_slice(),
         outputs.as_slice(),
         inkeys_ref.as_slice(),
@@ -155,6 +164,7 @@ fn do_transfer_tests_single_asset(
     outputs[3] = old_output3;
     let mut xfr_note = gen_xfr_note(
         &mut prng,
+        params,
         inputs.as_slice(),
         outputs.as_slice(),
         inkeys_ref.as_slice(),
@@ -201,6 +211,7 @@ fn do_transfer_tests_single_asset(
     outputs[3] = AssetRecord::from_template_no_identity_tracing(&mut prng, &asset_record).unwrap();
     let xfr_note = gen_xfr_note(
         &mut prng,
+        params,
         inputs.as_slice(),
         outputs.as_slice(),
         inkeys_ref.as_slice(),
@@ -215,6 +226,7 @@ fn do_transfer_tests_single_asset(
 
     let mut xfr_note = gen_xfr_note(
         &mut prng,
+        params,
         inputs.as_slice(),
         outputs.as_slice(),
         inkeys_ref.as_slice(),
@@ -260,6 +272,7 @@ fn do_transfer_tests_single_asset(
     inputs[1] = AssetRecord::from_template_no_identity_tracing(&mut prng, &ar_template).unwrap();
     let xfr_note = gen_xfr_note(
         &mut prng,
+        params,
         inputs.as_slice(),
         outputs.as_slice(),
         inkeys_ref.as_slice(),
@@ -274,6 +287,7 @@ fn do_transfer_tests_single_asset(
 
     let mut xfr_note = gen_xfr_note(
         &mut prng,
+        params,
         inputs.as_slice(),
         outputs.as_slice(),
         inkeys_ref.as_slice(),
@@ -308,6 +322,75 @@ mod single_asset_no_tracing {
     use super::*;
     use crate::parameters::bulletproofs::BulletproofParams;
 
+    #[test]
+    fn test_classify_transfer() {
+        use AssetRecordType::*;
+        let mut prng = test_rng();
+        let params = BulletproofParams::default();
+        let asset_type = AssetType::from_identical_byte(0u8);
+        let inkeys = gen_key_pair_vec(2, &mut prng);
+        let outkeys = gen_key_pair_vec(2, &mut prng);
+
+        let cases = [
+            (
+                [NonConfidentialAmount_NonConfidentialAssetType; 2],
+                TransferKind::NonConfidential,
+            ),
+            (
+                [ConfidentialAmount_NonConfidentialAssetType; 2],
+                TransferKind::ConfidentialAmount,
+            ),
+            (
+                [NonConfidentialAmount_ConfidentialAssetType; 2],
+                TransferKind::ConfidentialType,
+            ),
+            (
+                [ConfidentialAmount_ConfidentialAssetType; 2],
+                TransferKind::FullyConfidential,
+            ),
+            (
+                [
+                    ConfidentialAmount_NonConfidentialAssetType,
+                    NonConfidentialAmount_ConfidentialAssetType,
+                ],
+                TransferKind::FullyConfidential,
+            ),
+        ];
+
+        for (record_types, expected) in cases.iter() {
+            let templates = |keys: &[KeyPair]| {
+                record_types
+                    .iter()
+                    .zip(keys.iter())
+                    .map(|(record_type, key_pair)| {
+                        AssetRecordTemplate::with_no_asset_tracing(
+                            10,
+                            asset_type,
+                            *record_type,
+                            key_pair.pub_key,
+                        )
+                    })
+                    .collect_vec()
+            };
+            let (xfr_note, _, _) = create_xfr(
+                &mut prng,
+                &params,
+                &templates(&inkeys),
+                &templates(&outkeys),
+                &inkeys.iter().collect_vec(),
+            );
+            assert_eq!(classify_transfer(&xfr_note.body), *expected);
+            assert_eq!(
+                classify_records(&xfr_note.body.outputs),
+                classify_records(&xfr_note.body.inputs)
+            );
+            assert_eq!(
+                expected.has_confidential_amount(),
+                record_types[0].get_flags().0 || record_types[1].get_flags().0
+            );
+        }
+    }
+
     #[test]
     fn test_transfer_not_confidential() {
         /*! Test non confidential transfers*/
@@ -469,7 +552,7 @@ mod single_asset_no_tracing {
 
 mod multi_asset_no_tracing {
     use super::*;
-    use crate::xfr::XfrNotePolicies;
+    use crate::xfr::{distinct_asset_types, XfrNotePolicies};
 
     #[test]
     fn do_multiasset_transfer_tests() {
@@ -526,7 +609,22 @@ mod multi_asset_no_tracing {
             })
             .collect_vec();
 
-        let (xfr_note, _, _) = create_xfr(&mut prng, &input_record, &output_record, &inkeys_ref);
+        let (xfr_note, inputs, _) = create_xfr(
+            &mut prng,
+            &params,
+            &input_record,
+            &output_record,
+            &inkeys_ref,
+        );
+
+        let open_inputs = inputs
+            .into_iter()
+            .map(|record| record.open_asset_record)
+            .collect_vec();
+        assert_eq!(
+            distinct_asset_types(&open_inputs),
+            vec![asset_type0, asset_type1, asset_type2]
+        );
 
         let policies = XfrNotePolicies::empty_policies(input_record.len(), output_record.len());
 
@@ -582,6 +680,7 @@ mod multi_asset_no_tracing {
 
         let (mut xfr_note, _, _) = create_xfr(
             &mut prng,
+            &params,
             &input_record,
             &output_record,
             inkeys_ref.as_slice(),
@@ -614,6 +713,7 @@ mod keys {
 
     #[test]
     fn xfr_keys_error() {
+        let params = BulletproofParams::default();
         let amounts = [
             (10, AssetType::from_identical_byte(0u8)),
             (10, AssetType::from_identical_byte(1u8)),
@@ -663,6 +763,7 @@ mod keys {
 
         let xfr_note = gen_xfr_note(
             &mut prng,
+            &params,
             inputs.as_slice(),
             outputs.as_slice(),
             &[], //no keys
@@ -673,6 +774,7 @@ mod keys {
         let key2 = KeyPair::sample(&mut prng, SECP256K1);
         let xfr_note = gen_xfr_note(
             &mut prng,
+            &params,
             inputs.as_slice(),
             outputs.as_slice(),
             &[&key1, &key2],
@@ -682,10 +784,242 @@ mod keys {
     }
 }
 
+mod body_structure {
+    use super::*;
+    use crate::xfr::structs::AssetTypeAndAmountProof;
+    use bulletproofs::BulletproofGens;
+
+    fn xfr_note<R: CryptoRng + RngCore>(prng: &mut R, record_type: AssetRecordType) -> XfrNote {
+        let params = BulletproofParams::default();
+        let asset_type = AssetType::from_identical_byte(0u8);
+        let keypair = KeyPair::sample(prng, SECP256K1);
+        let template = |amount: u64| {
+            AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                record_type,
+                keypair.pub_key,
+            )
+        };
+        create_xfr(
+            prng,
+            &params,
+            &[template(30)],
+            &[template(10), template(20)],
+            &[&keypair],
+        )
+        .0
+    }
+
+    #[test]
+    fn short_bulletproof_generators() {
+        let mut prng = test_rng();
+        let asset_type = AssetType::from_identical_byte(0u8);
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        // two outputs need 8 parties, these generators only have 4
+        let small_params = BulletproofParams {
+            bp_gens: BulletproofGens::new(32, 4),
+            ..BulletproofParams::default()
+        };
+
+        for (record_type, has_range_proof) in [
+            (
+                AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+                false,
+            ),
+            (
+                AssetRecordType::NonConfidentialAmount_ConfidentialAssetType,
+                false,
+            ),
+            (
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+                true,
+            ),
+            (
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                true,
+            ),
+        ] {
+            let mut record = |amount: u64| {
+                let template = AssetRecordTemplate::with_no_asset_tracing(
+                    amount,
+                    asset_type,
+                    record_type,
+                    keypair.pub_key,
+                );
+                AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap()
+            };
+            let inputs = [record(30)];
+            let outputs = [record(10), record(20)];
+
+            let result = gen_xfr_note(&mut prng, &small_params, &inputs, &outputs, &[&keypair]);
+            assert_eq!(result.is_err(), has_range_proof);
+        }
+    }
+
+    fn assert_invalid(body: &XfrBody, message: &str) {
+        let err = body.validate_structure().unwrap_err();
+        assert!(err.to_string().contains(message));
+    }
+
+    #[test]
+    fn validate_structure() {
+        let mut prng = test_rng();
+        for record_type in [
+            AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+            AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+            AssetRecordType::NonConfidentialAmount_ConfidentialAssetType,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+        ] {
+            pnk!(xfr_note(&mut prng, record_type).body.validate_structure());
+        }
+
+        let body = xfr_note(
+            &mut prng,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+        )
+        .body;
+
+        let mut wrong_body = body.clone();
+        wrong_body.inputs.clear();
+        assert_invalid(&wrong_body, "needs at least one of each");
+
+        let mut wrong_body = body.clone();
+        wrong_body.owners_memos.pop();
+        assert_invalid(&wrong_body, "1 owner memos for 2 outputs");
+
+        let mut wrong_body = body.clone();
+        wrong_body.owners_memos[1] = None;
+        assert_invalid(
+            &wrong_body,
+            "output 1 is confidential but has no owner memo",
+        );
+
+        let mut non_confidential_body = xfr_note(
+            &mut prng,
+            AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        )
+        .body;
+        non_confidential_body.owners_memos[0] = body.owners_memos[0].clone();
+        assert_invalid(
+            &non_confidential_body,
+            "output 0 is not confidential but has an owner memo",
+        );
+
+        let mut wrong_body = body.clone();
+        wrong_body.asset_tracing_memos.pop();
+        assert_invalid(
+            &wrong_body,
+            "2 lists of tracing memos for 1 inputs and 2 outputs",
+        );
+
+        let mut wrong_body = body.clone();
+        wrong_body
+            .proofs
+            .asset_tracing_proof
+            .inputs_identity_proofs
+            .push(vec![]);
+        assert_invalid(&wrong_body, "2 and 2 lists of identity proofs");
+
+        let mut wrong_body = body.clone();
+        wrong_body
+            .proofs
+            .asset_tracing_proof
+            .outputs_identity_proofs[0]
+            .push(None);
+        assert_invalid(
+            &wrong_body,
+            "record 1 has 0 tracing memos but 1 identity proofs",
+        );
+
+        let mut wrong_body = body.clone();
+        wrong_body.proofs.asset_type_and_amount_proof = AssetTypeAndAmountProof::NoProof;
+        assert_invalid(
+            &wrong_body,
+            "no proof but its records have confidential amounts and asset types",
+        );
+
+        let mut wrong_body = body;
+        if let AssetTypeAndAmountProof::ConfAll(proofs) =
+            &wrong_body.proofs.asset_type_and_amount_proof
+        {
+            let range_proof = proofs.0.clone();
+            wrong_body.proofs.asset_type_and_amount_proof =
+                AssetTypeAndAmountProof::ConfAmount(range_proof);
+        }
+        assert_invalid(
+            &wrong_body,
+            "a confidential amount proof but its records have confidential asset types",
+        );
+    }
+}
+
+mod owner_memo {
+    use super::*;
+    use crate::keys::KeyType;
+    use crate::parameters::AddressFormat::ED25519;
+    use crate::xfr::asset_record::{build_blind_asset_record, open_blind_asset_record};
+    use noah_algebra::{ed25519::Ed25519Point, secp256k1::SECP256K1G1};
+
+    #[test]
+    fn owner_memo_validate() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+
+        for address_format in [SECP256K1, ED25519] {
+            let keypair = KeyPair::sample(&mut prng, address_format);
+            for asset_record_type in [
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+                AssetRecordType::NonConfidentialAmount_ConfidentialAssetType,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            ] {
+                let template = AssetRecordTemplate::with_no_asset_tracing(
+                    10,
+                    AssetType::from_identical_byte(1u8),
+                    asset_record_type,
+                    keypair.get_pk(),
+                );
+                let (bar, _, memo) =
+                    build_blind_asset_record(&mut prng, &pc_gens, &template, vec![]);
+                let memo = memo.unwrap();
+                pnk!(memo.validate());
+
+                // a corrupted ephemeral key
+                let is_invalid_point = |bytes: &[u8]| match memo.key_type {
+                    KeyType::Ed25519 => Ed25519Point::from_compressed_bytes(bytes).is_err(),
+                    KeyType::Secp256k1 => SECP256K1G1::from_compressed_bytes(bytes).is_err(),
+                    KeyType::EthAddress => unreachable!(),
+                };
+                let corrupted = (1u8..=255)
+                    .map(|i| {
+                        let mut bytes = memo.blind_share_bytes.clone();
+                        bytes[1] ^= i;
+                        bytes
+                    })
+                    .find(|bytes| is_invalid_point(bytes))
+                    .unwrap();
+                let mut bad_memo = memo.clone();
+                bad_memo.blind_share_bytes = corrupted;
+                assert!(bad_memo.validate().is_err());
+                assert!(open_blind_asset_record(&bar, &Some(bad_memo), &keypair).is_err());
+
+                let mut bad_memo = memo.clone();
+                bad_memo.blind_share_bytes.pop();
+                assert!(bad_memo.validate().is_err());
+
+                // a truncated ciphertext
+                let mut bad_memo = memo.clone();
+                bad_memo.lock_bytes.pop();
+                assert!(bad_memo.validate().is_err());
+            }
+        }
+    }
+}
+
 mod identity_tracing {
     use super::*;
     use crate::parameters::AddressFormat::SECP256K1;
-    use crate::xfr::{structs::TracingPolicies, XfrNotePoliciesRef};
+    use crate::xfr::{structs::TracingPolicies, XfrNotePoliciesBuilder, XfrNotePoliciesRef};
 
     fn check_identity_tracing_for_asset_type(asset_record_type: AssetRecordType) {
         let mut params = BulletproofParams::default();
@@ -755,7 +1089,8 @@ mod identity_tracing {
         )
         .unwrap()];
 
-        let xfr_note = gen_xfr_note(&mut prng, &[input], &outputs, &[&input_keypair]).unwrap();
+        let xfr_note =
+            gen_xfr_note(&mut prng, &params, &[input], &outputs, &[&input_keypair]).unwrap();
 
         let null_policies_input = &TracingPolicies::new();
 
@@ -783,6 +1118,38 @@ mod identity_tracing {
             verify_xfr_note(&mut prng, &mut params, &xfr_note, &policies).unwrap_err(),
         );
 
+        // the builder produces consistent policies
+        let policies = XfrNotePoliciesBuilder::new(1, 1)
+            .add_input(null_policies_input, None)
+            .unwrap()
+            .add_output(&tracing_policy, Some(&sig_commitment))
+            .unwrap()
+            .build()
+            .unwrap();
+        pnk!(verify_xfr_note(
+            &mut prng,
+            &mut params,
+            &xfr_note,
+            &policies
+        ));
+
+        // an identity policy without an attribute commitment is rejected
+        assert!(XfrNotePoliciesBuilder::new(1, 1)
+            .add_output(&tracing_policy, None)
+            .is_err());
+        // so are policies for too many inputs
+        assert!(XfrNotePoliciesBuilder::new(1, 1)
+            .add_input(null_policies_input, None)
+            .unwrap()
+            .add_input(null_policies_input, None)
+            .is_err());
+        // and missing policies
+        assert!(XfrNotePoliciesBuilder::new(1, 1)
+            .add_input(null_policies_input, None)
+            .unwrap()
+            .build()
+            .is_err());
+
         //test serialization
         //to msg pack whole Xfr
         let mut vec = vec![];
@@ -908,6 +1275,7 @@ mod asset_tracing {
 
         let (xfr_note, inputs, outputs) = create_xfr(
             &mut prng,
+            params,
             inputs.as_slice(),
             outputs.as_slice(),
             in_keys_ref.as_slice(),
@@ -1086,7 +1454,8 @@ mod asset_tracing {
                     .unwrap(),
             ];
 
-        let xfr_note = gen_xfr_note(&mut prng, &[input], &outputs, &[&input_keypair]).unwrap();
+        let xfr_note =
+            gen_xfr_note(&mut prng, &params, &[input], &outputs, &[&input_keypair]).unwrap();
 
         let policies = XfrNotePoliciesRef::new(
             vec![&tracing_policy],
@@ -1103,6 +1472,146 @@ mod asset_tracing {
         ));
     }
 
+    #[test]
+    fn asset_only_policy_has_no_identity_proofs() {
+        let mut params = BulletproofParams::default();
+        let mut prng = test_rng();
+        let tracer_keypair = AssetTracerKeyPair::generate(&mut prng);
+        let policy = TracingPolicy::asset_only(tracer_keypair.enc_key.clone());
+        assert!(policy.asset_tracing);
+        assert!(policy.identity_tracing.is_none());
+        let tracing_policy = TracingPolicies::from_policy(policy);
+
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_record_type = AssetRecordType::ConfidentialAmount_ConfidentialAssetType;
+        let template = AssetRecordTemplate::with_asset_tracing(
+            10,
+            GOLD_ASSET,
+            asset_record_type,
+            keypair.pub_key,
+            tracing_policy.clone(),
+        );
+        let input = AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
+        let output = AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
+
+        let xfr_note = gen_xfr_note(&mut prng, &params, &[input], &[output], &[&keypair]).unwrap();
+
+        // one memo per record, without any attribute ciphertext
+        assert!(xfr_note
+            .body
+            .asset_tracing_memos
+            .iter()
+            .all(|memos| memos.len() == 1 && memos[0].lock_attributes.is_empty()));
+        // one empty identity proof per record
+        let tracing_proof = &xfr_note.body.proofs.asset_tracing_proof;
+        assert!(tracing_proof
+            .inputs_identity_proofs
+            .iter()
+            .chain(tracing_proof.outputs_identity_proofs.iter())
+            .all(|proofs| proofs.len() == 1 && proofs[0].is_none()));
+
+        let policies = XfrNotePoliciesRef::new(
+            vec![&tracing_policy],
+            vec![None; 1],
+            vec![&tracing_policy],
+            vec![None; 1],
+        );
+        pnk!(verify_xfr_note(
+            &mut prng,
+            &mut params,
+            &xfr_note,
+            &policies
+        ));
+
+        let records_data = trace_assets(&xfr_note.body, &tracer_keypair).unwrap();
+        assert_eq!(records_data.len(), 2);
+        for record_data in records_data.iter() {
+            assert_eq!(record_data.0, 10);
+            assert_eq!(record_data.1, GOLD_ASSET);
+            assert!(record_data.2.is_empty());
+        }
+
+        // the identity proofs must still match the policies one to one
+        let mut bad_note = xfr_note.clone();
+        bad_note
+            .body
+            .proofs
+            .asset_tracing_proof
+            .outputs_identity_proofs[0]
+            .clear();
+        assert!(verify_xfr_note(&mut prng, &mut params, &bad_note, &policies).is_err());
+    }
+
+    #[test]
+    fn unnormalized_tracing_policies_round_trip() {
+        let mut params = BulletproofParams::default();
+        let mut prng = test_rng();
+        let tracer1 = AssetTracerKeyPair::generate(&mut prng);
+        let tracer2 = AssetTracerKeyPair::generate(&mut prng);
+        let policy1 = TracingPolicy::asset_only(tracer1.enc_key.clone());
+        let policy2 = TracingPolicy::asset_only(tracer2.enc_key.clone());
+
+        // the same tracers in both orders, with a duplicated policy
+        let policies_a = TracingPolicies(vec![policy1.clone(), policy2.clone()]);
+        let policies_b = TracingPolicies(vec![policy2.clone(), policy1.clone(), policy2]);
+
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let asset_record_type = AssetRecordType::ConfidentialAmount_ConfidentialAssetType;
+        let record = |prng: &mut _, policies: &TracingPolicies| {
+            let template = AssetRecordTemplate::with_asset_tracing(
+                10,
+                GOLD_ASSET,
+                asset_record_type,
+                keypair.pub_key,
+                policies.clone(),
+            );
+            AssetRecord::from_template_no_identity_tracing(prng, &template).unwrap()
+        };
+        let input = record(&mut prng, &policies_b);
+        let output = record(&mut prng, &policies_a);
+        let xfr_note = gen_xfr_note(&mut prng, &params, &[input], &[output], &[&keypair]).unwrap();
+        assert!(xfr_note
+            .body
+            .asset_tracing_memos
+            .iter()
+            .all(|memos| memos.len() == 2));
+
+        // the note verifies with the policies it was built from, in any order
+        for (inputs_policies, outputs_policies) in [
+            (&policies_b, &policies_a),
+            (&policies_a, &policies_b),
+            (&policies_b, &policies_b),
+        ] {
+            let policies = XfrNotePoliciesRef::new(
+                vec![inputs_policies],
+                vec![None; 1],
+                vec![outputs_policies],
+                vec![None; 1],
+            );
+            pnk!(verify_xfr_note(
+                &mut prng,
+                &mut params,
+                &xfr_note,
+                &policies
+            ));
+        }
+
+        // but not without one of the tracers
+        let policies_1 = TracingPolicies::from_policy(policy1);
+        let policies = XfrNotePoliciesRef::new(
+            vec![&policies_b],
+            vec![None; 1],
+            vec![&policies_1],
+            vec![None; 1],
+        );
+        assert!(verify_xfr_note(&mut prng, &mut params, &xfr_note, &policies).is_err());
+
+        for tracer in [&tracer1, &tracer2] {
+            let records_data = trace_assets(&xfr_note.body, tracer).unwrap();
+            assert_eq!(records_data.len(), 2);
+        }
+    }
+
     #[test]
     fn test_one_input_one_output_all_confidential() {
         let mut params = BulletproofParams::default();
@@ -1562,6 +2071,7 @@ mod asset_tracing {
 
         let (xfr_note, inputs, outputs) = create_xfr(
             &mut prng,
+            &params,
             inputs.as_slice(),
             outputs.as_slice(),
             in_keys_ref.as_slice(),
@@ -1571,6 +2081,10 @@ mod asset_tracing {
         let string = serde_json::to_string(&xfr_note).unwrap();
         let xfr_note2 = serde_json::from_str(&string).unwrap();
         assert_eq!(xfr_note, xfr_note2);
+        assert_eq!(
+            xfr_note.body.serialized_size(),
+            bincode::serialize(&xfr_note.body).unwrap().len()
+        );
 
         let xfr_body = &xfr_note.body;
 
@@ -1673,6 +2187,7 @@ mod asset_tracing {
         let policies_ref = policies.to_ref();
         let (xfr_note, _, _) = create_xfr(
             &mut prng,
+            &params,
             inputs.as_slice(),
             outputs.as_slice(),
             &inkeys_ref,
@@ -1731,4 +2246,89 @@ mod asset_tracing {
         let v2 = at1_bls_scalar.to_bytes();
         assert_eq!(v1, v2);
     }
+
+    #[test]
+    fn test_asset_type_from_scalar() {
+        let mut prng = test_rng();
+        let asset_types = (0..10)
+            .map(|_| {
+                let mut bytes = [0u8; ASSET_TYPE_LENGTH];
+                prng.fill_bytes(&mut bytes);
+                AssetType(bytes)
+            })
+            .collect_vec();
+
+        for asset_type in asset_types.iter() {
+            let s = asset_type.as_scalar::<RistrettoScalar>();
+            assert_eq!(
+                pnk!(AssetType::try_from_scalar(&s, &asset_types)),
+                *asset_type
+            );
+            let s = asset_type.as_scalar::<BLSScalar>();
+            assert_eq!(
+                pnk!(AssetType::try_from_scalar(&s, &asset_types)),
+                *asset_type
+            );
+        }
+
+        let s = asset_types[0].as_scalar::<RistrettoScalar>();
+        msg_eq!(
+            NoahError::AssetTracingExtractionError,
+            AssetType::try_from_scalar(&s, &asset_types[1..]).unwrap_err()
+        );
+        msg_eq!(
+            NoahError::ParameterError,
+            AssetType::try_from_scalar(&s, &[]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_asset_record_new_checked() {
+        let mut prng = test_rng();
+        let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
+        let other_tracer_keys = AssetTracerKeyPair::generate(&mut prng);
+        let policy = TracingPolicy {
+            enc_keys: tracer_keys.enc_key.clone(),
+            asset_tracing: true,
+            identity_tracing: None,
+        };
+        let other_policy = TracingPolicy {
+            enc_keys: other_tracer_keys.enc_key.clone(),
+            asset_tracing: true,
+            identity_tracing: None,
+        };
+        let policies = TracingPolicies::from_policy(policy.clone());
+
+        let keypair = KeyPair::sample(&mut prng, SECP256K1);
+        let template = AssetRecordTemplate::with_asset_tracing(
+            10,
+            GOLD_ASSET,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            keypair.pub_key,
+            policies.clone(),
+        );
+        let record = AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
+        let oar = record.open_asset_record.clone();
+        let memos = record.asset_tracers_memos.clone();
+
+        let checked = AssetRecord::new_checked(oar.clone(), policies.clone(), memos.clone());
+        let checked = pnk!(checked);
+        assert_eq!(checked.identity_proofs.len(), 1);
+
+        // mismatched counts of policies and memos
+        let err = AssetRecord::new_checked(oar.clone(), policies.clone(), vec![]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("1 tracing policies but 0 asset tracer memos"));
+        let mut two_policies = policies.clone();
+        two_policies.add(other_policy.clone());
+        assert!(AssetRecord::new_checked(oar.clone(), two_policies, memos.clone()).is_err());
+
+        // a memo for another tracer
+        let other_policies = TracingPolicies::from_policy(other_policy);
+        let err = AssetRecord::new_checked(oar, other_policies, memos).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("is not encrypted under the keys of tracing policy 0"));
+    }
 }
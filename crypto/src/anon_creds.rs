@@ -112,6 +112,26 @@ impl<G1: Group> CredentialComm<G1> {
             sigma2: sigma2_r,
         })
     }
+
+    /// Convert the commitment into bytes, as the compressed `sigma1` followed by the compressed `sigma2`.
+    /// Unlike serde, this encoding has no framing and is stable across serialization formats.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.0.sigma1.to_compressed_bytes();
+        bytes.extend_from_slice(&self.0.sigma2.to_compressed_bytes());
+        bytes
+    }
+
+    /// Recover the commitment from the bytes produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 2 * G1::COMPRESSED_LEN {
+            return Err(eg!(NoahError::DeserializationError));
+        }
+        let sigma1 = G1::from_compressed_bytes(&bytes[..G1::COMPRESSED_LEN])
+            .c(d!(NoahError::DeserializationError))?;
+        let sigma2 = G1::from_compressed_bytes(&bytes[G1::COMPRESSED_LEN..])
+            .c(d!(NoahError::DeserializationError))?;
+        Ok(Self(CredentialSig { sigma1, sigma2 }))
+    }
 }
 
 /// User public key (`upk`).
@@ -473,6 +493,26 @@ pub(crate) fn verify_pok<P: Pairing>(
     attrs: &[Attribute<P::ScalarField>],
     challenge: &P::ScalarField,
 ) -> Result<()> {
+    let (p, sigma2_c) = pok_pairing_terms::<P>(ipk, cm, proof_open, attrs, challenge).c(d!())?;
+    let lhs = P::pairing(&cm.0.sigma1, &p);
+    let rhs = P::pairing(&sigma2_c, &ipk.gen2);
+
+    if lhs == rhs {
+        Ok(())
+    } else {
+        Err(eg!(NoahError::IdentityRevealVerifyError))
+    }
+}
+
+/// Compute `(p, sigma2 * challenge)` such that the proof is valid iff
+/// `e(sigma1, p) = e(sigma2 * challenge, G2)`, so that the pairing check can be batched.
+pub(crate) fn pok_pairing_terms<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    cm: &CredentialComm<P::G1>,
+    proof_open: &CredentialPoK<P::G2, P::ScalarField>,
+    attrs: &[Attribute<P::ScalarField>],
+    challenge: &P::ScalarField,
+) -> Result<(P::G2, P::G1)> {
     // p = X_2*c - proof_blinding + &G2 * r_t + Z2 * r_sk + \sum r_attr_i * Y2_i;
     let minus_one: P::ScalarField = P::ScalarField::one().neg();
     let mut scalars = vec![
@@ -508,15 +548,7 @@ pub(crate) fn verify_pok<P: Pairing>(
         elems.push(y);
     }
     let p = P::G2::multi_exp(scalars.as_slice(), elems.as_slice());
-
-    let lhs = P::pairing(&cm.0.sigma1, &p);
-    let rhs = P::pairing(&cm.0.sigma2.mul(challenge), &ipk.gen2);
-
-    if lhs == rhs {
-        Ok(())
-    } else {
-        Err(eg!(NoahError::IdentityRevealVerifyError))
-    }
+    Ok((p, cm.0.sigma2.mul(challenge)))
 }
 
 #[cfg(test)]
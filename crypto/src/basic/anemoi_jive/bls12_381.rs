@@ -1,6 +1,10 @@
-use crate::basic::anemoi_jive::AnemoiJive;
+use crate::basic::anemoi_jive::{AnemoiJive, ANEMOI_JIVE_381_SALTS};
 use noah_algebra::bls12_381::BLSScalar;
-use noah_algebra::new_bls12_381;
+use noah_algebra::prelude::*;
+use noah_algebra::{cfg_into_iter, new_bls12_381};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
 /// The structure that stores the parameters for the Anemoi-Jive hash function for BLS12-381.
 pub struct AnemoiJive381;
@@ -415,3 +419,57 @@ impl AnemoiJive<BLSScalar, 2usize, 12usize> for AnemoiJive381 {
         ]
     }
 }
+
+impl AnemoiJive381 {
+    /// Compute the root of the 3-ary Merkle tree of the given `depth` whose leaves are `leaves`,
+    /// placed from the leftmost position, by hashing the tree bottom-up level by level.
+    ///
+    /// Missing nodes count as zero and a node is only hashed if one of its children is present,
+    /// so the result equals the root of a `PersistentMerkleTree` after inserting the same leaves
+    /// in order. With the `parallel` feature, the nodes of each level are hashed in parallel.
+    ///
+    /// Only `arity` 3 is supported, since the node hash is the Anemoi-Jive CRH on three children.
+    /// Returns an error if `arity` is not 3, if `depth` exceeds the number of salts, or if
+    /// there are more than `3^depth` leaves.
+    pub fn merkle_root_from_leaves(
+        leaves: &[BLSScalar],
+        arity: usize,
+        depth: usize,
+    ) -> Result<BLSScalar> {
+        if arity != 3 {
+            return Err(eg!(format!(
+                "only 3-ary Merkle trees are supported, not {}-ary",
+                arity
+            )));
+        }
+        if depth > ANEMOI_JIVE_381_SALTS.len() {
+            return Err(eg!(format!(
+                "the tree depth {} exceeds the number of salts {}",
+                depth,
+                ANEMOI_JIVE_381_SALTS.len()
+            )));
+        }
+        if leaves.len() as u128 > 3u128.pow(depth as u32) {
+            return Err(eg!(format!(
+                "{} leaves do not fit in a tree of depth {}",
+                leaves.len(),
+                depth
+            )));
+        }
+
+        let mut level = leaves.to_vec();
+        for salt in ANEMOI_JIVE_381_SALTS.iter().take(depth) {
+            let zero = BLSScalar::zero();
+            level = cfg_into_iter!(0..(level.len() + 2) / 3)
+                .map(|i| {
+                    let left = level[3 * i];
+                    let mid = level.get(3 * i + 1).copied().unwrap_or(zero);
+                    let right = level.get(3 * i + 2).copied().unwrap_or(zero);
+                    Self::eval_jive(&[left, mid], &[right, *salt])
+                })
+                .collect::<Vec<BLSScalar>>();
+        }
+
+        Ok(level.first().copied().unwrap_or_default())
+    }
+}
@@ -0,0 +1,443 @@
+use crate::basic::anemoi_jive::AnemoiJive;
+use noah_algebra::ed25519::Ed25519Scalar;
+use noah_algebra::new_ed25519_scalar;
+
+/// The structure that stores the parameters for the Anemoi-Jive hash function for the scalar
+/// field of Curve25519, which is shared by Ed25519 and Ristretto.
+///
+/// The parameters follow the same procedure as `AnemoiJive381`, namely the reference
+/// implementation of the Anemoi paper, with `l = 2` columns:
+/// - `ALPHA` is the smallest integer `alpha >= 3` coprime with `r - 1`, where `r` is the order
+///   of the field, which is 5, and its inverse is computed modulo `r - 1`.
+/// - `GENERATOR` is the smallest generator of the multiplicative group, which is 2, as
+///   `r - 1 = 2^2 * 3 * 11 * 198211423230930754013084525763697 *
+///   276602624281642239937218680557139826668747`.
+/// - The MDS matrix is `[[1, g], [g, g^2 + 1]]`.
+/// - With `pi_0 = 1415926535897932384626433832795028841971693993751058209749445923078164062862089986280348253421170679`
+///   and `pi_1 = 8214808651328230664709384460955058223172535940812848111745028410270193852110555964462294895493038196`,
+///   the first digits of pi, the round keys of round `i` and column `j` are
+///   `C[i][j] = g * (pi_0^i)^2 + (pi_0^i + pi_1^j)^alpha` and
+///   `D[i][j] = g * (pi_1^j)^2 + (pi_0^i + pi_1^j)^alpha + g^(-1)`.
+/// - The preprocessed round keys are `M * C[i]` and `M * (D[i][1], D[i][0])`.
+/// - The number of rounds is 12, as for `AnemoiJive381`, which has the same alpha and `l`.
+///
+/// The test `test_anemoi_jive_25519_parameters` regenerates the parameters this way, and the
+/// script `reference.py` in this directory, an independent Python implementation, prints them
+/// along with the known-answer values of the tests.
+///
+/// The field is given as `Ed25519Scalar` rather than `RistrettoScalar`, though they are the same
+/// field: the constants of `AnemoiJive` must be built in a const context, which
+/// `new_ed25519_scalar!` allows and the dalek scalar behind `RistrettoScalar` does not. Both use
+/// the same canonical little-endian encoding, so a `RistrettoScalar` converts through its bytes.
+pub struct AnemoiJive25519;
+
+impl AnemoiJive<Ed25519Scalar, 2usize, 12usize> for AnemoiJive25519 {
+    const ALPHA: u32 = 5u32;
+    const GENERATOR: Ed25519Scalar = new_ed25519_scalar!("2");
+    const GENERATOR_INV: Ed25519Scalar = new_ed25519_scalar!(
+        "3618502788666131106986593281521497120428558179689953803000975469142727125495"
+    );
+    const GENERATOR_SQUARE_PLUS_ONE: Ed25519Scalar = new_ed25519_scalar!("5");
+    const ROUND_KEYS_X: [[Ed25519Scalar; 2usize]; 12usize] = [
+        [
+            new_ed25519_scalar!("34"),
+            new_ed25519_scalar!(
+                "160893805036756889423159854107127760729249782398633808300452547391111737650"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "3809909144449562648156806636149181401963811036945913253339584014650080914037"
+            ),
+            new_ed25519_scalar!(
+                "7077672566650971112167539298931586115915319592250463136831039313482072357951"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "5948113001246356735011605298930179840753468738115021248052741105868782558070"
+            ),
+            new_ed25519_scalar!(
+                "5111953304902408466003790582226332300060145024865972047813499268119421393139"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2952864246305217546381974818611098169596136133678492517675035895888549708085"
+            ),
+            new_ed25519_scalar!(
+                "5751172312477951504341434833162268207531687362596174266861988500204771672688"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2823628752119485534969524239950277676901482514052692791510201928769174123387"
+            ),
+            new_ed25519_scalar!(
+                "6764035263937294483076580028911350245158349848665272870857542997248776277599"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "6967865461780079988642396472689259577779315953251259745703845995757739719014"
+            ),
+            new_ed25519_scalar!(
+                "586440829648880111166378601922918255775356165640343704923287223995908125554"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "4093253080961649235143179452752529717443589421835194331513416050122912500053"
+            ),
+            new_ed25519_scalar!(
+                "5068917613004543679188299198563560235295026072897955375981938536112936317717"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2875224289009825220364090210707393656422271315465203060799414586339019915373"
+            ),
+            new_ed25519_scalar!(
+                "5056662723106988516055125134695901512429458218966702393959942198433818800937"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2157948832921671978057202418751452467945074865526933231800309122636741503497"
+            ),
+            new_ed25519_scalar!(
+                "4114686966391895232961268330102416488091232789457877178112148903362958418689"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "1670962628934549112049700988067032701629388810979212303013983203878983206316"
+            ),
+            new_ed25519_scalar!(
+                "4386347035008464817261793199393241504334235440677733574205401414152408772285"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "1058411397885507811270714119984752765866757567160492313073051308962165350551"
+            ),
+            new_ed25519_scalar!(
+                "6252767477111223638809723593638325078746345489297947801594941573939093430687"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2150973306711766379741994919574091508639376151005154332167456124768669358554"
+            ),
+            new_ed25519_scalar!(
+                "657633411399680719097005332939121280434468653055012865554536803895487541390"
+            ),
+        ],
+    ];
+    const ROUND_KEYS_Y: [[Ed25519Scalar; 2usize]; 12usize] = [
+        [
+            new_ed25519_scalar!(
+                "3618502788666131106986593281521497120428558179689953803000975469142727125529"
+            ),
+            new_ed25519_scalar!(
+                "954112843305193708433405001948271138376906400173000984740859674481881559"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "1061231373691659625762910170850518792126795648196464924683826408775032636555"
+            ),
+            new_ed25519_scalar!(
+                "550552315033485287072323103006246896058873147812600197858594550747667098883"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "4021418497650172374159365754065237959239246419414299597652605230151083379242"
+            ),
+            new_ed25519_scalar!(
+                "6643821897778903516423417869777708049383608009856743393098627173827819483714"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2743062208409107573598391716201814947332012381797368123112661008541290421408"
+            ),
+            new_ed25519_scalar!(
+                "1762927793722258728856532000126308375248132555026635261982926455998155404425"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "7013781149376780954626634829124083615936619373400690680418523277346399125851"
+            ),
+            new_ed25519_scalar!(
+                "7175745180335007100032370887458479574174055652324856149449177188966644298477"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2227112382907323269406040973360826689808001928178511789757998045166092614271"
+            ),
+            new_ed25519_scalar!(
+                "6541256424581065017175076498053797239498843803638996350664653993115812541203"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2415317465828897316423114252459321545185368340512098968250860360413248446862"
+            ),
+            new_ed25519_scalar!(
+                "6849545094344471171740100830686669693874490295266353008404646627829369533929"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "91672102840375539983398451375002728947511268203066620279272747078698127860"
+            ),
+            new_ed25519_scalar!(
+                "5731673633410218246946300207779828215792383475396058949125064140599594282827"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "5343411305071970406048274554179966099184463305687419050770148989446012087510"
+            ),
+            new_ed25519_scalar!(
+                "3521706957682610858251020734904253509311190173929948386765301613312872021116"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "603376407599195225343733777974181292615232035258243888526764123943600383796"
+            ),
+            new_ed25519_scalar!(
+                "6777323910145790341827692821716707726157763968648258155403446115643123219168"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2655377687775644947778540279892211683169786768780664291942509308991353211707"
+            ),
+            new_ed25519_scalar!(
+                "4071291286141777972616230022919107386029943635229705170147712417108924310257"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "5123131199564240161640435090004279652836747371445975496914294303101523803101"
+            ),
+            new_ed25519_scalar!(
+                "7088354400724833912267312335785627055469525177187327025986638763654439255340"
+            ),
+        ],
+    ];
+    const PREPROCESSED_ROUND_KEYS_X: [[Ed25519Scalar; 2usize]; 12usize] = [
+        [
+            new_ed25519_scalar!(
+                "321787610073513778846319708214255521458499564797267616600905094782223475334"
+            ),
+            new_ed25519_scalar!(
+                "804469025183784447115799270535638803646248911993169041502262736955558688318"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "3491243123086980444545512107926365152080217502687024314997760765043317127961"
+            ),
+            new_ed25519_scalar!(
+                "6823153235492669787285376951741322179218638238244604160824609905283252362884"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "1698008456386649239072813337296855959159526069087150131675837765536716842370"
+            ),
+            new_ed25519_scalar!(
+                "1270964640343444730176230693777049977522080803660364705163223860907400826890"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "7218203293928858341091657921892640343802394499490933445397061958012638802472"
+            ),
+            new_ed25519_scalar!(
+                "5713567745671143758578377550861560413422243642818225945652210539659140775654"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "1877688125329550073176311171686989685503949492623423321221386046695818176607"
+            ),
+            new_ed25519_scalar!(
+                "3282405937264132415456015809242335375309132474532211907298364152354958379824"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "903741543745577997001967113492101848472911925152039549548469505464101719133"
+            ),
+            new_ed25519_scalar!(
+                "2393923917140036105170312828907121952721180015944422804020226234924111563820"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "6994082729638474379546591286836655947176525208251197477475342184063330884498"
+            ),
+            new_ed25519_scalar!(
+                "4583071917616968010335108646150883647933843770640535118928721027668689584735"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "5751544157891540038501153917056202440424071394018700242717348044921203266258"
+            ),
+            new_ed25519_scalar!(
+                "2085739884225544165111059842722317911563368288244287667390736411705316831475"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "3150317188373200230006552515913291203270424085062779982022655991077204089886"
+            ),
+            new_ed25519_scalar!(
+                "3178315765806033479001186798886004653774964600203529536155509947231912347472"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "3206651121619216532600100823810521469440743332954771845422835093898346499897"
+            ),
+            new_ed25519_scalar!(
+                "3562643700914635668488808283971290202358605747207369659049120663663647521090"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "6326940774775692874916974744218408682502332186376480310260983518554897960936"
+            ),
+            new_ed25519_scalar!(
+                "4432637871998084960697299955989153962036777143291093210113006734477980850581"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "3466240129511127817936005585452334069508313457115180063276529732559644441334"
+            ),
+            new_ed25519_scalar!(
+                "353108093089674140995829940800795178593979207905465386105645330729322173069"
+            ),
+        ],
+    ];
+    const PREPROCESSED_ROUND_KEYS_Y: [[Ed25519Scalar; 2usize]; 12usize] = [
+        [
+            new_ed25519_scalar!(
+                "954112843305193708433405001948271138376906400173000984740859674481881628"
+            ),
+            new_ed25519_scalar!(
+                "3620411014352741494403460091525393662705311992490299804970457188491690888785"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2673015062416804538598143444707284480312464444205530047226247368297732371993"
+            ),
+            new_ed25519_scalar!(
+                "6407261498525268702959197060265087752751724536607525019136321145370497380541"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "212647738414723836795776251822195486147868129925527376399935757559077740220"
+            ),
+            new_ed25519_scalar!(
+                "4446713974479620047750918257709628931534982679265354350452476745269238859682"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "12046633208211662080128869486944029055040959241463902206297534795281996252"
+            ),
+            new_ed25519_scalar!(
+                "2767155474825530897758649455175703005442094300280295927525256078131854413912"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "6729296324424044581339267419620658324333061680366422298282321867088534048201"
+            ),
+            new_ed25519_scalar!(
+                "5998362643560345689358796542279411782888510015373720064979265134952558720275"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "3758475613063449342013971881732456378257731300616112324178699145162543518756"
+            ),
+            new_ed25519_scalar!(
+                "2507058031701959739460798173782745205466348170030828832113445397205725400794"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "4443174448670003590613142772562318543388110616910643338904416410370412176664"
+            ),
+            new_ed25519_scalar!(
+                "4064660785836642283676213234540964391104473214953478040057742242868618549201"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "5915017839090969326913097110529833673687406011802192189683609634756990538547"
+            ),
+            new_ed25519_scalar!(
+                "4684702203690051979836406109391675835465206932427543393644541078307224953965"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "6971523990494289456374383280221191466823000425924878882303648653919441945147"
+            ),
+            new_ed25519_scalar!(
+                "4812448131396024890850667988536360551116231438777361603373544420713987475826"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "747071148011918578541973814622076070531111679784838326455023425244869735771"
+            ),
+            new_ed25519_scalar!(
+                "2097518703623032382427681407218333433677455394827920541436810974433339855338"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2145041084360805654200124019660536511512400813411126148030780096806176482682"
+            ),
+            new_ed25519_scalar!(
+                "6945459856497256256178788319213284706194588395602916588004069502603706177071"
+            ),
+        ],
+        [
+            new_ed25519_scalar!(
+                "2860605645188789807601809389708197879428787201319462807811325493286578359564"
+            ),
+            new_ed25519_scalar!(
+                "3607336912609557562870867306377681170837205414704993506534994351389226271240"
+            ),
+        ],
+    ];
+    const MDS_MATRIX: [[Ed25519Scalar; 2usize]; 2usize] = [
+        [new_ed25519_scalar!("1"), new_ed25519_scalar!("2")],
+        [new_ed25519_scalar!("2"), new_ed25519_scalar!("5")],
+    ];
+    fn get_alpha_inv() -> Vec<u64> {
+        vec![
+            118397459137108161u64,
+            11970394880346041063u64,
+            11068046444225730969u64,
+            691752902764108185u64,
+        ]
+    }
+}
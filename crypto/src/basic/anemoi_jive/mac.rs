@@ -0,0 +1,43 @@
+use crate::basic::anemoi_jive::{AnemoiJive, AnemoiJive381, AnemoiVLHTrace};
+use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+use num_bigint::BigUint;
+
+/// The domain separator of the Anemoi MAC.
+const ANEMOI_MAC_DOMAIN: &[u8] = b"Noah Anemoi MAC";
+
+/// Return the domain separator of the Anemoi MAC, which is absorbed before the message.
+pub fn anemoi_mac_domain_separator() -> BLSScalar {
+    BLSScalar::from(&BigUint::from_bytes_le(ANEMOI_MAC_DOMAIN))
+}
+
+/// Compute the Anemoi MAC of `message` under `key`, a pseudorandom function of the message.
+///
+/// It is the Anemoi sponge of the domain separator, the length of the message and the message,
+/// where the capacity is initialized to the key. The first permutation then takes
+/// `(sep, len, m_0, key)`, while the variable length hash of `[key, message...]` starts with
+/// `(key, m_0, m_1, 0)`, and the two never coincide since the domain separator is not zero.
+///
+/// The length is absorbed because the padding of the sponge is only added to the capacity
+/// after the last permutation, where it does not reach the output, so that without it
+/// `[m_0]` and `[m_0, 1]` would have the same MAC.
+pub fn anemoi_mac(key: &BLSScalar, message: &[BLSScalar]) -> BLSScalar {
+    AnemoiJive381::eval_keyed_variable_length_hash(key, &mac_input(message))
+}
+
+/// Compute the Anemoi MAC of `message` under `key`, and return the trace for the circuit.
+pub fn anemoi_mac_with_trace(
+    key: &BLSScalar,
+    message: &[BLSScalar],
+) -> AnemoiVLHTrace<BLSScalar, 2, 12> {
+    AnemoiJive381::eval_keyed_variable_length_hash_with_trace(key, &mac_input(message))
+}
+
+/// The input of the sponge: the domain separator and the length of the message, followed by
+/// the message.
+fn mac_input(message: &[BLSScalar]) -> Vec<BLSScalar> {
+    let mut input = Vec::with_capacity(message.len() + 2);
+    input.push(anemoi_mac_domain_separator());
+    input.push(BLSScalar::from(message.len() as u64));
+    input.extend_from_slice(message);
+    input
+}
@@ -2,10 +2,16 @@ use noah_algebra::prelude::*;
 
 /// The module for the AnemoiJive381 data structure.
 mod bls12_381;
+/// The module for the AnemoiJive25519 data structure.
+mod ed25519;
+/// The module for the keyed Anemoi MAC.
+mod mac;
 /// The module for the MDS matrices.
 mod mds;
 /// The module for the salts used for Merkle tree.
 mod salts;
+/// The module for the incremental sponge.
+mod sponge;
 /// The module for tests.
 #[cfg(test)]
 mod tests;
@@ -13,8 +19,11 @@ mod tests;
 mod traces;
 
 pub use bls12_381::AnemoiJive381;
+pub use ed25519::AnemoiJive25519;
+pub use mac::{anemoi_mac, anemoi_mac_domain_separator, anemoi_mac_with_trace};
 pub use mds::{ApplicableMDSMatrix, MDSMatrix};
 pub use salts::ANEMOI_JIVE_381_SALTS;
+pub use sponge::{AnemoiSponge, AnemoiSponge381};
 pub use traces::{AnemoiStreamCipherTrace, AnemoiVLHTrace, JiveTrace};
 
 /// The trait for the Anemoi-Jive parameters.
@@ -54,6 +63,11 @@ where
 
     /// Eval the Anemoi sponge.
     fn eval_variable_length_hash(input: &[F]) -> F {
+        Self::eval_keyed_variable_length_hash(&F::zero(), input)
+    }
+
+    /// Eval the Anemoi sponge, with the capacity initialized to `key` instead of zero.
+    fn eval_keyed_variable_length_hash(key: &F, input: &[F]) -> F {
         let mut input = input.to_vec();
 
         let sigma = if input.len() % (2 * N - 1) == 0 && !input.is_empty() {
@@ -75,6 +89,7 @@ where
         // initialize the internal state.
         let mut x = [F::zero(); N];
         let mut y = [F::zero(); N];
+        y[N - 1] = *key;
         for chunk in input.chunks_exact(2 * N - 1) {
             for i in 0..N {
                 x[i] += &chunk[i];
@@ -94,6 +109,15 @@ where
 
     /// Eval the Anemoi sponge and return the trace.
     fn eval_variable_length_hash_with_trace(input: &[F]) -> AnemoiVLHTrace<F, N, NUM_ROUNDS> {
+        Self::eval_keyed_variable_length_hash_with_trace(&F::zero(), input)
+    }
+
+    /// Eval the Anemoi sponge, with the capacity initialized to `key` instead of zero,
+    /// and return the trace.
+    fn eval_keyed_variable_length_hash_with_trace(
+        key: &F,
+        input: &[F],
+    ) -> AnemoiVLHTrace<F, N, NUM_ROUNDS> {
         let mut trace = AnemoiVLHTrace::<F, N, NUM_ROUNDS>::default();
 
         let mut input = input.to_vec();
@@ -121,6 +145,7 @@ where
         // initialize the internal state.
         let mut x = [F::zero(); N];
         let mut y = [F::zero(); N];
+        y[N - 1] = *key;
         for chunk in input.chunks_exact(2 * N - 1) {
             for i in 0..N {
                 x[i] += &chunk[i];
@@ -0,0 +1,115 @@
+#!/usr/bin/env python3
+# An independent Python implementation of Anemoi-Jive with l = 2, which regenerates the
+# parameters of `AnemoiJive25519` and the known-answer values of its tests.
+#
+# It follows the Anemoi paper (https://eprint.iacr.org/2022/840) and its reference
+# implementation (https://github.com/anemoi-hash/anemoi-hash, anemoi.sage), with the two
+# choices made by noah: 12 rounds, and the linear layer is the MDS matrix alone.
+# As a sanity check of those choices, it first reproduces the known-answer values of
+# `AnemoiJive381`, which come from the noah code base rather than from this script.
+#
+# Usage: python3 reference.py
+
+from math import gcd
+
+PI_0 = 1415926535897932384626433832795028841971693993751058209749445923078164062862089986280348253421170679
+PI_1 = 8214808651328230664709384460955058223172535940812848111745028410270193852110555964462294895493038196
+
+N_ROUNDS = 12
+
+# The order of the scalar field of BLS12-381, whose smallest generator is 7.
+BLS12_381_R = 0x73EDA753299D7D483339D80809A1D80553BDA402FFFE5BFEFFFFFFFF00000001
+# The order of the prime-order subgroup of Curve25519, which is the scalar field of both
+# Ed25519 and Ristretto, and whose smallest generator is 2.
+CURVE25519_L = 2**252 + 27742317777372353535851937790883648493
+
+
+class AnemoiJive:
+    def __init__(self, q, g):
+        self.q = q
+        self.alpha = 3
+        while gcd(self.alpha, q - 1) != 1:
+            self.alpha += 1
+        self.alpha_inv = pow(self.alpha, -1, q - 1)
+        self.g = g
+        self.g_inv = pow(g, -1, q)
+        self.mds_matrix = [[1, g], [g, (g * g + 1) % q]]
+
+        self.round_keys_x, self.round_keys_y = [], []
+        for r in range(N_ROUNDS):
+            pi_0_r = pow(PI_0, r, q)
+            c, d = [], []
+            for i in range(2):
+                pi_1_i = pow(PI_1, i, q)
+                pow_alpha = pow(pi_0_r + pi_1_i, self.alpha, q)
+                c.append((g * pi_0_r * pi_0_r + pow_alpha) % q)
+                d.append((g * pi_1_i * pi_1_i + pow_alpha + self.g_inv) % q)
+            self.round_keys_x.append(c)
+            self.round_keys_y.append(d)
+
+    def mul_mds(self, v):
+        m = self.mds_matrix
+        return [(m[i][0] * v[0] + m[i][1] * v[1]) % self.q for i in range(2)]
+
+    def linear_layer(self, x, y):
+        return self.mul_mds(x), self.mul_mds([y[1], y[0]])
+
+    def permutation(self, x, y):
+        q = self.q
+        x, y = list(x), list(y)
+        for r in range(N_ROUNDS):
+            x = [(x[i] + self.round_keys_x[r][i]) % q for i in range(2)]
+            y = [(y[i] + self.round_keys_y[r][i]) % q for i in range(2)]
+            x, y = self.linear_layer(x, y)
+            for i in range(2):
+                x[i] = (x[i] - self.g * y[i] * y[i]) % q
+                y[i] = (y[i] - pow(x[i], self.alpha_inv, q)) % q
+                x[i] = (x[i] + self.g * y[i] * y[i] + self.g_inv) % q
+        return self.linear_layer(x, y)
+
+    def jive(self, x, y):
+        a, b = self.permutation(x, y)
+        return (sum(x) + sum(y) + sum(a) + sum(b)) % self.q
+
+    def variable_length_hash(self, inputs):
+        q = self.q
+        inputs = list(inputs)
+        if len(inputs) == 0 or len(inputs) % 3 != 0:
+            inputs.append(1)
+            while len(inputs) % 3 != 0:
+                inputs.append(0)
+        x, y = [0, 0], [0, 0]
+        for k in range(0, len(inputs), 3):
+            x = [(x[0] + inputs[k]) % q, (x[1] + inputs[k + 1]) % q]
+            y = [(y[0] + inputs[k + 2]) % q, y[1]]
+            x, y = self.permutation(x, y)
+        return x[0]
+
+
+def main():
+    bls = AnemoiJive(BLS12_381_R, 7)
+    assert bls.jive([1, 2], [3, 0]) == 40534080031161498828112599909199108154146698842441932527619782321134903095510
+    assert bls.variable_length_hash([1, 2, 3, 4]) == 17913626440896376279858183231538520765146521393387279167163788217724133906091
+
+    h = AnemoiJive(CURVE25519_L, 2)
+    print("ALPHA =", h.alpha)
+    print("GENERATOR =", h.g)
+    print("GENERATOR_INV =", h.g_inv)
+    print("GENERATOR_SQUARE_PLUS_ONE =", (h.g * h.g + 1) % h.q)
+    print("ALPHA_INV =", h.alpha_inv)
+    print("ROUND_KEYS_X =", h.round_keys_x)
+    print("ROUND_KEYS_Y =", h.round_keys_y)
+    print("PREPROCESSED_ROUND_KEYS_X =", [h.mul_mds(c) for c in h.round_keys_x])
+    print("PREPROCESSED_ROUND_KEYS_Y =", [h.mul_mds([d[1], d[0]]) for d in h.round_keys_y])
+
+    print("test_jive_25519:")
+    print("  jive([1, 2], [3, 0]) =", h.jive([1, 2], [3, 0]))
+    print("  jive([0, 0], [0, 0]) =", h.jive([0, 0], [0, 0]))
+    print("test_anemoi_variable_length_hash_25519:")
+    print("  hash([]) =", h.variable_length_hash([]))
+    print("  hash([1, 2, 3]) =", h.variable_length_hash([1, 2, 3]))
+    print("  hash([1, 2, 3, 4]) =", h.variable_length_hash([1, 2, 3, 4]))
+
+
+if __name__ == "__main__":
+    main()
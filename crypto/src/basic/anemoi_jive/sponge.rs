@@ -0,0 +1,117 @@
+use crate::basic::anemoi_jive::{AnemoiJive, AnemoiJive381, ApplicableMDSMatrix, MDSMatrix};
+use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+use std::marker::PhantomData;
+
+/// The Anemoi sponge, which absorbs its input incrementally.
+///
+/// It uses the same padding and the same split of the state into rate and capacity as
+/// `AnemoiJive::eval_variable_length_hash`, so that absorbing an input, in one or several
+/// chunks, and squeezing one element gives the same output as the one-shot hash. More generally,
+/// the elements squeezed by consecutive calls are those of `AnemoiJive::eval_stream_cipher`.
+pub struct AnemoiSponge<F: Scalar, H, const N: usize, const NUM_ROUNDS: usize> {
+    /// The first part of the state.
+    x: [F; N],
+    /// The second part of the state, whose last element is the capacity.
+    y: [F; N],
+    /// The absorbed elements which do not fill a chunk of `2 * N - 1` elements yet.
+    buffer: Vec<F>,
+    /// Whether some chunk has been absorbed into the state.
+    absorbed: bool,
+    /// The number of elements already squeezed from the current state, once squeezing started.
+    squeezed: Option<usize>,
+    /// The Anemoi-Jive parameters.
+    hash: PhantomData<H>,
+}
+
+/// The Anemoi sponge with the AnemoiJive381 parameters.
+pub type AnemoiSponge381 = AnemoiSponge<BLSScalar, AnemoiJive381, 2, 12>;
+
+impl<F: Scalar, H: AnemoiJive<F, N, NUM_ROUNDS>, const N: usize, const NUM_ROUNDS: usize> Default
+    for AnemoiSponge<F, H, N, NUM_ROUNDS>
+where
+    MDSMatrix<F, N>: ApplicableMDSMatrix<F, N>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Scalar, H: AnemoiJive<F, N, NUM_ROUNDS>, const N: usize, const NUM_ROUNDS: usize>
+    AnemoiSponge<F, H, N, NUM_ROUNDS>
+where
+    MDSMatrix<F, N>: ApplicableMDSMatrix<F, N>,
+{
+    /// The number of elements absorbed or squeezed per permutation.
+    const RATE: usize = 2 * N - 1;
+
+    /// Create a sponge with the zero state.
+    pub fn new() -> Self {
+        Self {
+            x: [F::zero(); N],
+            y: [F::zero(); N],
+            buffer: Vec::with_capacity(Self::RATE),
+            absorbed: false,
+            squeezed: None,
+            hash: PhantomData,
+        }
+    }
+
+    /// Absorb the elements, which are appended to the previously absorbed ones.
+    /// It panics if the sponge has already been squeezed.
+    pub fn absorb(&mut self, elems: &[F]) {
+        assert!(self.squeezed.is_none(), "cannot absorb after squeezing");
+        for elem in elems.iter() {
+            self.buffer.push(*elem);
+            if self.buffer.len() == Self::RATE {
+                self.absorb_buffer();
+            }
+        }
+    }
+
+    /// Squeeze `n` elements. The first call pads the absorbed input.
+    pub fn squeeze(&mut self, n: usize) -> Vec<F> {
+        let mut pos = match self.squeezed {
+            Some(pos) => pos,
+            None => {
+                // the padding of the variable length hash
+                if self.buffer.is_empty() && self.absorbed {
+                    self.y[N - 1] += &F::one();
+                } else {
+                    self.buffer.push(F::one());
+                    self.buffer.resize(Self::RATE, F::zero());
+                    self.absorb_buffer();
+                }
+                0
+            }
+        };
+
+        let mut output = Vec::with_capacity(n);
+        for _ in 0..n {
+            if pos == Self::RATE {
+                H::anemoi_permutation(&mut self.x, &mut self.y);
+                pos = 0;
+            }
+            if pos < N {
+                output.push(self.x[pos]);
+            } else {
+                output.push(self.y[pos - N]);
+            }
+            pos += 1;
+        }
+        self.squeezed = Some(pos);
+        output
+    }
+
+    /// Add the buffered chunk to the state, and apply the permutation.
+    fn absorb_buffer(&mut self) {
+        for i in 0..N {
+            self.x[i] += &self.buffer[i];
+        }
+        for i in 0..(N - 1) {
+            self.y[i] += &self.buffer[N + i];
+        }
+        H::anemoi_permutation(&mut self.x, &mut self.y);
+        self.buffer.clear();
+        self.absorbed = true;
+    }
+}
@@ -1,7 +1,13 @@
-use crate::basic::anemoi_jive::{AnemoiJive, AnemoiJive381, ApplicableMDSMatrix, MDSMatrix};
+use crate::basic::anemoi_jive::{
+    anemoi_mac, AnemoiJive, AnemoiJive25519, AnemoiJive381, AnemoiSponge381, ApplicableMDSMatrix,
+    MDSMatrix,
+};
 use noah_algebra::bls12_381::BLSScalar;
-use noah_algebra::new_bls12_381;
+use noah_algebra::ed25519::Ed25519Scalar;
 use noah_algebra::prelude::Scalar;
+use noah_algebra::ristretto::RistrettoScalar;
+use noah_algebra::{new_bls12_381, new_ed25519_scalar};
+use num_bigint::BigUint;
 use num_traits::{One, Zero};
 
 #[test]
@@ -139,6 +145,119 @@ fn test_anemoi_variable_length_hash() {
     );
 }
 
+// The known-answer values of `AnemoiJive25519` are printed by `reference.py`, an independent
+// Python implementation that also reproduces the values of `test_jive` and
+// `test_anemoi_variable_length_hash` for `AnemoiJive381`.
+#[test]
+fn test_jive_25519() {
+    type F = Ed25519Scalar;
+
+    let res =
+        AnemoiJive25519::eval_jive(&[F::from(1u64), F::from(2u64)], &[F::from(3u64), F::zero()]);
+    assert_eq!(
+        res,
+        new_ed25519_scalar!(
+            "4884204531189911256103649243079916911774251610126843828049750938604669554500"
+        )
+    );
+
+    let res = AnemoiJive25519::eval_jive(&[F::zero(), F::zero()], &[F::zero(), F::zero()]);
+    assert_eq!(
+        res,
+        new_ed25519_scalar!(
+            "3884828465323774121031830357146779851493176365236309419490363418527307504697"
+        )
+    );
+}
+
+#[test]
+fn test_anemoi_variable_length_hash_25519() {
+    type F = Ed25519Scalar;
+
+    let res = AnemoiJive25519::eval_variable_length_hash(&[]);
+    assert_eq!(
+        res,
+        new_ed25519_scalar!(
+            "5298321746024710623196944753351531030709346939414749640129587561799525968010"
+        )
+    );
+
+    let res =
+        AnemoiJive25519::eval_variable_length_hash(&[F::from(1u64), F::from(2u64), F::from(3u64)]);
+    assert_eq!(
+        res,
+        new_ed25519_scalar!(
+            "7106381768294726659294996169613444278989242859859087773064934684124065031174"
+        )
+    );
+
+    let res = AnemoiJive25519::eval_variable_length_hash(&[
+        F::from(1u64),
+        F::from(2u64),
+        F::from(3u64),
+        F::from(4u64),
+    ]);
+    assert_eq!(
+        res,
+        new_ed25519_scalar!(
+            "6790893223201428140115064427223418183843193364957087515651810819800775246610"
+        )
+    );
+}
+
+#[test]
+fn test_anemoi_jive_25519_parameters() {
+    type F = Ed25519Scalar;
+    type H = AnemoiJive25519;
+
+    // the first digits of pi
+    let pi_0 = b"1415926535897932384626433832795028841971693993751058209749445923078164062862\
+        089986280348253421170679";
+    let pi_1 = b"8214808651328230664709384460955058223172535940812848111745028410270193852110\
+        555964462294895493038196";
+    let pi_0 = F::from(&BigUint::parse_bytes(pi_0, 10).unwrap());
+    let pi_1 = F::from(&BigUint::parse_bytes(pi_1, 10).unwrap());
+
+    // the same field as the Ristretto scalars, with the same encoding
+    let minus_one = RistrettoScalar::zero() - RistrettoScalar::one();
+    assert_eq!(
+        F::from_bytes(&minus_one.to_bytes()).unwrap(),
+        F::zero() - F::one()
+    );
+
+    let g = H::GENERATOR;
+    assert_eq!(g, F::from(2u64));
+    assert_eq!(g * H::GENERATOR_INV, F::one());
+    assert_eq!(g.square() + F::one(), H::GENERATOR_SQUARE_PLUS_ONE);
+
+    // alpha_inv is the inverse of alpha = 5
+    let x = F::from(12345u64);
+    assert_eq!(x.pow(&H::get_alpha_inv()).pow(&[5u64]), x);
+
+    let mds = MDSMatrix::<F, 2>::from_generator(&g);
+    assert_eq!(mds.0, H::MDS_MATRIX);
+
+    let mut pi_0_r = F::one();
+    for r in 0..12 {
+        let mut pi_1_i = F::one();
+        for i in 0..2 {
+            let pow_alpha = (pi_0_r + pi_1_i).pow(&[5u64]);
+            assert_eq!(H::ROUND_KEYS_X[r][i], g * pi_0_r.square() + pow_alpha);
+            assert_eq!(
+                H::ROUND_KEYS_Y[r][i],
+                g * pi_1_i.square() + pow_alpha + H::GENERATOR_INV
+            );
+            pi_1_i = pi_1_i * pi_1;
+        }
+        pi_0_r = pi_0_r * pi_0;
+
+        let (x, _) = mds.permute(&H::ROUND_KEYS_X[r], &[F::zero(); 2]);
+        let (_, y) = mds.permute(&[F::zero(); 2], &H::ROUND_KEYS_Y[r]);
+        assert_eq!(x, H::PREPROCESSED_ROUND_KEYS_X[r]);
+        assert_eq!(y, H::PREPROCESSED_ROUND_KEYS_Y[r]);
+    }
+}
+
 #[test]
 fn test_anemoi_variable_length_hash_flatten() {
     type F = BLSScalar;
@@ -328,6 +447,74 @@ fn test_eval_stream_cipher() {
     assert_eq!(res, expect[..7]);
 }
 
+#[test]
+fn test_anemoi_sponge() {
+    type F = BLSScalar;
+
+    for len in 0..10u64 {
+        let input: Vec<F> = (0..len).map(|i| F::from(i + 1)).collect();
+        let expect = AnemoiJive381::eval_variable_length_hash(&input);
+        assert_eq!(
+            AnemoiJive381::eval_variable_length_hash_with_trace(&input).output,
+            expect
+        );
+
+        let mut sponge = AnemoiSponge381::new();
+        sponge.absorb(&input);
+        assert_eq!(sponge.squeeze(1), vec![expect]);
+
+        // absorb the input in chunks of every size
+        for chunk_size in 1..5 {
+            let mut sponge = AnemoiSponge381::new();
+            for chunk in input.chunks(chunk_size) {
+                sponge.absorb(chunk);
+            }
+            sponge.absorb(&[]);
+            assert_eq!(sponge.squeeze(1), vec![expect]);
+        }
+    }
+
+    // consecutive squeezes give the output of the stream cipher
+    let input = [F::from(1u64), F::from(2u64), F::from(3u64), F::from(4u64)];
+    let expect = AnemoiJive381::eval_stream_cipher(&input, 8);
+    let mut sponge = AnemoiSponge381::new();
+    sponge.absorb(&input[..1]);
+    sponge.absorb(&input[1..]);
+    let mut res = sponge.squeeze(2);
+    res.extend(sponge.squeeze(0));
+    res.extend(sponge.squeeze(4));
+    res.extend(sponge.squeeze(2));
+    assert_eq!(res, expect);
+}
+
+#[test]
+fn test_anemoi_mac() {
+    type F = BLSScalar;
+
+    for len in 0..6u64 {
+        let message = (1..=len).map(F::from).collect::<Vec<F>>();
+        let key = F::from(7u64);
+        let mac = anemoi_mac(&key, &message);
+
+        // another key gives another MAC
+        assert_ne!(mac, anemoi_mac(&F::from(8u64), &message));
+
+        // the MAC is not the hash of the key and the message
+        let mut hash_input = vec![key];
+        hash_input.extend_from_slice(&message);
+        assert_ne!(mac, AnemoiJive381::eval_variable_length_hash(&hash_input));
+    }
+
+    // the padding of a message cannot be appended to it without changing the MAC
+    let key = F::from(7u64);
+    let m0 = F::from(5u64);
+    assert_ne!(anemoi_mac(&key, &[m0]), anemoi_mac(&key, &[m0, F::one()]));
+    assert_ne!(
+        anemoi_mac(&key, &[m0, F::one()]),
+        anemoi_mac(&key, &[m0, F::one(), F::zero()])
+    );
+}
+
 #[test]
 fn test_eval_stream_cipher_flatten() {
     type F = BLSScalar;
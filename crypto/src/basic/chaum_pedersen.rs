@@ -1,5 +1,6 @@
 use crate::basic::matrix_sigma::{
-    sigma_prove, sigma_verify, sigma_verify_scalars, SigmaProof, SigmaTranscript,
+    sigma_prove, sigma_verify, sigma_verify_scalars, transcript_verifier_rng, SigmaProof,
+    SigmaTranscript,
 };
 use curve25519_dalek::traits::{Identity, VartimeMultiscalarMul};
 use merlin::Transcript;
@@ -113,7 +114,7 @@ pub fn chaum_pedersen_verify_eq<R: CryptoRng + RngCore>(
         responses: vec![proof.z1, proof.z2, proof.z3],
     };
 
-    sigma_verify::<_, RistrettoPoint>(
+    sigma_verify::<_, _, RistrettoPoint>(
         transcript,
         prng,
         elems.as_slice(),
@@ -387,6 +388,28 @@ pub fn chaum_pedersen_batch_verify_multiple_eq<R: CryptoRng + RngCore>(
     }
 }
 
+/// Same as `chaum_pedersen_batch_verify_multiple_eq`, but the random scalars are derived from
+/// the transcript after appending all the instances and proofs, see `transcript_verifier_rng`.
+pub fn chaum_pedersen_batch_verify_multiple_eq_deterministic(
+    transcript: &mut Transcript,
+    instances: &[(Vec<RistrettoPoint>, &ChaumPedersenProofX)],
+) -> Result<()> {
+    let mut rng_transcript = transcript.clone();
+    rng_transcript.append_message(b"new_domain", b"Chaum Pedersen batch verification");
+    for (commitments, proof) in instances {
+        init_chaum_pedersen_multiple(&mut rng_transcript, commitments);
+        for eq_proof in Some(&proof.c1_eq_c2).into_iter().chain(proof.zero.iter()) {
+            rng_transcript.append_proof_commitment(&eq_proof.c3);
+            rng_transcript.append_proof_commitment(&eq_proof.c4);
+            rng_transcript.append_field_element(b"proof z1", &eq_proof.z1);
+            rng_transcript.append_field_element(b"proof z2", &eq_proof.z2);
+            rng_transcript.append_field_element(b"proof z3", &eq_proof.z3);
+        }
+    }
+    let mut prng = transcript_verifier_rng(&mut rng_transcript);
+    chaum_pedersen_batch_verify_multiple_eq(transcript, &mut prng, instances).c(d!())
+}
+
 // Helper functions for the proof of multiple commitments equality below
 
 // Obtain a fake compressed commitment to zero, eg The identity
@@ -530,6 +553,19 @@ mod test {
             &proof
         )
         .is_ok());
+
+        let instances = [(com_vec.to_vec(), &proof), (com_vec.to_vec(), &proof)];
+        assert!(chaum_pedersen_batch_verify_multiple_eq_deterministic(
+            &mut Transcript::new(b"Test"),
+            &instances
+        )
+        .is_ok());
+        let wrong_com_vec = vec![c1, c2, pc_gens.commit(value1, bf1)];
+        assert!(chaum_pedersen_batch_verify_multiple_eq_deterministic(
+            &mut Transcript::new(b"Test"),
+            &[(com_vec.to_vec(), &proof), (wrong_com_vec, &proof)]
+        )
+        .is_err());
     }
 
     #[test]
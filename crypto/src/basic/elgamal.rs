@@ -1,8 +1,13 @@
+use digest::Digest;
 use noah_algebra::ristretto::RistrettoPoint;
 use noah_algebra::{
     hash::{Hash, Hasher},
     prelude::*,
 };
+use rand_chacha::ChaChaRng;
+use sha2::Sha512;
+use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// The ElGamal encryption key/public key.
@@ -12,6 +17,36 @@ pub struct ElGamalEncKey<G>(pub G);
 /// The ElGamal decryption key/secret key.
 pub struct ElGamalDecKey<S>(pub(crate) S);
 
+#[derive(PartialEq, Eq, Serialize, Deserialize)]
+/// A share of an ElGamal decryption key split among several parties, which is the evaluation
+/// of a Shamir secret sharing polynomial at `index`.
+/// The share is secret, so its `Debug` output omits it. Its serialized form, which a party
+/// needs to store the share, must be kept as private as the share itself.
+pub struct ElGamalDecKeyShare<S> {
+    /// The index of the share, starting from 1.
+    pub index: u64,
+    /// The share of the decryption key.
+    pub(crate) share: S,
+}
+
+impl<S> fmt::Debug for ElGamalDecKeyShare<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ElGamalDecKeyShare")
+            .field("index", &self.index)
+            .field("share", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A share of the decryption of an ElGamal ciphertext, `share * e1`.
+pub struct ElGamalDecryptShare<G> {
+    /// The index of the key share that produced it.
+    pub index: u64,
+    /// `share * e1`
+    pub point: G,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// An ElGamal ciphertext.
 pub struct ElGamalCiphertext<G> {
@@ -80,6 +115,58 @@ pub fn elgamal_verify<G: Group>(
     }
 }
 
+/// Verify that each ciphertext in `ctexts` encrypts the corresponding message in `msgs`.
+/// All the checks are folded into a single multi-exponentiation using a random linear
+/// combination, whose coefficients are derived from the secret key and the inputs.
+/// An error does not tell which ciphertext is wrong; use `elgamal_verify` for that.
+pub fn elgamal_batch_verify<G: Group>(
+    msgs: &[G::ScalarType],
+    ctexts: &[&ElGamalCiphertext<G>],
+    sec_key: &ElGamalDecKey<G::ScalarType>,
+) -> Result<()> {
+    if msgs.len() != ctexts.len() {
+        return Err(eg!(NoahError::ParameterError));
+    }
+    if ctexts.is_empty() {
+        return Ok(());
+    }
+
+    let mut hasher = Sha512::new();
+    hasher.update(b"ElGamal batch verification");
+    hasher.update(sec_key.0.to_bytes());
+    for (m, ctext) in msgs.iter().zip(ctexts.iter()) {
+        hasher.update(m.to_bytes());
+        hasher.update(ctext.e1.to_compressed_bytes());
+        hasher.update(ctext.e2.to_compressed_bytes());
+    }
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&hasher.finalize()[0..32]);
+    let mut prng = ChaChaRng::from_seed(seed);
+
+    // sum_i rho_i * (e2_i - sk * e1_i - m_i * G) = 0
+    let mut scalars = Vec::with_capacity(2 * ctexts.len() + 1);
+    let mut points = Vec::with_capacity(2 * ctexts.len() + 1);
+    let mut msg_sum = G::ScalarType::zero();
+    for (m, ctext) in msgs.iter().zip(ctexts.iter()) {
+        let rho = G::ScalarType::random(&mut prng);
+        msg_sum += &(rho.clone() * m);
+        scalars.push(-(rho.clone() * &sec_key.0));
+        points.push(&ctext.e1);
+        scalars.push(rho);
+        points.push(&ctext.e2);
+    }
+    scalars.push(-msg_sum);
+    let base = G::get_base();
+    points.push(&base);
+
+    let scalars_ref = scalars.iter().collect_vec();
+    if G::multi_exp(&scalars_ref, &points) == G::get_identity() {
+        Ok(())
+    } else {
+        Err(eg!(NoahError::ElGamalVerificationError))
+    }
+}
+
 /// Perform a partial decryption for the ElGamal ciphertext that returns `m * G`
 pub fn elgamal_partial_decrypt<G: Group>(
     ctext: &ElGamalCiphertext<G>,
@@ -88,6 +175,175 @@ pub fn elgamal_partial_decrypt<G: Group>(
     ctext.e2.sub(&ctext.e1.mul(&sec_key.0))
 }
 
+/// The largest bound of a `BsgsTable`, whose table then has about a million points.
+pub const MAX_BSGS_BOUND: u64 = 1 << 40;
+
+/// A baby-step giant-step table over the base point, which recovers `m` from `m * G` for
+/// `m` below a bound, such as the output of `elgamal_partial_decrypt` for a small message.
+/// It stores about `sqrt(bound)` points, and is meant to be built once and reused across
+/// many ciphertexts. Both building the table and a recovery take `O(sqrt(bound))` group
+/// operations.
+pub struct BsgsTable<G: Group> {
+    /// The bound on the recovered values.
+    bound: u64,
+    /// The number of baby steps, at least `sqrt(bound)`.
+    step: u64,
+    /// The compressed bytes of `j * G` for `j` in `[0, step)`, mapped to `j`.
+    baby_steps: HashMap<Vec<u8>, u64>,
+    /// `-step * G`
+    giant_step: G,
+}
+
+impl<G: Group> BsgsTable<G> {
+    /// Precompute the table for the values in `[0, bound)`.
+    /// Returns Err(NoahError::ParameterError) if `bound` exceeds `MAX_BSGS_BOUND`.
+    pub fn new(bound: u64) -> Result<Self> {
+        if bound > MAX_BSGS_BOUND {
+            return Err(eg!(NoahError::ParameterError));
+        }
+
+        let mut step = ((bound as f64).sqrt() as u64).max(1);
+        while (step as u128) * (step as u128) < bound as u128 {
+            step += 1;
+        }
+
+        let base = G::get_base();
+        let mut baby_steps = HashMap::with_capacity(step as usize);
+        let mut point = G::get_identity();
+        for j in 0..step {
+            baby_steps.insert(point.to_compressed_bytes(), j);
+            point = point.add(&base);
+        }
+        let giant_step = G::get_identity().sub(&point);
+
+        Ok(Self {
+            bound,
+            step,
+            baby_steps,
+            giant_step,
+        })
+    }
+
+    /// Return the bound on the recovered values.
+    pub fn bound(&self) -> u64 {
+        self.bound
+    }
+
+    /// Return `m` such that `point = m * G` if `m < bound`, and none otherwise.
+    pub fn recover(&self, point: &G) -> Option<u64> {
+        if self.bound == 0 {
+            return None;
+        }
+
+        // m = i * step + j, with i < ceil(bound / step)
+        let num_giant_steps = (self.bound - 1) / self.step + 1;
+        let mut point = *point;
+        for i in 0..num_giant_steps {
+            if let Some(j) = self.baby_steps.get(&point.to_compressed_bytes()) {
+                let m = i * self.step + j;
+                return if m < self.bound { Some(m) } else { None };
+            }
+            point = point.add(&self.giant_step);
+        }
+        None
+    }
+}
+
+/// Recover `m` from `point = m * G` if `m < bound`, which is typically the output of
+/// `elgamal_partial_decrypt`, in `O(sqrt(bound))` group operations. To recover many values,
+/// build a `BsgsTable` once instead.
+/// Returns Err(NoahError::ParameterError) if `bound` exceeds `MAX_BSGS_BOUND`.
+pub fn elgamal_recover_small<G: Group>(point: &G, bound: u64) -> Result<Option<u64>> {
+    Ok(BsgsTable::<G>::new(bound).c(d!())?.recover(point))
+}
+
+/// Return `n` shares of a fresh ElGamal decryption key, any `t` of which can decrypt, and the
+/// encryption key. The decryption key is shared with Shamir secret sharing by a trusted dealer:
+/// the caller samples the key and the sharing polynomial, so the whole key is in its memory
+/// while this runs. The dealer must send each share to its party over a private channel, and
+/// then erase the shares and any memory that held the polynomial.
+pub fn threshold_keygen<R: CryptoRng + RngCore, G: Group>(
+    prng: &mut R,
+    t: usize,
+    n: usize,
+) -> Result<(Vec<ElGamalDecKeyShare<G::ScalarType>>, ElGamalEncKey<G>)> {
+    if t == 0 || t > n {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    // f(X) = sk + a_1 * X + ... + a_{t-1} * X^{t-1}
+    let coefficients = (0..t).map(|_| G::ScalarType::random(prng)).collect_vec();
+    let shares = (1..=n as u64)
+        .map(|index| {
+            let x = G::ScalarType::from(index);
+            let share = coefficients
+                .iter()
+                .rev()
+                .fold(G::ScalarType::zero(), |acc, a| acc * &x + a);
+            ElGamalDecKeyShare { index, share }
+        })
+        .collect_vec();
+
+    let public_key = ElGamalEncKey(G::get_base().mul(&coefficients[0]));
+    Ok((shares, public_key))
+}
+
+/// Perform the decryption of the ElGamal ciphertext with a key share, which is combined
+/// with the others by `combine_shares`.
+pub fn partial_decrypt_share<G: Group>(
+    ctext: &ElGamalCiphertext<G>,
+    share: &ElGamalDecKeyShare<G::ScalarType>,
+) -> ElGamalDecryptShare<G> {
+    ElGamalDecryptShare {
+        index: share.index,
+        point: ctext.e1.mul(&share.share),
+    }
+}
+
+/// Combine the decryption shares of the ElGamal ciphertext `ctext` into `m * G`, as returned
+/// by `elgamal_partial_decrypt` with the shared decryption key. The shares must all be
+/// computed by `partial_decrypt_share` on `ctext`.
+/// The term `sk * e1` is the Lagrange interpolation of the shares, which is only correct
+/// with at least `t` shares from distinct parties. With fewer shares, the result is a wrong
+/// point rather than an error.
+/// Returns Err(NoahError::ParameterError) if there are no shares, or if two shares have the
+/// same index or an index is zero.
+pub fn combine_shares<G: Group>(
+    ctext: &ElGamalCiphertext<G>,
+    shares: &[ElGamalDecryptShare<G>],
+) -> Result<G> {
+    let indices = shares.iter().map(|share| share.index).collect_vec();
+    let lambdas = lagrange_coefficients_at_zero::<G::ScalarType>(&indices).c(d!())?;
+
+    let lambdas_ref = lambdas.iter().collect_vec();
+    let points = shares.iter().map(|share| &share.point).collect_vec();
+    Ok(ctext.e2.sub(&G::multi_exp(&lambdas_ref, &points)))
+}
+
+/// Compute the Lagrange coefficients for interpolating at zero from the given indices,
+/// which must be distinct and nonzero.
+fn lagrange_coefficients_at_zero<S: Scalar>(indices: &[u64]) -> Result<Vec<S>> {
+    if indices.is_empty() || indices.contains(&0) || !indices.iter().all_unique() {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    let xs = indices.iter().map(|index| S::from(*index)).collect_vec();
+    let mut lambdas = Vec::with_capacity(xs.len());
+    for (i, x_i) in xs.iter().enumerate() {
+        // lambda_i = prod_{j != i} x_j / (x_j - x_i)
+        let mut num = S::one();
+        let mut den = S::one();
+        for (j, x_j) in xs.iter().enumerate() {
+            if i != j {
+                num *= x_j;
+                den *= &(*x_j - x_i);
+            }
+        }
+        lambdas.push(num * &den.inv().c(d!())?);
+    }
+    Ok(lambdas)
+}
+
 #[cfg(test)]
 mod elgamal_test {
     use noah_algebra::bls12_381::BLSGt;
@@ -128,6 +384,160 @@ mod elgamal_test {
         pnk!(super::elgamal_verify(&m, &ctext, &secret_key));
     }
 
+    fn batch_verification<G: Group>() {
+        let mut prng = test_rng();
+        let (secret_key, public_key) = super::elgamal_key_gen::<_, G>(&mut prng);
+
+        let msgs = (0..5u32).map(G::ScalarType::from).collect_vec();
+        let ctexts = msgs
+            .iter()
+            .map(|m| super::elgamal_encrypt(m, &G::ScalarType::random(&mut prng), &public_key))
+            .collect_vec();
+        let ctexts_ref = ctexts.iter().collect_vec();
+        pnk!(super::elgamal_batch_verify(&msgs, &ctexts_ref, &secret_key));
+        pnk!(super::elgamal_batch_verify::<G>(&[], &[], &secret_key));
+
+        let mut wrong_msgs = msgs.clone();
+        wrong_msgs[3] = G::ScalarType::from(99u32);
+        let err = super::elgamal_batch_verify(&wrong_msgs, &ctexts_ref, &secret_key)
+            .err()
+            .unwrap();
+        msg_eq!(NoahError::ElGamalVerificationError, err);
+        assert!(super::elgamal_batch_verify(&msgs[1..], &ctexts_ref, &secret_key).is_err());
+    }
+
+    fn threshold_decryption<G: Group>() {
+        let mut prng = test_rng();
+        let (t, n) = (3, 5);
+        let (key_shares, public_key) = pnk!(super::threshold_keygen::<_, G>(&mut prng, t, n));
+        assert_eq!(key_shares.len(), n);
+        assert_eq!(
+            format!("{:?}", key_shares[0]),
+            "ElGamalDecKeyShare { index: 1, share: \"<redacted>\" }"
+        );
+
+        let m = G::ScalarType::from(100u32);
+        let r = G::ScalarType::random(&mut prng);
+        let ctext = super::elgamal_encrypt(&m, &r, &public_key);
+        let dec_shares = key_shares
+            .iter()
+            .map(|share| super::partial_decrypt_share(&ctext, share))
+            .collect_vec();
+
+        // the decryption key interpolated from t key shares
+        let lambdas = pnk!(super::lagrange_coefficients_at_zero::<G::ScalarType>(&[
+            1, 2, 3
+        ]));
+        let secret_key = super::ElGamalDecKey(
+            lambdas
+                .iter()
+                .zip(key_shares.iter())
+                .fold(G::ScalarType::zero(), |acc, (lambda, share)| {
+                    acc + &(*lambda * &share.share)
+                }),
+        );
+        pnk!(super::elgamal_verify(&m, &ctext, &secret_key));
+        let expected = super::elgamal_partial_decrypt(&ctext, &secret_key);
+        assert_eq!(expected, G::get_base().mul(&m));
+
+        // any t shares decrypt
+        for subset in [[0, 1, 2], [0, 2, 4], [4, 3, 1]] {
+            let shares = subset.iter().map(|i| dec_shares[*i].clone()).collect_vec();
+            assert_eq!(pnk!(super::combine_shares(&ctext, &shares)), expected);
+        }
+        assert_eq!(pnk!(super::combine_shares(&ctext, &dec_shares)), expected);
+
+        // t - 1 shares do not
+        for subset in [[0, 1], [2, 4]] {
+            let shares = subset.iter().map(|i| dec_shares[*i].clone()).collect_vec();
+            assert_ne!(pnk!(super::combine_shares(&ctext, &shares)), expected);
+        }
+
+        // the same share twice is not a second share
+        let shares = vec![
+            dec_shares[0].clone(),
+            dec_shares[0].clone(),
+            dec_shares[1].clone(),
+        ];
+        assert!(super::combine_shares(&ctext, &shares).is_err());
+        assert!(super::combine_shares(&ctext, &[]).is_err());
+
+        // a party can store its key share
+        let bytes = bincode::serialize(&key_shares[1]).unwrap();
+        let stored: super::ElGamalDecKeyShare<G::ScalarType> =
+            bincode::deserialize(&bytes).unwrap();
+        assert_eq!(stored, key_shares[1]);
+        assert_eq!(super::partial_decrypt_share(&ctext, &stored), dec_shares[1]);
+
+        assert!(super::threshold_keygen::<_, G>(&mut prng, 0, n).is_err());
+        assert!(super::threshold_keygen::<_, G>(&mut prng, n + 1, n).is_err());
+    }
+
+    fn small_recovery<G: Group>() {
+        let mut prng = test_rng();
+        let (secret_key, public_key) = super::elgamal_key_gen::<_, G>(&mut prng);
+        let bound = 1u64 << 20;
+        let table = pnk!(super::BsgsTable::<G>::new(bound));
+        assert_eq!(table.bound(), bound);
+
+        let mut values = vec![0, 1, 2, 1023, 1024, 1025, bound - 1];
+        values.extend((0..20).map(|_| prng.next_u64() % bound));
+        for m in values {
+            let r = G::ScalarType::random(&mut prng);
+            let ctext = super::elgamal_encrypt(&G::ScalarType::from(m), &r, &public_key);
+            let point = super::elgamal_partial_decrypt(&ctext, &secret_key);
+            assert_eq!(table.recover(&point), Some(m));
+        }
+
+        for m in [bound, bound + 1, 2 * bound, prng.next_u64()] {
+            let point = G::get_base().mul(&G::ScalarType::from(m));
+            assert_eq!(table.recover(&point), None);
+        }
+        let point = G::get_base().mul(&G::ScalarType::random(&mut prng));
+        assert_eq!(table.recover(&point), None);
+
+        assert_eq!(
+            pnk!(super::elgamal_recover_small(
+                &G::get_base().mul(&G::ScalarType::from(99u32)),
+                100
+            )),
+            Some(99)
+        );
+        assert_eq!(
+            pnk!(super::elgamal_recover_small(
+                &G::get_base().mul(&G::ScalarType::from(100u32)),
+                100
+            )),
+            None
+        );
+        assert_eq!(
+            pnk!(super::elgamal_recover_small(&G::get_identity(), 0)),
+            None
+        );
+
+        // the table size is capped
+        assert!(super::BsgsTable::<G>::new(super::MAX_BSGS_BOUND + 1).is_err());
+        assert!(super::elgamal_recover_small(&G::get_identity(), u64::MAX).is_err());
+    }
+
+    #[test]
+    fn recover_small() {
+        small_recovery::<RistrettoPoint>();
+        small_recovery::<BLSG1>();
+    }
+
+    #[test]
+    fn threshold_decrypt() {
+        threshold_decryption::<RistrettoPoint>();
+        threshold_decryption::<BLSG1>();
+    }
+
+    #[test]
+    fn batch_verify() {
+        batch_verification::<RistrettoPoint>();
+        batch_verification::<BLSG1>();
+    }
+
     #[test]
     fn verify() {
         verification::<RistrettoPoint>();
@@ -1,27 +1,19 @@
 use digest::Digest;
 use merlin::Transcript;
 use noah_algebra::prelude::*;
+use rand_chacha::ChaChaRng;
 
 /// The transcript methods for the Sigma protocol.
+///
+/// Only `append_message` and `challenge_bytes` are required, and the other methods are
+/// derived from them, so that every implementation appends the same messages.
 pub trait SigmaTranscript {
-    /// Initialize the transcript for the Sigma protocol.
-    fn init_sigma<G: Group>(
-        &mut self,
-        instance_name: &'static [u8],
-        public_scalars: &[&G::ScalarType],
-        public_elems: &[G],
-    );
-    /// Append group elements to the transcript.
-    fn append_group_element<G: Group>(&mut self, label: &'static [u8], elem: &G);
-    /// Append field elements to the transcript.
-    fn append_field_element<S: Scalar>(&mut self, label: &'static [u8], scalar: &S);
-    /// Append the proof commitment to the transcript.
-    fn append_proof_commitment<G: Group>(&mut self, elem: &G);
-    /// Compute a challenge from the transcript.
-    fn get_challenge<S: Scalar>(&mut self) -> S;
-}
+    /// Append a message to the transcript.
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]);
+    /// Fill `dest` with challenge bytes from the transcript.
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]);
 
-impl SigmaTranscript for Transcript {
+    /// Initialize the transcript for the Sigma protocol.
     fn init_sigma<G: Group>(
         &mut self,
         instance_name: &'static [u8],
@@ -40,15 +32,30 @@ impl SigmaTranscript for Transcript {
             self.append_message(b"public elem", elem.to_compressed_bytes().as_slice())
         }
     }
+    /// Append group elements to the transcript.
     fn append_group_element<G: Group>(&mut self, label: &'static [u8], elem: &G) {
         self.append_message(label, elem.to_compressed_bytes().as_slice());
     }
+    /// Append a vector of group elements to the transcript, each with its index,
+    /// so that vectors of different lengths or orders give different transcripts.
+    /// `init_sigma` keeps appending the public elements without indices, so that existing
+    /// proofs still verify; new protocols opt into this method for their vector statements.
+    fn append_group_elements_indexed<G: Group>(&mut self, label: &'static [u8], elems: &[G]) {
+        for (i, elem) in elems.iter().enumerate() {
+            let mut bytes = (i as u64).to_le_bytes().to_vec();
+            bytes.extend_from_slice(elem.to_compressed_bytes().as_slice());
+            self.append_message(label, bytes.as_slice());
+        }
+    }
+    /// Append field elements to the transcript.
     fn append_field_element<S: Scalar>(&mut self, label: &'static [u8], scalar: &S) {
         self.append_message(label, scalar.to_bytes().as_slice());
     }
+    /// Append the proof commitment to the transcript.
     fn append_proof_commitment<G: Group>(&mut self, elem: &G) {
         self.append_group_element(b"proof_commitment", elem);
     }
+    /// Compute a challenge from the transcript.
     fn get_challenge<S: Scalar>(&mut self) -> S {
         let mut buffer = vec![0u8; 32];
         self.challenge_bytes(b"Sigma challenge", &mut buffer);
@@ -58,7 +65,16 @@ impl SigmaTranscript for Transcript {
     }
 }
 
-fn init_sigma_protocol<G: Group>(transcript: &mut Transcript, elems: &[G]) {
+impl SigmaTranscript for Transcript {
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        Transcript::append_message(self, label, message);
+    }
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        Transcript::challenge_bytes(self, label, dest);
+    }
+}
+
+fn init_sigma_protocol<T: SigmaTranscript, G: Group>(transcript: &mut T, elems: &[G]) {
     transcript.init_sigma(b"New Sigma Protocol", &[], elems);
 }
 
@@ -70,8 +86,8 @@ fn sample_blindings<R: CryptoRng + RngCore, S: Scalar>(prng: &mut R, n: usize) -
     r
 }
 
-fn compute_proof_commitments<G: Group>(
-    transcript: &mut Transcript,
+fn compute_proof_commitments<T: SigmaTranscript, G: Group>(
+    transcript: &mut T,
     blindings: &[G::ScalarType],
     elems: &[G],
     lhs_matrix: &[Vec<usize>],
@@ -97,19 +113,86 @@ pub struct SigmaProof<S, G> {
     pub(crate) responses: Vec<S>,
 }
 
+impl<G: Group> SigmaProof<G::ScalarType, G> {
+    /// Serialize several proofs into one byte string: the number of proofs, then for each
+    /// proof the compressed commitments followed by the responses, all with fixed-length
+    /// encodings. The numbers of commitments and responses of a proof are not encoded, since
+    /// the verifier knows them from the statement.
+    pub fn pack_many(proofs: &[SigmaProof<G::ScalarType, G>]) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&(proofs.len() as u32).to_le_bytes());
+        for proof in proofs {
+            for commitment in proof.commitments.iter() {
+                bytes.extend_from_slice(&commitment.to_compressed_bytes());
+            }
+            for response in proof.responses.iter() {
+                bytes.extend_from_slice(&response.to_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Deserialize the proofs serialized by `pack_many`, given the shape of each proof: the
+    /// number of rows of its `lhs_matrix` and the number of its secret scalars.
+    pub fn unpack_many(
+        bytes: &[u8],
+        shapes: &[(usize, usize)],
+    ) -> Result<Vec<SigmaProof<G::ScalarType, G>>> {
+        let mut reader = bytes;
+        if read_u32(&mut reader)? != shapes.len() {
+            return Err(eg!(NoahError::DeserializationError));
+        }
+        let mut proofs = vec![];
+        for &(n_commitments, n_responses) in shapes {
+            let mut commitments = vec![];
+            for _ in 0..n_commitments {
+                let bytes = read_bytes(&mut reader, G::COMPRESSED_LEN)?;
+                commitments.push(G::from_compressed_bytes(bytes).c(d!())?);
+            }
+            let mut responses = vec![];
+            for _ in 0..n_responses {
+                let bytes = read_bytes(&mut reader, G::ScalarType::bytes_len())?;
+                responses.push(G::ScalarType::from_bytes(bytes).c(d!())?);
+            }
+            proofs.push(SigmaProof {
+                commitments,
+                responses,
+            });
+        }
+        if !reader.is_empty() {
+            return Err(eg!(NoahError::DeserializationError));
+        }
+        Ok(proofs)
+    }
+}
+
+fn read_bytes<'a>(reader: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if reader.len() < len {
+        return Err(eg!(NoahError::DeserializationError));
+    }
+    let (head, tail) = reader.split_at(len);
+    *reader = tail;
+    Ok(head)
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<usize> {
+    let bytes = read_bytes(reader, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
 /// Simple Sigma protocol PoK for the statement `lhs_matrix` * `secrets_scalars` = `rhs_vec`
 /// Elements in `lhs_matrix` and `rhs_vec` must be in `elems` slice
-pub fn sigma_prove<R: CryptoRng + RngCore, G: Group>(
-    transcript: &mut Transcript,
+pub fn sigma_prove<T: SigmaTranscript, R: CryptoRng + RngCore, G: Group>(
+    transcript: &mut T,
     prng: &mut R,
     elems: &[G],               // public elements of the proofs
     lhs_matrix: &[Vec<usize>], // each row defines a lhs of a constraint
     secret_scalars: &[&G::ScalarType],
 ) -> SigmaProof<G::ScalarType, G> {
-    init_sigma_protocol::<G>(transcript, elems);
+    init_sigma_protocol(transcript, elems);
     let blindings = sample_blindings::<_, G::ScalarType>(prng, secret_scalars.len());
     let proof_commitments =
-        compute_proof_commitments::<G>(transcript, blindings.as_slice(), elems, lhs_matrix);
+        compute_proof_commitments(transcript, blindings.as_slice(), elems, lhs_matrix);
 
     let challenge = transcript.get_challenge::<G::ScalarType>();
 
@@ -165,8 +248,8 @@ fn collect_multi_exp_scalars<R: CryptoRng + RngCore, S: Scalar>(
 /// Returns a scalar vector for a sigma protocol proof verification. The scalars can then be used
 /// in a single multi-exponentiation to verify the proof. The associated elements are elems
 /// concatenated wit proof.commitments.
-pub fn sigma_verify_scalars<R: CryptoRng + RngCore, G: Group>(
-    transcript: &mut Transcript,
+pub fn sigma_verify_scalars<T: SigmaTranscript, R: CryptoRng + RngCore, G: Group>(
+    transcript: &mut T,
     prng: &mut R, //use of for linear combination multiexp
     elems: &[G],
     lhs_matrix: &[Vec<usize>],
@@ -176,7 +259,7 @@ pub fn sigma_verify_scalars<R: CryptoRng + RngCore, G: Group>(
     assert_eq!(lhs_matrix.len(), rhs_vec.len());
     assert_eq!(rhs_vec.len(), proof.commitments.len());
 
-    init_sigma_protocol::<G>(transcript, elems);
+    init_sigma_protocol(transcript, elems);
     for c in proof.commitments.iter() {
         transcript.append_proof_commitment(c);
     }
@@ -193,8 +276,8 @@ pub fn sigma_verify_scalars<R: CryptoRng + RngCore, G: Group>(
 
 /// Simple Sigma protocol PoK verification for the statement `lhs_matrix` * `secrets_scalars` = `rhs_vec`
 /// Elements in `lhs_matrix` and `rhs_vec` must be in `elems` slice
-pub fn sigma_verify<R: CryptoRng + RngCore, G: Group>(
-    transcript: &mut Transcript,
+pub fn sigma_verify<T: SigmaTranscript, R: CryptoRng + RngCore, G: Group>(
+    transcript: &mut T,
     prng: &mut R, //use of for linear combination multiexp
     elems: &[G],
     lhs_matrix: &[Vec<usize>],
@@ -220,6 +303,55 @@ pub fn sigma_verify<R: CryptoRng + RngCore, G: Group>(
     }
 }
 
+/// Derive the randomness of a verifier from the transcript instead of an external PRNG,
+/// so that every verifier of the same proofs draws the same scalars, e.g. in consensus.
+/// The transcript must already contain the statements and the proofs being verified: the
+/// derived scalars are then Fiat-Shamir challenges, which the prover only learns after fixing
+/// the proofs, and random linear combinations remain sound in the random oracle model.
+pub fn transcript_verifier_rng<T: SigmaTranscript>(transcript: &mut T) -> ChaChaRng {
+    let mut seed = [0u8; 32];
+    transcript.challenge_bytes(b"verifier randomness", &mut seed);
+    ChaChaRng::from_seed(seed)
+}
+
+/// Same as `sigma_verify`, but the random linear combination is derived from the transcript
+/// after appending the proof, see `transcript_verifier_rng`.
+pub fn sigma_verify_deterministic<T: SigmaTranscript + Clone, G: Group>(
+    transcript: &mut T,
+    elems: &[G],
+    lhs_matrix: &[Vec<usize>],
+    rhs_vec: &[usize],
+    proof: &SigmaProof<G::ScalarType, G>,
+) -> Result<()> {
+    let mut rng_transcript = transcript.clone();
+    init_sigma_protocol(&mut rng_transcript, elems);
+    for c in proof.commitments.iter() {
+        rng_transcript.append_proof_commitment(c);
+    }
+    for response in proof.responses.iter() {
+        rng_transcript.append_field_element(b"proof response", response);
+    }
+    let mut prng = transcript_verifier_rng(&mut rng_transcript);
+    sigma_verify(transcript, &mut prng, elems, lhs_matrix, rhs_vec, proof).c(d!())
+}
+
+/// The domain separator of `hash_points_to_scalar`.
+const HASH_POINTS_TO_SCALAR_DOMAIN: &[u8] = b"Noah hash points to scalar v.0.1";
+
+/// Hash a list of group elements to a scalar, for Fiat-Shamir challenges derived without a
+/// transcript. The domain-separated Sha512 digest of the number of elements and their
+/// compressed encodings, in order, is reduced to a scalar, which makes the output a random
+/// oracle on the list.
+pub fn hash_points_to_scalar<G: Group>(points: &[G]) -> G::ScalarType {
+    let mut hash = sha2::Sha512::new();
+    hash.update(HASH_POINTS_TO_SCALAR_DOMAIN);
+    hash.update((points.len() as u64).to_le_bytes());
+    for point in points.iter() {
+        hash.update(point.to_compressed_bytes());
+    }
+    G::ScalarType::from_hash(hash)
+}
+
 #[cfg(test)]
 mod tests {
     use merlin::Transcript;
@@ -250,6 +382,7 @@ mod tests {
             lhs_matrix.as_slice(),
             &[&secret],
         );
+        let mut deterministic_transcript = verifier_transcript.clone();
         assert!(super::sigma_verify(
             &mut verifier_transcript,
             &mut prng,
@@ -259,6 +392,22 @@ mod tests {
             &dlog_proof
         )
         .is_ok());
+        assert!(super::sigma_verify_deterministic(
+            &mut deterministic_transcript.clone(),
+            &elems,
+            lhs_matrix.as_slice(),
+            rhs_vec.as_slice(),
+            &dlog_proof
+        )
+        .is_ok());
+        assert!(super::sigma_verify_deterministic(
+            &mut deterministic_transcript,
+            &[G, H.double()],
+            lhs_matrix.as_slice(),
+            rhs_vec.as_slice(),
+            &dlog_proof
+        )
+        .is_err());
 
         let bad_matrix = vec![vec![1]];
         let dlog_proof = super::sigma_prove(
@@ -373,4 +522,175 @@ mod tests {
         )
         .is_err());
     }
+
+    #[test]
+    fn test_append_group_elements_indexed() {
+        use super::SigmaTranscript;
+
+        let mut prng = test_rng();
+        let a = RistrettoPoint::random(&mut prng);
+        let b = RistrettoPoint::random(&mut prng);
+
+        let challenge = |elems: &[RistrettoPoint]| -> Scalar {
+            let mut transcript = Transcript::new(b"Test");
+            transcript.append_group_elements_indexed(b"elems", elems);
+            transcript.get_challenge()
+        };
+        assert_eq!(challenge(&[a, b]), challenge(&[a, b]));
+        assert_ne!(challenge(&[a, b]), challenge(&[b, a]));
+        assert_ne!(challenge(&[a, b]), challenge(&[a]));
+
+        // the same elements appended without indices give another transcript
+        let mut transcript = Transcript::new(b"Test");
+        transcript.append_group_element(b"elems", &a);
+        transcript.append_group_element(b"elems", &b);
+        let unindexed: Scalar = transcript.get_challenge();
+        assert_ne!(challenge(&[a, b]), unindexed);
+    }
+
+    #[test]
+    fn test_init_sigma_unindexed() {
+        use super::SigmaTranscript;
+
+        let mut prng = test_rng();
+        let elems = [
+            RistrettoPoint::random(&mut prng),
+            RistrettoPoint::random(&mut prng),
+        ];
+
+        // the public elements are absorbed as before the indexed appends were added
+        let mut transcript = Transcript::new(b"Test");
+        transcript.init_sigma::<RistrettoPoint>(b"instance", &[], &elems);
+        let challenge: Scalar = transcript.get_challenge();
+
+        let mut legacy = Transcript::new(b"Test");
+        legacy.append_message(
+            b"Sigma Protocol domain",
+            b"Sigma protocol single group v.0.1",
+        );
+        legacy.append_message(b"Sigma Protocol instance", b"instance");
+        for elem in elems.iter() {
+            legacy.append_message(b"public elem", elem.to_compressed_bytes().as_slice());
+        }
+        let legacy_challenge: Scalar = legacy.get_challenge();
+        assert_eq!(challenge, legacy_challenge);
+    }
+
+    #[test]
+    fn test_hash_points_to_scalar() {
+        use super::hash_points_to_scalar;
+
+        let mut prng = test_rng();
+        let a = RistrettoPoint::random(&mut prng);
+        let b = RistrettoPoint::random(&mut prng);
+
+        assert_eq!(
+            hash_points_to_scalar(&[a, b]),
+            hash_points_to_scalar(&[a, b])
+        );
+        assert_ne!(
+            hash_points_to_scalar(&[a, b]),
+            hash_points_to_scalar(&[b, a])
+        );
+        assert_ne!(hash_points_to_scalar(&[a, b]), hash_points_to_scalar(&[a]));
+        assert_ne!(
+            hash_points_to_scalar::<RistrettoPoint>(&[]),
+            hash_points_to_scalar(&[RistrettoPoint::get_identity()])
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_pack_many() {
+        let G = RistrettoPoint::get_base();
+        let secret = Scalar::from(10u32);
+        let secret2 = Scalar::from(20u32);
+        let H = G.mul(&secret);
+        let H2 = G.mul(&secret2);
+        let zero = RistrettoPoint::get_identity();
+        let mut prng = test_rng();
+
+        let elems1 = [G, H];
+        let matrix1: &[Vec<usize>] = &[vec![0]];
+        let rhs1: &[usize] = &[1];
+        let elems2 = [zero, G, H, H2];
+        let matrix2: &[Vec<usize>] = &[vec![1, 0], vec![0, 1]];
+        let rhs2: &[usize] = &[2, 3];
+
+        let proof1 = super::sigma_prove(
+            &mut Transcript::new(b"Test"),
+            &mut prng,
+            &elems1,
+            matrix1,
+            &[&secret],
+        );
+        let proof2 = super::sigma_prove(
+            &mut Transcript::new(b"Test"),
+            &mut prng,
+            &elems2,
+            matrix2,
+            &[&secret, &secret2],
+        );
+        let proofs = vec![proof1, proof2];
+
+        let shapes = [(1, 1), (2, 2)];
+        let packed = super::SigmaProof::pack_many(&proofs);
+        assert_eq!(packed.len(), 4 + 6 * 32);
+        let separate_len: usize = proofs
+            .iter()
+            .map(|proof| bincode::serialize(proof).unwrap().len())
+            .sum();
+        assert!(packed.len() < separate_len);
+
+        let unpacked =
+            super::SigmaProof::<Scalar, RistrettoPoint>::unpack_many(&packed, &shapes).unwrap();
+        assert_eq!(unpacked, proofs);
+        assert!(super::sigma_verify(
+            &mut Transcript::new(b"Test"),
+            &mut prng,
+            &elems1,
+            matrix1,
+            rhs1,
+            &unpacked[0]
+        )
+        .is_ok());
+        assert!(super::sigma_verify(
+            &mut Transcript::new(b"Test"),
+            &mut prng,
+            &elems2,
+            matrix2,
+            rhs2,
+            &unpacked[1]
+        )
+        .is_ok());
+
+        let empty = super::SigmaProof::<Scalar, RistrettoPoint>::pack_many(&[]);
+        assert!(
+            super::SigmaProof::<Scalar, RistrettoPoint>::unpack_many(&empty, &[])
+                .unwrap()
+                .is_empty()
+        );
+
+        // truncated or trailing bytes
+        assert!(super::SigmaProof::<Scalar, RistrettoPoint>::unpack_many(
+            &packed[..packed.len() - 1],
+            &shapes
+        )
+        .is_err());
+        let mut extended = packed.clone();
+        extended.push(0u8);
+        assert!(
+            super::SigmaProof::<Scalar, RistrettoPoint>::unpack_many(&extended, &shapes).is_err()
+        );
+
+        // shapes that do not match the proofs
+        assert!(
+            super::SigmaProof::<Scalar, RistrettoPoint>::unpack_many(&packed, &[(1, 1)]).is_err()
+        );
+        assert!(super::SigmaProof::<Scalar, RistrettoPoint>::unpack_many(
+            &packed,
+            &[(1, 1), (2, 1)]
+        )
+        .is_err());
+    }
 }
@@ -12,3 +12,6 @@ pub mod matrix_sigma;
 pub mod pedersen_elgamal;
 /// The module for the Schnorr signature.
 pub mod schnorr_signature;
+/// The module for recording transcripts, to debug Fiat-Shamir mismatches.
+#[cfg(feature = "transcript-recorder")]
+pub mod transcript_recorder;
@@ -1,11 +1,16 @@
 use crate::basic::elgamal::{ElGamalCiphertext, ElGamalEncKey};
-use crate::basic::matrix_sigma::{sigma_prove, sigma_verify_scalars, SigmaProof, SigmaTranscript};
+use crate::basic::matrix_sigma::{
+    sigma_prove, sigma_verify_scalars, transcript_verifier_rng, SigmaProof, SigmaTranscript,
+};
 use curve25519_dalek::traits::{Identity, MultiscalarMul};
 use merlin::Transcript;
 use noah_algebra::prelude::*;
 use noah_algebra::ristretto::PedersenCommitmentRistretto;
 use noah_algebra::ristretto::RistrettoPoint;
 use noah_algebra::ristretto::RistrettoScalar;
+use rand_chacha::ChaChaRng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// The Pedersen ElGamal equality proof.
@@ -248,6 +253,8 @@ pub struct PedersenElGamalProofInstance<'a> {
 /// Verify a batch of PedersenElGamal aggregate proof instances with a single multiexponentiation
 /// of size `2 + n*7` elems. Each instance verification equation is scaled by a random factor.
 /// Then, scaled equations are aggregated into a single equation of size 2 + n*7 elements.
+/// With the `parallel` feature, the terms of the instances are computed concurrently, from
+/// randomness drawn beforehand, so that the multiexponentiation is the same as without it.
 pub fn pedersen_elgamal_batch_verify<'a, R: CryptoRng + RngCore>(
     transcript: &mut Transcript,
     prng: &mut R,
@@ -255,6 +262,20 @@ pub fn pedersen_elgamal_batch_verify<'a, R: CryptoRng + RngCore>(
 ) -> Result<()> {
     let m = instances.len();
     let pc_gens = PedersenCommitmentRistretto::default();
+
+    // The scaling factor of each instance, and a seed for the randomness of its sigma protocol.
+    let randomness: Vec<(RistrettoScalar, [u8; 32])> = instances
+        .iter()
+        .map(|_| {
+            let alpha = RistrettoScalar::random(prng);
+            let mut seed = [0u8; 32];
+            prng.fill_bytes(&mut seed);
+            (alpha, seed)
+        })
+        .collect();
+
+    let terms = pedersen_elgamal_batch_terms(transcript, &randomness, instances);
+
     // 2 common elems: B, B_blinding
     // 7 elems per instance: public key,
     //                       ctext.e1, ctext.e2, commitment,
@@ -265,54 +286,11 @@ pub fn pedersen_elgamal_batch_verify<'a, R: CryptoRng + RngCore>(
     all_scalars.push(RistrettoScalar::zero());
     all_elems.push(pc_gens.B);
     all_elems.push(pc_gens.B_blinding);
-    for instance in instances {
-        let n = instance.cts.len();
-        assert_eq!(n, instance.commitments.len());
-        let mut inst_transcript = transcript.clone();
-        let alpha = RistrettoScalar::random(prng);
-        init_pedersen_elgamal_transcript(
-            &mut inst_transcript,
-            instance.public_key,
-            &instance.cts,
-            &instance.commitments,
-        );
-        // 1. compute x vector
-        let x = get_linear_combination_scalars(&mut inst_transcript, n);
-        // 2. compute linear combination
-        let mut lc_e1 = RistrettoPoint::get_identity();
-        let mut lc_e2 = RistrettoPoint::get_identity();
-        let mut lc_c = RistrettoPoint::get_identity();
-        for (xi, ei, ci) in izip!(x.iter(), instance.cts.iter(), instance.commitments.iter()) {
-            lc_e1 = lc_e1.add(&ei.e1.mul(xi));
-            lc_e2 = lc_e2.add(&ei.e2.mul(xi));
-            lc_c = lc_c.add(&ci.mul(xi));
-        }
-        let lc_e = ElGamalCiphertext {
-            e1: lc_e1,
-            e2: lc_e2,
-        };
-
-        let instance_scalars = pedersem_elgamal_eq_verify_scalars(
-            &mut inst_transcript,
-            prng,
-            instance.public_key,
-            &lc_e,
-            &lc_c,
-            instance.proof,
-        );
-
-        all_scalars[0] = all_scalars[0].add(&alpha.mul(&instance_scalars[0]));
-        all_scalars[1] = all_scalars[1].add(&alpha.mul(&instance_scalars[1]));
-        all_elems.push(instance.public_key.0);
-        all_elems.push(lc_e1);
-        all_elems.push(lc_e2);
-        all_elems.push(lc_c);
-        all_elems.push(instance.proof.e1.e1);
-        all_elems.push(instance.proof.e1.e2);
-        all_elems.push(instance.proof.c1);
-        for scalar in instance_scalars[2..].iter() {
-            all_scalars.push(alpha.mul(scalar));
-        }
+    for (scalars, elems) in terms {
+        all_scalars[0] = all_scalars[0].add(&scalars[0]);
+        all_scalars[1] = all_scalars[1].add(&scalars[1]);
+        all_scalars.extend_from_slice(&scalars[2..]);
+        all_elems.extend_from_slice(&elems);
     }
 
     let multi_exp = curve25519_dalek::ristretto::RistrettoPoint::multiscalar_mul(
@@ -325,6 +303,105 @@ pub fn pedersen_elgamal_batch_verify<'a, R: CryptoRng + RngCore>(
 
     Ok(())
 }
+
+/// Compute the terms of the instances in the batch verification equation, concurrently with the
+/// `parallel` feature, from the scaling factor and seed of each instance.
+fn pedersen_elgamal_batch_terms(
+    transcript: &Transcript,
+    randomness: &[(RistrettoScalar, [u8; 32])],
+    instances: &[PedersenElGamalProofInstance<'_>],
+) -> Vec<(Vec<RistrettoScalar>, [RistrettoPoint; 7])> {
+    #[cfg(not(feature = "parallel"))]
+    let iter = instances.iter().zip(randomness.iter());
+    #[cfg(feature = "parallel")]
+    let iter = instances.par_iter().zip(randomness.par_iter());
+    iter.map(|(instance, (alpha, seed))| {
+        pedersen_elgamal_instance_terms(transcript, alpha, seed, instance)
+    })
+    .collect()
+}
+
+/// Compute the scaled scalars of an instance in the batch verification equation, the first two
+/// for the common elems, and the elems specific to the instance.
+fn pedersen_elgamal_instance_terms(
+    transcript: &Transcript,
+    alpha: &RistrettoScalar,
+    seed: &[u8; 32],
+    instance: &PedersenElGamalProofInstance<'_>,
+) -> (Vec<RistrettoScalar>, [RistrettoPoint; 7]) {
+    let n = instance.cts.len();
+    assert_eq!(n, instance.commitments.len());
+    let mut inst_transcript = transcript.clone();
+    let mut prng = ChaChaRng::from_seed(*seed);
+    init_pedersen_elgamal_transcript(
+        &mut inst_transcript,
+        instance.public_key,
+        &instance.cts,
+        &instance.commitments,
+    );
+    // 1. compute x vector
+    let x = get_linear_combination_scalars(&mut inst_transcript, n);
+    // 2. compute linear combination
+    let mut lc_e1 = RistrettoPoint::get_identity();
+    let mut lc_e2 = RistrettoPoint::get_identity();
+    let mut lc_c = RistrettoPoint::get_identity();
+    for (xi, ei, ci) in izip!(x.iter(), instance.cts.iter(), instance.commitments.iter()) {
+        lc_e1 = lc_e1.add(&ei.e1.mul(xi));
+        lc_e2 = lc_e2.add(&ei.e2.mul(xi));
+        lc_c = lc_c.add(&ci.mul(xi));
+    }
+    let lc_e = ElGamalCiphertext {
+        e1: lc_e1,
+        e2: lc_e2,
+    };
+
+    let instance_scalars = pedersem_elgamal_eq_verify_scalars(
+        &mut inst_transcript,
+        &mut prng,
+        instance.public_key,
+        &lc_e,
+        &lc_c,
+        instance.proof,
+    );
+
+    let scalars = instance_scalars.iter().map(|s| alpha.mul(s)).collect();
+    let elems = [
+        instance.public_key.0,
+        lc_e1,
+        lc_e2,
+        lc_c,
+        instance.proof.e1.e1,
+        instance.proof.e1.e2,
+        instance.proof.c1,
+    ];
+    (scalars, elems)
+}
+
+/// Same as `pedersen_elgamal_batch_verify`, but the random scalars are derived from the
+/// transcript after appending all the instances and proofs, see `transcript_verifier_rng`.
+pub fn pedersen_elgamal_batch_verify_deterministic(
+    transcript: &mut Transcript,
+    instances: &[PedersenElGamalProofInstance<'_>],
+) -> Result<()> {
+    let mut rng_transcript = transcript.clone();
+    rng_transcript.append_message(b"new_domain", b"PedersenElGamal batch verification");
+    for instance in instances {
+        init_pedersen_elgamal_transcript(
+            &mut rng_transcript,
+            instance.public_key,
+            &instance.cts,
+            &instance.commitments,
+        );
+        rng_transcript.append_field_element(b"proof z1", &instance.proof.z1);
+        rng_transcript.append_field_element(b"proof z2", &instance.proof.z2);
+        rng_transcript.append_proof_commitment(&instance.proof.e1.e1);
+        rng_transcript.append_proof_commitment(&instance.proof.e1.e2);
+        rng_transcript.append_proof_commitment(&instance.proof.c1);
+    }
+    let mut prng = transcript_verifier_rng(&mut rng_transcript);
+    pedersen_elgamal_batch_verify(transcript, &mut prng, instances).c(d!())
+}
+
 /// Verify Proof of Knowledge for PedersenElGamal equality proof, for a set of statement.
 pub fn pedersen_elgamal_aggregate_eq_verify<R: CryptoRng + RngCore>(
     transcript: &mut Transcript,
@@ -353,7 +430,9 @@ mod test {
     };
     use crate::basic::pedersen_elgamal::{
         pedersen_elgamal_aggregate_eq_proof, pedersen_elgamal_aggregate_eq_verify,
-        pedersen_elgamal_batch_verify, PedersenElGamalProofInstance,
+        pedersen_elgamal_batch_terms, pedersen_elgamal_batch_verify,
+        pedersen_elgamal_batch_verify_deterministic, pedersen_elgamal_instance_terms,
+        PedersenElGamalProofInstance,
     };
     use merlin::Transcript;
     use noah_algebra::prelude::*;
@@ -711,8 +790,78 @@ mod test {
                 proof: &proof2,
             },
         ];
+        assert!(pedersen_elgamal_batch_verify(
+            &mut verifier_transcript.clone(),
+            &mut prng,
+            &instances
+        )
+        .is_ok());
         assert!(
-            pedersen_elgamal_batch_verify(&mut verifier_transcript, &mut prng, &instances).is_ok()
+            pedersen_elgamal_batch_verify_deterministic(&mut verifier_transcript, &instances)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn batch_terms_match_serial() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let transcript = Transcript::new(b"test");
+
+        let keys = (0..16)
+            .map(|_| elgamal_key_gen::<_, RistrettoPoint>(&mut prng).1)
+            .collect_vec();
+        let statements = keys
+            .iter()
+            .map(|pk| {
+                let m = [RistrettoScalar::random(&mut prng)];
+                let r = [RistrettoScalar::random(&mut prng)];
+                let ctexts = vec![elgamal_encrypt(&m[0], &r[0], pk)];
+                let commitments = vec![pc_gens.commit(m[0], r[0])];
+                let proof = pedersen_elgamal_aggregate_eq_proof(
+                    &mut transcript.clone(),
+                    &mut prng,
+                    &m,
+                    &r,
+                    pk,
+                    &ctexts,
+                    &commitments,
+                );
+                (ctexts, commitments, proof)
+            })
+            .collect_vec();
+        let instances = keys
+            .iter()
+            .zip(statements.iter())
+            .map(
+                |(public_key, (cts, commitments, proof))| PedersenElGamalProofInstance {
+                    public_key,
+                    cts: cts.clone(),
+                    commitments: commitments.clone(),
+                    proof,
+                },
+            )
+            .collect_vec();
+        let randomness = instances
+            .iter()
+            .map(|_| {
+                let mut seed = [0u8; 32];
+                prng.fill_bytes(&mut seed);
+                (RistrettoScalar::random(&mut prng), seed)
+            })
+            .collect_vec();
+
+        // with the `parallel` feature, the terms are computed concurrently, in the same order
+        let serial = instances
+            .iter()
+            .zip(randomness.iter())
+            .map(|(instance, (alpha, seed))| {
+                pedersen_elgamal_instance_terms(&transcript, alpha, seed, instance)
+            })
+            .collect_vec();
+        assert_eq!(
+            pedersen_elgamal_batch_terms(&transcript, &randomness, &instances),
+            serial
         );
     }
 }
@@ -123,18 +123,262 @@ impl<M: Scalar, S: Scalar, G: Group<ScalarType = S> + Coordinate<ScalarField = M
     }
 }
 
+/// The domain separator of the hash of the list of verifying keys in MuSig.
+const MUSIG_KEY_LIST_DOMAIN: u32 = 1;
+
+/// The domain separator of the hash for the coefficient of a verifying key in MuSig.
+const MUSIG_KEY_COEFFICIENT_DOMAIN: u32 = 2;
+
+/// The domain separator of the hash for combining the nonces in MuSig.
+const MUSIG_NONCE_DOMAIN: u32 = 3;
+
+/// The aggregated verifying key of several signers in MuSig.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchnorrAggregatedVerifyingKey<G: Group> {
+    /// The aggregated verifying key, which verifies the aggregated signature.
+    pub(crate) verifying_key: SchnorrVerifyingKey<G>,
+    /// The verifying keys of the signers.
+    pub(crate) keys: Vec<SchnorrVerifyingKey<G>>,
+    /// The coefficient of each verifying key in the aggregated key.
+    pub(crate) coefficients: Vec<G::ScalarType>,
+}
+
+impl<G: Group> SchnorrAggregatedVerifyingKey<G> {
+    /// Get the aggregated verifying key.
+    pub fn get_verifying_key(&self) -> SchnorrVerifyingKey<G> {
+        self.verifying_key
+    }
+}
+
+/// The secret nonces of a signer for one MuSig signature, which must not be reused.
+#[derive(Debug)]
+pub struct MuSigSecretNonce<S: Scalar>([S; 2]);
+
+/// The nonce commitments of a signer for one MuSig signature, sent to the other signers.
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct MuSigNonceCommitment<G: Group>([G; 2]);
+
+impl<G: Group> MuSigNonceCommitment<G> {
+    /// Convert into bytes, the compressed representations of the two nonce points.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.0[0].to_compressed_bytes();
+        bytes.extend_from_slice(&self.0[1].to_compressed_bytes());
+        bytes
+    }
+
+    /// Convert from bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 2 * G::COMPRESSED_LEN {
+            return Err(eg!(NoahError::DeserializationError));
+        }
+        let (first, second) = bytes.split_at(G::COMPRESSED_LEN);
+        Ok(Self([
+            G::from_compressed_bytes(first).c(d!(NoahError::DeserializationError))?,
+            G::from_compressed_bytes(second).c(d!(NoahError::DeserializationError))?,
+        ]))
+    }
+}
+
+/// The partial signature of a signer in MuSig.
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct MuSigPartialSignature<S: Scalar, E: Scalar> {
+    /// The share of the s element of the signature.
+    pub schnorr_s: S,
+    /// The e element of the signature, which is the same for all the signers.
+    pub schnorr_e: E,
+}
+
+/// Aggregate the verifying keys of the signers into one for MuSig.
+///
+/// The aggregated key is `sum_i a_i * pk_i`, where the coefficient `a_i` is the hash of
+/// `pk_i` and of the hash `L` of the list of all the keys. Since `a_i` depends on all the
+/// keys, a signer cannot choose its key as a function of the others to cancel them, which
+/// prevents rogue-key attacks.
+pub fn aggregate_public_keys<M, S, G, H>(
+    pks: &[SchnorrVerifyingKey<G>],
+) -> Result<SchnorrAggregatedVerifyingKey<G>>
+where
+    M: Scalar,
+    S: Scalar,
+    G: Group<ScalarType = S> + Coordinate<ScalarField = M>,
+    H: AnemoiJive<M, 2, 12>,
+{
+    if pks.is_empty() {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    let mut list = vec![M::from(MUSIG_KEY_LIST_DOMAIN)];
+    for pk in pks.iter() {
+        list.push(pk.0.get_x());
+        list.push(pk.0.get_y());
+    }
+    let list_hash = H::eval_variable_length_hash(&list);
+
+    let coefficients = pks
+        .iter()
+        .map(|pk| {
+            let a = H::eval_variable_length_hash(&[
+                M::from(MUSIG_KEY_COEFFICIENT_DOMAIN),
+                list_hash,
+                pk.0.get_x(),
+                pk.0.get_y(),
+            ]);
+            // This will perform a modular reduction.
+            S::from(&a.into())
+        })
+        .collect::<Vec<S>>();
+
+    let verifying_key = pks
+        .iter()
+        .zip(coefficients.iter())
+        .fold(G::get_identity(), |acc, (pk, a)| acc + &pk.0.mul(a));
+
+    Ok(SchnorrAggregatedVerifyingKey {
+        verifying_key: SchnorrVerifyingKey(verifying_key),
+        keys: pks.to_vec(),
+        coefficients,
+    })
+}
+
+/// The first round of MuSig: sample the secret nonces for one signature, and return them
+/// with the nonce commitments to send to the other signers.
+pub fn round1_commit<S, G, R>(prng: &mut R) -> (MuSigSecretNonce<S>, MuSigNonceCommitment<G>)
+where
+    S: Scalar,
+    G: Group<ScalarType = S>,
+    R: CryptoRng + RngCore,
+{
+    let k = [S::random(prng), S::random(prng)];
+    let base = G::get_base();
+    (
+        MuSigSecretNonce(k),
+        MuSigNonceCommitment([base.mul(&k[0]), base.mul(&k[1])]),
+    )
+}
+
+/// The second round of MuSig: sign the message, given the nonce commitments of all the
+/// signers, and return the partial signature to aggregate.
+pub fn round2_sign<M, S, G, H>(
+    signing_key: &SchnorrSigningKey<S>,
+    secret_nonce: MuSigSecretNonce<S>,
+    agg_key: &SchnorrAggregatedVerifyingKey<G>,
+    commitments: &[MuSigNonceCommitment<G>],
+    aux: M,
+    msg: &[M],
+) -> Result<MuSigPartialSignature<S, M>>
+where
+    M: Scalar,
+    S: Scalar,
+    G: Group<ScalarType = S> + Coordinate<ScalarField = M>,
+    H: AnemoiJive<M, 2, 12>,
+{
+    let verifying_key = signing_key.to_verifying_key::<G>();
+    let index = agg_key
+        .keys
+        .iter()
+        .position(|pk| *pk == verifying_key)
+        .ok_or(eg!(NoahError::ParameterError))?;
+
+    let (b, point_r) = combine_nonces::<M, S, G, H>(agg_key, commitments, aux, msg)?;
+
+    let mut input = vec![aux, point_r.get_x(), point_r.get_y()];
+    input.extend_from_slice(msg);
+
+    let e = H::eval_variable_length_hash(&input);
+
+    // This will perform a modular reduction.
+    let e_converted = S::from(&e.into());
+
+    let k = secret_nonce.0[0] + &(secret_nonce.0[1] * &b);
+    let s = k - &(agg_key.coefficients[index] * &signing_key.0 * &e_converted);
+
+    Ok(MuSigPartialSignature {
+        schnorr_s: s,
+        schnorr_e: e,
+    })
+}
+
+/// Aggregate the partial signatures of all the signers into a signature, which verifies
+/// against the aggregated verifying key.
+pub fn aggregate_partial_sigs<S: Scalar, M: Scalar>(
+    partial_sigs: &[MuSigPartialSignature<S, M>],
+) -> Result<SchnorrSignature<S, M>> {
+    let schnorr_e = partial_sigs
+        .first()
+        .ok_or(eg!(NoahError::ParameterError))?
+        .schnorr_e;
+    if partial_sigs.iter().any(|sig| sig.schnorr_e != schnorr_e) {
+        return Err(eg!(NoahError::SignatureError));
+    }
+
+    let schnorr_s = partial_sigs
+        .iter()
+        .fold(S::zero(), |acc, sig| acc + &sig.schnorr_s);
+
+    Ok(SchnorrSignature {
+        schnorr_s,
+        schnorr_e,
+    })
+}
+
+/// Combine the nonce commitments of the signers into the nonce point `R = R_1 + b * R_2` of
+/// the signature, where `R_j` is the sum of the `j`-th nonce commitments, and return `b`
+/// and `R`.
+fn combine_nonces<M, S, G, H>(
+    agg_key: &SchnorrAggregatedVerifyingKey<G>,
+    commitments: &[MuSigNonceCommitment<G>],
+    aux: M,
+    msg: &[M],
+) -> Result<(S, G)>
+where
+    M: Scalar,
+    S: Scalar,
+    G: Group<ScalarType = S> + Coordinate<ScalarField = M>,
+    H: AnemoiJive<M, 2, 12>,
+{
+    if commitments.len() != agg_key.keys.len() {
+        return Err(eg!(NoahError::ParameterError));
+    }
+
+    let mut points = [G::get_identity(); 2];
+    for commitment in commitments.iter() {
+        points[0] += &commitment.0[0];
+        points[1] += &commitment.0[1];
+    }
+
+    let mut input = vec![
+        M::from(MUSIG_NONCE_DOMAIN),
+        agg_key.verifying_key.0.get_x(),
+        agg_key.verifying_key.0.get_y(),
+        points[0].get_x(),
+        points[0].get_y(),
+        points[1].get_x(),
+        points[1].get_y(),
+        aux,
+    ];
+    input.extend_from_slice(msg);
+
+    // This will perform a modular reduction.
+    let b = S::from(&H::eval_variable_length_hash(&input).into());
+
+    Ok((b, points[0] + &points[1].mul(&b)))
+}
+
 #[cfg(test)]
 mod tests {
     use noah_algebra::{
         bls12_381::BLSScalar,
         jubjub::{JubjubPoint, JubjubScalar},
         rand_helper::test_rng,
-        traits::Scalar,
+        traits::{Group, Scalar},
     };
 
     use crate::basic::anemoi_jive::AnemoiJive381;
 
-    use super::SchnorrKeyPair;
+    use super::{
+        aggregate_partial_sigs, aggregate_public_keys, round1_commit, round2_sign,
+        MuSigNonceCommitment, SchnorrKeyPair, SchnorrSigningKey, SchnorrVerifyingKey,
+    };
 
     #[test]
     fn test_schnorr_signature() {
@@ -165,4 +409,97 @@ mod tests {
             .verify::<AnemoiJive381>(&sign, aux, &msg[..4])
             .is_err());
     }
+
+    #[test]
+    fn test_musig() {
+        let mut rng = test_rng();
+
+        let key_pairs = (0..3)
+            .map(|_| SchnorrKeyPair::<JubjubScalar, JubjubPoint>::sample(&mut rng))
+            .collect::<Vec<_>>();
+        let pks = key_pairs
+            .iter()
+            .map(|key_pair| key_pair.get_verifying_key())
+            .collect::<Vec<_>>();
+        let agg_key =
+            aggregate_public_keys::<BLSScalar, JubjubScalar, JubjubPoint, AnemoiJive381>(&pks)
+                .unwrap();
+
+        let msg = vec![BLSScalar::random(&mut rng), BLSScalar::random(&mut rng)];
+        let aux = BLSScalar::random(&mut rng);
+
+        let (nonces, commitments): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| round1_commit::<JubjubScalar, JubjubPoint, _>(&mut rng))
+            .unzip();
+
+        // the commitments are sent as bytes
+        let commitments = commitments
+            .iter()
+            .map(|c| MuSigNonceCommitment::<JubjubPoint>::from_bytes(&c.to_bytes()).unwrap())
+            .collect::<Vec<_>>();
+
+        let partial_sigs = key_pairs
+            .iter()
+            .zip(nonces)
+            .map(|(key_pair, nonce)| {
+                round2_sign::<BLSScalar, JubjubScalar, JubjubPoint, AnemoiJive381>(
+                    &key_pair.get_signing_key(),
+                    nonce,
+                    &agg_key,
+                    &commitments,
+                    aux,
+                    &msg,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let sign = aggregate_partial_sigs(&partial_sigs).unwrap();
+        let verifying_key = agg_key.get_verifying_key();
+        assert!(verifying_key
+            .verify::<AnemoiJive381>(&sign, aux, &msg)
+            .is_ok());
+        assert!(verifying_key
+            .verify::<AnemoiJive381>(&sign, aux, &msg[..1])
+            .is_err());
+
+        // a signature missing a signer does not verify
+        let sign = aggregate_partial_sigs(&partial_sigs[..2]).unwrap();
+        assert!(verifying_key
+            .verify::<AnemoiJive381>(&sign, aux, &msg)
+            .is_err());
+    }
+
+    #[test]
+    fn test_musig_rogue_key() {
+        let mut rng = test_rng();
+
+        let honest_pk =
+            SchnorrKeyPair::<JubjubScalar, JubjubPoint>::sample(&mut rng).get_verifying_key();
+
+        // the attacker announces `x * G - honest_pk`, so that the plain sum of the keys is
+        // `x * G`, of which it knows the signing key
+        let x = JubjubScalar::random(&mut rng);
+        let rogue_signing_key = SchnorrSigningKey::from_raw(x);
+        let rogue_pk = SchnorrVerifyingKey(JubjubPoint::get_base().mul(&x) - &honest_pk.0);
+        assert_eq!(
+            rogue_pk.0 + &honest_pk.0,
+            rogue_signing_key.to_verifying_key::<JubjubPoint>().0
+        );
+
+        let agg_key =
+            aggregate_public_keys::<BLSScalar, JubjubScalar, JubjubPoint, AnemoiJive381>(&[
+                honest_pk, rogue_pk,
+            ])
+            .unwrap();
+
+        let msg = vec![BLSScalar::random(&mut rng)];
+        let aux = BLSScalar::random(&mut rng);
+        let sign =
+            rogue_signing_key.sign::<BLSScalar, AnemoiJive381, JubjubPoint, _>(&mut rng, aux, &msg);
+        assert!(agg_key
+            .get_verifying_key()
+            .verify::<AnemoiJive381>(&sign, aux, &msg)
+            .is_err());
+    }
 }
@@ -0,0 +1,169 @@
+use crate::basic::matrix_sigma::SigmaTranscript;
+use merlin::Transcript;
+
+/// An operation performed on a transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptRecord {
+    /// A message appended with `append_message`.
+    Message {
+        /// The label of the message.
+        label: Vec<u8>,
+        /// The bytes of the message.
+        message: Vec<u8>,
+    },
+    /// A challenge squeezed with `challenge_bytes`.
+    Challenge {
+        /// The label of the challenge.
+        label: Vec<u8>,
+        /// The bytes of the challenge.
+        challenge: Vec<u8>,
+    },
+}
+
+/// A wrapper around `merlin::Transcript` that records every operation.
+/// It produces the same challenges as the underlying transcript, and can be passed to the Sigma
+/// protocols in place of it. When a prover and a verifier disagree on a challenge, comparing
+/// their two recordings pinpoints the first divergent append.
+/// This is a debugging aid only and is not meant to be used in production.
+#[derive(Clone)]
+pub struct TranscriptRecorder {
+    transcript: Transcript,
+    records: Vec<TranscriptRecord>,
+}
+
+impl TranscriptRecorder {
+    /// Create a new recording transcript with the given domain separation label.
+    pub fn new(label: &'static [u8]) -> Self {
+        Self {
+            transcript: Transcript::new(label),
+            records: vec![],
+        }
+    }
+
+    /// Return the operations recorded so far.
+    pub fn records(&self) -> &[TranscriptRecord] {
+        &self.records
+    }
+
+    /// Return the index of the first operation that differs between the two recordings,
+    /// or None if one recording is a prefix of the other.
+    pub fn first_divergence(&self, other: &TranscriptRecorder) -> Option<usize> {
+        self.records
+            .iter()
+            .zip(other.records.iter())
+            .position(|(a, b)| a != b)
+    }
+
+    /// Return the underlying transcript, dropping the recording.
+    pub fn into_transcript(self) -> Transcript {
+        self.transcript
+    }
+}
+
+impl SigmaTranscript for TranscriptRecorder {
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.records.push(TranscriptRecord::Message {
+            label: label.to_vec(),
+            message: message.to_vec(),
+        });
+        self.transcript.append_message(label, message);
+    }
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.transcript.challenge_bytes(label, dest);
+        self.records.push(TranscriptRecord::Challenge {
+            label: label.to_vec(),
+            challenge: dest.to_vec(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TranscriptRecorder;
+    use crate::basic::matrix_sigma::{sigma_prove, sigma_verify, SigmaTranscript};
+    use merlin::Transcript;
+    use noah_algebra::{prelude::*, ristretto::RistrettoPoint, ristretto::RistrettoScalar};
+
+    #[test]
+    fn recorder_matches_transcript() {
+        let mut prng = test_rng();
+        let elem = RistrettoPoint::random(&mut prng);
+
+        let mut transcript = Transcript::new(b"test");
+        transcript.init_sigma::<RistrettoPoint>(b"instance", &[], &[elem]);
+        let expected: RistrettoScalar = transcript.get_challenge();
+
+        let mut recorder = TranscriptRecorder::new(b"test");
+        recorder.init_sigma::<RistrettoPoint>(b"instance", &[], &[elem]);
+        let challenge: RistrettoScalar = recorder.get_challenge();
+        assert_eq!(expected, challenge);
+        assert_eq!(recorder.records().len(), 4);
+
+        let mut other = TranscriptRecorder::new(b"test");
+        other.init_sigma::<RistrettoPoint>(b"instance", &[], &[elem.double()]);
+        let other_challenge: RistrettoScalar = other.get_challenge();
+        assert_ne!(challenge, other_challenge);
+        assert_eq!(recorder.first_divergence(&other), Some(2));
+        assert_eq!(recorder.first_divergence(&recorder), None);
+    }
+
+    #[test]
+    fn recorder_records_sigma_proof() {
+        let g = RistrettoPoint::get_base();
+        let x = RistrettoScalar::random(&mut test_rng());
+        let h = g.mul(&x);
+        let elems = [g, h];
+        let lhs_matrix = vec![vec![0]];
+        let rhs_vec = [1];
+
+        // the recorder is a drop-in replacement of the transcript in the prover
+        let mut recorder = TranscriptRecorder::new(b"test");
+        let proof = sigma_prove(
+            &mut recorder,
+            &mut test_rng_seeded(1),
+            &elems,
+            &lhs_matrix,
+            &[&x],
+        );
+        let expected = sigma_prove(
+            &mut Transcript::new(b"test"),
+            &mut test_rng_seeded(1),
+            &elems,
+            &lhs_matrix,
+            &[&x],
+        );
+        assert_eq!(proof, expected);
+
+        // the domain, the instance, the two public elements, the proof commitment and the
+        // challenge
+        assert_eq!(recorder.records().len(), 6);
+
+        // the verifier records the same operations as the prover
+        let mut verifier = TranscriptRecorder::new(b"test");
+        let mut prng = test_rng();
+        sigma_verify(
+            &mut verifier,
+            &mut prng,
+            &elems,
+            &lhs_matrix,
+            &rhs_vec,
+            &proof,
+        )
+        .unwrap();
+        assert_eq!(verifier.records(), recorder.records());
+        assert_eq!(recorder.first_divergence(&verifier), None);
+
+        // a verifier with another statement diverges at its public element
+        let mut other = TranscriptRecorder::new(b"test");
+        assert!(sigma_verify(
+            &mut other,
+            &mut prng,
+            &[g, h.double()],
+            &lhs_matrix,
+            &rhs_vec,
+            &proof,
+        )
+        .is_err());
+        assert_eq!(recorder.first_divergence(&other), Some(3));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::anon_creds::{
-    verify_pok, Attribute,
+    pok_pairing_terms, Attribute,
     Attribute::{Hidden, Revealed},
     Credential, CredentialComm, CredentialCommRandomizer, CredentialIssuerPK, CredentialPoK,
     CredentialUserSK, POK_LABEL,
@@ -195,6 +195,87 @@ pub fn confidential_verify_open<P: Pairing>(
     pok: &CACPoK<P::G1, P::G2, P::ScalarField>,
     m: &[u8],
 ) -> Result<()> {
+    let (p, sigma2_c) =
+        confidential_verify_open_terms::<P>(ipk, ek, reveal_map, cm, cts, pok, m).c(d!())?;
+    if P::pairing(&cm.0.sigma1, &p) == P::pairing(&sigma2_c, &ipk.gen2) {
+        Ok(())
+    } else {
+        Err(eg!(NoahError::IdentityRevealVerifyError))
+    }
+}
+
+/// An instance of a confidential selective opening to verify, see `confidential_verify_open`.
+pub struct CACVerifyInstance<'a, P: Pairing> {
+    /// The issuer public key.
+    pub ipk: &'a CredentialIssuerPK<P::G1, P::G2>,
+    /// The encryption key of the attributes.
+    pub ek: &'a ElGamalEncKey<P::G1>,
+    /// The attributes to be revealed.
+    pub reveal_map: &'a [bool],
+    /// The credential commitment.
+    pub cm: &'a CredentialComm<P::G1>,
+    /// The ciphertexts of the revealed attributes.
+    pub cts: &'a [ElGamalCiphertext<P::G1>],
+    /// The proof of knowledge.
+    pub pok: &'a CACPoK<P::G1, P::G2, P::ScalarField>,
+    /// The message bound to the proof.
+    pub m: &'a [u8],
+}
+
+impl<'a, P: Pairing> Clone for CACVerifyInstance<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: Pairing> Copy for CACVerifyInstance<'a, P> {}
+
+/// Batch verify confidential selective openings.
+/// The ciphertexts are checked per instance, while the pairing equations of all the instances
+/// are combined with random weights into a single product of pairings.
+/// Returns an error if any of the instances is invalid.
+pub fn confidential_batch_verify_open<R: CryptoRng + RngCore, P: Pairing>(
+    prng: &mut R,
+    instances: &[CACVerifyInstance<'_, P>],
+) -> Result<()> {
+    let mut g1_elems = Vec::with_capacity(2 * instances.len());
+    let mut g2_elems = Vec::with_capacity(2 * instances.len());
+    for instance in instances.iter() {
+        let (p, sigma2_c) = confidential_verify_open_terms::<P>(
+            instance.ipk,
+            instance.ek,
+            instance.reveal_map,
+            instance.cm,
+            instance.cts,
+            instance.pok,
+            instance.m,
+        )
+        .c(d!())?;
+        // e(rho * sigma1, p) * e(-rho * sigma2 * c, G2) = 1
+        let rho = P::ScalarField::random(prng);
+        g1_elems.push(instance.cm.0.sigma1.mul(&rho));
+        g2_elems.push(p);
+        g1_elems.push(sigma2_c.mul(&rho.neg()));
+        g2_elems.push(instance.ipk.gen2.clone());
+    }
+    if P::product_of_pairings(&g1_elems, &g2_elems) == P::Gt::get_identity() {
+        Ok(())
+    } else {
+        Err(eg!(NoahError::IdentityRevealVerifyError))
+    }
+}
+
+/// Check the structure and the ciphertexts of a confidential selective opening, and return the
+/// terms `(p, sigma2 * challenge)` of the remaining pairing equation `e(sigma1, p) = e(sigma2 * challenge, G2)`.
+fn confidential_verify_open_terms<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    ek: &ElGamalEncKey<P::G1>,
+    reveal_map: &[bool],
+    cm: &CredentialComm<P::G1>,
+    cts: &[ElGamalCiphertext<P::G1>],
+    pok: &CACPoK<P::G1, P::G2, P::ScalarField>,
+    m: &[u8],
+) -> Result<(P::G2, P::G1)> {
     let n = cts.len();
     let revealed_count = reveal_map
         .iter()
@@ -295,7 +376,7 @@ fn confidential_verify_pok<P: Pairing>(
     pok: &CACPoK<P::G1, P::G2, P::ScalarField>,
     reveal_map: &[bool],
     m: &[u8],
-) -> Result<()> {
+) -> Result<(P::G2, P::G1)> {
     transcript.cac_init::<P>(ipk, ek, cm, cts);
     transcript.append_message(POK_LABEL, m);
 
@@ -326,7 +407,7 @@ fn confidential_verify_pok<P: Pairing>(
 
     // 3. verify credential proof
     let hidden_attrs = vec![Hidden(None); ipk.num_attrs()];
-    verify_pok::<P>(ipk, cm, &pok.pok, hidden_attrs.as_slice(), &challenge).c(d!())
+    pok_pairing_terms::<P>(ipk, cm, &pok.pok, hidden_attrs.as_slice(), &challenge).c(d!())
 }
 
 fn verify_ciphertext<P: Pairing>(
@@ -353,10 +434,13 @@ fn verify_ciphertext<P: Pairing>(
 pub(crate) mod test_helper {
     use crate::anon_creds::{
         check_comm, commit_without_randomizer, grant_credential, issuer_keygen, user_keygen,
-        Credential,
+        Credential, CredentialComm,
     };
     use crate::basic::elgamal::elgamal_key_gen;
-    use crate::confidential_anon_creds::{confidential_open_comm, confidential_verify_open};
+    use crate::confidential_anon_creds::{
+        confidential_batch_verify_open, confidential_open_comm, confidential_verify_open,
+        CACVerifyInstance,
+    };
     use noah_algebra::prelude::*;
     use noah_algebra::traits::Pairing;
 
@@ -420,6 +504,50 @@ pub(crate) mod test_helper {
         )
         .is_ok());
 
+        // Batch verification
+        let instance = CACVerifyInstance::<P> {
+            ipk: &credential.ipk,
+            ek: &ek,
+            reveal_map,
+            cm: &cm,
+            cts: &conf_reveal_proof.cts,
+            pok: &conf_reveal_proof.pok,
+            m: proof_msg,
+        };
+        let (_, ipk2) = issuer_keygen::<_, P>(&mut prng, num_attr);
+        let bad_instance = CACVerifyInstance::<P> {
+            ipk: &ipk2,
+            ..instance
+        };
+        pnk!(confidential_batch_verify_open(&mut prng, &[]));
+        pnk!(confidential_batch_verify_open(
+            &mut prng,
+            &[instance, instance]
+        ));
+        assert!(
+            confidential_batch_verify_open(&mut prng, &[instance, bad_instance, instance]).is_err()
+        );
+
+        // The commitment survives its byte encoding
+        let cm_bytes = cm.to_bytes();
+        assert_eq!(cm_bytes.len(), 2 * <P::G1 as Group>::COMPRESSED_LEN);
+        let cm2 = CredentialComm::<P::G1>::from_bytes(&cm_bytes).unwrap();
+        assert_eq!(cm, cm2);
+        assert!(confidential_verify_open::<P>(
+            &credential.ipk,
+            &ek,
+            reveal_map,
+            &cm2,
+            &conf_reveal_proof.cts,
+            &conf_reveal_proof.pok,
+            proof_msg,
+        )
+        .is_ok());
+        assert!(CredentialComm::<P::G1>::from_bytes(&cm_bytes[1..]).is_err());
+        assert!(
+            CredentialComm::<P::G1>::from_bytes(&[cm_bytes.clone(), vec![0u8]].concat()).is_err()
+        );
+
         // Error cases
 
         // Inconsistent bitmap
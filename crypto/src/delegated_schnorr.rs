@@ -66,6 +66,15 @@ impl<S: Scalar, G: Group<ScalarType = S>, P: SimFrParams> DelegatedSchnorrProof<
         }
     }
 
+    /// Check that the proof has one randomizer and one pair of response scalars
+    /// for each of the `expected` commitments.
+    pub fn validate_shape(&self, expected: usize) -> Result<()> {
+        if self.randomizers.len() != expected || self.response_scalars.len() != expected {
+            return Err(eg!(NoahError::ZKProofVerificationError));
+        }
+        Ok(())
+    }
+
     /// Represent the information needed by zk-SNARKs in its format.
     pub fn to_verifier_input(&self) -> Vec<BLSScalar> {
         let response_scalars_sim_fr_limbs = self
@@ -256,8 +265,7 @@ pub fn verify_delegated_schnorr<
     proof: &DelegatedSchnorrProof<S, G, P>,
     transcript: &mut Transcript,
 ) -> Result<(S, S)> {
-    assert_eq!(commitments.len(), proof.randomizers.len());
-    assert_eq!(commitments.len(), proof.response_scalars.len());
+    proof.validate_shape(commitments.len()).c(d!())?;
 
     let len = commitments.len();
 
@@ -367,6 +375,45 @@ mod test_ristretto {
             .unwrap();
         }
     }
+
+    #[test]
+    fn test_malformed_shape() {
+        let mut prng = test_rng();
+
+        let x = RistrettoScalar::random(&mut prng);
+        let gamma = RistrettoScalar::random(&mut prng);
+        let y = RistrettoScalar::random(&mut prng);
+        let delta = RistrettoScalar::random(&mut prng);
+
+        let pc_gens = PedersenCommitmentRistretto::default();
+
+        let point_p = pc_gens.commit(x, gamma);
+        let point_q = pc_gens.commit(y, delta);
+
+        let mut transcript = Transcript::new(b"Test");
+
+        let (mut proof, _, _, _) = prove_delegated_schnorr::<_, _, _, SimFrParamsRistretto, _>(
+            &mut prng,
+            &vec![(x, gamma), (y, delta)],
+            &pc_gens,
+            &vec![point_p, point_q],
+            &mut transcript,
+        )
+        .unwrap();
+        assert!(proof.validate_shape(2).is_ok());
+
+        proof.response_scalars.pop();
+        assert!(proof.validate_shape(2).is_err());
+
+        let mut transcript = Transcript::new(b"Test");
+        assert!(verify_delegated_schnorr(
+            &pc_gens,
+            &vec![point_p, point_q],
+            &proof,
+            &mut transcript,
+        )
+        .is_err());
+    }
 }
 
 #[cfg(test)]
@@ -3,7 +3,8 @@ use noah_algebra::bls12_381::BLSScalar;
 use noah_algebra::ops::Neg;
 use noah_algebra::{One, Zero};
 use noah_crypto::basic::anemoi_jive::{
-    AnemoiJive, AnemoiJive381, AnemoiStreamCipherTrace, AnemoiVLHTrace, JiveTrace,
+    anemoi_mac_domain_separator, anemoi_mac_with_trace, AnemoiJive, AnemoiJive381,
+    AnemoiStreamCipherTrace, AnemoiVLHTrace, JiveTrace,
 };
 
 impl TurboCS<BLSScalar> {
@@ -197,6 +198,19 @@ impl TurboCS<BLSScalar> {
         trace: &AnemoiVLHTrace<BLSScalar, 2, 12>,
         input_var: &[VarIndex],
         output_var: VarIndex,
+    ) {
+        let zero_var = self.zero_var();
+        self.anemoi_keyed_variable_length_hash(trace, zero_var, input_var, output_var);
+    }
+
+    /// Create constraints for the Anemoi variable length hash function, where the capacity
+    /// is initialized to `key_var` instead of zero.
+    fn anemoi_keyed_variable_length_hash(
+        &mut self,
+        trace: &AnemoiVLHTrace<BLSScalar, 2, 12>,
+        key_var: VarIndex,
+        input_var: &[VarIndex],
+        output_var: VarIndex,
     ) {
         assert_eq!(input_var.len(), trace.input.len());
 
@@ -226,7 +240,7 @@ impl TurboCS<BLSScalar> {
         let num_chunks = chunks.len();
 
         let mut x_var = [chunks[0][0], chunks[0][1]];
-        let mut y_var = [chunks[0][2], zero_var];
+        let mut y_var = [chunks[0][2], key_var];
 
         if num_chunks == 1 {
             self.anemoi_permutation_round(
@@ -308,6 +322,55 @@ impl TurboCS<BLSScalar> {
         }
     }
 
+    /// Create constraints for opening a commitment: `commitment = Anemoi(values || blind)`,
+    /// where Anemoi is the variable length hash.
+    ///
+    /// This is the commitment format of the inspector's state in address folding, and it can be
+    /// reused by any circuit that commits to a list of values with a blinding factor.
+    /// The trace of the hash is computed from the witness of the inputs.
+    pub fn prove_commitment_opening(
+        &mut self,
+        commitment_var: VarIndex,
+        value_vars: &[VarIndex],
+        blind_var: VarIndex,
+    ) {
+        let mut input_vars = value_vars.to_vec();
+        input_vars.push(blind_var);
+
+        let input = input_vars
+            .iter()
+            .map(|var| self.witness[*var])
+            .collect::<Vec<BLSScalar>>();
+        let trace = AnemoiJive381::eval_variable_length_hash_with_trace(&input);
+
+        self.anemoi_variable_length_hash(&trace, &input_vars, commitment_var);
+    }
+
+    /// Create constraints for the Anemoi MAC of the message under the key, and return the
+    /// variable of the MAC.
+    ///
+    /// The domain separator and the length of the message are fixed by constant gates, and the
+    /// trace of the sponge is computed from the witness of the key and of the message.
+    pub fn anemoi_mac(&mut self, key_var: VarIndex, message_vars: &[VarIndex]) -> VarIndex {
+        let separator_var = self.new_variable(anemoi_mac_domain_separator());
+        self.insert_constant_gate(separator_var, anemoi_mac_domain_separator());
+        let len = BLSScalar::from(message_vars.len() as u64);
+        let len_var = self.new_variable(len);
+        self.insert_constant_gate(len_var, len);
+
+        let message = message_vars
+            .iter()
+            .map(|var| self.witness[*var])
+            .collect::<Vec<BLSScalar>>();
+        let trace = anemoi_mac_with_trace(&self.witness[key_var], &message);
+        let output_var = self.new_variable(trace.output);
+
+        let mut input_vars = vec![separator_var, len_var];
+        input_vars.extend_from_slice(message_vars);
+        self.anemoi_keyed_variable_length_hash(&trace, key_var, &input_vars, output_var);
+        output_var
+    }
+
     /// Create constraints for the Jive CRH.
     pub fn jive_crh(
         &mut self,
@@ -597,7 +660,9 @@ impl TurboCS<BLSScalar> {
 mod test {
     use crate::plonk::constraint_system::TurboCS;
     use noah_algebra::bls12_381::BLSScalar;
-    use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381, ANEMOI_JIVE_381_SALTS};
+    use noah_crypto::basic::anemoi_jive::{
+        anemoi_mac, AnemoiJive, AnemoiJive381, ANEMOI_JIVE_381_SALTS,
+    };
 
     #[test]
     fn test_jive_constraint_system() {
@@ -646,6 +711,77 @@ mod test {
         cs.verify_witness(&witness, &[]).unwrap();
     }
 
+    #[test]
+    fn test_prove_commitment_opening() {
+        let values = [BLSScalar::from(1u64), BLSScalar::from(2u64)];
+        let blind = BLSScalar::from(3u64);
+        let commitment = AnemoiJive381::eval_variable_length_hash(&[values[0], values[1], blind]);
+
+        let mut cs = TurboCS::new();
+        cs.load_anemoi_jive_parameters::<AnemoiJive381>();
+
+        let value_vars = [cs.new_variable(values[0]), cs.new_variable(values[1])];
+        let blind_var = cs.new_variable(blind);
+        let commitment_var = cs.new_variable(commitment);
+
+        cs.prove_commitment_opening(commitment_var, &value_vars, blind_var);
+
+        let mut witness = cs.get_and_clear_witness();
+        cs.verify_witness(&witness, &[]).unwrap();
+
+        // a different blinding factor does not open the commitment
+        witness[blind_var] = BLSScalar::from(4u64);
+        assert!(cs.verify_witness(&witness, &[]).is_err());
+    }
+
+    #[test]
+    fn test_anemoi_mac() {
+        let key = BLSScalar::from(7u64);
+        for len in 0..6u64 {
+            let message = (1..=len).map(BLSScalar::from).collect::<Vec<BLSScalar>>();
+
+            let mut cs = TurboCS::new();
+            cs.load_anemoi_jive_parameters::<AnemoiJive381>();
+
+            let key_var = cs.new_variable(key);
+            let message_vars = message
+                .iter()
+                .map(|m| cs.new_variable(*m))
+                .collect::<Vec<_>>();
+            let mac_var = cs.anemoi_mac(key_var, &message_vars);
+
+            let mut witness = cs.get_and_clear_witness();
+            assert_eq!(witness[mac_var], anemoi_mac(&key, &message));
+            cs.verify_witness(&witness, &[]).unwrap();
+
+            // the MAC under another key does not satisfy the circuit
+            witness[key_var] = BLSScalar::from(8u64);
+            assert!(cs.verify_witness(&witness, &[]).is_err());
+        }
+
+        // a message and its padded extension have different MACs
+        let macs = [
+            vec![BLSScalar::from(5u64)],
+            vec![BLSScalar::from(5u64), BLSScalar::from(1u64)],
+        ]
+        .iter()
+        .map(|message| {
+            let mut cs = TurboCS::new();
+            cs.load_anemoi_jive_parameters::<AnemoiJive381>();
+            let key_var = cs.new_variable(key);
+            let message_vars = message
+                .iter()
+                .map(|m| cs.new_variable(*m))
+                .collect::<Vec<_>>();
+            let mac_var = cs.anemoi_mac(key_var, &message_vars);
+            let witness = cs.get_and_clear_witness();
+            cs.verify_witness(&witness, &[]).unwrap();
+            witness[mac_var]
+        })
+        .collect::<Vec<_>>();
+        assert_ne!(macs[0], macs[1]);
+    }
+
     #[test]
     fn test_anemoi_stream_cipher() {
         for output_len in 1..=7 {
@@ -131,7 +131,23 @@ impl<P: SimFrParams> SimFrVar<P> {
 
     /// Alloc a witness variable and range check gate.
     pub fn alloc_witness(cs: &mut TurboCS<BLSScalar>, val: &SimFr<P>) -> (Self, Vec<VarIndex>) {
+        let mut per_limb_bits = vec![P::BIT_PER_LIMB; P::NUM_OF_LIMBS];
+        per_limb_bits[P::NUM_OF_LIMBS - 1] = P::BIT_IN_TOP_LIMB;
+        Self::alloc_witness_with_bounds(cs, val, &per_limb_bits)
+    }
+
+    /// Alloc a witness variable and range check gate, with a bit bound for each limb.
+    ///
+    /// The bounds must not exceed the default ones, i.e., `BIT_PER_LIMB` for each limb
+    /// and `BIT_IN_TOP_LIMB` for the top limb. A limb with a bound of zero bits must be zero,
+    /// and is not allocated.
+    pub fn alloc_witness_with_bounds(
+        cs: &mut TurboCS<BLSScalar>,
+        val: &SimFr<P>,
+        per_limb_bits: &[usize],
+    ) -> (Self, Vec<VarIndex>) {
         assert!(val.num_of_additions_over_normal_form == SimReducibility::StrictlyNotReducible);
+        assert_eq!(per_limb_bits.len(), P::NUM_OF_LIMBS);
 
         let mut res = Self::new(cs);
         res.val = (*val).clone();
@@ -139,13 +155,27 @@ impl<P: SimFrParams> SimFrVar<P> {
 
         let mut bits = Vec::new();
 
-        for i in 0..P::NUM_OF_LIMBS {
-            res.var[i] = cs.new_variable(val.limbs[i]);
-
+        for (i, &n_bits) in per_limb_bits.iter().enumerate() {
             if i == P::NUM_OF_LIMBS - 1 {
-                bits.extend_from_slice(&cs.range_check(res.var[i], P::BIT_IN_TOP_LIMB));
+                assert!(n_bits <= P::BIT_IN_TOP_LIMB);
             } else {
-                bits.extend_from_slice(&cs.range_check(res.var[i], P::BIT_PER_LIMB));
+                assert!(n_bits <= P::BIT_PER_LIMB);
+            }
+
+            match n_bits {
+                0 => {
+                    assert!(val.limbs[i].is_zero());
+                    res.var[i] = cs.zero_var();
+                }
+                1 => {
+                    res.var[i] = cs.new_variable(val.limbs[i]);
+                    cs.insert_boolean_gate(res.var[i]);
+                    bits.push(res.var[i]);
+                }
+                _ => {
+                    res.var[i] = cs.new_variable(val.limbs[i]);
+                    bits.extend_from_slice(&cs.range_check(res.var[i], n_bits));
+                }
             }
         }
         (res, bits)
@@ -251,6 +281,37 @@ mod test_ristretto {
         }
     }
 
+    #[test]
+    fn test_alloc_witness_with_bounds() {
+        let mut prng = test_rng();
+        let n_limbs = SimFrParamsRistretto::NUM_OF_LIMBS;
+
+        // a value that fits in the two lowest limbs
+        let a = prng.gen_biguint((SimFrParamsRistretto::BIT_PER_LIMB + 10) as u64);
+        let a_sim_fr = SimFrTest::from(&a);
+        let mut per_limb_bits = vec![0; n_limbs];
+        per_limb_bits[0] = SimFrParamsRistretto::BIT_PER_LIMB;
+        per_limb_bits[1] = 10;
+
+        let mut cs = TurboCS::<BLSScalar>::new();
+        let _ = SimFrVarTest::alloc_witness(&mut cs, &a_sim_fr);
+        let default_size = cs.size;
+
+        let mut cs = TurboCS::<BLSScalar>::new();
+        let (a_sim_fr_var, _) =
+            SimFrVarTest::alloc_witness_with_bounds(&mut cs, &a_sim_fr, &per_limb_bits);
+        assert!(cs.size < default_size);
+        test_sim_fr_equality(cs, &a_sim_fr_var);
+
+        // a value that exceeds the bounds
+        let b = BigUint::from(1u32).shl(SimFrParamsRistretto::BIT_PER_LIMB + 10);
+        let b_sim_fr = SimFrTest::from(&b);
+        let mut cs = TurboCS::<BLSScalar>::new();
+        let _ = SimFrVarTest::alloc_witness_with_bounds(&mut cs, &b_sim_fr, &per_limb_bits);
+        let witness = cs.get_and_clear_witness();
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+    }
+
     #[test]
     fn test_sub() {
         let mut prng = test_rng();
@@ -402,6 +463,37 @@ mod test_secq256k1 {
         }
     }
 
+    #[test]
+    fn test_alloc_witness_with_bounds() {
+        let mut prng = test_rng();
+        let n_limbs = SimFrParamsSecq256k1::NUM_OF_LIMBS;
+
+        // a value that fits in the two lowest limbs
+        let a = prng.gen_biguint((SimFrParamsSecq256k1::BIT_PER_LIMB + 10) as u64);
+        let a_sim_fr = SimFrTest::from(&a);
+        let mut per_limb_bits = vec![0; n_limbs];
+        per_limb_bits[0] = SimFrParamsSecq256k1::BIT_PER_LIMB;
+        per_limb_bits[1] = 10;
+
+        let mut cs = TurboCS::<BLSScalar>::new();
+        let _ = SimFrVarTest::alloc_witness(&mut cs, &a_sim_fr);
+        let default_size = cs.size;
+
+        let mut cs = TurboCS::<BLSScalar>::new();
+        let (a_sim_fr_var, _) =
+            SimFrVarTest::alloc_witness_with_bounds(&mut cs, &a_sim_fr, &per_limb_bits);
+        assert!(cs.size < default_size);
+        test_sim_fr_equality(cs, &a_sim_fr_var);
+
+        // a value that exceeds the bounds
+        let b = BigUint::from(1u32).shl(SimFrParamsSecq256k1::BIT_PER_LIMB + 10);
+        let b_sim_fr = SimFrTest::from(&b);
+        let mut cs = TurboCS::<BLSScalar>::new();
+        let _ = SimFrVarTest::alloc_witness_with_bounds(&mut cs, &b_sim_fr, &per_limb_bits);
+        let witness = cs.get_and_clear_witness();
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+    }
+
     #[test]
     fn test_sub() {
         let mut prng = test_rng();
@@ -1,4 +1,6 @@
 use noah_algebra::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt;
 
 /// Module for Field Simulation Constrain System.
 pub mod field_simulation;
@@ -12,6 +14,9 @@ pub mod ecc;
 /// Module for the Anemoi-Jive hash function.
 pub mod anemoi_jive;
 
+/// Module for exporting a constraint system as R1CS.
+pub mod r1cs;
+
 /// Default used constraint system.
 #[doc(hidden)]
 pub use turbo::TurboCS;
@@ -22,6 +27,57 @@ pub type VarIndex = usize;
 /// Constraint index
 pub type CsIndex = usize;
 
+/// Lookup table identifier
+pub type TableId = usize;
+
+/// The number of wires of a gate whose values are looked up, which are its first wires.
+pub const N_LOOKUP_INPUTS: usize = 3;
+
+/// The number of columns of the lookup tables: one per looked up wire, and the table identifier.
+pub const N_LOOKUP_COLUMNS: usize = N_LOOKUP_INPUTS + 1;
+
+/// The number of selectors of the lookup argument: the lookup selector, the table selector,
+/// and the columns of the lookup tables.
+pub const N_LOOKUP_SELECTORS: usize = N_LOOKUP_COLUMNS + 2;
+
+/// Statistics of a constraint system, as computed by `ConstraintSystem::stats`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CsStats {
+    /// The number of constraints, including the padding.
+    pub size: usize,
+    /// The number of padding constraints at the end of the system, which constrain nothing.
+    pub padding_rows: usize,
+    /// The number of variables.
+    pub num_vars: usize,
+    /// The number of constraints with a boolean constraint on their inputs.
+    pub boolean_rows: usize,
+    /// The number of constraints with Anemoi round keys.
+    pub anemoi_rows: usize,
+    /// The number of lookup gates.
+    pub lookup_rows: usize,
+    /// The number of constraints of public inputs.
+    pub public_input_rows: usize,
+    /// For each number of wires, the number of variables which appear in that many wires,
+    /// outside of the padding.
+    pub wires_per_variable: BTreeMap<usize, usize>,
+}
+
+impl fmt::Display for CsStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<24}{:>12}", "constraints", self.size)?;
+        writeln!(f, "{:<24}{:>12}", "  padding", self.padding_rows)?;
+        writeln!(f, "{:<24}{:>12}", "  boolean", self.boolean_rows)?;
+        writeln!(f, "{:<24}{:>12}", "  anemoi", self.anemoi_rows)?;
+        writeln!(f, "{:<24}{:>12}", "  lookup", self.lookup_rows)?;
+        writeln!(f, "{:<24}{:>12}", "  public input", self.public_input_rows)?;
+        writeln!(f, "{:<24}{:>12}", "variables", self.num_vars)?;
+        for (n_wires, n_vars) in self.wires_per_variable.iter() {
+            writeln!(f, "{:<24}{:>12}", format!("  in {} wires", n_wires), n_vars)?;
+        }
+        Ok(())
+    }
+}
+
 /// Trait for PLONK constraint systems.
 pub trait ConstraintSystem: Sized {
     /// Type of scalar field.
@@ -50,33 +106,103 @@ pub trait ConstraintSystem: Sized {
     /// Return the number of selectors.
     fn num_selectors(&self) -> usize;
 
+    /// Return the selectors of the lookup argument, or nothing if the system has no lookup table.
+    /// They are the lookup selector, which is one on the lookup gates, the table selector, which
+    /// is the identifier of the table of each lookup gate, and then the `N_LOOKUP_COLUMNS`
+    /// columns of the lookup tables, all of length `size`.
+    /// A lookup gate checks that its first `N_LOOKUP_INPUTS` wires and its table identifier
+    /// form a row of the columns.
+    fn lookup_selectors(&self) -> &[Vec<Self::Field>] {
+        &[]
+    }
+
+    /// Return, for each variable, the wire positions where it appears, in increasing order.
+    /// The positions are numbered as in `compute_permutation`.
+    /// It is useful to check that a copy constraint connects the expected wires.
+    fn variable_positions(&self) -> Vec<Vec<usize>> {
+        let mut positions: Vec<Vec<usize>> = vec![Vec::new(); self.num_vars()];
+        for (i, value) in self.wiring().iter().flatten().enumerate() {
+            positions[*value].push(i);
+        }
+        positions
+    }
+
+    /// Return statistics of the constraint system, to see which kinds of constraints dominate
+    /// its size. The padding constraints are the trailing ones without any selector, and their
+    /// wires are not counted in the number of wires per variable.
+    /// A system shrunk to verifier only has no wiring, so only its size and number of variables
+    /// are reported.
+    fn stats(&self) -> CsStats {
+        let n = self.size();
+        let mut stats = CsStats {
+            size: n,
+            num_vars: self.num_vars(),
+            ..CsStats::default()
+        };
+        if self.is_verifier_only() {
+            return stats;
+        }
+
+        let is_set = |selector: &[Self::Field], row: usize| {
+            selector.get(row).map_or(false, |value| !value.is_zero())
+        };
+        let selectors: Vec<&[Self::Field]> = (0..self.num_selectors())
+            .filter_map(|i| self.selector(i).ok())
+            .collect();
+        let anemoi_selectors = self.compute_anemoi_jive_selectors();
+        let q_lookup = self.lookup_selectors().first().map_or(&[][..], |q| &q[..]);
+        let public_rows = self.public_vars_constraint_indices();
+        let boolean_rows = self.boolean_constraint_indices();
+
+        stats.boolean_rows = boolean_rows.len();
+        stats.public_input_rows = public_rows.len();
+        stats.anemoi_rows = (0..n)
+            .filter(|&row| anemoi_selectors.iter().any(|s| is_set(s, row)))
+            .count();
+        stats.lookup_rows = (0..n).filter(|&row| is_set(q_lookup, row)).count();
+        stats.padding_rows = (0..n)
+            .rev()
+            .take_while(|&row| {
+                !selectors.iter().any(|s| is_set(s, row))
+                    && !anemoi_selectors.iter().any(|s| is_set(s, row))
+                    && !is_set(q_lookup, row)
+                    && !public_rows.contains(&row)
+                    && !boolean_rows.contains(&row)
+            })
+            .count();
+
+        let used_rows = n - stats.padding_rows;
+        for positions in self.variable_positions() {
+            let n_wires = positions
+                .iter()
+                .filter(|&&position| position % n < used_rows)
+                .count();
+            *stats.wires_per_variable.entry(n_wires).or_insert(0) += 1;
+        }
+        stats
+    }
+
     /// Compute the permutation implied by the copy constraints.
+    ///
+    /// The wire positions are numbered wire by wire, i.e., position `i * n + j` is the `i`-th
+    /// wire of the `j`-th gate. The positions of each variable form one cycle, in which each
+    /// position is mapped to the next position of the same variable, and the last one is
+    /// mapped to the first one.
     fn compute_permutation(&self) -> Vec<usize> {
         let n = self.size();
         let n_wires_per_gate = Self::n_wires_per_gate();
         let mut perm = vec![0usize; n_wires_per_gate * n];
-        let mut marked = vec![false; self.num_vars()];
-        let mut v = Vec::with_capacity(n_wires_per_gate * n);
-        for wire_slice in self.wiring().iter() {
-            v.extend_from_slice(wire_slice);
-        }
+
         // form a cycle for each variable value
-        // marked variables already processd
-        // for each unmarked variable, find all position where this variable occurs to form a cycle.
-        for (i, value) in v.iter().enumerate() {
-            if marked[*value] {
-                continue;
+        for cycle in self
+            .variable_positions()
+            .iter()
+            .filter(|cycle| !cycle.is_empty())
+        {
+            for pair in cycle.windows(2) {
+                perm[pair[0]] = pair[1];
             }
-            let first = i;
-            let mut prev = i;
-            for (j, current_value) in v[i + 1..].iter().enumerate() {
-                if current_value == value {
-                    perm[prev] = i + 1 + j; //current index in v
-                    prev = i + 1 + j;
-                }
-            }
-            perm[prev] = first;
-            marked[*value] = true
+            perm[cycle[cycle.len() - 1]] = cycle[0];
         }
         perm
     }
@@ -87,6 +213,50 @@ pub trait ConstraintSystem: Sized {
     /// Compute the indices of the witnesses related to public inputs.
     fn public_vars_witness_indices(&self) -> &[usize];
 
+    /// Check that the public input indices are consistent: both index vectors
+    /// have the same length, every index is in range, and the constraint
+    /// indices are strictly increasing (hence sorted and without duplicates).
+    fn validate_public_inputs(&self) -> Result<()> {
+        let cs_indices = self.public_vars_constraint_indices();
+        let witness_indices = self.public_vars_witness_indices();
+        if cs_indices.len() != witness_indices.len() {
+            return Err(eg!(format!(
+                "public inputs: {} constraint indices but {} witness indices",
+                cs_indices.len(),
+                witness_indices.len()
+            )));
+        }
+        if let Some(cs_index) = cs_indices.iter().find(|&&i| i >= self.size()) {
+            return Err(eg!(format!(
+                "public inputs: constraint index {} out of bound, size = {}",
+                cs_index,
+                self.size()
+            )));
+        }
+        if let Some(w_index) = witness_indices.iter().find(|&&i| i >= self.num_vars()) {
+            return Err(eg!(format!(
+                "public inputs: witness index {} out of bound, num_vars = {}",
+                w_index,
+                self.num_vars()
+            )));
+        }
+        for pair in cs_indices.windows(2) {
+            if pair[0] == pair[1] {
+                return Err(eg!(format!(
+                    "public inputs: duplicated constraint index {}",
+                    pair[0]
+                )));
+            }
+            if pair[0] > pair[1] {
+                return Err(eg!(format!(
+                    "public inputs: constraint indices not sorted, {} before {}",
+                    pair[0], pair[1]
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Compute the indices of the constraints that need a boolean constraint of the second, third, and fourth inputs.
     fn boolean_constraint_indices(&self) -> &[CsIndex];
 
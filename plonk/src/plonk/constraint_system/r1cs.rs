@@ -0,0 +1,384 @@
+//! The module exports a TurboPLONK constraint system as a rank-1 constraint system (R1CS),
+//! for the analysis of the circuits by external tooling.
+//!
+//! An R1CS is a list of constraints `<A_i, z> * <B_i, z> = <C_i, z>` over an assignment `z`,
+//! where `A_i`, `B_i`, `C_i` are the rows of three sparse matrices. The assignment starts
+//! with the constant one, followed by the variables of the TurboPLONK constraint system, and
+//! then by auxiliary variables for the products in the gates, each defined by the constraint
+//! that introduces it.
+use super::{turbo::TurboCS, ConstraintSystem, CsIndex, VarIndex};
+use noah_algebra::prelude::*;
+
+/// A sparse linear combination of the variables of an R1CS assignment.
+pub type LinearCombination<F> = Vec<(usize, F)>;
+
+/// The index of the constant one in an R1CS assignment.
+pub const R1CS_ONE: usize = 0;
+
+/// A rank-1 constraint system.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct R1CS<F> {
+    /// The number of variables of the assignment, including the constant one and the
+    /// auxiliary variables.
+    pub num_vars: usize,
+    /// The number of variables of the TurboPLONK constraint system, which take the indices
+    /// `1..=num_cs_vars` in the assignment.
+    pub num_cs_vars: usize,
+    /// The indices of the public inputs in the assignment.
+    pub public_inputs: Vec<usize>,
+    /// The rows of the matrix A.
+    pub a: Vec<LinearCombination<F>>,
+    /// The rows of the matrix B.
+    pub b: Vec<LinearCombination<F>>,
+    /// The rows of the matrix C.
+    pub c: Vec<LinearCombination<F>>,
+    /// The auxiliary variables, in the order of their indices: each is the product of the
+    /// evaluations of its pair of linear combinations.
+    pub aux_vars: Vec<(LinearCombination<F>, LinearCombination<F>)>,
+}
+
+impl<F: Scalar> R1CS<F> {
+    fn new(num_cs_vars: usize) -> Self {
+        Self {
+            num_vars: num_cs_vars + 1,
+            num_cs_vars,
+            public_inputs: vec![],
+            a: vec![],
+            b: vec![],
+            c: vec![],
+            aux_vars: vec![],
+        }
+    }
+
+    /// Return the number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Return the index in the assignment of a variable of the TurboPLONK constraint system.
+    pub fn var_index(cs_var: VarIndex) -> usize {
+        cs_var + 1
+    }
+
+    /// Build the full assignment from the witness of the TurboPLONK constraint system, by
+    /// computing the auxiliary variables.
+    pub fn assignment(&self, witness: &[F]) -> Result<Vec<F>> {
+        if witness.len() != self.num_cs_vars {
+            return Err(eg!(format!(
+                "witness len = {}, num_cs_vars = {}",
+                witness.len(),
+                self.num_cs_vars
+            )));
+        }
+        let mut z = Vec::with_capacity(self.num_vars);
+        z.push(F::one());
+        z.extend_from_slice(witness);
+        for (left, right) in self.aux_vars.iter() {
+            let value = eval_lc(left, &z).mul(&eval_lc(right, &z));
+            z.push(value);
+        }
+        Ok(z)
+    }
+
+    /// Check that the assignment satisfies every constraint.
+    pub fn verify_assignment(&self, z: &[F]) -> Result<()> {
+        if z.len() != self.num_vars {
+            return Err(eg!(format!(
+                "assignment len = {}, num_vars = {}",
+                z.len(),
+                self.num_vars
+            )));
+        }
+        if z[R1CS_ONE] != F::one() {
+            return Err(eg!("the first variable of the assignment is not one"));
+        }
+        for (i, ((a, b), c)) in self
+            .a
+            .iter()
+            .zip(self.b.iter())
+            .zip(self.c.iter())
+            .enumerate()
+        {
+            if eval_lc(a, z).mul(&eval_lc(b, z)) != eval_lc(c, z) {
+                return Err(eg!(format!("constraint {} is not satisfied", i)));
+            }
+        }
+        Ok(())
+    }
+
+    fn enforce(
+        &mut self,
+        a: LinearCombination<F>,
+        b: LinearCombination<F>,
+        c: LinearCombination<F>,
+    ) {
+        self.a.push(a);
+        self.b.push(b);
+        self.c.push(c);
+    }
+
+    fn enforce_zero(&mut self, lc: LinearCombination<F>) {
+        if lc.is_empty() {
+            return;
+        }
+        self.enforce(lc, vec![(R1CS_ONE, F::one())], vec![]);
+    }
+
+    fn mul(&mut self, left: LinearCombination<F>, right: LinearCombination<F>) -> usize {
+        let var = self.num_vars;
+        self.num_vars += 1;
+        self.enforce(left.clone(), right.clone(), vec![(var, F::one())]);
+        self.aux_vars.push((left, right));
+        var
+    }
+
+    fn pow5(&mut self, lc: LinearCombination<F>) -> usize {
+        let square = self.mul(lc.clone(), lc.clone());
+        let fourth = self.mul(vec![(square, F::one())], vec![(square, F::one())]);
+        self.mul(vec![(fourth, F::one())], lc)
+    }
+}
+
+fn eval_lc<F: Scalar>(lc: &[(usize, F)], z: &[F]) -> F {
+    let mut res = F::zero();
+    for (var, coef) in lc.iter() {
+        res.add_assign(&z[*var].mul(coef));
+    }
+    res
+}
+
+/// Add `coef * var` to a linear combination, merging the terms of the same variable.
+fn add_term<F: Scalar>(lc: &mut LinearCombination<F>, var: usize, coef: F) {
+    if coef.is_zero() {
+        return;
+    }
+    match lc.iter_mut().find(|(v, _)| *v == var) {
+        Some((_, c)) => c.add_assign(&coef),
+        None => lc.push((var, coef)),
+    }
+    lc.retain(|(_, c)| !c.is_zero());
+}
+
+impl<F: Scalar> TurboCS<F> {
+    /// Convert the constraint system into an equivalent R1CS, which the same witness satisfies.
+    ///
+    /// Each gate becomes a linear constraint, after one constraint per product of wires that
+    /// its selectors enable. Each boolean constraint becomes three constraints, and each round
+    /// of the Anemoi-Jive constraints becomes five linear constraints and ten products.
+    /// The public inputs of a gate are the variables that it takes from
+    /// `public_vars_witness_indices`, so the R1CS does not need the online values.
+    /// A constraint system with lookup gates is rejected, as table lookups have no direct
+    /// R1CS equivalent.
+    pub fn to_r1cs(&self) -> Result<R1CS<F>> {
+        if !self.lookup_selectors().is_empty() {
+            return Err(eg!(
+                "the constraint system has lookup gates, which cannot be converted to R1CS"
+            ));
+        }
+
+        let mut r1cs = R1CS::new(self.num_vars);
+        let one = F::one();
+        let wire = |i: usize, cs_index: CsIndex| R1CS::<F>::var_index(self.wiring[i][cs_index]);
+
+        r1cs.public_inputs = self
+            .public_vars_witness_indices
+            .iter()
+            .map(|w_i| R1CS::<F>::var_index(*w_i))
+            .collect();
+
+        for cs_index in 0..self.size() {
+            let sel = |i: usize| self.selectors[i][cs_index];
+            let w: Vec<usize> = (0..5).map(|i| wire(i, cs_index)).collect();
+
+            let mut lc = vec![];
+            for (i, w_i) in w.iter().take(4).enumerate() {
+                add_term(&mut lc, *w_i, sel(i));
+            }
+            add_term(&mut lc, R1CS_ONE, sel(6));
+            add_term(&mut lc, w[4], sel(8).neg());
+            for (c_i, w_i) in self
+                .public_vars_constraint_indices
+                .iter()
+                .zip(self.public_vars_witness_indices.iter())
+            {
+                if *c_i == cs_index {
+                    add_term(&mut lc, R1CS::<F>::var_index(*w_i), one);
+                }
+            }
+
+            let needs_w1w2 = !sel(4).is_zero() || !sel(7).is_zero();
+            let needs_w3w4 = !sel(5).is_zero() || !sel(7).is_zero();
+            let w1w2 = needs_w1w2.then(|| r1cs.mul(vec![(w[0], one)], vec![(w[1], one)]));
+            let w3w4 = needs_w3w4.then(|| r1cs.mul(vec![(w[2], one)], vec![(w[3], one)]));
+            if let Some(w1w2) = w1w2 {
+                add_term(&mut lc, w1w2, sel(4));
+            }
+            if let Some(w3w4) = w3w4 {
+                add_term(&mut lc, w3w4, sel(5));
+            }
+            if !sel(7).is_zero() {
+                let w1w2w3w4 = r1cs.mul(vec![(w1w2.unwrap(), one)], vec![(w3w4.unwrap(), one)]);
+                let ecc = r1cs.mul(vec![(w1w2w3w4, one)], vec![(w[4], one)]);
+                add_term(&mut lc, ecc, sel(7));
+            }
+            r1cs.enforce_zero(lc);
+        }
+
+        for cs_index in self.boolean_constraint_indices.iter() {
+            for i in 1..4 {
+                let w = wire(i, *cs_index);
+                r1cs.enforce(vec![(w, one)], vec![(w, one)], vec![(w, one)]);
+            }
+        }
+
+        let g = self.anemoi_generator;
+        let g2 = g.square().add(&one);
+        let g_inv = self.anemoi_generator_inv;
+        for cs_index in self.anemoi_constraints_indices.iter() {
+            for r in 0..12 {
+                let (a, b, c, d, o) = (
+                    wire(0, cs_index + r),
+                    wire(1, cs_index + r),
+                    wire(2, cs_index + r),
+                    wire(3, cs_index + r),
+                    wire(4, cs_index + r),
+                );
+                let (a_next, b_next, c_next, d_next) = (
+                    wire(0, cs_index + 1 + r),
+                    wire(1, cs_index + 1 + r),
+                    wire(2, cs_index + 1 + r),
+                    wire(3, cs_index + 1 + r),
+                );
+                let [prk_a, prk_b] = self.anemoi_preprocessed_round_keys_x[r];
+                let [prk_c, prk_d] = self.anemoi_preprocessed_round_keys_y[r];
+
+                // the output wire is the fourth wire of the next gate
+                let mut lc = vec![];
+                add_term(&mut lc, o, one);
+                add_term(&mut lc, d_next, one.neg());
+                r1cs.enforce_zero(lc);
+
+                // u1 = d + g * c + prk_c, and t1 = u1 - c_next
+                let mut u1 = vec![];
+                add_term(&mut u1, d, one);
+                add_term(&mut u1, c, g);
+                add_term(&mut u1, R1CS_ONE, prk_c);
+                let mut t1 = u1.clone();
+                add_term(&mut t1, c_next, one.neg());
+
+                // u2 = g * d + g2 * c + prk_d, and t2 = u2 - d_next
+                let mut u2 = vec![];
+                add_term(&mut u2, d, g);
+                add_term(&mut u2, c, g2);
+                add_term(&mut u2, R1CS_ONE, prk_d);
+                let mut t2 = u2.clone();
+                add_term(&mut t2, d_next, one.neg());
+
+                let t1_pow5 = r1cs.pow5(t1);
+                let t2_pow5 = r1cs.pow5(t2);
+                let u1_square = r1cs.mul(u1.clone(), u1);
+                let u2_square = r1cs.mul(u2.clone(), u2);
+                let c_next_square = r1cs.mul(vec![(c_next, one)], vec![(c_next, one)]);
+                let d_next_square = r1cs.mul(vec![(d_next, one)], vec![(d_next, one)]);
+
+                // equation 1: t1^5 + g * u1^2 = a + g * b + prk_a
+                let mut lc = vec![];
+                add_term(&mut lc, t1_pow5, one);
+                add_term(&mut lc, u1_square, g);
+                add_term(&mut lc, a, one.neg());
+                add_term(&mut lc, b, g.neg());
+                add_term(&mut lc, R1CS_ONE, prk_a.neg());
+                r1cs.enforce_zero(lc);
+
+                // equation 2: t2^5 + g * u2^2 = g * a + g2 * b + prk_b
+                let mut lc = vec![];
+                add_term(&mut lc, t2_pow5, one);
+                add_term(&mut lc, u2_square, g);
+                add_term(&mut lc, a, g.neg());
+                add_term(&mut lc, b, g2.neg());
+                add_term(&mut lc, R1CS_ONE, prk_b.neg());
+                r1cs.enforce_zero(lc);
+
+                // equation 3: t1^5 + g * c_next^2 + g_inv = a_next
+                let mut lc = vec![];
+                add_term(&mut lc, t1_pow5, one);
+                add_term(&mut lc, c_next_square, g);
+                add_term(&mut lc, R1CS_ONE, g_inv);
+                add_term(&mut lc, a_next, one.neg());
+                r1cs.enforce_zero(lc);
+
+                // equation 4: t2^5 + g * d_next^2 + g_inv = b_next
+                let mut lc = vec![];
+                add_term(&mut lc, t2_pow5, one);
+                add_term(&mut lc, d_next_square, g);
+                add_term(&mut lc, R1CS_ONE, g_inv);
+                add_term(&mut lc, b_next, one.neg());
+                r1cs.enforce_zero(lc);
+            }
+        }
+
+        Ok(r1cs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::R1CS;
+    use crate::plonk::constraint_system::TurboCS;
+    use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+    use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+
+    type F = BLSScalar;
+
+    #[test]
+    fn test_r1cs_satisfied_by_witness() {
+        let trace =
+            AnemoiJive381::eval_variable_length_hash_with_trace(&[F::from(1u64), F::from(2u64)]);
+
+        let mut cs = TurboCS::new();
+        cs.load_anemoi_jive_parameters::<AnemoiJive381>();
+
+        let one = cs.new_variable(F::from(1u64));
+        let two = cs.new_variable(F::from(2u64));
+        let three = cs.add(one, two);
+        let six = cs.mul(two, three);
+        let bits = cs.range_check(six, 8);
+        assert_eq!(bits.len(), 8);
+
+        let output_var = cs.new_variable(trace.output);
+        cs.anemoi_variable_length_hash(&trace, &[one, two], output_var);
+        cs.prepare_pi_variable(six);
+        cs.pad();
+
+        let witness = cs.get_and_clear_witness();
+        cs.verify_witness(&witness, &[F::from(6u64)]).unwrap();
+
+        let r1cs = cs.to_r1cs().unwrap();
+        assert_eq!(r1cs.public_inputs, vec![R1CS::<F>::var_index(six)]);
+        let z = r1cs.assignment(&witness).unwrap();
+        r1cs.verify_assignment(&z).unwrap();
+
+        // a witness violating an arithmetic gate does not satisfy the R1CS
+        let mut bad_witness = witness.clone();
+        bad_witness[six] = F::from(7u64);
+        let z = r1cs.assignment(&bad_witness).unwrap();
+        assert!(r1cs.verify_assignment(&z).is_err());
+
+        // neither does a witness with a wrong hash output
+        let mut bad_witness = witness;
+        bad_witness[output_var] = F::from(8u64);
+        let z = r1cs.assignment(&bad_witness).unwrap();
+        assert!(r1cs.verify_assignment(&z).is_err());
+    }
+
+    #[test]
+    fn test_r1cs_rejects_lookup_gates() {
+        let mut cs = TurboCS::<F>::new();
+        let byte_table = cs.create_byte_table();
+        let var = cs.new_variable(F::from(300u64));
+        cs.range_check_with_lookup(var, 16, byte_table);
+        cs.pad();
+
+        assert!(cs.to_r1cs().is_err());
+    }
+}
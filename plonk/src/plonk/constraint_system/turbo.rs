@@ -1,12 +1,17 @@
 //! It also implements a set of arithmetic/boolean/range gates that
 //! will be used in anonymous transfer.
-use super::{ConstraintSystem, CsIndex, VarIndex};
+use super::{
+    ConstraintSystem, CsIndex, TableId, VarIndex, N_LOOKUP_COLUMNS, N_LOOKUP_INPUTS,
+    N_LOOKUP_SELECTORS,
+};
 use crate::plonk::errors::PlonkError;
 use noah_algebra::prelude::*;
 
 use noah_crypto::basic::anemoi_jive::AnemoiJive;
+use std::borrow::Cow;
 #[cfg(feature = "debug")]
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 /// The wires number of a gate in Turbo CS.
 pub const N_WIRES_PER_GATE: usize = 5;
@@ -17,7 +22,8 @@ pub const N_SELECTORS: usize = 9;
 /// Turbo PLONK Constraint System.
 #[derive(Serialize, Deserialize)]
 pub struct TurboCS<F> {
-    /// the selectors of the circuit.
+    /// the selectors of the circuit, followed by the selectors of the lookup argument
+    /// once a lookup table is created.
     pub selectors: Vec<Vec<F>>,
     /// the wiring of the circuit.
     pub wiring: [Vec<VarIndex>; N_WIRES_PER_GATE],
@@ -51,6 +57,25 @@ pub struct TurboCS<F> {
     pub witness_backtrace: HashMap<VarIndex, std::backtrace::Backtrace>,
 }
 
+/// The circuit of a Turbo CS, i.e., everything but the witness, as serialized by
+/// `TurboCS::noah_to_bytes`.
+#[derive(Serialize, Deserialize)]
+struct TurboCSCircuit<'a, F: Clone> {
+    selectors: Cow<'a, [Vec<F>]>,
+    wiring: Cow<'a, [Vec<VarIndex>; N_WIRES_PER_GATE]>,
+    anemoi_preprocessed_round_keys_x: [[F; 2]; 12],
+    anemoi_preprocessed_round_keys_y: [[F; 2]; 12],
+    anemoi_generator: F,
+    anemoi_generator_inv: F,
+    anemoi_constraints_indices: Cow<'a, [CsIndex]>,
+    num_vars: usize,
+    size: usize,
+    public_vars_constraint_indices: Cow<'a, [CsIndex]>,
+    public_vars_witness_indices: Cow<'a, [VarIndex]>,
+    boolean_constraint_indices: Cow<'a, [CsIndex]>,
+    verifier_only: bool,
+}
+
 impl<F: Scalar> ConstraintSystem for TurboCS<F> {
     type Field = F;
 
@@ -85,6 +110,10 @@ impl<F: Scalar> ConstraintSystem for TurboCS<F> {
         N_SELECTORS
     }
 
+    fn lookup_selectors(&self) -> &[Vec<F>] {
+        self.selectors.get(N_SELECTORS..).unwrap_or(&[])
+    }
+
     fn public_vars_constraint_indices(&self) -> &[CsIndex] {
         &self.public_vars_constraint_indices
     }
@@ -667,6 +696,132 @@ impl<F: Scalar> TurboCS<F> {
         (diff_is_zero, mul_var)
     }
 
+    /// Return a boolean variable that equals `left_var` AND `right_var`.
+    /// The inputs are assumed to be already boolean-constrained, which is not enforced here.
+    /// It uses one gate: wo = w1 * w2.
+    pub fn and(&mut self, left_var: VarIndex, right_var: VarIndex) -> VarIndex {
+        self.mul(left_var, right_var)
+    }
+
+    /// Return a boolean variable that equals `left_var` OR `right_var`.
+    /// The inputs are assumed to be already boolean-constrained, which is not enforced here.
+    /// It uses one gate: wo = w1 + w2 - w1 * w2.
+    pub fn or(&mut self, left_var: VarIndex, right_var: VarIndex) -> VarIndex {
+        self.insert_boolean_op_gate(left_var, right_var, F::one().neg())
+    }
+
+    /// Return a boolean variable that equals `left_var` XOR `right_var`.
+    /// The inputs are assumed to be already boolean-constrained, which is not enforced here.
+    /// It uses one gate: wo = w1 + w2 - 2 * w1 * w2.
+    pub fn xor(&mut self, left_var: VarIndex, right_var: VarIndex) -> VarIndex {
+        let one = F::one();
+        self.insert_boolean_op_gate(left_var, right_var, one.add(&one).neg())
+    }
+
+    /// Create an output variable and insert a gate: wo = w1 + w2 + q_mul * w1 * w2.
+    /// Wires: (w1, w2, w3 , w4) = (left_var, right_var, 0, 0)
+    /// Selectors: q1 = q2 = qo = 1, qm1 = q_mul
+    fn insert_boolean_op_gate(
+        &mut self,
+        left_var: VarIndex,
+        right_var: VarIndex,
+        q_mul: F,
+    ) -> VarIndex {
+        assert!(left_var < self.num_vars, "left_var index out of bound");
+        assert!(right_var < self.num_vars, "right_var index out of bound");
+        let zero = F::zero();
+        let one = F::one();
+        let left = self.witness[left_var];
+        let right = self.witness[right_var];
+        let out = left.add(&right).add(&q_mul.mul(&left).mul(&right));
+        let out_var = self.new_variable(out);
+
+        self.push_add_selectors(one, one, zero, zero);
+        self.push_mul_selectors(q_mul, zero);
+        self.push_constant_selector(zero);
+        self.push_ecc_selector(zero);
+        self.push_out_selector(one);
+
+        self.wiring[0].push(left_var);
+        self.wiring[1].push(right_var);
+        self.wiring[2].push(0);
+        self.wiring[3].push(0);
+        self.wiring[4].push(out_var);
+        self.finish_new_gate();
+        out_var
+    }
+
+    /// Return a boolean variable that equals 1 if and only if the value represented by
+    /// `a_bits` is smaller than the value represented by `b_bits`.
+    ///
+    /// Both inputs are little-endian binary representations of the same bit width,
+    /// e.g., as returned by `range_check`. The bits are assumed to be already
+    /// boolean-constrained, which is not enforced here.
+    pub fn less_than(&mut self, a_bits: &[VarIndex], b_bits: &[VarIndex]) -> VarIndex {
+        assert_eq!(a_bits.len(), b_bits.len(), "the bit widths do not match");
+        let zero = F::zero();
+        let one = F::one();
+        let zero_var = self.zero_var();
+
+        // `is_less` is 1 if a < b has been decided by the bits processed so far, and
+        // `undecided` is 1 as long as all the bits processed so far are equal.
+        let mut is_less = zero_var;
+        let mut undecided = self.one_var();
+
+        // Scan the bits starting from the most significant one.
+        for (&a, &b) in a_bits.iter().zip(b_bits.iter()).rev() {
+            let a_and_b = self.and(a, b);
+            // `a_lt_b` = (1 - a) * b
+            let a_lt_b = self.sub(b, a_and_b);
+            let a_xor_b = self.xor(a, b);
+
+            // `is_less` = `is_less` + `undecided` * `a_lt_b`
+            is_less = {
+                let value =
+                    self.witness[is_less].add(&self.witness[undecided].mul(&self.witness[a_lt_b]));
+                let res = self.new_variable(value);
+
+                self.push_add_selectors(zero, zero, one, zero);
+                self.push_mul_selectors(one, zero);
+                self.push_constant_selector(zero);
+                self.push_ecc_selector(zero);
+                self.push_out_selector(one);
+
+                self.wiring[0].push(undecided);
+                self.wiring[1].push(a_lt_b);
+                self.wiring[2].push(is_less);
+                self.wiring[3].push(zero_var);
+                self.wiring[4].push(res);
+                self.finish_new_gate();
+
+                res
+            };
+
+            // `undecided` = `undecided` * (1 - `a_xor_b`)
+            undecided = {
+                let value = self.witness[undecided].mul(&one.sub(&self.witness[a_xor_b]));
+                let res = self.new_variable(value);
+
+                self.push_add_selectors(one, zero, zero, zero);
+                self.push_mul_selectors(one.neg(), zero);
+                self.push_constant_selector(zero);
+                self.push_ecc_selector(zero);
+                self.push_out_selector(one);
+
+                self.wiring[0].push(undecided);
+                self.wiring[1].push(a_xor_b);
+                self.wiring[2].push(zero_var);
+                self.wiring[3].push(zero_var);
+                self.wiring[4].push(res);
+                self.finish_new_gate();
+
+                res
+            };
+        }
+
+        is_less
+    }
+
     /// Add a constant constraint: wo = constant.
     pub fn insert_constant_gate(&mut self, var: VarIndex, constant: F) {
         assert!(var < self.num_vars, "variable index out of bound");
@@ -742,12 +897,181 @@ impl<F: Scalar> TurboCS<F> {
         self.anemoi_generator_inv = H::GENERATOR_INV;
     }
 
-    /// Pad the number of constraints to a power of two.
+    /// Create a lookup table from its entries, each of at most `N_LOOKUP_INPUTS` values,
+    /// which are padded with zeros, and return its identifier for `lookup_gate`.
+    pub fn create_lookup_table(&mut self, entries: &[Vec<F>]) -> TableId {
+        assert!(!entries.is_empty(), "empty lookup table");
+        assert!(
+            entries.iter().all(|entry| entry.len() <= N_LOOKUP_INPUTS),
+            "lookup table entry too long"
+        );
+        if self.selectors.len() == N_SELECTORS {
+            // the lookup and table selectors, and the columns, which start with the zero row
+            self.selectors.push(vec![]);
+            self.selectors.push(vec![]);
+            for _ in 0..N_LOOKUP_COLUMNS {
+                self.selectors.push(vec![F::zero()]);
+            }
+        }
+
+        let table = self.num_lookup_tables() + 1;
+        let table_id = F::from(table as u64);
+        for entry in entries.iter() {
+            for i in 0..N_LOOKUP_INPUTS {
+                let value = entry.get(i).copied().unwrap_or_else(F::zero);
+                self.selectors[N_SELECTORS + 2 + i].push(value);
+            }
+            self.selectors[N_SELECTORS + 2 + N_LOOKUP_INPUTS].push(table_id);
+        }
+        table
+    }
+
+    /// Return the number of lookup tables.
+    pub fn num_lookup_tables(&self) -> usize {
+        match self.lookup_selectors().last() {
+            Some(table_ids) => table_ids
+                .windows(2)
+                .filter(|pair| pair[0] != pair[1])
+                .count(),
+            None => 0,
+        }
+    }
+
+    /// Add a lookup gate, which checks that the values of `inputs`, padded with the zero
+    /// variable to `N_LOOKUP_INPUTS` variables, form an entry of the lookup table `table`.
+    pub fn lookup_gate(&mut self, table: TableId, inputs: &[VarIndex]) {
+        assert!(
+            (1..=self.num_lookup_tables()).contains(&table),
+            "lookup table not found"
+        );
+        assert!(inputs.len() <= N_LOOKUP_INPUTS, "too many lookup inputs");
+        assert!(
+            inputs.iter().all(|&x| x < self.num_vars),
+            "input wire index out of bound"
+        );
+        let zero = F::zero();
+        self.push_add_selectors(zero, zero, zero, zero);
+        self.push_mul_selectors(zero, zero);
+        self.push_constant_selector(zero);
+        self.push_ecc_selector(zero);
+        self.push_out_selector(zero);
+
+        for i in 0..N_WIRES_PER_GATE {
+            self.wiring[i].push(inputs.get(i).copied().unwrap_or(0));
+        }
+
+        // the lookup and table selectors are only extended up to the lookup gates
+        let size = self.size;
+        self.selectors[N_SELECTORS].resize(size, zero);
+        self.selectors[N_SELECTORS].push(F::one());
+        self.selectors[N_SELECTORS + 1].resize(size, zero);
+        self.selectors[N_SELECTORS + 1].push(F::from(table as u64));
+        self.finish_new_gate();
+    }
+
+    /// Create a lookup table of the 256 bytes, for `range_check_with_lookup`.
+    pub fn create_byte_table(&mut self) -> TableId {
+        let entries: Vec<Vec<F>> = (0..256u32).map(|byte| vec![F::from(byte)]).collect();
+        self.create_lookup_table(&entries)
+    }
+
+    /// Enforce a range constraint: `0 <= witness[var] < 2^n_bits`, where `n_bits` is a multiple
+    /// of 8 and at least 16, with the byte table `byte_table` from `create_byte_table`:
+    /// 1. Transform `witness[var]` into a byte vector and look up each byte in the table.
+    /// 2. Add a set of linear combination constraints showing that the byte vector is the
+    ///    representation of `witness[var]`.
+    /// 3. Return witness indices of the byte vector, in little endian form.
+    ///
+    /// It takes about `n_bits / 6` constraints, instead of about `n_bits / 3` for `range_check`.
+    pub fn range_check_with_lookup(
+        &mut self,
+        var: VarIndex,
+        n_bits: usize,
+        byte_table: TableId,
+    ) -> Vec<VarIndex> {
+        assert!(var < self.num_vars, "var index out of bound");
+        assert!(
+            n_bits >= 16 && n_bits % 8 == 0,
+            "the number of bits is not a multiple of 8 at least 16"
+        );
+        let n_bytes = n_bits / 8;
+        let mut witness_bytes = self.witness[var].to_bytes();
+        if witness_bytes.len() < n_bytes {
+            witness_bytes.resize(n_bytes, 0);
+        }
+
+        let b: Vec<VarIndex> = witness_bytes
+            .into_iter()
+            .take(n_bytes)
+            .map(|byte| self.new_variable(F::from(byte as u32)))
+            .collect();
+        for byte in b.iter() {
+            self.lookup_gate(byte_table, &[*byte]);
+        }
+
+        let shift = [
+            F::one(),
+            F::from(1u32 << 8),
+            F::from(1u32 << 16),
+            F::from(1u32 << 24),
+        ];
+
+        let mut acc = b[n_bytes - 1];
+        let m = (n_bytes - 2) / 3;
+        for i in 0..m {
+            acc = self.linear_combine(
+                &[
+                    acc,
+                    b[n_bytes - 1 - i * 3 - 1],
+                    b[n_bytes - 1 - i * 3 - 2],
+                    b[n_bytes - 1 - i * 3 - 3],
+                ],
+                shift[3],
+                shift[2],
+                shift[1],
+                shift[0],
+            );
+        }
+        let zero = F::zero();
+        match (n_bytes - 1) - 3 * m {
+            1 => self.insert_lc_gate(&[acc, b[0], 0, 0], var, shift[1], shift[0], zero, zero),
+            2 => self.insert_lc_gate(
+                &[acc, b[1], b[0], 0],
+                var,
+                shift[2],
+                shift[1],
+                shift[0],
+                zero,
+            ),
+            _ => self.insert_lc_gate(
+                &[acc, b[2], b[1], b[0]],
+                var,
+                shift[3],
+                shift[2],
+                shift[1],
+                shift[0],
+            ),
+        }
+        b
+    }
+
+    /// Pad the number of constraints to a power of two, which is at least the number of rows
+    /// of the lookup tables.
     pub fn pad(&mut self) {
-        let n = self.size.next_power_of_two();
+        let table_len = self
+            .lookup_selectors()
+            .last()
+            .map_or(0, |column| column.len());
+        let n = self.size.max(table_len).next_power_of_two();
         let diff = n - self.size();
-        for selector in self.selectors.iter_mut() {
-            selector.extend(vec![F::zero(); diff]);
+        for (i, selector) in self.selectors.iter_mut().enumerate() {
+            if i < N_SELECTORS + 2 {
+                selector.resize(n, F::zero());
+            } else {
+                // the columns of the lookup tables are padded with their last row
+                let last = *selector.last().unwrap();
+                selector.resize(n, last);
+            }
         }
         for wire in self.wiring.iter_mut() {
             wire.extend(vec![0; diff]);
@@ -764,6 +1088,91 @@ impl<F: Scalar> TurboCS<F> {
         }
     }
 
+    /// Serialize the circuit, so that it can be cached instead of being rebuilt.
+    /// The witness is not serialized. The constraint system must be padded, or shrunk
+    /// to verifier only, in which case only its size and number of variables are kept.
+    pub fn noah_to_bytes(&self) -> Result<Vec<u8>> {
+        if self.size == 0 {
+            return Err(eg!("cannot serialize an empty constraint system"));
+        }
+        if !self.verifier_only
+            && (!self.size.is_power_of_two()
+                || self.selectors.iter().any(|s| s.len() != self.size)
+                || self.wiring.iter().any(|w| w.len() != self.size))
+        {
+            return Err(eg!(format!(
+                "the constraint system of size {} must be padded before serialization",
+                self.size
+            )));
+        }
+
+        let circuit = TurboCSCircuit {
+            selectors: Cow::Borrowed(&self.selectors[..]),
+            wiring: Cow::Borrowed(&self.wiring),
+            anemoi_preprocessed_round_keys_x: self.anemoi_preprocessed_round_keys_x,
+            anemoi_preprocessed_round_keys_y: self.anemoi_preprocessed_round_keys_y,
+            anemoi_generator: self.anemoi_generator,
+            anemoi_generator_inv: self.anemoi_generator_inv,
+            anemoi_constraints_indices: Cow::Borrowed(&self.anemoi_constraints_indices[..]),
+            num_vars: self.num_vars,
+            size: self.size,
+            public_vars_constraint_indices: Cow::Borrowed(&self.public_vars_constraint_indices[..]),
+            public_vars_witness_indices: Cow::Borrowed(&self.public_vars_witness_indices[..]),
+            boolean_constraint_indices: Cow::Borrowed(&self.boolean_constraint_indices[..]),
+            verifier_only: self.verifier_only,
+        };
+        bincode::serialize(&circuit).c(d!())
+    }
+
+    /// Deserialize a constraint system serialized by `noah_to_bytes`, with an empty witness.
+    pub fn noah_from_bytes(bytes: &[u8]) -> Result<Self> {
+        let circuit: TurboCSCircuit<F> =
+            bincode::deserialize(bytes).c(d!(PlonkError::DeserializationError))?;
+
+        if !circuit.verifier_only {
+            let size = circuit.size;
+            let num_vars = circuit.num_vars;
+            let valid = (circuit.selectors.len() == N_SELECTORS
+                || circuit.selectors.len() == N_SELECTORS + N_LOOKUP_SELECTORS)
+                && circuit.selectors.iter().all(|s| s.len() == size)
+                && circuit.wiring.iter().all(|w| w.len() == size)
+                && circuit.wiring.iter().flatten().all(|&v| v < num_vars)
+                && circuit
+                    .public_vars_witness_indices
+                    .iter()
+                    .all(|&v| v < num_vars)
+                && circuit
+                    .anemoi_constraints_indices
+                    .iter()
+                    .chain(circuit.public_vars_constraint_indices.iter())
+                    .chain(circuit.boolean_constraint_indices.iter())
+                    .all(|&i| i < size);
+            if !valid {
+                return Err(eg!(PlonkError::DeserializationError));
+            }
+        }
+
+        Ok(Self {
+            selectors: circuit.selectors.into_owned(),
+            wiring: circuit.wiring.into_owned(),
+            anemoi_preprocessed_round_keys_x: circuit.anemoi_preprocessed_round_keys_x,
+            anemoi_preprocessed_round_keys_y: circuit.anemoi_preprocessed_round_keys_y,
+            anemoi_generator: circuit.anemoi_generator,
+            anemoi_generator_inv: circuit.anemoi_generator_inv,
+            anemoi_constraints_indices: circuit.anemoi_constraints_indices.into_owned(),
+            num_vars: circuit.num_vars,
+            size: circuit.size,
+            public_vars_constraint_indices: circuit.public_vars_constraint_indices.into_owned(),
+            public_vars_witness_indices: circuit.public_vars_witness_indices.into_owned(),
+            boolean_constraint_indices: circuit.boolean_constraint_indices.into_owned(),
+            verifier_only: circuit.verifier_only,
+            witness: vec![],
+
+            #[cfg(feature = "debug")]
+            witness_backtrace: HashMap::new(),
+        })
+    }
+
     /// Add a Add selectors.
     pub fn push_add_selectors(&mut self, q1: F, q2: F, q3: F, q4: F) {
         self.selectors[0].push(q1);
@@ -878,6 +1287,26 @@ impl<F: Scalar> TurboCS<F> {
             }
         }
 
+        let lookup_selectors = self.lookup_selectors();
+        if !lookup_selectors.is_empty() {
+            let table = self.lookup_table_rows();
+            for (cs_index, q_lookup) in lookup_selectors[0].iter().enumerate() {
+                if q_lookup.is_zero() {
+                    continue;
+                }
+                let mut row: Vec<F> = (0..N_LOOKUP_INPUTS)
+                    .map(|i| witness[self.get_witness_index(i, cs_index)])
+                    .collect();
+                row.push(lookup_selectors[1][cs_index]);
+                if !table.contains(&lookup_row_bytes(&row)) {
+                    return Err(eg!(format!(
+                        "cs index {}: the lookup ({:?}) is not in the lookup tables",
+                        cs_index, row
+                    )));
+                }
+            }
+        }
+
         if !self.anemoi_constraints_indices.is_empty() {
             assert!(!self.anemoi_generator.is_zero());
         }
@@ -963,6 +1392,127 @@ impl<F: Scalar> TurboCS<F> {
         Ok(())
     }
 
+    /// Return the rows of the lookup tables, each followed by the identifier of its table,
+    /// encoded by `lookup_row_bytes`.
+    fn lookup_table_rows(&self) -> HashSet<Vec<u8>> {
+        let columns = match self.lookup_selectors().get(2..) {
+            Some(columns) if !columns.is_empty() => columns,
+            _ => return HashSet::new(),
+        };
+        (0..columns[0].len())
+            .map(|i| {
+                let row: Vec<F> = columns.iter().map(|column| column[i]).collect();
+                lookup_row_bytes(&row)
+            })
+            .collect()
+    }
+
+    /// Find the first gate which is not satisfied by the given witness and publics, for
+    /// debugging a failing `verify_witness` or proof. Return the index of the gate together
+    /// with its residual, i.e., the first nonzero term among its gate equation, its boolean
+    /// constraints and its Anemoi constraints, evaluated as in the quotient polynomial.
+    /// The lookup argument has no equation per gate, so a lookup gate whose values are not
+    /// an entry of its table has its lookup selector as residual.
+    pub fn find_unsatisfied_gate(
+        &self,
+        witness: &[F],
+        public_inputs: &[F],
+    ) -> Option<(CsIndex, F)> {
+        assert_eq!(witness.len(), self.num_vars, "wrong witness length");
+        assert_eq!(
+            public_inputs.len(),
+            self.public_vars_constraint_indices.len(),
+            "wrong number of public inputs"
+        );
+        let n = self.size;
+        let extended_witness = self.extend_witness(witness);
+        let mut pi = vec![F::zero(); n];
+        for (cs_index, value) in self
+            .public_vars_constraint_indices
+            .iter()
+            .zip(public_inputs.iter())
+        {
+            pi[*cs_index] = *value;
+        }
+
+        let anemoi_selectors = self.compute_anemoi_jive_selectors();
+        let g = self.anemoi_generator;
+        let g2 = g.square().add(F::one());
+        let g_inv = self.anemoi_generator_inv;
+        let five = &[5u64];
+
+        let boolean_constraint_indices: HashSet<CsIndex> =
+            self.boolean_constraint_indices.iter().copied().collect();
+        let lookup_selectors = self.lookup_selectors();
+        let lookup_table = self.lookup_table_rows();
+
+        for cs_index in 0..n {
+            let w: Vec<&F> = (0..N_WIRES_PER_GATE)
+                .map(|i| &extended_witness[i * n + cs_index])
+                .collect();
+            let sel_vals: Vec<&F> = (0..N_SELECTORS)
+                .map(|i| &self.selectors[i][cs_index])
+                .collect();
+            let mut residuals = vec![Self::eval_gate_func(&w, &sel_vals, &pi[cs_index]).unwrap()];
+
+            // w[i] (w[i] - 1), for i = 1, 2, 3
+            if boolean_constraint_indices.contains(&cs_index) {
+                for w_i in w[1..4].iter().map(|w_i| **w_i) {
+                    residuals.push(w_i.mul(&w_i.sub(&F::one())));
+                }
+            }
+
+            // the looked-up values, with the table identifier, must be a row of the tables
+            if let Some(q_lookup) = lookup_selectors
+                .first()
+                .and_then(|q_lookups| q_lookups.get(cs_index))
+                .filter(|q_lookup| !q_lookup.is_zero())
+            {
+                let mut row: Vec<F> = w[..N_LOOKUP_INPUTS].iter().map(|w_i| **w_i).collect();
+                row.push(lookup_selectors[1][cs_index]);
+                if !lookup_table.contains(&lookup_row_bytes(&row)) {
+                    residuals.push(*q_lookup);
+                }
+            }
+
+            // the four Anemoi equations, multiplied by q_{prk3}
+            let q_prk = [
+                anemoi_selectors[0][cs_index],
+                anemoi_selectors[1][cs_index],
+                anemoi_selectors[2][cs_index],
+                anemoi_selectors[3][cs_index],
+            ];
+            if !q_prk[2].is_zero() {
+                let next = (cs_index + 1) % n;
+                let w0_next = extended_witness[next];
+                let w1_next = extended_witness[n + next];
+                let w2_next = extended_witness[2 * n + next];
+
+                let tmp = *w[3] + g * w[2] + q_prk[2];
+                residuals.push(q_prk[2].mul(
+                    (tmp - &w2_next).pow(five) + g * tmp.square() - &(*w[0] + g * w[1] + q_prk[0]),
+                ));
+                residuals.push(
+                    q_prk[2]
+                        .mul((tmp - &w2_next).pow(five) + g * w2_next.square() + g_inv - &w0_next),
+                );
+
+                let tmp = g * w[3] + g2 * w[2] + q_prk[3];
+                residuals.push(q_prk[2].mul(
+                    (tmp - w[4]).pow(five) + g * tmp.square() - &(g * w[0] + g2 * w[1] + q_prk[1]),
+                ));
+                residuals.push(
+                    q_prk[2].mul((tmp - w[4]).pow(five) + g * w[4].square() + g_inv - &w1_next),
+                );
+            }
+
+            if let Some(residual) = residuals.into_iter().find(|r| !r.is_zero()) {
+                return Some((cs_index, residual));
+            }
+        }
+        None
+    }
+
     /// Extract and clear the entire witness of the circuit. The witness consists of
     /// secret inputs, public inputs, and the values of intermediate variables.
     pub fn get_and_clear_witness(&mut self) -> Vec<F> {
@@ -972,17 +1522,26 @@ impl<F: Scalar> TurboCS<F> {
     }
 }
 
+/// Encode a row of a lookup table, for comparing rows.
+fn lookup_row_bytes<F: Scalar>(row: &[F]) -> Vec<u8> {
+    row.iter().flat_map(|v| v.to_bytes()).collect()
+}
+
 #[cfg(test)]
 mod test {
     use crate::plonk::{
         constraint_system::{ConstraintSystem, TurboCS},
-        indexer::indexer,
-        prover::prover,
-        verifier::verifier,
+        indexer::{indexer, PlonkPK, PlonkProof},
+        prover::{prove_deterministic, prover, prover_with_options, LagrangeMode, ProvingOptions},
+        verifier::{assert_proof_binds_to, verifier},
+    };
+    use crate::poly_commit::{
+        field_polynomial::FpPolynomial, kzg_poly_com::KZGCommitmentScheme, pcs::PolyComScheme,
     };
-    use crate::poly_commit::{kzg_poly_com::KZGCommitmentScheme, pcs::PolyComScheme};
     use merlin::Transcript;
     use noah_algebra::{bls12_381::BLSScalar, prelude::*};
+    use serde::{de::DeserializeOwned, Serialize};
+    use std::fmt::Debug;
 
     type F = BLSScalar;
 
@@ -1096,6 +1655,62 @@ mod test {
         assert!(cs.verify_witness(&witness, &[]).is_err());
     }
 
+    #[test]
+    fn test_less_than() {
+        let mut prng = test_rng();
+        for _ in 0..10 {
+            let x = prng.next_u32();
+            let y = prng.next_u32();
+            for (a, b) in [(x, y), (x, x), (y, x)] {
+                let mut cs = TurboCS::new();
+                let a_var = cs.new_variable(F::from(a));
+                let b_var = cs.new_variable(F::from(b));
+                let a_bits = cs.range_check(a_var, 32);
+                let b_bits = cs.range_check(b_var, 32);
+                let res = cs.less_than(&a_bits, &b_bits);
+                assert_eq!(cs.witness[res], F::from((a < b) as u32));
+
+                let mut witness = cs.get_and_clear_witness();
+                pnk!(cs.verify_witness(&witness, &[]));
+
+                witness[res] = F::one().sub(&witness[res]);
+                assert!(cs.verify_witness(&witness, &[]).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_boolean_ops() {
+        for a in 0..2u32 {
+            for b in 0..2u32 {
+                let mut cs = TurboCS::new();
+                let a_var = cs.new_variable(F::from(a));
+                let b_var = cs.new_variable(F::from(b));
+                cs.insert_boolean_gate(a_var);
+                cs.insert_boolean_gate(b_var);
+                let n_gates = cs.size;
+
+                let and_var = cs.and(a_var, b_var);
+                let or_var = cs.or(a_var, b_var);
+                let xor_var = cs.xor(a_var, b_var);
+                assert_eq!(cs.size, n_gates + 3);
+
+                assert_eq!(cs.witness[and_var], F::from(a & b));
+                assert_eq!(cs.witness[or_var], F::from(a | b));
+                assert_eq!(cs.witness[xor_var], F::from(a ^ b));
+
+                let mut witness = cs.get_and_clear_witness();
+                pnk!(cs.verify_witness(&witness, &[]));
+
+                for var in [and_var, or_var, xor_var] {
+                    witness[var] = F::one().sub(&witness[var]);
+                    assert!(cs.verify_witness(&witness, &[]).is_err());
+                    witness[var] = F::one().sub(&witness[var]);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_turbo_plonk_circuit_1() {
         let mut cs = TurboCS::new();
@@ -1337,6 +1952,694 @@ mod test {
             .is_err());
     }
 
+    #[test]
+    fn test_validate_public_inputs() {
+        let mut prng = test_rng();
+        let pcs = KZGCommitmentScheme::new(20, &mut prng);
+
+        let mut cs = TurboCS::<F>::new();
+        let a = cs.new_variable(F::one());
+        let b = cs.new_variable(F::from(2u32));
+        let c = cs.add(a, b);
+        cs.prepare_pi_variable(a);
+        cs.prepare_pi_variable(c);
+        cs.pad();
+        pnk!(cs.validate_public_inputs());
+        assert!(indexer(&cs, &pcs).is_ok());
+
+        // Duplicate the last public-input constraint index.
+        let last = *cs.public_vars_constraint_indices.last().unwrap();
+        cs.public_vars_constraint_indices.push(last);
+        cs.public_vars_witness_indices.push(b);
+        assert!(cs.validate_public_inputs().is_err());
+        assert!(indexer(&cs, &pcs).is_err());
+
+        // Misaligned index vectors.
+        cs.public_vars_constraint_indices.pop();
+        assert!(cs.validate_public_inputs().is_err());
+
+        // Out-of-range constraint index.
+        cs.public_vars_witness_indices.pop();
+        cs.public_vars_constraint_indices.push(cs.size);
+        cs.public_vars_witness_indices.push(b);
+        assert!(cs.validate_public_inputs().is_err());
+    }
+
+    #[test]
+    fn test_compute_permutation() {
+        let mut prng = test_rng();
+        let mut cs = TurboCS::<F>::new();
+        let mut vars: Vec<_> = (0..5)
+            .map(|_| cs.new_variable(F::random(&mut prng)))
+            .collect();
+        for i in 0..30 {
+            let a = vars[(7 * i) % vars.len()];
+            let b = vars[(3 * i + 1) % vars.len()];
+            let c = match i % 3 {
+                0 => cs.add(a, b),
+                1 => cs.mul(a, b),
+                _ => cs.sub(a, a),
+            };
+            vars.push(c);
+        }
+        cs.pad();
+
+        // the quadratic algorithm scanning the wires for each new variable
+        let n_wires = TurboCS::<F>::n_wires_per_gate() * cs.size();
+        let v: Vec<usize> = cs.wiring().iter().flatten().copied().collect();
+        let mut expected = vec![0usize; n_wires];
+        let mut marked = vec![false; cs.num_vars()];
+        for (i, value) in v.iter().enumerate() {
+            if marked[*value] {
+                continue;
+            }
+            let mut prev = i;
+            for (j, current_value) in v[i + 1..].iter().enumerate() {
+                if current_value == value {
+                    expected[prev] = i + 1 + j;
+                    prev = i + 1 + j;
+                }
+            }
+            expected[prev] = i;
+            marked[*value] = true;
+        }
+
+        let perm = cs.compute_permutation();
+        assert_eq!(perm, expected);
+        let mut sorted = perm.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..n_wires).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_variable_positions() {
+        let mut cs = TurboCS::<F>::new();
+        let a = cs.new_variable(F::one());
+        let b = cs.new_variable(F::from(2u32));
+        let c = cs.add(a, b);
+        let d = cs.mul(c, a);
+        cs.pad();
+
+        let n = cs.size();
+        let positions = cs.variable_positions();
+        assert_eq!(positions.len(), cs.num_vars());
+        // `a` is the left input of the first gate and the right input of the second gate
+        assert_eq!(positions[a], vec![0, n + 1]);
+        // `b` is the right input of the first gate
+        assert_eq!(positions[b], vec![n]);
+        // `c` is the output of the first gate and the left input of the second gate
+        assert_eq!(positions[c], vec![1, 4 * n]);
+        // `d` is the output of the second gate
+        assert_eq!(positions[d], vec![4 * n + 1]);
+        // the constant one is not used
+        assert!(positions[cs.one_var()].is_empty());
+
+        // every wire position appears exactly once
+        let mut all: Vec<usize> = positions.into_iter().flatten().collect();
+        all.sort_unstable();
+        assert_eq!(
+            all,
+            (0..TurboCS::<F>::n_wires_per_gate() * n).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_cs_bytes() {
+        let mut prng = test_rng();
+        let pcs = KZGCommitmentScheme::new(20, &mut prng);
+
+        let mut cs = TurboCS::<F>::new();
+        assert!(cs.noah_to_bytes().is_err());
+
+        let a = cs.new_variable(F::one());
+        let b = cs.new_variable(F::from(2u32));
+        let c = cs.add(a, b);
+        let d = cs.mul(c, b);
+        cs.prepare_pi_variable(d);
+        let _ = cs.mul(c, d);
+        assert!(cs.noah_to_bytes().is_err());
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+
+        let bytes = cs.noah_to_bytes().unwrap();
+        let loaded = TurboCS::<F>::noah_from_bytes(&bytes).unwrap();
+        assert!(loaded.witness.is_empty());
+        assert_eq!(loaded.compute_permutation(), cs.compute_permutation());
+        assert_eq!(loaded.noah_to_bytes().unwrap(), bytes);
+
+        let prover_params = indexer(&cs, &pcs).unwrap();
+        let loaded_prover_params = indexer(&loaded, &pcs).unwrap();
+        assert_eq!(
+            bincode::serialize(&loaded_prover_params.verifier_params).unwrap(),
+            bincode::serialize(&prover_params.verifier_params).unwrap()
+        );
+        let proof = prover(
+            &mut prng,
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            &loaded,
+            &loaded_prover_params,
+            &witness,
+        )
+        .unwrap();
+        assert!(verifier(
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            &cs,
+            &prover_params.verifier_params,
+            &[F::from(6u32)],
+            &proof
+        )
+        .is_ok());
+
+        // the shrunk system keeps its size and number of variables only
+        let shrunk_bytes = cs.shrink_to_verifier_only().noah_to_bytes().unwrap();
+        assert!(shrunk_bytes.len() < bytes.len());
+        let shrunk = TurboCS::<F>::noah_from_bytes(&shrunk_bytes).unwrap();
+        assert!(shrunk.is_verifier_only());
+        assert_eq!(shrunk.size(), cs.size());
+        assert_eq!(shrunk.num_vars(), cs.num_vars());
+
+        assert!(TurboCS::<F>::noah_from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut bad_cs = TurboCS::<F>::noah_from_bytes(&bytes).unwrap();
+        bad_cs.num_vars = 1;
+        let bad_bytes = bad_cs.noah_to_bytes().unwrap();
+        assert!(TurboCS::<F>::noah_from_bytes(&bad_bytes).is_err());
+    }
+
+    #[test]
+    fn test_cs_stats() {
+        use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+        let mut cs = TurboCS::<F>::new();
+        cs.load_anemoi_jive_parameters::<AnemoiJive381>();
+        let a = cs.new_variable(F::one());
+        let b = cs.new_variable(F::from(2u32));
+        cs.insert_boolean_gate(a);
+        cs.attach_boolean_constraint_to_gate();
+        let c = cs.add(a, b);
+        cs.prepare_pi_variable(c);
+
+        let trace = AnemoiJive381::eval_variable_length_hash_with_trace(&[F::one(), F::from(3u32)]);
+        let h = cs.new_variable(trace.output);
+        cs.anemoi_variable_length_hash(&trace, &[a, c], h);
+
+        let table = cs.create_byte_table();
+        cs.lookup_gate(table, &[b]);
+        let used_rows = cs.size();
+        cs.pad();
+
+        let stats = cs.stats();
+        assert_eq!(stats.size, 512);
+        assert_eq!(stats.padding_rows, 512 - used_rows);
+        assert_eq!(stats.num_vars, cs.num_vars());
+        assert_eq!(stats.boolean_rows, 1);
+        assert_eq!(stats.public_input_rows, 1);
+        assert_eq!(stats.lookup_rows, 1);
+        assert_eq!(stats.anemoi_rows, 12 * cs.anemoi_constraints_indices.len());
+        assert!(stats.anemoi_rows > 0);
+
+        // every variable is counted once, and every wire outside of the padding once
+        assert_eq!(
+            stats.wires_per_variable.values().sum::<usize>(),
+            cs.num_vars()
+        );
+        assert_eq!(
+            stats
+                .wires_per_variable
+                .iter()
+                .map(|(n_wires, n_vars)| n_wires * n_vars)
+                .sum::<usize>(),
+            TurboCS::<F>::n_wires_per_gate() * used_rows
+        );
+        assert!(stats.to_string().contains("padding"));
+
+        let shrunk_stats = cs.shrink_to_verifier_only().stats();
+        assert_eq!(shrunk_stats.size, stats.size);
+        assert_eq!(shrunk_stats.num_vars, stats.num_vars);
+        assert_eq!(shrunk_stats.padding_rows, 0);
+    }
+
+    #[test]
+    fn test_find_unsatisfied_gate() {
+        use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+        let mut cs = TurboCS::<F>::new();
+        cs.load_anemoi_jive_parameters::<AnemoiJive381>();
+        let a = cs.new_variable(F::from(3u32));
+        let b = cs.new_variable(F::from(4u32));
+        let c = cs.add(a, b);
+        let d = cs.mul(c, b);
+        let mul_gate = cs.size() - 1;
+        cs.prepare_pi_variable(d);
+        let pi_gate = cs.size() - 1;
+        let bits = cs.range_check(a, 4);
+        let range_check_gate = cs.size() - 1;
+
+        let trace =
+            AnemoiJive381::eval_variable_length_hash_with_trace(&[F::from(3u32), F::from(7u32)]);
+        let anemoi_start = cs.size();
+        let anemoi_first_var = cs.num_vars();
+        let h = cs.new_variable(trace.output);
+        cs.anemoi_variable_length_hash(&trace, &[a, c], h);
+        let anemoi_end = cs.size();
+        cs.pad();
+
+        let witness = cs.get_and_clear_witness();
+        let online_vars = [F::from(28u32)];
+        assert!(cs.verify_witness(&witness, &online_vars).is_ok());
+        assert!(cs.find_unsatisfied_gate(&witness, &online_vars).is_none());
+
+        // a wrong product: c * b - d = -1
+        let mut bad_witness = witness.clone();
+        bad_witness[d] = F::from(29u32);
+        assert!(cs.verify_witness(&bad_witness, &[F::from(29u32)]).is_err());
+        assert_eq!(
+            cs.find_unsatisfied_gate(&bad_witness, &[F::from(29u32)]),
+            Some((mul_gate, F::one().neg()))
+        );
+
+        // a wrong public input: 29 - d = 1
+        assert_eq!(
+            cs.find_unsatisfied_gate(&witness, &[F::from(29u32)]),
+            Some((pi_gate, F::one()))
+        );
+
+        // a bit which is not boolean, but still recomposes a: 3 * (3 - 1) = 6
+        let mut bad_witness = witness.clone();
+        bad_witness[bits[0]] = F::from(3u32);
+        bad_witness[bits[1]] = F::zero();
+        assert!(cs.verify_witness(&bad_witness, &online_vars).is_err());
+        assert_eq!(
+            cs.find_unsatisfied_gate(&bad_witness, &online_vars),
+            Some((range_check_gate, F::from(6u32)))
+        );
+
+        // a wrong intermediate value of the Anemoi permutation
+        let mut bad_witness = witness;
+        bad_witness[anemoi_first_var + 1].add_assign(&F::one());
+        assert!(cs.verify_witness(&bad_witness, &online_vars).is_err());
+        let (cs_index, residual) = cs
+            .find_unsatisfied_gate(&bad_witness, &online_vars)
+            .unwrap();
+        assert!((anemoi_start..anemoi_end).contains(&cs_index));
+        assert!(!residual.is_zero());
+    }
+
+    /// Check that a proof round-trips through binary and human-readable serde formats.
+    fn assert_proof_serde_round_trip<C, E>(proof: &PlonkProof<C, E>)
+    where
+        C: Debug + Eq + Serialize + DeserializeOwned,
+        E: Debug + Eq + Serialize + DeserializeOwned,
+    {
+        let bytes = bincode::serialize(proof).unwrap();
+        let decoded: PlonkProof<C, E> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(&decoded, proof);
+
+        let bytes = rmp_serde::to_vec(proof).unwrap();
+        let decoded: PlonkProof<C, E> = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(&decoded, proof);
+
+        let bytes = rmp_serde::to_vec_named(proof).unwrap();
+        let decoded: PlonkProof<C, E> = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(&decoded, proof);
+
+        let json = serde_json::to_string(proof).unwrap();
+        let decoded: PlonkProof<C, E> = serde_json::from_str(&json).unwrap();
+        assert_eq!(&decoded, proof);
+    }
+
+    #[test]
+    fn test_lookup_xor() {
+        let mut cs = TurboCS::<F>::new();
+        let mut entries = vec![];
+        for a in 0..16u32 {
+            for b in 0..16u32 {
+                entries.push(vec![F::from(a), F::from(b), F::from(a ^ b)]);
+            }
+        }
+        let xor_table = cs.create_lookup_table(&entries);
+        assert_eq!(cs.num_lookup_tables(), 1);
+
+        let a = cs.new_variable(F::from(9u32));
+        let b = cs.new_variable(F::from(5u32));
+        let c = cs.new_variable(F::from(12u32));
+        let lookup_gate = cs.size();
+        cs.lookup_gate(xor_table, &[a, b, c]);
+        let d = cs.add(a, c);
+        cs.prepare_pi_variable(d);
+        cs.pad();
+        // the 256 entries and the zero row of the table
+        assert_eq!(cs.size(), 512);
+
+        let witness = cs.get_and_clear_witness();
+        let online_vars = [F::from(21u32)];
+        cs.verify_witness(&witness, &online_vars).unwrap();
+        assert!(cs.find_unsatisfied_gate(&witness, &online_vars).is_none());
+
+        let mut prng = test_rng();
+        let pcs = KZGCommitmentScheme::new(600, &mut prng);
+        check_turbo_plonk_proof(&pcs, &mut prng, &cs, &witness, &online_vars);
+
+        let prover_params = indexer(&cs, &pcs).unwrap();
+        let proof = prover(
+            &mut prng,
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            &cs,
+            &prover_params,
+            &witness,
+        )
+        .unwrap();
+        assert!(proof.lookup.is_some());
+        let bytes = proof.to_compressed_bytes().unwrap();
+        assert_eq!(PlonkProof::from_compressed_bytes(&bytes).unwrap(), proof);
+
+        assert_proof_serde_round_trip(&proof);
+
+        // the proof does not verify without its lookup part
+        let mut bad_proof = proof;
+        bad_proof.lookup = None;
+        assert!(verifier(
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            &cs,
+            &prover_params.verifier_params,
+            &online_vars,
+            &bad_proof
+        )
+        .is_err());
+
+        // a wrong XOR is not in the table
+        let mut bad_witness = witness;
+        bad_witness[c] = F::from(13u32);
+        bad_witness[d] = F::from(22u32);
+        assert!(cs.verify_witness(&bad_witness, &[F::from(22u32)]).is_err());
+        assert_eq!(
+            cs.find_unsatisfied_gate(&bad_witness, &[F::from(22u32)]),
+            Some((lookup_gate, F::one()))
+        );
+        assert!(prover(
+            &mut prng,
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            &cs,
+            &prover_params,
+            &bad_witness,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_range_check_with_lookup() {
+        let mut cs = TurboCS::<F>::new();
+        let value = cs.new_variable(F::from(u64::MAX));
+        let _ = cs.range_check(value, 248);
+        let range_check_size = cs.size();
+
+        let mut cs = TurboCS::<F>::new();
+        let byte_table = cs.create_byte_table();
+        let value = cs.new_variable(F::from(u64::MAX));
+        let bytes = cs.range_check_with_lookup(value, 248, byte_table);
+        assert_eq!(bytes.len(), 31);
+        assert!(cs.size() < range_check_size);
+
+        cs.prepare_pi_variable(value);
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+        let online_vars = [F::from(u64::MAX)];
+        cs.verify_witness(&witness, &online_vars).unwrap();
+
+        let mut prng = test_rng();
+        let pcs = KZGCommitmentScheme::new(600, &mut prng);
+        check_turbo_plonk_proof(&pcs, &mut prng, &cs, &witness, &online_vars);
+
+        // a value of 249 bits is out of range
+        let mut cs = TurboCS::<F>::new();
+        let byte_table = cs.create_byte_table();
+        let value = cs.new_variable(F::from(2u32).pow(&[248u64]));
+        let _ = cs.range_check_with_lookup(value, 248, byte_table);
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+        assert!(cs.verify_witness(&witness, &[]).is_err());
+    }
+
+    /// Check that proving twice with the same seed gives byte-identical proofs.
+    fn assert_proof_deterministic<PCS: PolyComScheme>(
+        seed: [u8; 32],
+        pcs: &PCS,
+        cs: &TurboCS<PCS::Field>,
+        prover_params: &PlonkPK<PCS>,
+        witness: &[PCS::Field],
+    ) -> Vec<u8> {
+        let prove = || {
+            let proof = prove_deterministic(
+                seed,
+                &mut Transcript::new(b"TestTurboPlonk"),
+                pcs,
+                None,
+                cs,
+                prover_params,
+                witness,
+            )
+            .unwrap();
+            bincode::serialize(&proof).unwrap()
+        };
+        let bytes = prove();
+        assert_eq!(bytes, prove());
+        bytes
+    }
+
+    #[test]
+    fn test_prove_deterministic() {
+        let mut prng = test_rng();
+        let pcs = KZGCommitmentScheme::new(20, &mut prng);
+
+        let mut cs = TurboCS::<F>::new();
+        let a = cs.new_variable(F::one());
+        let b = cs.new_variable(F::from(2u32));
+        let c = cs.add(a, b);
+        cs.prepare_pi_variable(c);
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+        let prover_params = indexer(&cs, &pcs).unwrap();
+
+        let bytes = assert_proof_deterministic([1u8; 32], &pcs, &cs, &prover_params, &witness);
+        let other_bytes =
+            assert_proof_deterministic([2u8; 32], &pcs, &cs, &prover_params, &witness);
+        assert_ne!(bytes, other_bytes);
+
+        let proof = bincode::deserialize(&bytes).unwrap();
+        assert!(verifier(
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            &cs,
+            &prover_params.verifier_params,
+            &[F::from(3u32)],
+            &proof
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_proof_compressed_bytes() {
+        let mut prng = test_rng();
+        let pcs = KZGCommitmentScheme::new(20, &mut prng);
+
+        let mut cs = TurboCS::<F>::new();
+        let a = cs.new_variable(F::one());
+        let b = cs.new_variable(F::from(2u32));
+        let c = cs.add(a, b);
+        cs.prepare_pi_variable(c);
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+        let prover_params = indexer(&cs, &pcs).unwrap();
+
+        let proof = prove_deterministic(
+            [1u8; 32],
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            None,
+            &cs,
+            &prover_params,
+            &witness,
+        )
+        .unwrap();
+
+        let bytes = proof.to_compressed_bytes().unwrap();
+        // 5 lengths, 13 commitments and 15 evaluations
+        assert_eq!(bytes.len(), 5 + 13 * 48 + 15 * 32);
+        assert!(bytes.len() < bincode::serialize(&proof).unwrap().len());
+
+        let decompressed = PlonkProof::from_compressed_bytes(&bytes).unwrap();
+        assert_eq!(decompressed, proof);
+
+        assert_proof_serde_round_trip(&proof);
+
+        // a proof without a lookup part is the fields from before the lookup argument
+        // followed by an empty lookup part
+        let expected_bytes = bincode::serialize(&(
+            &proof.cm_w_vec,
+            &proof.cm_t_vec,
+            &proof.cm_z,
+            &proof.prk_3_poly_eval_zeta,
+            &proof.prk_4_poly_eval_zeta,
+            &proof.w_polys_eval_zeta,
+            &proof.w_polys_eval_zeta_omega,
+            &proof.z_eval_zeta_omega,
+            &proof.s_polys_eval_zeta,
+            &proof.opening_witness_zeta,
+            &proof.opening_witness_zeta_omega,
+            None::<()>,
+        ))
+        .unwrap();
+        assert_eq!(bincode::serialize(&proof).unwrap(), expected_bytes);
+        assert!(verifier(
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            &cs,
+            &prover_params.verifier_params,
+            &[F::from(3u32)],
+            &decompressed
+        )
+        .is_ok());
+
+        assert!(PlonkProof::from_compressed_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(PlonkProof::from_compressed_bytes(&bytes[..3]).is_err());
+        let mut bad_bytes = bytes.clone();
+        bad_bytes[0] += 1;
+        assert!(PlonkProof::from_compressed_bytes(&bad_bytes).is_err());
+    }
+
+    #[test]
+    fn test_prover_lagrange_mode() {
+        let mut prng = test_rng();
+        let pcs = KZGCommitmentScheme::new(20, &mut prng);
+
+        let mut cs = TurboCS::<F>::new();
+        let a = cs.new_variable(F::one());
+        let b = cs.new_variable(F::from(2u32));
+        let c = cs.add(a, b);
+        cs.prepare_pi_variable(c);
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+        let prover_params = indexer(&cs, &pcs).unwrap();
+
+        // the Lagrange bases of the size of the constraint system, for the same SRS as `pcs`
+        let domain = FpPolynomial::<F>::evaluation_domain(cs.size).unwrap();
+        let lagrange_pcs = KZGCommitmentScheme {
+            public_parameter_group_1: (0..cs.size)
+                .map(|i| {
+                    let mut values = vec![F::zero(); cs.size];
+                    values[i] = F::one();
+                    let lagrange_poly = FpPolynomial::ifft_with_domain(&domain, &values);
+                    pcs.commit(&lagrange_poly).unwrap().0
+                })
+                .collect(),
+            public_parameter_group_2: pcs.public_parameter_group_2.clone(),
+        };
+
+        // the bases of `pcs` do not match the size of the constraint system
+        let prove = |lagrange_pcs, lagrange| {
+            let mut prng = test_rng();
+            prover_with_options(
+                &mut prng,
+                &mut Transcript::new(b"TestTurboPlonk"),
+                &pcs,
+                lagrange_pcs,
+                &cs,
+                &prover_params,
+                &witness,
+                &ProvingOptions { lagrange },
+            )
+        };
+
+        for (lagrange_pcs, lagrange) in [
+            (Some(&pcs), LagrangeMode::Prefer),
+            (None, LagrangeMode::Prefer),
+            (Some(&pcs), LagrangeMode::Disable),
+        ] {
+            let proof = prove(lagrange_pcs, lagrange).unwrap();
+            assert!(verifier(
+                &mut Transcript::new(b"TestTurboPlonk"),
+                &pcs,
+                &cs,
+                &prover_params.verifier_params,
+                &[F::from(3u32)],
+                &proof
+            )
+            .is_ok());
+        }
+
+        for lagrange_pcs in [Some(&pcs), None] {
+            assert!(prove(lagrange_pcs, LagrangeMode::Require).is_err());
+        }
+
+        for lagrange in [LagrangeMode::Require, LagrangeMode::Prefer] {
+            let proof = prove(Some(&lagrange_pcs), lagrange).unwrap();
+            assert!(verifier(
+                &mut Transcript::new(b"TestTurboPlonk"),
+                &pcs,
+                &cs,
+                &prover_params.verifier_params,
+                &[F::from(3u32)],
+                &proof
+            )
+            .is_ok());
+        }
+        assert_eq!(LagrangeMode::default(), LagrangeMode::Prefer);
+    }
+
+    #[test]
+    #[cfg(feature = "prove-timing")]
+    fn test_prove_with_timing() {
+        use crate::plonk::prover::{prove_with_timing, prover_with_lagrange};
+        use rand_chacha::ChaChaRng;
+
+        let mut prng = test_rng();
+        let pcs = KZGCommitmentScheme::new(20, &mut prng);
+
+        let mut cs = TurboCS::<F>::new();
+        let a = cs.new_variable(F::one());
+        let b = cs.new_variable(F::from(2u32));
+        let c = cs.add(a, b);
+        cs.prepare_pi_variable(c);
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+        let prover_params = indexer(&cs, &pcs).unwrap();
+
+        let proof = prover_with_lagrange(
+            &mut ChaChaRng::from_seed([0u8; 32]),
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            None,
+            &cs,
+            &prover_params,
+            &witness,
+        )
+        .unwrap();
+        let (timed_proof, timing) = prove_with_timing(
+            &mut ChaChaRng::from_seed([0u8; 32]),
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            None,
+            &cs,
+            &prover_params,
+            &witness,
+        )
+        .unwrap();
+        assert_eq!(proof, timed_proof);
+        assert!(timing.openings > core::time::Duration::ZERO);
+
+        assert!(verifier(
+            &mut Transcript::new(b"TestTurboPlonk"),
+            &pcs,
+            &cs,
+            &prover_params.verifier_params,
+            &[F::from(3u32)],
+            &timed_proof
+        )
+        .is_ok());
+    }
+
     #[test]
     fn test_turbo_plonk_kzg() {
         let mut prng = test_rng();
@@ -1453,6 +2756,45 @@ mod test {
         )
         .is_ok());
 
+        let mut transcript = Transcript::new(b"TestTurboPlonk");
+        assert!(assert_proof_binds_to(
+            &mut transcript,
+            pcs,
+            cs,
+            verifier_params_ref,
+            &proof,
+            online_vars
+        )
+        .is_ok());
+
+        let mut longer_vars = online_vars.to_vec();
+        longer_vars.push(PCS::Field::one());
+        let mut transcript = Transcript::new(b"TestTurboPlonk");
+        assert!(assert_proof_binds_to(
+            &mut transcript,
+            pcs,
+            cs,
+            verifier_params_ref,
+            &proof,
+            &longer_vars
+        )
+        .is_err());
+
+        for i in 0..online_vars.len() {
+            let mut wrong_vars = online_vars.to_vec();
+            wrong_vars[i].add_assign(&PCS::Field::one());
+            let mut transcript = Transcript::new(b"TestTurboPlonk");
+            assert!(assert_proof_binds_to(
+                &mut transcript,
+                pcs,
+                cs,
+                verifier_params_ref,
+                &proof,
+                &wrong_vars
+            )
+            .is_err());
+        }
+
         let prover_cs = cs.shrink_to_verifier_only();
 
         let mut transcript = Transcript::new(b"TestTurboPlonk");
@@ -21,6 +21,10 @@ pub enum PlonkError {
     DivisionByZero,
     /// Function params error.
     FuncParamsError,
+    /// The Lagrange bases are unavailable for a constraint system of this size.
+    LagrangeBasesUnavailable(usize),
+    /// Deserialization error.
+    DeserializationError,
 }
 
 impl fmt::Display for PlonkError {
@@ -35,6 +39,10 @@ impl fmt::Display for PlonkError {
             PlonkError::VerificationError => "Verification error.",
             PlonkError::DivisionByZero => "Division by zero.",
             PlonkError::FuncParamsError => "Function params error",
+            PlonkError::LagrangeBasesUnavailable(_n) => {
+                "The Lagrange bases are unavailable for the size of the constraint system."
+            }
+            PlonkError::DeserializationError => "Deserialization error.",
         };
 
         write!(f, "{}", c)
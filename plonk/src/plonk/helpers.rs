@@ -1,7 +1,11 @@
 use crate::plonk::{
     constraint_system::ConstraintSystem,
     errors::PlonkError,
-    indexer::{PlonkPK, PlonkPf, PlonkVK},
+    indexer::{PlonkLookupProof, PlonkPK, PlonkPf, PlonkVK},
+    lookup::{
+        compress, eval_f, lookup_denominator, lookup_numerator, lookup_r_eval_zeta,
+        lookup_z_scalar_in_r, LookupPolys,
+    },
 };
 use crate::poly_commit::{
     field_polynomial::FpPolynomial,
@@ -23,6 +27,7 @@ use rayon::{
 #[derive(Default)]
 pub(super) struct PlonkChallenges<F> {
     challenges: Vec<F>,
+    lookup_challenges: Vec<F>,
 }
 
 impl<F: Scalar> PlonkChallenges<F> {
@@ -30,6 +35,7 @@ impl<F: Scalar> PlonkChallenges<F> {
     pub(super) fn new() -> PlonkChallenges<F> {
         PlonkChallenges {
             challenges: Vec::with_capacity(4),
+            lookup_challenges: Vec::new(),
         }
     }
 
@@ -74,6 +80,27 @@ impl<F: Scalar> PlonkChallenges<F> {
         }
     }
 
+    /// Insert eta, the challenge for compressing the lookups.
+    pub(super) fn insert_eta(&mut self, eta: F) -> Result<()> {
+        if self.lookup_challenges.is_empty() {
+            self.lookup_challenges.push(eta);
+            Ok(())
+        } else {
+            Err(eg!())
+        }
+    }
+
+    /// Insert delta and epsilon, the challenges for the lookup grand product.
+    pub(super) fn insert_delta_epsilon(&mut self, delta: F, epsilon: F) -> Result<()> {
+        if self.lookup_challenges.len() == 1 {
+            self.lookup_challenges.push(delta);
+            self.lookup_challenges.push(epsilon);
+            Ok(())
+        } else {
+            Err(eg!())
+        }
+    }
+
     /// Return beta and gamma.
     pub(super) fn get_beta_gamma(&self) -> Result<(&F, &F)> {
         if self.challenges.len() > 1 {
@@ -109,6 +136,24 @@ impl<F: Scalar> PlonkChallenges<F> {
             Err(eg!())
         }
     }
+
+    /// Return eta.
+    pub(super) fn get_eta(&self) -> Result<&F> {
+        if !self.lookup_challenges.is_empty() {
+            Ok(&self.lookup_challenges[0])
+        } else {
+            Err(eg!())
+        }
+    }
+
+    /// Return delta and epsilon.
+    pub(super) fn get_delta_epsilon(&self) -> Result<(&F, &F)> {
+        if self.lookup_challenges.len() > 2 {
+            Ok((&self.lookup_challenges[1], &self.lookup_challenges[2]))
+        } else {
+            Err(eg!())
+        }
+    }
 }
 
 /// Return the PI polynomial.
@@ -235,6 +280,7 @@ pub(super) fn t_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
     z: &FpPolynomial<PCS::Field>,
     challenges: &PlonkChallenges<PCS::Field>,
     pi: &FpPolynomial<PCS::Field>,
+    lookup: Option<&LookupPolys<PCS>>,
 ) -> Result<FpPolynomial<PCS::Field>> {
     let n = cs.size();
     let m = cs.quot_eval_dom_size();
@@ -269,6 +315,16 @@ pub(super) fn t_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
     let pi_coset_evals = pi.coset_fft_with_domain(&domain_m, &k[1]);
     let z_coset_evals = z.coset_fft_with_domain(&domain_m, &k[1]);
 
+    // Compute the evaluations of the h1/h2/z polynomials of the lookup argument on the coset.
+    let n_selectors = cs.num_selectors();
+    let lookup_coset_evals = lookup.map(|lookup| {
+        (
+            lookup.h1.coset_fft_with_domain(&domain_m, &k[1]),
+            lookup.h2.coset_fft_with_domain(&domain_m, &k[1]),
+            lookup.z.coset_fft_with_domain(&domain_m, &k[1]),
+        )
+    });
+
     // Compute the evaluations of the quotient polynomial on the coset.
     let (beta, gamma) = challenges.get_beta_gamma().unwrap();
 
@@ -281,6 +337,17 @@ pub(super) fn t_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
     let alpha_pow_7 = alpha_pow_6.mul(alpha);
     let alpha_pow_8 = alpha_pow_7.mul(alpha);
     let alpha_pow_9 = alpha_pow_8.mul(alpha);
+    let alpha_pow_10 = alpha_pow_9.mul(alpha);
+    let alpha_pow_11 = alpha_pow_10.mul(alpha);
+
+    // The compressed row of the lookup tables at a point of the coset.
+    let table_coset_eval = |point: usize, eta: &PCS::Field| -> PCS::Field {
+        let row: Vec<&PCS::Field> = prover_params.q_coset_evals[n_selectors + 2..]
+            .iter()
+            .map(|poly_coset_evals| &poly_coset_evals[point])
+            .collect();
+        compress(&row, eta)
+    };
 
     let t_coset_evals = cfg_into_iter!(0..m)
         .map(|point| {
@@ -291,6 +358,7 @@ pub(super) fn t_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
             let q_vals: Vec<&PCS::Field> = prover_params
                 .q_coset_evals
                 .iter()
+                .take(n_selectors)
                 .map(|poly_coset_evals| &poly_coset_evals[point])
                 .collect();
             // q * w
@@ -406,7 +474,7 @@ pub(super) fn t_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
                     - &w1_eval_point_next,
             );
 
-            let numerator = term1
+            let mut numerator = term1
                 .add(&term2)
                 .add(&term4.sub(&term3))
                 .add(&term5)
@@ -416,6 +484,47 @@ pub(super) fn t_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
                 .sub(&term9)
                 .sub(&term10)
                 .sub(&term11);
+
+            if let Some((h1_coset_evals, h2_coset_evals, z_lookup_coset_evals)) =
+                &lookup_coset_evals
+            {
+                let eta = challenges.get_eta().unwrap();
+                let (delta, epsilon) = challenges.get_delta_epsilon().unwrap();
+                let point_next = (point + factor) % m;
+
+                // alpha^10 * [z'(X) (1 + delta) (epsilon + f(X))
+                //     (epsilon (1 + delta) + t(X) + delta * t(\omega * X))
+                //   - z'(\omega * X) (epsilon (1 + delta) + h1(X) + delta * h2(X))
+                //     (epsilon (1 + delta) + h2(X) + delta * h1(\omega * X))]
+                // where z' is the lookup grand product
+                let f_eval_point = eval_f(
+                    &prover_params.q_coset_evals[n_selectors][point],
+                    &prover_params.q_coset_evals[n_selectors + 1][point],
+                    &w_vals,
+                    eta,
+                );
+                let term12 = z_lookup_coset_evals[point].mul(&lookup_numerator(
+                    &f_eval_point,
+                    &table_coset_eval(point, eta),
+                    &table_coset_eval(point_next, eta),
+                    delta,
+                    epsilon,
+                ));
+                let term13 = z_lookup_coset_evals[point_next].mul(&lookup_denominator(
+                    &h1_coset_evals[point],
+                    &h2_coset_evals[point],
+                    &h1_coset_evals[point_next],
+                    delta,
+                    epsilon,
+                ));
+                numerator.add_assign(&alpha_pow_10.mul(&term12.sub(&term13)));
+
+                // alpha^11 * (z'(X) - 1) * L_1(X)
+                let term14 = alpha_pow_11
+                    .mul(&prover_params.l1_coset_evals[point])
+                    .mul(&z_lookup_coset_evals[point].sub(&PCS::Field::one()));
+                numerator.add_assign(&term14);
+            }
             numerator.mul(&z_h_inv_coset_evals[point % factor])
         })
         .collect::<Vec<PCS::Field>>();
@@ -665,11 +774,15 @@ pub(super) fn r_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
     first_lagrange_eval_zeta: &PCS::Field,
     z_h_eval_zeta: &PCS::Field,
     n_t_polys: usize,
+    lookup: Option<(
+        &FpPolynomial<PCS::Field>,
+        &PlonkLookupProof<PCS::Commitment, PCS::Field>,
+    )>,
 ) -> FpPolynomial<PCS::Field> {
     let w = CS::eval_selector_multipliers(w_polys_eval_zeta).unwrap(); // safe unwrap
-    r_poly_or_comm::<PCS::Field, FpPolynomial<PCS::Field>>(
+    let mut r = r_poly_or_comm::<PCS::Field, FpPolynomial<PCS::Field>>(
         &w,
-        &prover_params.q_polys,
+        &prover_params.q_polys[..w.len()],
         &prover_params.qb_poly,
         &prover_params.q_prk_polys[0],
         &prover_params.q_prk_polys[1],
@@ -685,7 +798,20 @@ pub(super) fn r_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
         first_lagrange_eval_zeta,
         z_h_eval_zeta,
         n_t_polys,
-    )
+    );
+
+    // z'(X) * [alpha^10 * (1 + delta) (epsilon + f(zeta)) (epsilon (1 + delta) + t(zeta) + delta * t(zeta * omega))
+    //              + alpha^11 * L1(zeta)]
+    if let Some((z_lookup, lookup)) = lookup {
+        let z_scalar = lookup_z_scalar_in_r(
+            w_polys_eval_zeta,
+            lookup,
+            challenges,
+            first_lagrange_eval_zeta,
+        );
+        r.add_assign(&z_lookup.mul_scalar(&z_scalar));
+    }
+    r
 }
 
 /// Commit the r commitment.
@@ -701,11 +827,12 @@ pub(super) fn r_commitment<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS:
     first_lagrange_eval_zeta: &PCS::Field,
     z_h_eval_zeta: &PCS::Field,
     n_t_polys: usize,
+    lookup: Option<&PlonkLookupProof<PCS::Commitment, PCS::Field>>,
 ) -> PCS::Commitment {
     let w = CS::eval_selector_multipliers(w_polys_eval_zeta).unwrap(); // safe unwrap
-    r_poly_or_comm::<PCS::Field, PCS::Commitment>(
+    let mut cm_r = r_poly_or_comm::<PCS::Field, PCS::Commitment>(
         &w,
-        &verifier_params.cm_q_vec,
+        &verifier_params.cm_q_vec[..w.len()],
         &verifier_params.cm_qb,
         &verifier_params.cm_prk_vec[0],
         &verifier_params.cm_prk_vec[1],
@@ -721,7 +848,18 @@ pub(super) fn r_commitment<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS:
         first_lagrange_eval_zeta,
         z_h_eval_zeta,
         n_t_polys,
-    )
+    );
+
+    if let Some(lookup) = lookup {
+        let z_scalar = lookup_z_scalar_in_r(
+            w_polys_eval_zeta,
+            lookup,
+            challenges,
+            first_lagrange_eval_zeta,
+        );
+        cm_r.add_assign(&lookup.cm_z.mul(&z_scalar));
+    }
+    cm_r
 }
 
 /// Compute sum_{i=1}^\ell w_i L_j(X), where j is the constraint
@@ -861,12 +999,21 @@ pub(super) fn r_eval_zeta<PCS: PolyComScheme>(
     );
 
     let term1_plus_term2 = term1.add(&term2);
-    term1_plus_term2
+    let r_eval_zeta = term1_plus_term2
         .sub(&term0)
         .add(&term3)
         .add(&term4)
         .add(&term5)
-        .add(&term6)
+        .add(&term6);
+
+    match &proof.lookup {
+        Some(lookup) => r_eval_zeta.add(&lookup_r_eval_zeta(
+            lookup,
+            challenges,
+            first_lagrange_eval_zeta,
+        )),
+        None => r_eval_zeta,
+    }
 }
 
 /// Split the t polynomial into `n_wires_per_gate` degree-`n` polynomials and commit.
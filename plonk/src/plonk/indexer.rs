@@ -1,12 +1,23 @@
 use crate::plonk::{
-    constraint_system::ConstraintSystem, errors::PlonkError, helpers::compute_lagrange_constant,
+    constraint_system::{ConstraintSystem, N_LOOKUP_SELECTORS},
+    errors::PlonkError,
+    helpers::compute_lagrange_constant,
+};
+use crate::poly_commit::{
+    field_polynomial::FpPolynomial, kzg_poly_com::KZGCommitment, pcs::PolyComScheme,
 };
-use crate::poly_commit::{field_polynomial::FpPolynomial, pcs::PolyComScheme};
 use ark_poly::EvaluationDomain;
-use noah_algebra::{prelude::*, traits::Domain};
+use noah_algebra::{
+    bls12_381::{BLSScalar, BLS12_381_SCALAR_LEN, BLSG1},
+    prelude::*,
+    traits::Domain,
+};
 use rand_chacha::ChaChaRng;
 
 /// The data structure of a Plonk proof.
+///
+/// The lookup part is an optional field in every serde format, so a proof without lookup
+/// tables is its fields from before the lookup argument followed by `None`.
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize, Clone)]
 pub struct PlonkProof<C, F> {
     /// The witness polynomial commitments.
@@ -31,6 +42,208 @@ pub struct PlonkProof<C, F> {
     pub opening_witness_zeta: C,
     /// The commitment for the second witness polynomial, for \zeta\omega.
     pub opening_witness_zeta_omega: C,
+    /// The part of the lookup argument, if the constraint system has lookup tables.
+    #[serde(default)]
+    pub lookup: Option<PlonkLookupProof<C, F>>,
+}
+
+/// The part of a Plonk proof for the lookup argument.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize, Clone)]
+pub struct PlonkLookupProof<C, F> {
+    /// The commitment of the even positions of the sorted lookups.
+    pub cm_h1: C,
+    /// The commitment of the odd positions of the sorted lookups.
+    pub cm_h2: C,
+    /// The commitment of the lookup grand product polynomial.
+    pub cm_z: C,
+    /// The opening of the lookup selector at \zeta.
+    pub q_lookup_eval_zeta: F,
+    /// The opening of the table selector at \zeta.
+    pub q_table_eval_zeta: F,
+    /// The opening of the compressed lookup tables at \zeta.
+    pub table_eval_zeta: F,
+    /// The opening of the compressed lookup tables at \zeta * \omega.
+    pub table_eval_zeta_omega: F,
+    /// The opening of h1(X) at \zeta.
+    pub h1_eval_zeta: F,
+    /// The opening of h2(X) at \zeta.
+    pub h2_eval_zeta: F,
+    /// The opening of h1(X) at \zeta * \omega.
+    pub h1_eval_zeta_omega: F,
+    /// The opening of the lookup grand product at \zeta * \omega.
+    pub z_eval_zeta_omega: F,
+}
+
+/// The number of vectors of a Plonk proof, whose lengths prefix its compressed bytes.
+const N_PROOF_VECTORS: usize = 5;
+
+/// The number of commitments of the lookup part of a Plonk proof.
+const N_LOOKUP_PROOF_POINTS: usize = 3;
+
+/// The number of evaluations of the lookup part of a Plonk proof.
+const N_LOOKUP_PROOF_EVALS: usize = 8;
+
+impl PlonkProof<KZGCommitment<BLSG1>, BLSScalar> {
+    /// Serialize the proof into a compact form, which is smaller than its serde serialization:
+    /// one byte for the length of each vector, then the compressed commitments, converted to
+    /// affine coordinates with a single shared inversion, then the evaluations, without any
+    /// other length prefix. The lookup part, if any, comes last, in the same form.
+    pub fn to_compressed_bytes(&self) -> Result<Vec<u8>> {
+        let lens = [
+            self.cm_w_vec.len(),
+            self.cm_t_vec.len(),
+            self.w_polys_eval_zeta.len(),
+            self.w_polys_eval_zeta_omega.len(),
+            self.s_polys_eval_zeta.len(),
+        ];
+        let mut bytes = Vec::new();
+        for len in lens {
+            bytes.push(u8::try_from(len).c(d!(PlonkError::FuncParamsError))?);
+        }
+
+        let points: Vec<BLSG1> = self
+            .cm_w_vec
+            .iter()
+            .chain(self.cm_t_vec.iter())
+            .chain([
+                &self.cm_z,
+                &self.opening_witness_zeta,
+                &self.opening_witness_zeta_omega,
+            ])
+            .map(|cm| cm.0)
+            .collect();
+        bytes.extend(BLSG1::batch_compress(&points));
+
+        let evals = [&self.prk_3_poly_eval_zeta, &self.prk_4_poly_eval_zeta]
+            .into_iter()
+            .chain(self.w_polys_eval_zeta.iter())
+            .chain(self.w_polys_eval_zeta_omega.iter())
+            .chain([&self.z_eval_zeta_omega])
+            .chain(self.s_polys_eval_zeta.iter());
+        for eval in evals {
+            bytes.extend(eval.to_bytes());
+        }
+
+        if let Some(lookup) = &self.lookup {
+            let points = [lookup.cm_h1.0, lookup.cm_h2.0, lookup.cm_z.0];
+            bytes.extend(BLSG1::batch_compress(&points));
+
+            let evals = [
+                &lookup.q_lookup_eval_zeta,
+                &lookup.q_table_eval_zeta,
+                &lookup.table_eval_zeta,
+                &lookup.table_eval_zeta_omega,
+                &lookup.h1_eval_zeta,
+                &lookup.h2_eval_zeta,
+                &lookup.h1_eval_zeta_omega,
+                &lookup.z_eval_zeta_omega,
+            ];
+            for eval in evals {
+                bytes.extend(eval.to_bytes());
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Deserialize a proof from the output of `to_compressed_bytes`.
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < N_PROOF_VECTORS {
+            return Err(eg!(PlonkError::DeserializationError));
+        }
+        let (lens, bytes) = bytes.split_at(N_PROOF_VECTORS);
+        let lens: Vec<usize> = lens.iter().map(|len| *len as usize).collect();
+        let n_points = lens[0] + lens[1] + 3;
+        let n_evals = lens[2] + lens[3] + lens[4] + 3;
+        let points_len = n_points * BLSG1::COMPRESSED_LEN;
+        let proof_len = points_len + n_evals * BLS12_381_SCALAR_LEN;
+        let lookup_len = N_LOOKUP_PROOF_POINTS * BLSG1::COMPRESSED_LEN
+            + N_LOOKUP_PROOF_EVALS * BLS12_381_SCALAR_LEN;
+        if bytes.len() != proof_len && bytes.len() != proof_len + lookup_len {
+            return Err(eg!(PlonkError::DeserializationError));
+        }
+
+        let (bytes, lookup_bytes) = bytes.split_at(proof_len);
+        let lookup = if lookup_bytes.is_empty() {
+            None
+        } else {
+            Some(Self::lookup_from_compressed_bytes(lookup_bytes)?)
+        };
+
+        let (points_bytes, evals_bytes) = bytes.split_at(points_len);
+        let mut points = points_bytes
+            .chunks(BLSG1::COMPRESSED_LEN)
+            .map(|chunk| BLSG1::from_compressed_bytes(chunk).map(KZGCommitment))
+            .collect::<Result<Vec<_>>>()
+            .c(d!(PlonkError::DeserializationError))?
+            .into_iter();
+        let mut evals = evals_bytes
+            .chunks(BLS12_381_SCALAR_LEN)
+            .map(BLSScalar::from_bytes)
+            .collect::<Result<Vec<_>>>()
+            .c(d!(PlonkError::DeserializationError))?
+            .into_iter();
+
+        // The lengths have been checked above, so that the iterators do not run out.
+        let cm_w_vec = points.by_ref().take(lens[0]).collect();
+        let cm_t_vec = points.by_ref().take(lens[1]).collect();
+        let cm_z = points.next().unwrap();
+        let opening_witness_zeta = points.next().unwrap();
+        let opening_witness_zeta_omega = points.next().unwrap();
+
+        let prk_3_poly_eval_zeta = evals.next().unwrap();
+        let prk_4_poly_eval_zeta = evals.next().unwrap();
+        let w_polys_eval_zeta = evals.by_ref().take(lens[2]).collect();
+        let w_polys_eval_zeta_omega = evals.by_ref().take(lens[3]).collect();
+        let z_eval_zeta_omega = evals.next().unwrap();
+        let s_polys_eval_zeta = evals.by_ref().take(lens[4]).collect();
+
+        Ok(Self {
+            cm_w_vec,
+            cm_t_vec,
+            cm_z,
+            prk_3_poly_eval_zeta,
+            prk_4_poly_eval_zeta,
+            w_polys_eval_zeta,
+            w_polys_eval_zeta_omega,
+            z_eval_zeta_omega,
+            s_polys_eval_zeta,
+            opening_witness_zeta,
+            opening_witness_zeta_omega,
+            lookup,
+        })
+    }
+
+    /// Deserialize the lookup part of a proof, whose length has been checked.
+    fn lookup_from_compressed_bytes(
+        bytes: &[u8],
+    ) -> Result<PlonkLookupProof<KZGCommitment<BLSG1>, BLSScalar>> {
+        let (points_bytes, evals_bytes) =
+            bytes.split_at(N_LOOKUP_PROOF_POINTS * BLSG1::COMPRESSED_LEN);
+        let points = points_bytes
+            .chunks(BLSG1::COMPRESSED_LEN)
+            .map(|chunk| BLSG1::from_compressed_bytes(chunk).map(KZGCommitment))
+            .collect::<Result<Vec<_>>>()
+            .c(d!(PlonkError::DeserializationError))?;
+        let evals = evals_bytes
+            .chunks(BLS12_381_SCALAR_LEN)
+            .map(BLSScalar::from_bytes)
+            .collect::<Result<Vec<_>>>()
+            .c(d!(PlonkError::DeserializationError))?;
+
+        Ok(PlonkLookupProof {
+            cm_h1: points[0].clone(),
+            cm_h2: points[1].clone(),
+            cm_z: points[2].clone(),
+            q_lookup_eval_zeta: evals[0],
+            q_table_eval_zeta: evals[1],
+            table_eval_zeta: evals[2],
+            table_eval_zeta_omega: evals[3],
+            h1_eval_zeta: evals[4],
+            h2_eval_zeta: evals[5],
+            h1_eval_zeta_omega: evals[6],
+            z_eval_zeta_omega: evals[7],
+        })
+    }
 }
 
 /// The type of the Plonk proof with a specific polynomial commitment scheme.
@@ -40,7 +253,7 @@ pub type PlonkPf<PCS> =
 /// Plonk prover parameters.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PlonkProverParams<O, C, F> {
-    /// The polynomials of the selectors.
+    /// The polynomials of the selectors, followed by the ones of the lookup argument, if any.
     pub q_polys: Vec<O>,
     /// The polynomials of perm1, perm2, ..., perm_{n_wires_per_gate}.
     pub s_polys: Vec<O>,
@@ -60,7 +273,7 @@ pub struct PlonkProverParams<O, C, F> {
     pub l1_coefs: FpPolynomial<F>,
     /// The l1's FFT of the polynomial of unity root set.
     pub l1_coset_evals: Vec<F>,
-    /// The selector polynomials' FFT of the polynomial of unity root set.
+    /// The selector polynomials' FFT of the polynomial of unity root set, in the order of `q_polys`.
     pub q_coset_evals: Vec<Vec<F>>,
     /// The permutation polynomials' FFT of the polynomial of unity root set.
     pub s_coset_evals: Vec<Vec<F>>,
@@ -92,7 +305,8 @@ impl<O, C, F> PlonkProverParams<O, C, F> {
 /// Plonk verifier parameters.
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct PlonkVerifierParams<C, F> {
-    /// The commitments of the selectors.
+    /// The commitments of the selectors, followed by the ones of the lookup argument, if any:
+    /// the lookup selector, the table selector, and the columns of the lookup tables.
     pub cm_q_vec: Vec<C>,
     /// The commitments of perm1, perm2, ..., perm_{n_wires_per_gate}.
     pub cm_s_vec: Vec<C>,
@@ -114,6 +328,13 @@ pub struct PlonkVerifierParams<C, F> {
     pub lagrange_constants: Vec<F>,
 }
 
+impl<C, F: Scalar> PlonkVerifierParams<C, F> {
+    /// Check that the coset separators `k` are valid, see `validate_ks`.
+    pub fn validate_coset_separators(&self) -> Result<()> {
+        validate_ks(&self.k)
+    }
+}
+
 /// Define the PLONK verifier params by given `PolyComScheme`.
 pub type PlonkVK<PCS> =
     PlonkVerifierParams<<PCS as PolyComScheme>::Commitment, <PCS as PolyComScheme>::Field>;
@@ -174,6 +395,7 @@ pub fn indexer_with_lagrange<PCS: PolyComScheme, CS: ConstraintSystem<Field = PC
     verifier_params: Option<PlonkVK<PCS>>,
 ) -> Result<PlonkPK<PCS>> {
     let no_verifier = verifier_params.is_none();
+    cs.validate_public_inputs().c(d!(PlonkError::SetupError))?;
 
     // It's okay to choose a fixed seed to generate quadratic non-residue.
     let mut prng = ChaChaRng::from_seed([0u8; 32]);
@@ -262,6 +484,24 @@ pub fn indexer_with_lagrange<PCS: PolyComScheme, CS: ConstraintSystem<Field = PC
         q_polys.push(q_coefs);
     }
 
+    // Step 2.1: compute the selector polynomials of the lookup argument and commit them.
+    let lookup_selectors = cs.lookup_selectors();
+    if (!lookup_selectors.is_empty() && lookup_selectors.len() != N_LOOKUP_SELECTORS)
+        || lookup_selectors.iter().any(|selector| selector.len() != n)
+    {
+        return Err(eg!(PlonkError::SetupError));
+    }
+    for selector in lookup_selectors.iter() {
+        let q_coefs = FpPolynomial::ifft_with_domain(&domain, selector);
+        q_coset_evals.push(q_coefs.coset_fft_with_domain(&domain_m, &k[1]));
+
+        if no_verifier {
+            let cm_q = commit(selector.to_vec(), &q_coefs)?;
+            cm_q_vec.push(cm_q);
+        }
+        q_polys.push(q_coefs);
+    }
+
     // Step 3: precompute two helper functions, L1 and Z_H.
     let mut l1_evals = FpPolynomial::from_coefs(vec![PCS::Field::zero(); group.len()]);
     l1_evals.coefs[0] = PCS::Field::from(n as u32); // X^n - 1 = (X - 1) (X^{n-1} + X^{n-2} + ... + 1)
@@ -357,9 +597,24 @@ pub fn indexer_with_lagrange<PCS: PolyComScheme, CS: ConstraintSystem<Field = PC
     })
 }
 
+/// Check that the coset separators `k` are as chosen by `choose_ks`: the first one is one,
+/// and the others are pairwise distinct quadratic non-residues, hence nonzero.
+pub fn validate_ks<F: Scalar>(k: &[F]) -> Result<()> {
+    if k.first() != Some(&F::one()) {
+        return Err(eg!(PlonkError::SetupError));
+    }
+    let exp = { u64_limbs_from_bytes(&F::field_size_minus_one_half()) };
+    for (i, ki) in k.iter().enumerate().skip(1) {
+        if ki.is_zero() || ki.pow(&exp) == F::one() || k[..i].contains(ki) {
+            return Err(eg!(PlonkError::SetupError));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
-    use crate::plonk::indexer::choose_ks;
+    use crate::plonk::indexer::{choose_ks, validate_ks};
     use noah_algebra::{bls12_381::BLSScalar, prelude::*};
 
     type F = BLSScalar;
@@ -379,4 +634,29 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_validate_ks() {
+        let mut prng = test_rng();
+        let m = 5;
+        let k = choose_ks::<_, F>(&mut prng, m);
+        assert!(validate_ks(&k).is_ok());
+
+        let mut bad_k = k.clone();
+        bad_k[3] = bad_k[2];
+        assert!(validate_ks(&bad_k).is_err());
+
+        let mut bad_k = k.clone();
+        bad_k[4] = F::zero();
+        assert!(validate_ks(&bad_k).is_err());
+
+        // a quadratic residue
+        let mut bad_k = k.clone();
+        bad_k[1] = k[1].square();
+        assert!(validate_ks(&bad_k).is_err());
+
+        let mut bad_k = k;
+        bad_k[0] = F::from(2u32);
+        assert!(validate_ks(&bad_k).is_err());
+    }
 }
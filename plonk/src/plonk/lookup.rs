@@ -0,0 +1,275 @@
+//! The lookup argument, which checks that the lookup gates take their values from the lookup
+//! tables. It follows Plookup (<https://eprint.iacr.org/2020/315>), over the multiplicative
+//! group H of size n, where the differences between consecutive elements wrap around.
+//!
+//! The rows of the gates and of the tables are compressed with a challenge eta into
+//! ```text
+//!     f_i = q_lookup_i * (w0_i + eta * w1_i + eta^2 * w2_i + eta^3 * q_table_i)
+//!     t_i = col0_i + eta * col1_i + eta^2 * col2_i + eta^3 * col3_i
+//! ```
+//! where the first row of the tables is zero, so that `f_i = 0` is always found. The 2n values
+//! of f and t, sorted in the order of t, are split into h1 (the even positions) and h2 (the
+//! odd positions), and the grand product z satisfies z(1) = 1 and
+//! ```text
+//!     z(\omega X) (eps(1+delta) + h1(X) + delta h2(X)) (eps(1+delta) + h2(X) + delta h1(\omega X))
+//!         = z(X) (1+delta) (eps + f(X)) (eps(1+delta) + t(X) + delta t(\omega X))
+//! ```
+//! on H. As the sorted values start with t_0 and end with t_{n-1}, the pair that wraps around
+//! is the same on both sides.
+use crate::plonk::{
+    constraint_system::{N_LOOKUP_INPUTS, N_LOOKUP_SELECTORS},
+    errors::PlonkError,
+    helpers::PlonkChallenges,
+    indexer::PlonkLookupProof,
+};
+use crate::poly_commit::{
+    field_polynomial::FpPolynomial,
+    pcs::{HomomorphicPolyComElem, PolyComScheme},
+    transcript::PolyComTranscript,
+};
+use ark_ff::batch_inversion;
+use merlin::Transcript;
+use noah_algebra::{cfg_into_iter, prelude::*, traits::Domain};
+use std::collections::HashMap;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+/// The polynomials of the lookup argument built by the prover, and their commitments.
+pub(super) struct LookupPolys<PCS: PolyComScheme> {
+    /// The polynomial of the even positions of the sorted values.
+    pub(super) h1: FpPolynomial<PCS::Field>,
+    /// The polynomial of the odd positions of the sorted values.
+    pub(super) h2: FpPolynomial<PCS::Field>,
+    /// The grand product polynomial.
+    pub(super) z: FpPolynomial<PCS::Field>,
+    /// The commitment of h1.
+    pub(super) cm_h1: PCS::Commitment,
+    /// The commitment of h2.
+    pub(super) cm_h2: PCS::Commitment,
+    /// The commitment of z.
+    pub(super) cm_z: PCS::Commitment,
+}
+
+/// Compress a row of values into `values[0] + eta * values[1] + eta^2 * values[2] + ...`.
+pub(super) fn compress<F: Scalar>(values: &[&F], eta: &F) -> F {
+    let mut res = F::zero();
+    for value in values.iter().rev() {
+        res.mul_assign(eta);
+        res.add_assign(*value);
+    }
+    res
+}
+
+/// Compress the columns of the lookup tables, given as polynomials or commitments.
+pub(super) fn compress_table<F: Scalar, P: HomomorphicPolyComElem<Scalar = F>>(
+    columns: &[P],
+    eta: &F,
+) -> P {
+    let mut iter = columns.iter().rev();
+    let mut res = iter.next().unwrap().clone();
+    for column in iter {
+        res.mul_assign(eta);
+        res.add_assign(column);
+    }
+    res
+}
+
+/// Evaluate f, given the evaluations of the lookup selector, of the table selector, and of the
+/// wires, at the same point.
+pub(super) fn eval_f<F: Scalar>(q_lookup: &F, q_table: &F, wire_vals: &[&F], eta: &F) -> F {
+    let mut row = wire_vals[..N_LOOKUP_INPUTS].to_vec();
+    row.push(q_table);
+    q_lookup.mul(&compress(&row, eta))
+}
+
+/// Compute the values of f and t on the group, from the selectors of the lookup argument and
+/// the extended witness.
+pub(super) fn compress_lookups<F: Scalar>(
+    lookup_selectors: &[Vec<F>],
+    extended_witness: &[F],
+    eta: &F,
+) -> Result<(Vec<F>, Vec<F>)> {
+    if lookup_selectors.len() != N_LOOKUP_SELECTORS {
+        return Err(eg!(PlonkError::FuncParamsError));
+    }
+    let n = lookup_selectors[0].len();
+    let (q_lookup, q_table) = (&lookup_selectors[0], &lookup_selectors[1]);
+    let columns = &lookup_selectors[2..];
+
+    let f = (0..n)
+        .map(|i| {
+            let wire_vals: Vec<&F> = (0..N_LOOKUP_INPUTS)
+                .map(|j| &extended_witness[j * n + i])
+                .collect();
+            eval_f(&q_lookup[i], &q_table[i], &wire_vals, eta)
+        })
+        .collect();
+    let t = (0..n)
+        .map(|i| {
+            let row: Vec<&F> = columns.iter().map(|column| &column[i]).collect();
+            compress(&row, eta)
+        })
+        .collect();
+    Ok((f, t))
+}
+
+/// Sort the values of f and t in the order of t, by inserting the values of f after the first
+/// occurrence of the same value in t, and split them into h1 and h2.
+/// It fails if a value of f is not in t.
+pub(super) fn sort_lookups<F: Scalar>(f: &[F], t: &[F]) -> Result<(Vec<F>, Vec<F>)> {
+    let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+    for value in f.iter() {
+        *counts.entry(value.to_bytes()).or_insert(0) += 1;
+    }
+
+    let mut sorted = Vec::with_capacity(f.len() + t.len());
+    for value in t.iter() {
+        sorted.push(*value);
+        if let Some(count) = counts.remove(&value.to_bytes()) {
+            sorted.extend(std::iter::repeat(*value).take(count));
+        }
+    }
+    if !counts.is_empty() {
+        return Err(eg!(PlonkError::ProofErrorInvalidWitness));
+    }
+
+    let h1 = sorted.iter().step_by(2).cloned().collect();
+    let h2 = sorted.iter().skip(1).step_by(2).cloned().collect();
+    Ok((h1, h2))
+}
+
+/// Compute (1 + delta) (epsilon + f) (epsilon (1 + delta) + t + delta * t_next).
+pub(super) fn lookup_numerator<F: Scalar>(f: &F, t: &F, t_next: &F, delta: &F, epsilon: &F) -> F {
+    let one_plus_delta = F::one().add(delta);
+    one_plus_delta
+        .mul(&epsilon.add(f))
+        .mul(&epsilon.mul(&one_plus_delta).add(t).add(&delta.mul(t_next)))
+}
+
+/// Compute (epsilon (1 + delta) + h1 + delta * h2) (epsilon (1 + delta) + h2 + delta * h1_next).
+pub(super) fn lookup_denominator<F: Scalar>(
+    h1: &F,
+    h2: &F,
+    h1_next: &F,
+    delta: &F,
+    epsilon: &F,
+) -> F {
+    let epsilon_one_plus_delta = epsilon.mul(&F::one().add(delta));
+    epsilon_one_plus_delta
+        .add(h1)
+        .add(&delta.mul(h2))
+        .mul(&epsilon_one_plus_delta.add(h2).add(&delta.mul(h1_next)))
+}
+
+/// Compute the values of the grand product z on the group, with z(1) = 1.
+pub(super) fn lookup_z_evals<F: Domain>(
+    f: &[F],
+    t: &[F],
+    h1: &[F],
+    h2: &[F],
+    challenges: &PlonkChallenges<F>,
+) -> Vec<F> {
+    let (delta, epsilon) = challenges.get_delta_epsilon().unwrap();
+    let n = f.len();
+
+    let res = cfg_into_iter!(0..n - 1)
+        .map(|i| {
+            let numerator = lookup_numerator(&f[i], &t[i], &t[i + 1], delta, epsilon);
+            let denominator = lookup_denominator(&h1[i], &h2[i], &h1[i + 1], delta, epsilon);
+            (numerator, denominator)
+        })
+        .collect::<Vec<(F, F)>>();
+
+    let (numerators, denominators): (Vec<F>, Vec<F>) = res.into_iter().unzip();
+    let mut denominators = denominators
+        .iter()
+        .map(|x| x.get_field())
+        .collect::<Vec<F::Field>>();
+    batch_inversion(&mut denominators);
+
+    let mut prev = F::one();
+    let mut z_evals = Vec::with_capacity(n);
+    z_evals.push(prev);
+    for (x, y) in denominators.iter().zip(numerators.iter()) {
+        prev.mul_assign(&y.mul(&F::from_field(*x)));
+        z_evals.push(prev);
+    }
+    z_evals
+}
+
+/// Compute the scalar factor of the lookup grand product z(X) in the r polynomial:
+/// alpha^10 * (1 + delta) (epsilon + f(zeta)) (epsilon (1 + delta) + t(zeta) + delta * t(zeta * omega))
+///     + alpha^11 * L1(zeta)
+pub(super) fn lookup_z_scalar_in_r<C, F: Scalar>(
+    w_polys_eval_zeta: &[&F],
+    lookup: &PlonkLookupProof<C, F>,
+    challenges: &PlonkChallenges<F>,
+    first_lagrange_eval_zeta: &F,
+) -> F {
+    let alpha = challenges.get_alpha().unwrap();
+    let alpha_pow_10 = alpha.pow(&[10u64]);
+    let eta = challenges.get_eta().unwrap();
+    let (delta, epsilon) = challenges.get_delta_epsilon().unwrap();
+
+    let f_eval_zeta = eval_f(
+        &lookup.q_lookup_eval_zeta,
+        &lookup.q_table_eval_zeta,
+        w_polys_eval_zeta,
+        eta,
+    );
+    let numerator = lookup_numerator(
+        &f_eval_zeta,
+        &lookup.table_eval_zeta,
+        &lookup.table_eval_zeta_omega,
+        delta,
+        epsilon,
+    );
+    alpha_pow_10.mul(&numerator.add(&alpha.mul(first_lagrange_eval_zeta)))
+}
+
+/// Compute the constant terms of the lookup argument in the r polynomial, negated:
+/// alpha^10 * z(zeta * omega) (epsilon (1 + delta) + h1(zeta) + delta * h2(zeta))
+///     (epsilon (1 + delta) + h2(zeta) + delta * h1(zeta * omega)) + alpha^11 * L1(zeta)
+pub(super) fn lookup_r_eval_zeta<C, F: Scalar>(
+    lookup: &PlonkLookupProof<C, F>,
+    challenges: &PlonkChallenges<F>,
+    first_lagrange_eval_zeta: &F,
+) -> F {
+    let alpha = challenges.get_alpha().unwrap();
+    let alpha_pow_10 = alpha.pow(&[10u64]);
+    let (delta, epsilon) = challenges.get_delta_epsilon().unwrap();
+
+    let denominator = lookup_denominator(
+        &lookup.h1_eval_zeta,
+        &lookup.h2_eval_zeta,
+        &lookup.h1_eval_zeta_omega,
+        delta,
+        epsilon,
+    );
+    alpha_pow_10.mul(
+        &lookup
+            .z_eval_zeta_omega
+            .mul(&denominator)
+            .add(&alpha.mul(first_lagrange_eval_zeta)),
+    )
+}
+
+/// Append the openings of the lookup argument to the transcript.
+pub(super) fn append_lookup_evals<C, F: Scalar>(
+    transcript: &mut Transcript,
+    lookup: &PlonkLookupProof<C, F>,
+) {
+    for eval in [
+        &lookup.q_lookup_eval_zeta,
+        &lookup.q_table_eval_zeta,
+        &lookup.table_eval_zeta,
+        &lookup.table_eval_zeta_omega,
+        &lookup.h1_eval_zeta,
+        &lookup.h2_eval_zeta,
+        &lookup.h1_eval_zeta_omega,
+        &lookup.z_eval_zeta_omega,
+    ] {
+        transcript.append_field_elem(eval);
+    }
+}
@@ -12,6 +12,9 @@ pub mod errors;
 /// Module for prover.
 pub mod prover;
 
+/// Module for the lookup argument.
+pub(crate) mod lookup;
+
 /// Module for indexer.
 pub mod indexer;
 
@@ -1,15 +1,21 @@
 use crate::plonk::{
-    constraint_system::ConstraintSystem,
+    constraint_system::{ConstraintSystem, N_LOOKUP_SELECTORS},
     errors::PlonkError,
     helpers::{
         first_lagrange_poly, hide_polynomial, pi_poly, r_poly, split_t_and_commit, t_poly, z_poly,
         PlonkChallenges,
     },
-    indexer::{PlonkPK, PlonkPf, PlonkProof},
+    indexer::{PlonkLookupProof, PlonkPK, PlonkPf, PlonkProof},
+    lookup::{
+        append_lookup_evals, compress_lookups, compress_table, lookup_z_evals, sort_lookups,
+        LookupPolys,
+    },
     transcript::{
         transcript_get_plonk_challenge_alpha, transcript_get_plonk_challenge_beta,
-        transcript_get_plonk_challenge_gamma, transcript_get_plonk_challenge_u,
-        transcript_get_plonk_challenge_zeta, transcript_init_plonk,
+        transcript_get_plonk_challenge_delta, transcript_get_plonk_challenge_epsilon,
+        transcript_get_plonk_challenge_eta, transcript_get_plonk_challenge_gamma,
+        transcript_get_plonk_challenge_u, transcript_get_plonk_challenge_zeta,
+        transcript_init_plonk,
     },
 };
 use crate::poly_commit::{
@@ -19,10 +25,77 @@ use ark_poly::Radix2EvaluationDomain;
 use ark_std::{end_timer, start_timer};
 use merlin::Transcript;
 use noah_algebra::{prelude::*, traits::Domain};
+use rand_chacha::ChaChaRng;
 
 #[cfg(target_arch = "wasm32")]
 use {noah_algebra::bls12_381::init_fast_msm_wasm, wasm_bindgen::prelude::*};
 
+#[cfg(feature = "prove-timing")]
+use std::time::{Duration, Instant};
+
+/// The time spent by the prover in each phase.
+#[cfg(feature = "prove-timing")]
+#[derive(Clone, Debug, Default)]
+pub struct ProveTiming {
+    /// Extending the witness and computing the public input polynomial.
+    pub witness_extension: Duration,
+    /// Interpolating (with FFTs) and hiding the witness polynomials.
+    pub w_polys: Duration,
+    /// Computing, interpolating and hiding the z polynomial.
+    pub z_poly: Duration,
+    /// Computing the t polynomial.
+    pub t_poly: Duration,
+    /// Evaluating the polynomials at the challenges and computing the r polynomial.
+    pub r_poly: Duration,
+    /// Committing the witness, z, and t polynomials.
+    pub commitments: Duration,
+    /// Computing the opening proofs.
+    pub openings: Duration,
+}
+
+#[cfg(not(feature = "prove-timing"))]
+#[derive(Default)]
+struct ProveTiming;
+
+/// How the prover uses the Lagrange bases of the SRS, which speed up the commitments.
+/// The Lagrange bases are only usable if their size is the size of the constraint system.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LagrangeMode {
+    /// Use the Lagrange bases, and fail if they are not usable.
+    Require,
+    /// Use the Lagrange bases if they are usable, and otherwise commit in coefficient form.
+    #[default]
+    Prefer,
+    /// Always commit in coefficient form.
+    Disable,
+}
+
+/// The options of the prover.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ProvingOptions {
+    /// How the Lagrange bases are used.
+    pub lagrange: LagrangeMode,
+}
+
+/// Evaluate the expression, adding the time spent to the given phase.
+#[cfg(feature = "prove-timing")]
+macro_rules! timed {
+    ($timing:ident.$phase:ident, $e:expr) => {{
+        let start = Instant::now();
+        let res = $e;
+        $timing.$phase += start.elapsed();
+        res
+    }};
+}
+
+#[cfg(not(feature = "prove-timing"))]
+macro_rules! timed {
+    ($timing:ident.$phase:ident, $e:expr) => {{
+        let _ = &$timing;
+        $e
+    }};
+}
+
 /// PLONK Prover: it produces a proof that `witness` satisfies the constraint system `cs`,
 /// Proof verifier must use a transcript with same state as prover and match the public parameters,
 /// It returns [PlonkError] if an error occurs in computing proof commitments, meaning parameters of the polynomial
@@ -91,6 +164,11 @@ pub fn prover<
 }
 
 /// Prover that uses Lagrange bases
+///
+/// Proofs are randomized: the witness, z, and t polynomials are hidden with blinds sampled
+/// from `prng`, which makes the proof zero-knowledge. All the randomness comes from `prng`,
+/// and is sampled in a fixed order, so the proof is a deterministic function of the state of
+/// `prng`, the transcript, the parameters, and the witness. See `prove_deterministic`.
 pub fn prover_with_lagrange<
     R: CryptoRng + RngCore,
     PCS: PolyComScheme,
@@ -103,6 +181,122 @@ pub fn prover_with_lagrange<
     cs: &CS,
     prover_params: &PlonkPK<PCS>,
     w: &[PCS::Field],
+) -> Result<PlonkPf<PCS>> {
+    prover_with_options(
+        prng,
+        transcript,
+        pcs,
+        lagrange_pcs,
+        cs,
+        prover_params,
+        w,
+        &ProvingOptions::default(),
+    )
+}
+
+/// Prover that uses Lagrange bases according to `options`.
+/// With the default options, this is `prover_with_lagrange`.
+#[allow(clippy::too_many_arguments)]
+pub fn prover_with_options<
+    R: CryptoRng + RngCore,
+    PCS: PolyComScheme,
+    CS: ConstraintSystem<Field = PCS::Field>,
+>(
+    prng: &mut R,
+    transcript: &mut Transcript,
+    pcs: &PCS,
+    lagrange_pcs: Option<&PCS>,
+    cs: &CS,
+    prover_params: &PlonkPK<PCS>,
+    w: &[PCS::Field],
+    options: &ProvingOptions,
+) -> Result<PlonkPf<PCS>> {
+    let mut timing = ProveTiming::default();
+    prove_phases(
+        prng,
+        transcript,
+        pcs,
+        lagrange_pcs,
+        cs,
+        prover_params,
+        w,
+        options,
+        &mut timing,
+    )
+}
+
+/// Prover that uses Lagrange bases, and also returns the time spent in each phase.
+/// The proof is the same as the one of `prover_with_lagrange`.
+#[cfg(feature = "prove-timing")]
+pub fn prove_with_timing<
+    R: CryptoRng + RngCore,
+    PCS: PolyComScheme,
+    CS: ConstraintSystem<Field = PCS::Field>,
+>(
+    prng: &mut R,
+    transcript: &mut Transcript,
+    pcs: &PCS,
+    lagrange_pcs: Option<&PCS>,
+    cs: &CS,
+    prover_params: &PlonkPK<PCS>,
+    w: &[PCS::Field],
+) -> Result<(PlonkPf<PCS>, ProveTiming)> {
+    let mut timing = ProveTiming::default();
+    let proof = prove_phases(
+        prng,
+        transcript,
+        pcs,
+        lagrange_pcs,
+        cs,
+        prover_params,
+        w,
+        &ProvingOptions::default(),
+        &mut timing,
+    )?;
+    Ok((proof, timing))
+}
+
+/// Prover that uses Lagrange bases, with all the randomness derived from `seed`.
+/// The same seed, transcript, parameters, and witness always give byte-identical proofs.
+///
+/// The seed must be secret and must never be reused for a different witness of the same
+/// constraint system, as the reused blinds would leak information about the witnesses.
+pub fn prove_deterministic<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
+    seed: [u8; 32],
+    transcript: &mut Transcript,
+    pcs: &PCS,
+    lagrange_pcs: Option<&PCS>,
+    cs: &CS,
+    prover_params: &PlonkPK<PCS>,
+    w: &[PCS::Field],
+) -> Result<PlonkPf<PCS>> {
+    let mut prng = ChaChaRng::from_seed(seed);
+    prover_with_lagrange(
+        &mut prng,
+        transcript,
+        pcs,
+        lagrange_pcs,
+        cs,
+        prover_params,
+        w,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prove_phases<
+    R: CryptoRng + RngCore,
+    PCS: PolyComScheme,
+    CS: ConstraintSystem<Field = PCS::Field>,
+>(
+    prng: &mut R,
+    transcript: &mut Transcript,
+    pcs: &PCS,
+    lagrange_pcs: Option<&PCS>,
+    cs: &CS,
+    prover_params: &PlonkPK<PCS>,
+    w: &[PCS::Field],
+    options: &ProvingOptions,
+    timing: &mut ProveTiming,
 ) -> Result<PlonkPf<PCS>> {
     if cs.is_verifier_only() {
         return Err(eg!(PlonkError::FuncParamsError));
@@ -134,18 +328,29 @@ pub fn prover_with_lagrange<
     let mut challenges = PlonkChallenges::new();
     let n_constraints = cs.size();
 
-    let lagrange_pcs =
-        if lagrange_pcs.is_some() && lagrange_pcs.unwrap().max_degree() + 1 == n_constraints {
-            lagrange_pcs
-        } else {
-            None
-        };
+    let has_lookup = prover_params.q_polys.len() > cs.num_selectors();
+    if has_lookup && cs.lookup_selectors().len() != N_LOOKUP_SELECTORS {
+        return Err(eg!(PlonkError::FuncParamsError));
+    }
+
+    let usable_lagrange_pcs =
+        lagrange_pcs.filter(|lagrange_pcs| lagrange_pcs.max_degree() + 1 == n_constraints);
+    let lagrange_pcs = match options.lagrange {
+        LagrangeMode::Require => {
+            Some(usable_lagrange_pcs.c(d!(PlonkError::LagrangeBasesUnavailable(n_constraints)))?)
+        }
+        LagrangeMode::Prefer => usable_lagrange_pcs,
+        LagrangeMode::Disable => None,
+    };
 
     let extended_witness_and_pi_timer =
         start_timer!(|| "Prepare the extended witness and the input");
     // Prepare extended witness
-    let extended_witness = cs.extend_witness(w);
-    let pi = pi_poly::<PCS, Radix2EvaluationDomain<_>>(&prover_params, &online_values, &domain);
+    let (extended_witness, pi) = timed!(timing.witness_extension, {
+        let extended_witness = cs.extend_witness(w);
+        let pi = pi_poly::<PCS, Radix2EvaluationDomain<_>>(&prover_params, &online_values, &domain);
+        (extended_witness, pi)
+    });
     end_timer!(extended_witness_and_pi_timer);
 
     // 1. build witness polynomials, hide them and commit
@@ -162,21 +367,25 @@ pub fn prover_with_lagrange<
             let f_eval = FpPolynomial::from_coefs(
                 extended_witness[i * n_constraints..(i + 1) * n_constraints].to_vec(),
             );
-            let mut f_coefs = FpPolynomial::ifft_with_domain(
-                &domain,
-                &extended_witness[i * n_constraints..(i + 1) * n_constraints],
-            );
-
-            let blinds =
-                hide_polynomial(prng, &mut f_coefs, cs.get_hiding_degree(i), n_constraints);
+            let (f_coefs, blinds) = timed!(timing.w_polys, {
+                let mut f_coefs = FpPolynomial::ifft_with_domain(
+                    &domain,
+                    &extended_witness[i * n_constraints..(i + 1) * n_constraints],
+                );
+                let blinds =
+                    hide_polynomial(prng, &mut f_coefs, cs.get_hiding_degree(i), n_constraints);
+                (f_coefs, blinds)
+            });
             end_timer!(this_w_poly_timer);
 
             let this_w_comm_timer = start_timer!(|| "Commit the polynomial");
 
-            let cm_w = lagrange_pcs
-                .commit(&f_eval)
-                .c(d!(PlonkError::CommitmentError))?;
-            let cm_w = pcs.apply_blind_factors(&cm_w, &blinds, n_constraints);
+            let cm_w = timed!(timing.commitments, {
+                let cm_w = lagrange_pcs
+                    .commit(&f_eval)
+                    .c(d!(PlonkError::CommitmentError))?;
+                pcs.apply_blind_factors(&cm_w, &blinds, n_constraints)
+            });
             transcript.append_commitment::<PCS::Commitment>(&cm_w);
             end_timer!(this_w_comm_timer);
 
@@ -190,15 +399,21 @@ pub fn prover_with_lagrange<
             let this_w_timer = start_timer!(|| format!("Round 1: processing wire {}", i));
 
             let this_w_poly_timer = start_timer!(|| "Prepare the polynomial");
-            let mut f_coefs = FpPolynomial::ifft_with_domain(
-                &domain,
-                &extended_witness[i * n_constraints..(i + 1) * n_constraints],
-            );
-            let _ = hide_polynomial(prng, &mut f_coefs, cs.get_hiding_degree(i), n_constraints);
+            let f_coefs = timed!(timing.w_polys, {
+                let mut f_coefs = FpPolynomial::ifft_with_domain(
+                    &domain,
+                    &extended_witness[i * n_constraints..(i + 1) * n_constraints],
+                );
+                let _ = hide_polynomial(prng, &mut f_coefs, cs.get_hiding_degree(i), n_constraints);
+                f_coefs
+            });
             end_timer!(this_w_poly_timer);
 
             let this_w_comm_timer = start_timer!(|| "Commit the polynomial");
-            let cm_w = pcs.commit(&f_coefs).c(d!(PlonkError::CommitmentError))?;
+            let cm_w = timed!(
+                timing.commitments,
+                pcs.commit(&f_coefs).c(d!(PlonkError::CommitmentError))?
+            );
             transcript.append_commitment::<PCS::Commitment>(&cm_w);
             end_timer!(this_w_comm_timer);
 
@@ -210,39 +425,77 @@ pub fn prover_with_lagrange<
     }
     end_timer!(w_timer);
 
-    // 2. get challenges beta and gamma
+    // 1.1 get challenge eta, build the sorted lookups h1 and h2, hide them and commit
+    let lookup_values = if has_lookup {
+        let eta = transcript_get_plonk_challenge_eta(transcript, n_constraints);
+        challenges.insert_eta(eta).unwrap();
+
+        let (f, t) = compress_lookups(cs.lookup_selectors(), &extended_witness, &eta).c(d!())?;
+        let (h1, h2) = sort_lookups(&f, &t).c(d!())?;
+        let (h1_poly, cm_h1) = timed!(
+            timing.w_polys,
+            interpolate_hide_and_commit(prng, pcs, lagrange_pcs, &domain, &h1, 3)?
+        );
+        let (h2_poly, cm_h2) = timed!(
+            timing.w_polys,
+            interpolate_hide_and_commit(prng, pcs, lagrange_pcs, &domain, &h2, 2)?
+        );
+        transcript.append_commitment::<PCS::Commitment>(&cm_h1);
+        transcript.append_commitment::<PCS::Commitment>(&cm_h2);
+        Some((f, t, h1, h2, h1_poly, h2_poly, cm_h1, cm_h2))
+    } else {
+        None
+    };
+
+    // 2. get challenges beta and gamma, and delta and epsilon for the lookups
     let beta = transcript_get_plonk_challenge_beta(transcript, n_constraints);
     let gamma = transcript_get_plonk_challenge_gamma(transcript, n_constraints);
     challenges.insert_beta_gamma(beta, gamma).unwrap(); // safe unwrap
+    if has_lookup {
+        let delta = transcript_get_plonk_challenge_delta(transcript, n_constraints);
+        let epsilon = transcript_get_plonk_challenge_epsilon(transcript, n_constraints);
+        challenges.insert_delta_epsilon(delta, epsilon).unwrap();
+    }
 
     // 3. build the z polynomial, hide it and commit
 
     let z_timer = start_timer!(|| "Round 2: z polynomial");
     let (cm_z, z_poly) = if let Some(lagrange_pcs) = lagrange_pcs {
         let z_poly_timer = start_timer!(|| "Prepare the polynomial");
-        let z_evals = z_poly::<PCS, CS>(prover_params, &extended_witness, &challenges);
-        let mut z_coefs = FpPolynomial::ifft_with_domain(&domain, &z_evals.coefs);
-        let blinds = hide_polynomial(prng, &mut z_coefs, 3, n_constraints);
+        let (z_evals, z_coefs, blinds) = timed!(timing.z_poly, {
+            let z_evals = z_poly::<PCS, CS>(prover_params, &extended_witness, &challenges);
+            let mut z_coefs = FpPolynomial::ifft_with_domain(&domain, &z_evals.coefs);
+            let blinds = hide_polynomial(prng, &mut z_coefs, 3, n_constraints);
+            (z_evals, z_coefs, blinds)
+        });
         end_timer!(z_poly_timer);
 
         let z_comm_timer = start_timer!(|| "Commit the polynomial");
-        let cm_z = lagrange_pcs
-            .commit(&z_evals)
-            .c(d!(PlonkError::CommitmentError))?;
-        let cm_z = pcs.apply_blind_factors(&cm_z, &blinds, n_constraints);
+        let cm_z = timed!(timing.commitments, {
+            let cm_z = lagrange_pcs
+                .commit(&z_evals)
+                .c(d!(PlonkError::CommitmentError))?;
+            pcs.apply_blind_factors(&cm_z, &blinds, n_constraints)
+        });
         transcript.append_commitment::<PCS::Commitment>(&cm_z);
         end_timer!(z_comm_timer);
 
         (cm_z, z_coefs)
     } else {
         let z_poly_timer = start_timer!(|| "Prepare the polynomial");
-        let z_evals = z_poly::<PCS, CS>(prover_params, &extended_witness, &challenges);
-        let mut z_coefs = FpPolynomial::ifft_with_domain(&domain, &z_evals.coefs);
-        let _ = hide_polynomial(prng, &mut z_coefs, 3, n_constraints);
+        let z_coefs = timed!(timing.z_poly, {
+            let z_evals = z_poly::<PCS, CS>(prover_params, &extended_witness, &challenges);
+            let mut z_coefs = FpPolynomial::ifft_with_domain(&domain, &z_evals.coefs);
+            let _ = hide_polynomial(prng, &mut z_coefs, 3, n_constraints);
+            z_coefs
+        });
         end_timer!(z_poly_timer);
 
         let z_comm_timer = start_timer!(|| "Commit the polynomial");
-        let cm_z = pcs.commit(&z_coefs).c(d!(PlonkError::CommitmentError))?;
+        let cm_z = timed!(
+            timing.commitments,
+            pcs.commit(&z_coefs).c(d!(PlonkError::CommitmentError))?
+        );
         transcript.append_commitment::<PCS::Commitment>(&cm_z);
         end_timer!(z_comm_timer);
 
@@ -250,6 +503,26 @@ pub fn prover_with_lagrange<
     };
     end_timer!(z_timer);
 
+    // 3.1 build the lookup grand product, hide it and commit
+    let lookup_polys = match lookup_values {
+        Some((f, t, h1_evals, h2_evals, h1, h2, cm_h1, cm_h2)) => {
+            let (z, cm_z) = timed!(timing.z_poly, {
+                let z_evals = lookup_z_evals(&f, &t, &h1_evals, &h2_evals, &challenges);
+                interpolate_hide_and_commit(prng, pcs, lagrange_pcs, &domain, &z_evals, 3)?
+            });
+            transcript.append_commitment::<PCS::Commitment>(&cm_z);
+            Some(LookupPolys::<PCS> {
+                h1,
+                h2,
+                z,
+                cm_h1,
+                cm_h2,
+                cm_z,
+            })
+        }
+        None => None,
+    };
+
     // 4. get challenge alpha
     let alpha = transcript_get_plonk_challenge_alpha(transcript, n_constraints);
     challenges.insert_alpha(alpha).unwrap();
@@ -257,19 +530,33 @@ pub fn prover_with_lagrange<
     // 5. build t, split into `n_wires_per_gate` degree-(N+2) polynomials and commit
     let t_timer = start_timer!(|| "Round 3: t polynomial");
     let t_poly_timer = start_timer!(|| "Prepare the polynomial");
-    let t_poly =
-        t_poly::<PCS, CS>(cs, prover_params, &w_polys, &z_poly, &challenges, &pi).c(d!())?;
+    let t_poly = timed!(
+        timing.t_poly,
+        t_poly::<PCS, CS>(
+            cs,
+            prover_params,
+            &w_polys,
+            &z_poly,
+            &challenges,
+            &pi,
+            lookup_polys.as_ref()
+        )
+        .c(d!())?
+    );
     end_timer!(t_poly_timer);
     let t_comm_timer = start_timer!(|| "Commit the polynomial");
-    let (cm_t_vec, t_polys) = split_t_and_commit(
-        prng,
-        pcs,
-        lagrange_pcs,
-        &t_poly,
-        n_wires_per_gate,
-        n_constraints + 2,
-    )
-    .c(d!())?;
+    let (cm_t_vec, t_polys) = timed!(
+        timing.commitments,
+        split_t_and_commit(
+            prng,
+            pcs,
+            lagrange_pcs,
+            &t_poly,
+            n_wires_per_gate,
+            n_constraints + 2,
+        )
+        .c(d!())?
+    );
     end_timer!(t_comm_timer);
     end_timer!(t_timer);
 
@@ -285,28 +572,66 @@ pub fn prover_with_lagrange<
     // evaluate the opening of z(X) at point \omega * \zeta.
     let r_timer = start_timer!(|| "Round 4: r polynomial and the rest");
     let eval_timer = start_timer!(|| "Compute the evaluation of polynomials");
-    let w_polys_eval_zeta: Vec<PCS::Field> =
-        w_polys.iter().map(|poly| pcs.eval(poly, &zeta)).collect();
-    let s_polys_eval_zeta: Vec<PCS::Field> = prover_params
-        .s_polys
-        .iter()
-        .take(n_wires_per_gate - 1)
-        .map(|poly| pcs.eval(poly, &zeta))
-        .collect();
-
-    let prk_3_poly_eval_zeta = pcs.eval(&prover_params.q_prk_polys[2], &zeta);
-    let prk_4_poly_eval_zeta = pcs.eval(&prover_params.q_prk_polys[3], &zeta);
-
     let zeta_omega = root.mul(&zeta);
-    let z_eval_zeta_omega = pcs.eval(&z_poly, &zeta_omega);
-
-    let w_polys_eval_zeta_omega: Vec<PCS::Field> = w_polys
-        .iter()
-        .take(3)
-        .map(|poly| pcs.eval(poly, &zeta_omega))
-        .collect();
+    let (
+        w_polys_eval_zeta,
+        s_polys_eval_zeta,
+        prk_3_poly_eval_zeta,
+        prk_4_poly_eval_zeta,
+        z_eval_zeta_omega,
+        w_polys_eval_zeta_omega,
+    ) = timed!(timing.r_poly, {
+        let w_polys_eval_zeta: Vec<PCS::Field> =
+            w_polys.iter().map(|poly| pcs.eval(poly, &zeta)).collect();
+        let s_polys_eval_zeta: Vec<PCS::Field> = prover_params
+            .s_polys
+            .iter()
+            .take(n_wires_per_gate - 1)
+            .map(|poly| pcs.eval(poly, &zeta))
+            .collect();
+
+        let prk_3_poly_eval_zeta = pcs.eval(&prover_params.q_prk_polys[2], &zeta);
+        let prk_4_poly_eval_zeta = pcs.eval(&prover_params.q_prk_polys[3], &zeta);
+
+        let z_eval_zeta_omega = pcs.eval(&z_poly, &zeta_omega);
+
+        let w_polys_eval_zeta_omega: Vec<PCS::Field> = w_polys
+            .iter()
+            .take(3)
+            .map(|poly| pcs.eval(poly, &zeta_omega))
+            .collect();
+        (
+            w_polys_eval_zeta,
+            s_polys_eval_zeta,
+            prk_3_poly_eval_zeta,
+            prk_4_poly_eval_zeta,
+            z_eval_zeta_omega,
+            w_polys_eval_zeta_omega,
+        )
+    });
     end_timer!(eval_timer);
 
+    // The compressed lookup tables, and the openings of the lookup argument.
+    let n_selectors = cs.num_selectors();
+    let lookup_table_and_proof = lookup_polys.as_ref().map(|lookup| {
+        let eta = challenges.get_eta().unwrap();
+        let table_poly = compress_table(&prover_params.q_polys[n_selectors + 2..], eta);
+        let lookup_proof = PlonkLookupProof {
+            cm_h1: lookup.cm_h1.clone(),
+            cm_h2: lookup.cm_h2.clone(),
+            cm_z: lookup.cm_z.clone(),
+            q_lookup_eval_zeta: pcs.eval(&prover_params.q_polys[n_selectors], &zeta),
+            q_table_eval_zeta: pcs.eval(&prover_params.q_polys[n_selectors + 1], &zeta),
+            table_eval_zeta: pcs.eval(&table_poly, &zeta),
+            table_eval_zeta_omega: pcs.eval(&table_poly, &zeta_omega),
+            h1_eval_zeta: pcs.eval(&lookup.h1, &zeta),
+            h2_eval_zeta: pcs.eval(&lookup.h2, &zeta),
+            h1_eval_zeta_omega: pcs.eval(&lookup.h1, &zeta_omega),
+            z_eval_zeta_omega: pcs.eval(&lookup.z, &zeta_omega),
+        };
+        (table_poly, lookup_proof)
+    });
+
     //  b). build the r polynomial, and eval at zeta
     for eval_zeta in w_polys_eval_zeta.iter().chain(s_polys_eval_zeta.iter()) {
         transcript.append_field_elem(eval_zeta);
@@ -317,6 +642,9 @@ pub fn prover_with_lagrange<
     for eval_zeta_omega in w_polys_eval_zeta_omega.iter() {
         transcript.append_field_elem(eval_zeta_omega);
     }
+    if let Some((_, lookup_proof)) = &lookup_table_and_proof {
+        append_lookup_evals(transcript, lookup_proof);
+    }
 
     // 8. get challenge u
     let u = transcript_get_plonk_challenge_u(transcript, cs.size());
@@ -328,18 +656,25 @@ pub fn prover_with_lagrange<
     let r_poly_timer = start_timer!(|| "Compute r polynomial");
     let (z_h_eval_zeta, first_lagrange_eval_zeta) =
         first_lagrange_poly::<PCS>(&challenges, cs.size() as u64);
-    let r_poly = r_poly::<PCS, CS>(
-        prover_params,
-        &z_poly,
-        &w_polys_eval_zeta_as_ref[..],
-        &s_poly_eval_zeta_as_ref[..],
-        &prk_3_poly_eval_zeta,
-        &z_eval_zeta_omega,
-        &challenges,
-        &t_polys,
-        &first_lagrange_eval_zeta,
-        &z_h_eval_zeta,
-        n_constraints + 2,
+    let r_poly = timed!(
+        timing.r_poly,
+        r_poly::<PCS, CS>(
+            prover_params,
+            &z_poly,
+            &w_polys_eval_zeta_as_ref[..],
+            &s_poly_eval_zeta_as_ref[..],
+            &prk_3_poly_eval_zeta,
+            &z_eval_zeta_omega,
+            &challenges,
+            &t_polys,
+            &first_lagrange_eval_zeta,
+            &z_h_eval_zeta,
+            n_constraints + 2,
+            lookup_polys
+                .as_ref()
+                .zip(lookup_table_and_proof.as_ref())
+                .map(|(lookup, (_, lookup_proof))| (&lookup.z, lookup_proof)),
+        )
     );
     end_timer!(r_poly_timer);
 
@@ -356,35 +691,51 @@ pub fn prover_with_lagrange<
     polys_to_open.push(&prover_params.q_prk_polys[2]);
     polys_to_open.push(&prover_params.q_prk_polys[3]);
     polys_to_open.push(&r_poly);
+    if let (Some(lookup), Some((table_poly, _))) = (&lookup_polys, &lookup_table_and_proof) {
+        polys_to_open.push(&prover_params.q_polys[n_selectors]);
+        polys_to_open.push(&prover_params.q_polys[n_selectors + 1]);
+        polys_to_open.push(table_poly);
+        polys_to_open.push(&lookup.h1);
+        polys_to_open.push(&lookup.h2);
+    }
     end_timer!(list_open_polys_timer);
 
     let zeta_proof_timer = start_timer!(|| "Compute the witness for opening at zeta");
     let zeta = challenges.get_zeta().unwrap();
 
-    let opening_witness_zeta = pcs
-        .batch_prove(
+    let opening_witness_zeta = timed!(
+        timing.openings,
+        pcs.batch_prove(
             transcript,
             lagrange_pcs,
             &polys_to_open[..],
             &zeta,
             n_constraints + 2,
         )
-        .c(d!(PlonkError::ProofError))?;
+        .c(d!(PlonkError::ProofError))?
+    );
     end_timer!(zeta_proof_timer);
 
     let zeta_omega_proof_timer = start_timer!(|| "Compute the witness for opening at zeta omega");
-    let polys_to_open: Vec<&FpPolynomial<PCS::Field>> =
+    let mut polys_to_open: Vec<&FpPolynomial<PCS::Field>> =
         vec![&z_poly, &w_polys[0], &w_polys[1], &w_polys[2]];
+    if let (Some(lookup), Some((table_poly, _))) = (&lookup_polys, &lookup_table_and_proof) {
+        polys_to_open.push(table_poly);
+        polys_to_open.push(&lookup.h1);
+        polys_to_open.push(&lookup.z);
+    }
 
-    let opening_witness_zeta_omega = pcs
-        .batch_prove(
+    let opening_witness_zeta_omega = timed!(
+        timing.openings,
+        pcs.batch_prove(
             transcript,
             lagrange_pcs,
             &polys_to_open[..],
             &zeta_omega,
             n_constraints + 2,
         )
-        .c(d!(PlonkError::ProofError))?;
+        .c(d!(PlonkError::ProofError))?
+    );
     end_timer!(zeta_omega_proof_timer);
 
     end_timer!(r_timer);
@@ -403,9 +754,34 @@ pub fn prover_with_lagrange<
         s_polys_eval_zeta,
         opening_witness_zeta,
         opening_witness_zeta_omega,
+        lookup: lookup_table_and_proof.map(|(_, lookup_proof)| lookup_proof),
     })
 }
 
+/// Interpolate the polynomial of the evaluations `evals` on the group, hide it with
+/// `hiding_degree` blinds, and commit it.
+fn interpolate_hide_and_commit<R: CryptoRng + RngCore, PCS: PolyComScheme>(
+    prng: &mut R,
+    pcs: &PCS,
+    lagrange_pcs: Option<&PCS>,
+    domain: &Radix2EvaluationDomain<<PCS::Field as Domain>::Field>,
+    evals: &[PCS::Field],
+    hiding_degree: usize,
+) -> Result<(FpPolynomial<PCS::Field>, PCS::Commitment)> {
+    let n = evals.len();
+    let mut coefs = FpPolynomial::ifft_with_domain(domain, evals);
+    let blinds = hide_polynomial(prng, &mut coefs, hiding_degree, n);
+    let cm = if let Some(lagrange_pcs) = lagrange_pcs {
+        let cm = lagrange_pcs
+            .commit(&FpPolynomial::from_coefs(evals.to_vec()))
+            .c(d!(PlonkError::CommitmentError))?;
+        pcs.apply_blind_factors(&cm, &blinds, n)
+    } else {
+        pcs.commit(&coefs).c(d!(PlonkError::CommitmentError))?
+    };
+    Ok((coefs, cm))
+}
+
 #[cfg(target_arch = "wasm32")]
 /// Init prover
 pub async fn init_prover() -> core::result::Result<(), JsValue> {
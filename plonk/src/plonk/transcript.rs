@@ -88,3 +88,27 @@ pub(crate) fn transcript_get_plonk_challenge_u<F: Scalar>(
 ) -> F {
     transcript_get_challenge_field_elem(transcript, group_order, b"u")
 }
+
+/// Return the challenge result by label: "eta".
+pub(crate) fn transcript_get_plonk_challenge_eta<F: Scalar>(
+    transcript: &mut Transcript,
+    group_order: usize,
+) -> F {
+    transcript_get_challenge_field_elem(transcript, group_order, b"eta")
+}
+
+/// Return the challenge result by label: "delta".
+pub(crate) fn transcript_get_plonk_challenge_delta<F: Scalar>(
+    transcript: &mut Transcript,
+    group_order: usize,
+) -> F {
+    transcript_get_challenge_field_elem(transcript, group_order, b"delta")
+}
+
+/// Return the challenge result by label: "epsilon".
+pub(crate) fn transcript_get_plonk_challenge_epsilon<F: Scalar>(
+    transcript: &mut Transcript,
+    group_order: usize,
+) -> F {
+    transcript_get_challenge_field_elem(transcript, group_order, b"epsilon")
+}
@@ -1,14 +1,17 @@
 use crate::poly_commit::{pcs::PolyComScheme, transcript::PolyComTranscript};
 use crate::{
     plonk::{
-        constraint_system::ConstraintSystem,
+        constraint_system::{ConstraintSystem, N_LOOKUP_SELECTORS},
         errors::PlonkError,
         helpers::{eval_pi_poly, first_lagrange_poly, r_commitment, r_eval_zeta, PlonkChallenges},
         indexer::{PlonkPf, PlonkVK},
+        lookup::{append_lookup_evals, compress_table},
         transcript::{
             transcript_get_plonk_challenge_alpha, transcript_get_plonk_challenge_beta,
-            transcript_get_plonk_challenge_gamma, transcript_get_plonk_challenge_u,
-            transcript_get_plonk_challenge_zeta, transcript_init_plonk,
+            transcript_get_plonk_challenge_delta, transcript_get_plonk_challenge_epsilon,
+            transcript_get_plonk_challenge_eta, transcript_get_plonk_challenge_gamma,
+            transcript_get_plonk_challenge_u, transcript_get_plonk_challenge_zeta,
+            transcript_init_plonk,
         },
     },
     poly_commit::field_polynomial::FpPolynomial,
@@ -25,10 +28,38 @@ pub fn verifier<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
     pi: &[PCS::Field],
     proof: &PlonkPf<PCS>,
 ) -> Result<()> {
+    let prepared = prepare_verifier(transcript, pcs, cs, verifier_params, pi, proof).c(d!())?;
+    pcs.verify_prepared(&[prepared], &[PCS::Field::one()])
+        .c(d!(PlonkError::VerificationError))
+}
+
+/// Run the verification of a proof up to its final check, and return the inputs of that check.
+/// The final checks of several proofs can then be performed at once, combined with random
+/// scalars, by `PolyComScheme::verify_prepared`.
+pub fn prepare_verifier<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
+    transcript: &mut Transcript,
+    pcs: &PCS,
+    cs: &CS,
+    verifier_params: &PlonkVK<PCS>,
+    pi: &[PCS::Field],
+    proof: &PlonkPf<PCS>,
+) -> Result<(PCS::Commitment, PCS::Commitment)> {
     let domain = FpPolynomial::<PCS::Field>::evaluation_domain(cs.size())
         .c(d!(PlonkError::GroupNotFound(cs.size())))?;
     let root = PCS::Field::from_field(domain.group_gen);
 
+    // The lookup selectors follow the selectors in the verifier parameters, and the proof has
+    // a lookup part if and only if they are present.
+    let n_selectors = cs.num_selectors();
+    let has_lookup = match verifier_params.cm_q_vec.len().checked_sub(n_selectors) {
+        Some(0) => false,
+        Some(N_LOOKUP_SELECTORS) => true,
+        _ => return Err(eg!(PlonkError::VerificationError)),
+    };
+    if proof.lookup.is_some() != has_lookup {
+        return Err(eg!(PlonkError::VerificationError));
+    }
+
     transcript_init_plonk(transcript, verifier_params, pi, &root);
     let mut challenges = PlonkChallenges::new();
     // 1. compute all challenges such as gamma, beta, alpha, zeta and u.
@@ -71,6 +102,7 @@ pub fn verifier<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
         &first_lagrange_eval_zeta,
         &z_h_eval_zeta,
         verifier_params.cs_size + 2,
+        proof.lookup.as_ref(),
     );
 
     // 5. verify opening proofs.
@@ -98,6 +130,45 @@ pub fn verifier<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
     values.push(proof.prk_4_poly_eval_zeta);
     values.push(r_eval_zeta);
 
+    let mut commitments_omega = vec![
+        &proof.cm_z,
+        &proof.cm_w_vec[0],
+        &proof.cm_w_vec[1],
+        &proof.cm_w_vec[2],
+    ];
+    let mut values_omega = vec![
+        proof.z_eval_zeta_omega,
+        proof.w_polys_eval_zeta_omega[0],
+        proof.w_polys_eval_zeta_omega[1],
+        proof.w_polys_eval_zeta_omega[2],
+    ];
+
+    let cm_table = proof.lookup.as_ref().map(|_| {
+        compress_table(
+            &verifier_params.cm_q_vec[n_selectors + 2..],
+            challenges.get_eta().unwrap(),
+        )
+    });
+    if let (Some(lookup), Some(cm_table)) = (&proof.lookup, &cm_table) {
+        commitments.push(&verifier_params.cm_q_vec[n_selectors]);
+        commitments.push(&verifier_params.cm_q_vec[n_selectors + 1]);
+        commitments.push(cm_table);
+        commitments.push(&lookup.cm_h1);
+        commitments.push(&lookup.cm_h2);
+        values.push(lookup.q_lookup_eval_zeta);
+        values.push(lookup.q_table_eval_zeta);
+        values.push(lookup.table_eval_zeta);
+        values.push(lookup.h1_eval_zeta);
+        values.push(lookup.h2_eval_zeta);
+
+        commitments_omega.push(cm_table);
+        commitments_omega.push(&lookup.cm_h1);
+        commitments_omega.push(&lookup.cm_z);
+        values_omega.push(lookup.table_eval_zeta_omega);
+        values_omega.push(lookup.h1_eval_zeta_omega);
+        values_omega.push(lookup.z_eval_zeta_omega);
+    }
+
     let zeta = challenges.get_zeta().unwrap();
     let zeta_omega = zeta.mul(&root);
 
@@ -111,23 +182,13 @@ pub fn verifier<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
 
     let (comm_omega, val_omega) = pcs.batch(
         transcript,
-        &[
-            &proof.cm_z,
-            &proof.cm_w_vec[0],
-            &proof.cm_w_vec[1],
-            &proof.cm_w_vec[2],
-        ],
+        &commitments_omega[..],
         verifier_params.cs_size + 2,
         &zeta_omega,
-        &[
-            proof.z_eval_zeta_omega,
-            proof.w_polys_eval_zeta_omega[0],
-            proof.w_polys_eval_zeta_omega[1],
-            proof.w_polys_eval_zeta_omega[2],
-        ],
+        &values_omega[..],
     );
 
-    pcs.batch_verify_diff_points(
+    Ok(pcs.prepare_batch_verify_diff_points(
         transcript,
         &[comm, comm_omega],
         verifier_params.cs_size + 32,
@@ -138,8 +199,55 @@ pub fn verifier<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
             proof.opening_witness_zeta_omega.clone(),
         ],
         challenges.get_u().unwrap(),
-    )
-    .c(d!(PlonkError::VerificationError))
+    ))
+}
+
+/// Check that a proof was generated for exactly the public inputs `expected_pis`.
+///
+/// This checks that the number of public inputs matches the constraint system, then runs
+/// the complete verification with `expected_pis`, which fails if the proof was generated
+/// for other public inputs. It then changes each public input in turn, and checks that the
+/// verification fails, so that no public input is left unbound to the proof.
+///
+/// It is redundant with a correct call to `verifier`, but helps catch integration bugs where
+/// the wrong public inputs are passed. It runs one verification per public input, plus one.
+pub fn assert_proof_binds_to<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
+    transcript: &mut Transcript,
+    pcs: &PCS,
+    cs: &CS,
+    verifier_params: &PlonkVK<PCS>,
+    proof: &PlonkPf<PCS>,
+    expected_pis: &[PCS::Field],
+) -> Result<()> {
+    if expected_pis.len() != verifier_params.public_vars_constraint_indices.len() {
+        return Err(eg!(PlonkError::FuncParamsError));
+    }
+    let initial_transcript = transcript.clone();
+    verifier(transcript, pcs, cs, verifier_params, expected_pis, proof)
+        .c(d!(PlonkError::VerificationError))?;
+
+    let mut tampered_pis = expected_pis.to_vec();
+    for i in 0..tampered_pis.len() {
+        tampered_pis[i].add_assign(&PCS::Field::one());
+        let mut transcript = initial_transcript.clone();
+        if verifier(
+            &mut transcript,
+            pcs,
+            cs,
+            verifier_params,
+            &tampered_pis,
+            proof,
+        )
+        .is_ok()
+        {
+            return Err(eg!(format!(
+                "the proof is not bound to the public input {}",
+                i
+            )));
+        }
+        tampered_pis[i] = expected_pis[i];
+    }
+    Ok(())
 }
 
 fn compute_challenges<PCS: PolyComScheme>(
@@ -148,16 +256,30 @@ fn compute_challenges<PCS: PolyComScheme>(
     proof: &PlonkPf<PCS>,
     group_order: usize,
 ) {
-    // 1. compute gamma and beta challenges.
+    // 1. compute gamma and beta challenges, and the challenges of the lookups.
     for cm_w in proof.cm_w_vec.iter() {
         transcript.append_commitment::<PCS::Commitment>(cm_w);
     }
+    if let Some(lookup) = &proof.lookup {
+        let eta = transcript_get_plonk_challenge_eta(transcript, group_order);
+        challenges.insert_eta(eta).unwrap();
+        transcript.append_commitment::<PCS::Commitment>(&lookup.cm_h1);
+        transcript.append_commitment::<PCS::Commitment>(&lookup.cm_h2);
+    }
     let beta = transcript_get_plonk_challenge_beta(transcript, group_order);
     let gamma = transcript_get_plonk_challenge_gamma(transcript, group_order);
     challenges.insert_beta_gamma(beta, gamma).unwrap();
+    if proof.lookup.is_some() {
+        let delta = transcript_get_plonk_challenge_delta(transcript, group_order);
+        let epsilon = transcript_get_plonk_challenge_epsilon(transcript, group_order);
+        challenges.insert_delta_epsilon(delta, epsilon).unwrap();
+    }
 
     // 2. compute alpha challenge.
     transcript.append_commitment::<PCS::Commitment>(&proof.cm_z);
+    if let Some(lookup) = &proof.lookup {
+        transcript.append_commitment::<PCS::Commitment>(&lookup.cm_z);
+    }
     let alpha = transcript_get_plonk_challenge_alpha(transcript, group_order);
     challenges.insert_alpha(alpha).unwrap();
     for cm_t in &proof.cm_t_vec {
@@ -180,6 +302,9 @@ fn compute_challenges<PCS: PolyComScheme>(
     for eval_zeta_omega in proof.w_polys_eval_zeta_omega.iter() {
         transcript.append_field_elem(eval_zeta_omega);
     }
+    if let Some(lookup) = &proof.lookup {
+        append_lookup_evals(transcript, lookup);
+    }
 
     // 4. compute u challenge.
     let u = transcript_get_plonk_challenge_u(transcript, group_order);
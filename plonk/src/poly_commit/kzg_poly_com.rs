@@ -321,6 +321,22 @@ impl<'b> PolyComScheme for KZGCommitmentSchemeBLS {
     }
 
     fn batch_verify_diff_points(
+        &self,
+        transcript: &mut Transcript,
+        cm_vec: &[Self::Commitment],
+        degree: usize,
+        point_vec: &[Self::Field],
+        eval_vec: &[Self::Field],
+        proofs: &[Self::Commitment],
+        challenge: &Self::Field,
+    ) -> Result<()> {
+        let prepared = self.prepare_batch_verify_diff_points(
+            transcript, cm_vec, degree, point_vec, eval_vec, proofs, challenge,
+        );
+        self.verify_prepared(&[prepared], &[BLSScalar::one()])
+    }
+
+    fn prepare_batch_verify_diff_points(
         &self,
         _transcript: &mut Transcript,
         cm_vec: &[Self::Commitment],
@@ -329,18 +345,13 @@ impl<'b> PolyComScheme for KZGCommitmentSchemeBLS {
         eval_vec: &[Self::Field],
         proofs: &[Self::Commitment],
         challenge: &Self::Field,
-    ) -> Result<()> {
+    ) -> (Self::Commitment, Self::Commitment) {
         assert!(proofs.len() > 0);
         assert_eq!(proofs.len(), point_vec.len());
         assert_eq!(proofs.len(), eval_vec.len());
         assert_eq!(proofs.len(), cm_vec.len());
 
         let g1_0 = self.public_parameter_group_1[0].clone();
-        let g2_0 = self.public_parameter_group_2[0].clone();
-        let g2_1 = self.public_parameter_group_2[1].clone();
-
-        let left_second = g2_1;
-        let right_second = g2_0;
 
         let mut left_first = proofs[0].0.clone();
         let mut right_first = proofs[0].0.mul(&point_vec[0]);
@@ -361,6 +372,30 @@ impl<'b> PolyComScheme for KZGCommitmentSchemeBLS {
         right_first.sub_assign(&g1_0.mul(&right_first_val));
         right_first.add_assign(&right_first_comm);
 
+        (KZGCommitment(left_first), KZGCommitment(right_first))
+    }
+
+    fn verify_prepared(
+        &self,
+        prepared: &[(Self::Commitment, Self::Commitment)],
+        rhos: &[Self::Field],
+    ) -> Result<()> {
+        if prepared.is_empty() || prepared.len() != rhos.len() {
+            return Err(eg!(PolyComSchemeError::PCSProveEvalError));
+        }
+
+        let g2_0 = self.public_parameter_group_2[0].clone();
+        let g2_1 = self.public_parameter_group_2[1].clone();
+
+        let left_second = g2_1;
+        let right_second = g2_0;
+
+        let rhos: Vec<&BLSScalar> = rhos.iter().collect();
+        let lefts: Vec<&BLSG1> = prepared.iter().map(|(left, _)| &left.0).collect();
+        let rights: Vec<&BLSG1> = prepared.iter().map(|(_, right)| &right.0).collect();
+        let left_first = BLSG1::multi_exp(&rhos, &lefts);
+        let right_first = BLSG1::multi_exp(&rhos, &rights);
+
         let pairing_eval = BLSPairingEngine::product_of_pairings(
             &[left_first, right_first.neg()],
             &[left_second, right_second],
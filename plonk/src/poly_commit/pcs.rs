@@ -211,6 +211,28 @@ pub trait PolyComScheme: Sized {
         challenge: &Self::Field,
     ) -> Result<()>;
 
+    /// Same as `batch_verify_diff_points`, but return the two group elements of the final
+    /// check instead of performing it, so that the checks of several proofs can be combined
+    /// with `verify_prepared`.
+    fn prepare_batch_verify_diff_points(
+        &self,
+        transcript: &mut Transcript,
+        cm_vec: &[Self::Commitment],
+        degree: usize,
+        point_vec: &[Self::Field],
+        eval_vec: &[Self::Field],
+        proof: &[Self::Commitment],
+        challenge: &Self::Field,
+    ) -> (Self::Commitment, Self::Commitment);
+
+    /// Perform the final checks of `prepared`, returned by `prepare_batch_verify_diff_points`,
+    /// at once, combined with the scalars `rhos`.
+    fn verify_prepared(
+        &self,
+        prepared: &[(Self::Commitment, Self::Commitment)],
+        rhos: &[Self::Field],
+    ) -> Result<()>;
+
     /// Initialize the transcript for batch evaluation.
     fn init_pcs_batch_eval_transcript(
         transcript: &mut Transcript,
@@ -2,33 +2,41 @@
 mod smoke_axfr {
     use digest::Digest;
     use mem_db::MemoryDB;
-    use noah::keys::SecretKey;
     use noah::parameters::params::{ProverParams, VerifierParams};
-    use noah::parameters::AddressFormat::{ED25519, SECP256K1};
+    use noah::parameters::AddressFormat::{self, ED25519, SECP256K1};
+    use noah::test_utils::setup_tree_with_records;
     use noah::{
         anon_xfr::{
             abar_to_abar::*,
             abar_to_ar::*,
             abar_to_bar::*,
+            aggregation::NoahAggregator,
             ar_to_abar::*,
             bar_to_abar::*,
+            notes_conflict, nullify,
+            ownership::*,
             structs::{
-                AnonAssetRecord, MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecord,
+                AnonAssetRecord, AnonXfrOutputs, MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecord,
                 OpenAnonAssetRecordBuilder,
             },
-            FEE_TYPE,
+            AXfrAddressFoldingInstance, AddressFoldingKind, FEE_TYPE,
         },
         keys::{KeyPair, KeyType, PublicKey},
         xfr::{
             asset_record::{build_blind_asset_record, open_blind_asset_record, AssetRecordType},
             structs::{
-                AssetRecordTemplate, AssetType, BlindAssetRecord, OwnerMemo, ASSET_TYPE_LENGTH,
+                AssetRecordTemplate, AssetType, BlindAssetRecord, OwnerMemo, XfrAmount,
+                ASSET_TYPE_LENGTH,
             },
         },
     };
     use noah_accumulators::merkle_tree::{PersistentMerkleTree, Proof, TreePath};
     use noah_algebra::{bls12_381::BLSScalar, prelude::*, ristretto::PedersenCommitmentRistretto};
     use noah_crypto::basic::anemoi_jive::{AnemoiJive, AnemoiJive381};
+    use noah_plonk::plonk::{
+        constraint_system::{ConstraintSystem, TurboCS},
+        indexer::{indexer_with_lagrange, PlonkProof},
+    };
     use parking_lot::RwLock;
     use sha2::Sha512;
     use std::sync::Arc;
@@ -146,6 +154,7 @@ mod smoke_axfr {
         let note =
             gen_ar_to_abar_note(&mut prng, &params, &obar, &sender, &receiver.get_pk()).unwrap();
         assert!(verify_ar_to_abar_note(&verify_params, &note).is_ok());
+        assert_eq!(note.spent_record(), &bar);
 
         #[cfg(feature = "parallel")]
         {
@@ -211,10 +220,49 @@ mod smoke_axfr {
             let pub_keys = vec![sender.get_pk_ref(); 6];
             assert!(batch_verify_bar_to_abar_note(&verify_params, &notes, &pub_keys).is_ok());
 
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(2)
+                .build()
+                .unwrap();
+            assert!(batch_verify_bar_to_abar_note_in_pool(
+                &pool,
+                &verify_params,
+                &notes,
+                &pub_keys
+            )
+            .is_ok());
+
             notes[5] = &err_note;
             assert!(batch_verify_bar_to_abar_note(&verify_params, &notes, &pub_keys).is_err());
+            assert!(batch_verify_bar_to_abar_note_in_pool(
+                &pool,
+                &verify_params,
+                &notes,
+                &pub_keys
+            )
+            .is_err());
         }
 
+        // check the consistency proofs, independently of the note
+        let proof = note.body.consistency_proof();
+        assert!(proof
+            .verify(&verify_params, &note.body.input, &note.body.output)
+            .is_ok());
+
+        let oabar = build_oabar(&mut prng, AMOUNT, ASSET, &receiver);
+        let abar = AnonAssetRecord::from_oabar(&oabar);
+        let proof = BarAbarConsistencyProof::prove(&mut prng, &params, &obar, &oabar).unwrap();
+        assert!(proof.verify(&verify_params, &bar, &abar).is_ok());
+        assert!(proof
+            .verify(&verify_params, &note.body.input, &note.body.output)
+            .is_err());
+
+        // an anonymous record with a different amount
+        let wrong_oabar = build_oabar(&mut prng, AMOUNT + 1, ASSET, &receiver);
+        let wrong_abar = AnonAssetRecord::from_oabar(&wrong_oabar);
+        assert!(BarAbarConsistencyProof::prove(&mut prng, &params, &obar, &wrong_oabar).is_err());
+        assert!(proof.verify(&verify_params, &bar, &wrong_abar).is_err());
+
         // check open ABAR
         let oabar =
             OpenAnonAssetRecordBuilder::from_abar(&note.body.output, note.body.memo, &receiver)
@@ -244,52 +292,43 @@ mod smoke_axfr {
     fn abar_to_ar(sender: KeyPair, receiver: KeyPair) {
         let mut prng = test_rng();
 
-        let address_format = match sender.get_sk_ref() {
-            SecretKey::Ed25519(_) => ED25519,
-            SecretKey::Secp256k1(_) => SECP256K1,
-        };
+        let address_format = sender.address_format();
 
         let params = ProverParams::gen_abar_to_ar(address_format).unwrap();
         let verify_params = VerifierParams::get_abar_to_ar(address_format).unwrap();
 
-        let fdb = MemoryDB::new();
-        let cs = Arc::new(RwLock::new(ChainState::new(fdb, "abar_ar".to_owned(), 0)));
-        let mut state = State::new(cs, false);
-        let store = PrefixedStore::new("my_store", &mut state);
-        let mut mt = PersistentMerkleTree::new(store).unwrap();
-
-        let mut oabar = build_oabar(&mut prng, AMOUNT, ASSET, &sender);
-        let abar = AnonAssetRecord::from_oabar(&oabar);
-        mt.add_commitment_hash(hash_abar(0, &abar)).unwrap();
-        mt.commit().unwrap();
-        let proof = mt.generate_proof(0).unwrap();
-        oabar.update_mt_leaf_info(build_mt_leaf_info_from_proof(proof.clone(), 0));
+        let (_, oabars, root) =
+            setup_tree_with_records(&mut prng, &[(AMOUNT, ASSET)], &sender).unwrap();
 
         let pre_note =
-            init_abar_to_ar_note(&mut prng, &oabar, &sender, &receiver.get_pk()).unwrap();
+            init_abar_to_ar_note(&mut prng, &oabars[0], &sender, &receiver.get_pk()).unwrap();
         let hash = random_hasher(&mut prng);
         let note = finish_abar_to_ar_note(&mut prng, &params, pre_note, hash.clone()).unwrap();
-        verify_abar_to_ar_note(&verify_params, &note, &proof.root, hash.clone()).unwrap();
+        verify_abar_to_ar_note(&verify_params, &note, &root, hash.clone()).unwrap();
+
+        // the parameters for light clients verify the note as well
+        let public_bytes = verify_params.to_public_bytes().unwrap();
+        let light_params = VerifierParams::from_public_bytes(&public_bytes).unwrap();
+        verify_abar_to_ar_note(&light_params, &note, &root, hash.clone()).unwrap();
+        let (nullifier, _) = nullify(&sender, AMOUNT, ASSET.as_scalar(), 0).unwrap();
+        assert_eq!(note.nullifiers(), &[nullifier]);
 
         let err_root = BLSScalar::random(&mut prng);
         assert!(verify_abar_to_ar_note(&verify_params, &note, &err_root, hash.clone()).is_err());
 
         let err_hash = random_hasher(&mut prng);
-        assert!(
-            verify_abar_to_ar_note(&verify_params, &note, &proof.root, err_hash.clone()).is_err()
-        );
+        assert!(verify_abar_to_ar_note(&verify_params, &note, &root, err_hash.clone()).is_err());
 
         let mut err_nullifier = note.clone();
         err_nullifier.body.input = BLSScalar::random(&mut prng);
         assert!(
-            verify_abar_to_ar_note(&verify_params, &err_nullifier, &proof.root, hash.clone())
-                .is_err()
+            verify_abar_to_ar_note(&verify_params, &err_nullifier, &root, hash.clone()).is_err()
         );
 
         #[cfg(feature = "parallel")]
         {
             let mut notes = vec![&note; 6];
-            let mut merkle_roots = vec![&proof.root; 6];
+            let mut merkle_roots = vec![&root; 6];
             let mut hashes = vec![hash.clone(); 6];
             batch_verify_abar_to_ar_note(&verify_params, &notes, &merkle_roots, hashes.clone())
                 .unwrap();
@@ -303,7 +342,7 @@ mod smoke_axfr {
             )
             .is_err());
 
-            merkle_roots[5] = &proof.root;
+            merkle_roots[5] = &root;
             hashes[5] = err_hash;
             assert!(batch_verify_abar_to_ar_note(
                 &verify_params,
@@ -338,12 +377,212 @@ mod smoke_axfr {
         assert_eq!(*obar.get_asset_type(), ASSET);
     }
 
+    #[test]
+    fn aggregate_ar_to_abar_and_abar_to_ar() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let sender = KeyPair::sample(&mut prng, SECP256K1);
+        let receiver = KeyPair::sample(&mut prng, SECP256K1);
+
+        let ar_to_abar_params = ProverParams::gen_ar_to_abar().unwrap();
+        let ar_to_abar_verify_params = VerifierParams::get_ar_to_abar().unwrap();
+        let abar_to_ar_params = ProverParams::gen_abar_to_ar(SECP256K1).unwrap();
+        let abar_to_ar_verify_params = VerifierParams::get_abar_to_ar(SECP256K1).unwrap();
+
+        let (bar, memo) = build_bar(
+            &sender.get_pk(),
+            &mut prng,
+            &pc_gens,
+            AMOUNT,
+            ASSET,
+            AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        );
+        let obar = open_blind_asset_record(&bar, &memo, &sender).unwrap();
+        let ar_to_abar_note = gen_ar_to_abar_note(
+            &mut prng,
+            &ar_to_abar_params,
+            &obar,
+            &sender,
+            &receiver.get_pk(),
+        )
+        .unwrap();
+
+        let (_, oabars, root) =
+            setup_tree_with_records(&mut prng, &[(AMOUNT, ASSET)], &sender).unwrap();
+        let pre_note =
+            init_abar_to_ar_note(&mut prng, &oabars[0], &sender, &receiver.get_pk()).unwrap();
+        let hash = random_hasher(&mut prng);
+        let abar_to_ar_note =
+            finish_abar_to_ar_note(&mut prng, &abar_to_ar_params, pre_note, hash.clone()).unwrap();
+
+        let mut aggregator = NoahAggregator::new();
+        assert!(aggregator.is_empty());
+        aggregator
+            .add_ar_to_abar_note(&ar_to_abar_verify_params, &ar_to_abar_note)
+            .unwrap();
+        aggregator
+            .add_abar_to_ar_note(
+                &abar_to_ar_verify_params,
+                &abar_to_ar_note,
+                &root,
+                hash.clone(),
+            )
+            .unwrap();
+        assert_eq!(aggregator.len(), 2);
+        aggregator.verify().unwrap();
+
+        // a wrong Merkle root is rejected when the note is added
+        let err_root = BLSScalar::random(&mut prng);
+        let mut aggregator = NoahAggregator::new();
+        assert!(aggregator
+            .add_abar_to_ar_note(
+                &abar_to_ar_verify_params,
+                &abar_to_ar_note,
+                &err_root,
+                hash.clone(),
+            )
+            .is_err());
+
+        // a wrong public input only fails the combined check
+        let mut err_nullifier = abar_to_ar_note.clone();
+        err_nullifier.body.input = BLSScalar::random(&mut prng);
+        let mut aggregator = NoahAggregator::new();
+        aggregator
+            .add_ar_to_abar_note(&ar_to_abar_verify_params, &ar_to_abar_note)
+            .unwrap();
+        aggregator
+            .add_abar_to_ar_note(
+                &abar_to_ar_verify_params,
+                &err_nullifier,
+                &root,
+                hash.clone(),
+            )
+            .unwrap();
+        assert!(aggregator.verify().is_err());
+    }
+
+    #[test]
+    fn ownership_secp256k1() {
+        let mut prng = test_rng();
+        let owner = KeyPair::sample(&mut prng, SECP256K1);
+        let other = KeyPair::sample(&mut prng, SECP256K1);
+        ownership(owner, other);
+    }
+
+    #[test]
+    fn ownership_ed25519() {
+        let mut prng = test_rng();
+        let owner = KeyPair::sample(&mut prng, ED25519);
+        let other = KeyPair::sample(&mut prng, ED25519);
+        ownership(owner, other);
+    }
+
+    fn ownership(owner: KeyPair, other: KeyPair) {
+        let mut prng = test_rng();
+
+        let address_format = owner.address_format();
+
+        let params = ProverParams::gen_ownership(address_format).unwrap();
+        let verify_params =
+            VerifierParams::from(ProverParams::gen_ownership(address_format).unwrap());
+
+        let fdb = MemoryDB::new();
+        let cs = Arc::new(RwLock::new(ChainState::new(fdb, "ownership".to_owned(), 0)));
+        let mut state = State::new(cs, false);
+        let store = PrefixedStore::new("my_store", &mut state);
+        let mut mt = PersistentMerkleTree::new(store).unwrap();
+
+        let mut oabar = build_oabar(&mut prng, AMOUNT, ASSET, &owner);
+        let abar = AnonAssetRecord::from_oabar(&oabar);
+        mt.add_commitment_hash(hash_abar(0, &abar)).unwrap();
+        mt.commit().unwrap();
+        let proof = mt.generate_proof(0).unwrap();
+        oabar.update_mt_leaf_info(build_mt_leaf_info_from_proof(proof.clone(), 0));
+
+        let hash = random_hasher(&mut prng);
+        let nonce = b"ownership challenge";
+        let ownership_proof =
+            prove_ownership(&mut prng, &params, &oabar, &owner, nonce, hash.clone()).unwrap();
+        assert_eq!(ownership_proof.amount, AMOUNT);
+        assert_eq!(ownership_proof.asset_type, ASSET);
+        verify_ownership(
+            &verify_params,
+            &ownership_proof,
+            &proof.root,
+            nonce,
+            hash.clone(),
+        )
+        .unwrap();
+
+        // A proof for one nonce does not verify for another.
+        assert!(verify_ownership(
+            &verify_params,
+            &ownership_proof,
+            &proof.root,
+            b"another challenge",
+            hash.clone()
+        )
+        .is_err());
+
+        // A key that does not own the record cannot produce a proof.
+        assert!(prove_ownership(&mut prng, &params, &oabar, &other, nonce, hash.clone()).is_err());
+
+        let err_root = BLSScalar::random(&mut prng);
+        assert!(verify_ownership(
+            &verify_params,
+            &ownership_proof,
+            &err_root,
+            nonce,
+            hash.clone()
+        )
+        .is_err());
+
+        let err_hash = random_hasher(&mut prng);
+        assert!(verify_ownership(
+            &verify_params,
+            &ownership_proof,
+            &proof.root,
+            nonce,
+            err_hash
+        )
+        .is_err());
+
+        let mut err_amount = ownership_proof.clone();
+        err_amount.amount += 1;
+        assert!(verify_ownership(
+            &verify_params,
+            &err_amount,
+            &proof.root,
+            nonce,
+            hash.clone()
+        )
+        .is_err());
+
+        // A record of another key, claimed against the owner's Merkle path, is rejected.
+        let mut other_oabar = build_oabar(&mut prng, AMOUNT, ASSET, &other);
+        other_oabar.update_mt_leaf_info(build_mt_leaf_info_from_proof(proof.clone(), 0));
+        let other_proof = prove_ownership(
+            &mut prng,
+            &params,
+            &other_oabar,
+            &other,
+            nonce,
+            hash.clone(),
+        )
+        .unwrap();
+        assert!(verify_ownership(&verify_params, &other_proof, &proof.root, nonce, hash).is_err());
+    }
+
     #[test]
     fn abar_to_bar_secp256k1() {
         let mut prng = test_rng();
         let sender = KeyPair::sample(&mut prng, SECP256K1);
         let receiver = KeyPair::sample(&mut prng, SECP256K1);
-        abar_to_bar(sender, receiver);
+        abar_to_bar(
+            sender,
+            receiver,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+        );
     }
 
     #[test]
@@ -351,16 +590,29 @@ mod smoke_axfr {
         let mut prng = test_rng();
         let sender = KeyPair::sample(&mut prng, ED25519);
         let receiver = KeyPair::sample(&mut prng, ED25519);
-        abar_to_bar(sender, receiver);
+        abar_to_bar(
+            sender,
+            receiver,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+        );
+    }
+
+    #[test]
+    fn abar_to_bar_transparent() {
+        let mut prng = test_rng();
+        let sender = KeyPair::sample(&mut prng, SECP256K1);
+        let receiver = KeyPair::sample(&mut prng, SECP256K1);
+        abar_to_bar(
+            sender,
+            receiver,
+            AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        );
     }
 
-    fn abar_to_bar(sender: KeyPair, receiver: KeyPair) {
+    fn abar_to_bar(sender: KeyPair, receiver: KeyPair, asset_record_type: AssetRecordType) {
         let mut prng = test_rng();
 
-        let address_format = match sender.get_sk_ref() {
-            SecretKey::Ed25519(_) => ED25519,
-            SecretKey::Secp256k1(_) => SECP256K1,
-        };
+        let address_format = sender.address_format();
 
         let params = ProverParams::gen_abar_to_bar(address_format).unwrap();
         let verify_params = VerifierParams::get_abar_to_bar(address_format).unwrap();
@@ -384,12 +636,14 @@ mod smoke_axfr {
             &oabar,
             &sender,
             &receiver.get_pk(),
-            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            asset_record_type,
         )
         .unwrap();
         let hash = random_hasher(&mut prng);
         let note = finish_abar_to_bar_note(&mut prng, &params, pre_note, hash.clone()).unwrap();
         verify_abar_to_bar_note(&verify_params, &note, &proof.root, hash.clone()).unwrap();
+        let (nullifier, _) = nullify(&sender, AMOUNT, ASSET.as_scalar(), 1).unwrap();
+        assert_eq!(note.nullifiers(), &[nullifier]);
 
         let err_root = BLSScalar::random(&mut prng);
         assert!(verify_abar_to_bar_note(&verify_params, &note, &err_root, hash.clone()).is_err());
@@ -406,6 +660,23 @@ mod smoke_axfr {
                 .is_err()
         );
 
+        if asset_record_type == AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType {
+            assert!(note.body.memo.is_none());
+            assert_eq!(note.body.output.amount.get_amount(), Some(AMOUNT));
+            assert_eq!(note.body.output.asset_type.get_asset_type(), Some(ASSET));
+
+            // The revealed amount is bound to the proof.
+            let mut err_amount = note.clone();
+            err_amount.body.output.amount = XfrAmount::NonConfidential(AMOUNT + 1);
+            assert!(verify_abar_to_bar_note(
+                &verify_params,
+                &err_amount,
+                &proof.root,
+                hash.clone()
+            )
+            .is_err());
+        }
+
         #[cfg(feature = "parallel")]
         {
             let mut notes = vec![&note; 6];
@@ -450,6 +721,7 @@ mod smoke_axfr {
         }
 
         // check open BAR
+        assert_eq!(note.body.output.get_record_type(), asset_record_type);
         let obar = open_blind_asset_record(&note.body.output, &note.body.memo, &receiver).unwrap();
         assert_eq!(*obar.get_amount(), AMOUNT);
         assert_eq!(*obar.get_asset_type(), ASSET);
@@ -483,6 +755,294 @@ mod smoke_axfr {
         );
     }
 
+    #[test]
+    fn abar_1in_1out_predicate() {
+        let mut prng = test_rng();
+        let fee_amount = mock_fee(1, 1);
+
+        let params = ProverParams::gen_abar_to_abar_with_predicate(1, 1, SECP256K1).unwrap();
+        let verifier_params =
+            VerifierParams::get_abar_to_abar_with_predicate(1, 1, SECP256K1).unwrap();
+        assert_ne!(
+            verifier_params.label,
+            VerifierParams::load_abar_to_abar(1, 1, SECP256K1)
+                .unwrap()
+                .label
+        );
+
+        let sender = KeyPair::sample(&mut prng, SECP256K1);
+        let receiver = KeyPair::sample(&mut prng, SECP256K1);
+        let (_, inputs, root) =
+            setup_tree_with_records(&mut prng, &[(fee_amount as u64 + 1, FEE_TYPE)], &sender)
+                .unwrap();
+        let outputs = [build_oabar(&mut prng, 1, FEE_TYPE, &receiver)];
+
+        let predicate_hash = BLSScalar::random(&mut prng);
+        let pre_note = init_anon_xfr_note_with_predicate(
+            &inputs,
+            &outputs,
+            fee_amount,
+            &sender,
+            predicate_hash,
+        )
+        .unwrap();
+        assert_eq!(pre_note.predicate_hash, Some(predicate_hash));
+
+        // the predicate hash is a public input, which the parameters without a predicate lack
+        let plain_params = ProverParams::gen_abar_to_abar(1, 1, SECP256K1).unwrap();
+        let hash = random_hasher(&mut prng);
+        assert!(
+            finish_anon_xfr_note(&mut prng, &plain_params, pre_note.clone(), hash.clone()).is_err()
+        );
+
+        let note = finish_anon_xfr_note(&mut prng, &params, pre_note, hash.clone()).unwrap();
+        verify_anon_xfr_note_with_predicate(
+            &verifier_params,
+            &note,
+            &root,
+            &predicate_hash,
+            hash.clone(),
+        )
+        .unwrap();
+
+        // the note is bound to its predicate
+        let other_predicate_hash = BLSScalar::random(&mut prng);
+        assert!(verify_anon_xfr_note_with_predicate(
+            &verifier_params,
+            &note,
+            &root,
+            &other_predicate_hash,
+            hash.clone(),
+        )
+        .is_err());
+        assert!(verify_anon_xfr_note(&verifier_params, &note, &root, hash.clone()).is_err());
+
+        // the note keeps the encoding of the notes without a predicate
+        let bytes = bincode::serialize(&note).unwrap();
+        let note_de: AXfrNote = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(note_de, note);
+        verify_anon_xfr_note_with_predicate(
+            &verifier_params,
+            &note_de,
+            &root,
+            &predicate_hash,
+            hash.clone(),
+        )
+        .unwrap();
+
+        // the verifier parameters are generated once, and then obtained from memory
+        let cached_params =
+            VerifierParams::get_abar_to_abar_with_predicate(1, 1, SECP256K1).unwrap();
+        assert_eq!(cached_params.label, verifier_params.label);
+        assert_eq!(
+            cached_params.address_folding,
+            verifier_params.address_folding
+        );
+        verify_anon_xfr_note_with_predicate(&cached_params, &note, &root, &predicate_hash, hash)
+            .unwrap();
+    }
+
+    /// Prove a 2-in-2-out transfer of the fee asset with `params`, and return the note with the
+    /// Merkle root and the hasher it is verified against.
+    fn prove_abar_2in_2out<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        params: &ProverParams,
+    ) -> (AXfrNote, BLSScalar, Sha512) {
+        let fee_amount = mock_fee(2, 2);
+
+        let sender = KeyPair::sample(prng, SECP256K1);
+        let receiver = KeyPair::sample(prng, SECP256K1);
+        let (_, inputs, root) = setup_tree_with_records(
+            prng,
+            &[(fee_amount as u64 + 10, FEE_TYPE), (20, FEE_TYPE)],
+            &sender,
+        )
+        .unwrap();
+        let outputs = [
+            build_oabar(prng, 25, FEE_TYPE, &receiver),
+            build_oabar(prng, 5, FEE_TYPE, &receiver),
+        ];
+
+        let pre_note = init_anon_xfr_note(&inputs, &outputs, fee_amount, &sender).unwrap();
+        let hash = random_hasher(prng);
+        let note = finish_anon_xfr_note(prng, params, pre_note, hash.clone()).unwrap();
+        (note, root, hash)
+    }
+
+    #[test]
+    fn abar_2in_2out_compressed_proof() {
+        let mut prng = test_rng();
+
+        let params = ProverParams::gen_abar_to_abar(2, 2, SECP256K1).unwrap();
+        let verifier_params = VerifierParams::load_abar_to_abar(2, 2, SECP256K1).unwrap();
+        let (note, root, hash) = prove_abar_2in_2out(&mut prng, &params);
+
+        let serde_len = bincode::serialize(&note.proof).unwrap().len();
+        let bytes = note.proof.to_compressed_bytes().unwrap();
+        // the Plonk proof has a constant size, whatever the shape of the transfer
+        assert_eq!(bytes.len(), 5 + 13 * 48 + 15 * 32);
+        assert!(bytes.len() < serde_len);
+
+        // the decompressed proof verifies identically
+        let mut decompressed_note = note.clone();
+        decompressed_note.proof = PlonkProof::from_compressed_bytes(&bytes).unwrap();
+        assert_eq!(decompressed_note, note);
+        verify_anon_xfr_note(&verifier_params, &decompressed_note, &root, hash).unwrap();
+    }
+
+    #[test]
+    fn abar_2in_2out_cached_cs() {
+        let mut prng = test_rng();
+
+        let params = ProverParams::gen_abar_to_abar(2, 2, SECP256K1).unwrap();
+        let verifier_params = VerifierParams::load_abar_to_abar(2, 2, SECP256K1).unwrap();
+
+        // reload the constraint system from its bytes, and index it again
+        let bytes = params.cs.noah_to_bytes().unwrap();
+        let cs = TurboCS::noah_from_bytes(&bytes).unwrap();
+        assert_eq!(cs.compute_permutation(), params.cs.compute_permutation());
+        let prover_params =
+            indexer_with_lagrange(&cs, &params.pcs, params.lagrange_pcs.as_ref(), None).unwrap();
+        assert_eq!(
+            bincode::serialize(&prover_params.verifier_params).unwrap(),
+            bincode::serialize(&params.prover_params.verifier_params).unwrap()
+        );
+        let params = ProverParams {
+            cs,
+            prover_params,
+            ..params
+        };
+
+        let (note, root, hash) = prove_abar_2in_2out(&mut prng, &params);
+        verify_anon_xfr_note(&verifier_params, &note, &root, hash).unwrap();
+    }
+
+    #[test]
+    fn abar_1in_1out_folding_kind() {
+        let mut prng = test_rng();
+        let fee_amount = mock_fee(1, 1);
+
+        let params = ProverParams::gen_abar_to_abar(1, 1, SECP256K1).unwrap();
+        let verifier_params = VerifierParams::load_abar_to_abar(1, 1, SECP256K1).unwrap();
+        let ed25519_verifier_params = VerifierParams::load_abar_to_abar(1, 1, ED25519).unwrap();
+        assert_eq!(
+            verifier_params.address_folding,
+            Some(AddressFoldingKind::Secp256k1)
+        );
+
+        let sender = KeyPair::sample(&mut prng, SECP256K1);
+        let receiver = KeyPair::sample(&mut prng, SECP256K1);
+        let (_, inputs, root) =
+            setup_tree_with_records(&mut prng, &[(fee_amount as u64 + 1, FEE_TYPE)], &sender)
+                .unwrap();
+        let outputs = [build_oabar(&mut prng, 1, FEE_TYPE, &receiver)];
+
+        let pre_note = init_anon_xfr_note(&inputs, &outputs, fee_amount, &sender).unwrap();
+        let hash = random_hasher(&mut prng);
+        let note = finish_anon_xfr_note(&mut prng, &params, pre_note, hash.clone()).unwrap();
+        assert_eq!(note.folding_kind, AddressFoldingKind::Secp256k1);
+        assert_eq!(note.folding_kind, note.folding_instance.kind());
+        verify_anon_xfr_note(&verifier_params, &note, &root, hash.clone()).unwrap();
+
+        // the version and the tag are part of the serialized note in every format
+        let json = serde_json::to_string(&note).unwrap();
+        assert!(json.starts_with(r#"{"version":1,"#));
+        assert!(json.contains(r#""folding_kind":"Secp256k1""#));
+        let decoded: AXfrNote = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, note);
+
+        let bytes = bincode::serialize(&note).unwrap();
+        let expected_bytes = bincode::serialize(&(
+            1u32,
+            &note.body,
+            &note.proof,
+            Some(AddressFoldingKind::Secp256k1),
+            &note.folding_instance,
+        ))
+        .unwrap();
+        assert_eq!(bytes, expected_bytes);
+        let decoded: AXfrNote = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded, note);
+
+        let bytes = rmp_serde::to_vec(&note).unwrap();
+        let decoded: AXfrNote = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, note);
+
+        // a legacy JSON note, without the version and the tag, takes the kind of its
+        // folding instance
+        let legacy_json = json
+            .replace(r#""version":1,"#, "")
+            .replace(r#""folding_kind":"Secp256k1","#, "");
+        assert!(!legacy_json.contains("folding_kind"));
+        let decoded: AXfrNote = serde_json::from_str(&legacy_json).unwrap();
+        assert_eq!(decoded, note);
+
+        // an unknown version is rejected
+        let future_json = json.replace(r#""version":1,"#, r#""version":2,"#);
+        assert!(serde_json::from_str::<AXfrNote>(&future_json).is_err());
+
+        // a secp256k1 note is rejected by the ed25519 verifier
+        let err =
+            verify_anon_xfr_note(&ed25519_verifier_params, &note, &root, hash.clone()).unwrap_err();
+        assert!(err.to_string().contains("uses Secp256k1 address folding"));
+
+        // a tag that does not match the folding instance is rejected by both verifiers
+        let mut err_note = note.clone();
+        err_note.folding_kind = AddressFoldingKind::Ed25519;
+        assert_ne!(err_note.anchor_hash(), note.anchor_hash());
+        let err = verify_anon_xfr_note(&ed25519_verifier_params, &err_note, &root, hash.clone())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("carries a Secp256k1 folding instance"));
+        assert!(verify_anon_xfr_note(&verifier_params, &err_note, &root, hash).is_err());
+    }
+
+    #[test]
+    fn abar_1in_1out_anchor_hash() {
+        let mut prng = test_rng();
+        let fee_amount = mock_fee(1, 1);
+
+        let params = ProverParams::gen_abar_to_abar(1, 1, SECP256K1).unwrap();
+
+        let sender = KeyPair::sample(&mut prng, SECP256K1);
+        let receiver = KeyPair::sample(&mut prng, SECP256K1);
+        let (_, inputs, _) =
+            setup_tree_with_records(&mut prng, &[(fee_amount as u64 + 1, FEE_TYPE)], &sender)
+                .unwrap();
+        let outputs = [build_oabar(&mut prng, 1, FEE_TYPE, &receiver)];
+
+        let pre_note = init_anon_xfr_note(&inputs, &outputs, fee_amount, &sender).unwrap();
+        let hash = random_hasher(&mut prng);
+        let note = finish_anon_xfr_note(&mut prng, &params, pre_note, hash).unwrap();
+
+        // the anchor hash is deterministic, and changed by a mutation of any field of the note
+        let anchor = note.anchor_hash();
+        assert_eq!(note.clone().anchor_hash(), anchor);
+        let mutations: Vec<Box<dyn Fn(&mut AXfrNote)>> = vec![
+            Box::new(|n| n.body.inputs[0] = BLSScalar::one()),
+            Box::new(|n| n.body.outputs[0].commitment = BLSScalar::one()),
+            Box::new(|n| n.body.merkle_root = BLSScalar::one()),
+            Box::new(|n| n.body.merkle_root_version += 1),
+            Box::new(|n| n.body.fee += 1),
+            Box::new(|n| n.body.owner_memos.push(n.body.owner_memos[0].clone())),
+            Box::new(|n| n.proof.z_eval_zeta_omega = BLSScalar::one()),
+            Box::new(|n| match &mut n.folding_instance {
+                AXfrAddressFoldingInstance::Secp256k1(a) => {
+                    a.scalar_mul_commitments.pop();
+                }
+                AXfrAddressFoldingInstance::Ed25519(a) => {
+                    a.scalar_mul_commitments.pop();
+                }
+            }),
+        ];
+        for mutation in mutations.iter() {
+            let mut mutated_note = note.clone();
+            mutation(&mut mutated_note);
+            assert_ne!(mutated_note.anchor_hash(), anchor);
+        }
+    }
+
     #[test]
     fn abar_2in_1out_1asset() {
         let fee_amount = mock_fee(2, 1);
@@ -571,6 +1131,253 @@ mod smoke_axfr {
         }
     }
 
+    #[test]
+    fn abar_1in_3out_to_three_recipients() {
+        let mut prng = test_rng();
+        let fee = mock_fee(1, 3);
+        let sender = KeyPair::sample(&mut prng, SECP256K1);
+        let receivers: Vec<KeyPair> = (0..3)
+            .map(|_| KeyPair::sample(&mut prng, SECP256K1))
+            .collect();
+
+        let params = ProverParams::gen_abar_to_abar(1, 3, SECP256K1).unwrap();
+        let verifier_params = VerifierParams::load_abar_to_abar(1, 3, SECP256K1).unwrap();
+
+        let (_, oabars, root) =
+            setup_tree_with_records(&mut prng, &[(60 + fee as u64, FEE_TYPE)], &sender).unwrap();
+
+        // a zero amount is only accepted when allowed
+        let dust = AnonXfrOutputs::new().add(0, FEE_TYPE, &receivers[0].get_pk());
+        assert!(dust.build(&mut prng).is_err());
+        assert!(dust.allow_zero_amount().build(&mut prng).is_ok());
+        assert!(AnonXfrOutputs::new().build(&mut prng).is_err());
+
+        let outputs = AnonXfrOutputs::new()
+            .add(10, FEE_TYPE, &receivers[0].get_pk())
+            .add(20, FEE_TYPE, &receivers[1].get_pk())
+            .add(30, FEE_TYPE, &receivers[2].get_pk());
+        assert_eq!(outputs.len(), 3);
+        let groups = outputs.by_recipient();
+        assert_eq!(groups.len(), 3);
+        for (i, (recipient, indices)) in groups.iter().enumerate() {
+            assert_eq!(*recipient, receivers[i].get_pk());
+            assert_eq!(*indices, vec![i]);
+        }
+
+        let oabars_out = outputs.build(&mut prng).unwrap();
+        let pre_note = init_anon_xfr_note(&oabars, &oabars_out, fee, &sender).unwrap();
+        let hash = random_hasher(&mut prng);
+        let note = finish_anon_xfr_note(&mut prng, &params, pre_note, hash.clone()).unwrap();
+        verify_anon_xfr_note(&verifier_params, &note, &root, hash).unwrap();
+
+        // each recipient opens the output paid to it
+        for (i, receiver) in receivers.iter().enumerate() {
+            let oabar = OpenAnonAssetRecordBuilder::from_abar(
+                &note.body.outputs[i],
+                note.body.owner_memos[i].clone(),
+                receiver,
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+            assert_eq!(oabar.get_amount(), 10 * (i as u64 + 1));
+            assert_eq!(oabar.get_asset_type(), FEE_TYPE);
+        }
+    }
+
+    #[test]
+    fn abar_2in_1out_fee_to_address() {
+        let mut prng = test_rng();
+        let fee_amount = mock_fee(2, 2);
+
+        let params = ProverParams::gen_abar_to_abar(2, 2, SECP256K1).unwrap();
+        let verifier_params = VerifierParams::load_abar_to_abar(2, 2, SECP256K1).unwrap();
+
+        let sender = KeyPair::sample(&mut prng, SECP256K1);
+        let receiver = KeyPair::sample(&mut prng, ED25519);
+        let treasury = KeyPair::sample(&mut prng, SECP256K1);
+
+        let mut oabars = vec![
+            build_oabar(&mut prng, 10, FEE_TYPE, &sender),
+            build_oabar(&mut prng, fee_amount as u64, FEE_TYPE, &sender),
+        ];
+        let abars: Vec<_> = oabars.iter().map(AnonAssetRecord::from_oabar).collect();
+
+        let fdb = MemoryDB::new();
+        let cs = Arc::new(RwLock::new(ChainState::new(
+            fdb,
+            "abar-fee-to-address".to_owned(),
+            0,
+        )));
+        let mut state = State::new(cs, false);
+        let store = PrefixedStore::new("my_store", &mut state);
+        let mut mt = PersistentMerkleTree::new(store).unwrap();
+        let mut uids = vec![];
+        for abar in abars.iter() {
+            let abar_comm = hash_abar(mt.entry_count(), abar);
+            uids.push(mt.add_commitment_hash(abar_comm).unwrap());
+        }
+        mt.commit().unwrap();
+        let root = mt.get_root().unwrap();
+        for (i, uid) in uids.iter().enumerate() {
+            let proof = mt.generate_proof(*uid).unwrap();
+            oabars[i].update_mt_leaf_info(build_mt_leaf_info_from_proof(proof, *uid));
+        }
+
+        let oabars_out = vec![build_oabar(&mut prng, 10, FEE_TYPE, &receiver)];
+
+        // the fee must balance, with or without a recipient
+        assert!(init_anon_xfr_note_with_fee_recipient(
+            &oabars,
+            &oabars_out,
+            fee_amount + 1,
+            (treasury.get_pk(), BLSScalar::random(&mut prng)),
+            &sender
+        )
+        .is_err());
+
+        let fee_recipient = (treasury.get_pk(), BLSScalar::random(&mut prng));
+        let pre_note = init_anon_xfr_note_with_fee_recipient(
+            &oabars,
+            &oabars_out,
+            fee_amount,
+            fee_recipient,
+            &sender,
+        )
+        .unwrap();
+        assert_eq!(pre_note.body.fee, fee_amount);
+        assert_eq!(pre_note.body.outputs.len(), 2);
+
+        let hash = random_hasher(&mut prng);
+        let note = finish_anon_xfr_note(&mut prng, &params, pre_note, hash.clone()).unwrap();
+        verify_anon_xfr_note_with_fee_recipient(
+            &verifier_params,
+            &note,
+            &root,
+            &fee_recipient,
+            hash.clone(),
+        )
+        .unwrap();
+        verify_fee_binding(&note, fee_amount).unwrap();
+
+        // the fee is not burned, and is only paid to the recipient
+        assert!(verify_anon_xfr_note(&verifier_params, &note, &root, hash.clone()).is_err());
+        let other_recipient = (receiver.get_pk(), fee_recipient.1);
+        assert!(verify_anon_xfr_note_with_fee_recipient(
+            &verifier_params,
+            &note,
+            &root,
+            &other_recipient,
+            hash.clone(),
+        )
+        .is_err());
+
+        // the other verifiers take the recipient too
+        let roots = [BLSScalar::random(&mut prng), root];
+        assert_eq!(
+            verify_anon_xfr_note_multi_root(
+                &verifier_params,
+                &note,
+                &roots,
+                Some(&fee_recipient),
+                hash.clone()
+            )
+            .unwrap(),
+            1
+        );
+        assert!(verify_anon_xfr_note_multi_root(
+            &verifier_params,
+            &note,
+            &roots,
+            None,
+            hash.clone()
+        )
+        .is_err());
+
+        let mut aggregator = NoahAggregator::new();
+        aggregator
+            .add_anon_xfr_note(
+                &verifier_params,
+                &note,
+                &root,
+                Some(&fee_recipient),
+                hash.clone(),
+            )
+            .unwrap();
+        aggregator.verify().unwrap();
+        let mut aggregator = NoahAggregator::new();
+        aggregator
+            .add_anon_xfr_note(&verifier_params, &note, &root, None, hash.clone())
+            .unwrap();
+        assert!(aggregator.verify().is_err());
+
+        #[cfg(feature = "parallel")]
+        {
+            let verifiers_params = vec![&verifier_params; 3];
+            let notes = vec![&note; 3];
+            let merkle_roots = vec![&root; 3];
+            let hashes = vec![hash.clone(); 3];
+            assert!(batch_verify_anon_xfr_note_with_fee_recipients(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                &[Some(&fee_recipient); 3],
+                hashes.clone(),
+            )
+            .is_ok());
+            assert!(batch_verify_anon_xfr_note_with_fee_recipients(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                &[
+                    Some(&fee_recipient),
+                    Some(&other_recipient),
+                    Some(&fee_recipient)
+                ],
+                hashes.clone(),
+            )
+            .is_err());
+            assert!(batch_verify_anon_xfr_note(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                hashes.clone()
+            )
+            .is_err());
+            assert!(batch_verify_anon_xfr_note_with_fee_recipients(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                &[Some(&fee_recipient); 2],
+                hashes,
+            )
+            .is_err());
+        }
+
+        // the treasury can open the fee record
+        let fee_oabar = OpenAnonAssetRecordBuilder::from_abar(
+            &note.body.outputs[1],
+            note.body.owner_memos[1].clone(),
+            &treasury,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        assert_eq!(fee_oabar.get_amount(), fee_amount as u64);
+        assert_eq!(fee_oabar.get_asset_type(), FEE_TYPE);
+        assert_eq!(fee_oabar.get_blind(), fee_recipient.1);
+
+        let oabar = OpenAnonAssetRecordBuilder::from_abar(
+            &note.body.outputs[0],
+            note.body.owner_memos[0].clone(),
+            &receiver,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        assert_eq!(oabar.get_amount(), 10);
+    }
+
     fn test_abar(
         inputs: Vec<(u64, AssetType)>,
         outputs: Vec<(u64, AssetType)>,
@@ -587,11 +1394,7 @@ mod smoke_axfr {
                 ED25519
             }
         } else {
-            match input_key_type.unwrap() {
-                KeyType::Ed25519 => ED25519,
-                KeyType::Secp256k1 => SECP256K1,
-                _ => unimplemented!(),
-            }
+            AddressFormat::from_key_type(input_key_type.unwrap()).unwrap()
         };
 
         let params =
@@ -646,16 +1449,127 @@ mod smoke_axfr {
 
         verify_anon_xfr_note(&verifier_params, &note, &root, hash.clone()).unwrap();
 
+        verify_fee_binding(&note, fee).unwrap();
+        assert!(verify_fee_binding(&note, fee + 1).is_err());
+
+        // a spoofed fee passes the fee check, but not the verification of the proof
+        let mut spoofed_note = note.clone();
+        spoofed_note.body.fee = fee + 1;
+        verify_fee_binding(&spoofed_note, fee + 1).unwrap();
+        assert!(
+            verify_anon_xfr_note(&verifier_params, &spoofed_note, &root, hash.clone()).is_err()
+        );
+
+        let mut roots = vec![BLSScalar::random(&mut prng), BLSScalar::random(&mut prng)];
+        assert!(verify_anon_xfr_note_multi_root(
+            &verifier_params,
+            &note,
+            &roots,
+            None,
+            hash.clone()
+        )
+        .is_err());
+        roots.insert(1, root);
+        assert_eq!(
+            verify_anon_xfr_note_multi_root(&verifier_params, &note, &roots, None, hash.clone())
+                .unwrap(),
+            1
+        );
+        assert_eq!(note.nullifiers().len(), inputs.len());
+        for (nullifier, (oabar, uid)) in note.nullifiers().iter().zip(oabars.iter().zip(uids)) {
+            let (expected, _) = nullify(
+                &sender,
+                oabar.get_amount(),
+                oabar.get_asset_type().as_scalar(),
+                uid,
+            )
+            .unwrap();
+            assert_eq!(*nullifier, expected);
+        }
+
+        // a note spending one of the inputs conflicts with this note, a note spending other
+        // inputs does not
+        let mut other_note = note.clone();
+        for nullifier in other_note.body.inputs.iter_mut() {
+            *nullifier = BLSScalar::random(&mut prng);
+        }
+        assert!(!notes_conflict(&note, &other_note));
+        other_note.body.inputs[0] = *note.nullifiers().last().unwrap();
+        assert!(notes_conflict(&note, &other_note));
+
+        assert!(notes_conflict(&other_note, &note));
+
         #[cfg(feature = "parallel")]
         {
             let verifiers_params = vec![&verifier_params; 6];
             let notes = vec![&note; 6];
             let merkle_roots = vec![&root; 6];
             let hashes = vec![hash.clone(); 6];
-            assert!(
-                batch_verify_anon_xfr_note(&verifiers_params, &notes, &merkle_roots, hashes)
-                    .is_ok()
-            );
+            assert!(batch_verify_anon_xfr_note(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                hashes.clone()
+            )
+            .is_ok());
+
+            let mut reported = vec![];
+            assert!(batch_verify_anon_xfr_note_with_progress(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                hashes.clone(),
+                &mut |verified, total| reported.push((verified, total)),
+            )
+            .is_ok());
+            assert_eq!(reported.last(), Some(&(6, 6)));
+            assert!(reported.windows(2).all(|w| w[0].0 < w[1].0));
+
+            // two validators agreeing on the scalars, e.g., derived from the block hash,
+            // reach identical results
+            let mut block_prng = test_rng_seeded(42);
+            let rhos: Vec<BLSScalar> = (0..6).map(|_| BLSScalar::random(&mut block_prng)).collect();
+            let mut spoofed_notes = notes.clone();
+            spoofed_notes[3] = &spoofed_note;
+            for notes in [&notes, &spoofed_notes] {
+                let results: Vec<bool> = (0..2)
+                    .map(|_| {
+                        batch_verify_anon_xfr_note_with_randomness(
+                            &verifiers_params,
+                            notes,
+                            &merkle_roots,
+                            hashes.clone(),
+                            &rhos,
+                        )
+                        .is_ok()
+                    })
+                    .collect();
+                assert_eq!(results[0], results[1]);
+            }
+            assert!(batch_verify_anon_xfr_note_with_randomness(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                hashes.clone(),
+                &rhos,
+            )
+            .is_ok());
+            assert!(batch_verify_anon_xfr_note_with_randomness(
+                &verifiers_params,
+                &spoofed_notes,
+                &merkle_roots,
+                hashes.clone(),
+                &rhos,
+            )
+            .is_err());
+            assert!(batch_verify_anon_xfr_note_with_randomness(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                hashes.clone(),
+                &rhos[1..],
+            )
+            .is_err());
         }
 
         // check abar
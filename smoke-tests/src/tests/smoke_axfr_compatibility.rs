@@ -4,7 +4,6 @@ mod smoke_axfr_compatibility {
     use ed25519_dalek::Sha512;
     use mem_db::MemoryDB;
     use noah::anon_xfr::AXfrAddressFoldingInstance;
-    use noah::keys::SecretKey;
     use noah::parameters::params::{ProverParams, VerifierParams};
     use noah::parameters::AddressFormat::{ED25519, SECP256K1};
     use noah::{
@@ -303,10 +302,7 @@ mod smoke_axfr_compatibility {
     }
 
     fn abar_to_ar(abar: &AnonAssetRecord, sender: &KeyPair, memo: AxfrOwnerMemo) {
-        let address_format = match sender.get_sk_ref() {
-            SecretKey::Ed25519(_) => ED25519,
-            SecretKey::Secp256k1(_) => SECP256K1,
-        };
+        let address_format = sender.address_format();
 
         let mut prng = test_rng();
         let params = ProverParams::gen_abar_to_ar(address_format).unwrap();
@@ -451,10 +447,7 @@ mod smoke_axfr_compatibility {
     fn abar_to_bar(abar: &AnonAssetRecord, sender: &KeyPair, memo: AxfrOwnerMemo) {
         let mut prng = test_rng();
 
-        let address_format = match sender.get_sk_ref() {
-            SecretKey::Ed25519(_) => ED25519,
-            SecretKey::Secp256k1(_) => SECP256K1,
-        };
+        let address_format = sender.address_format();
 
         let params = ProverParams::gen_abar_to_bar(address_format).unwrap();
         let verify_params = VerifierParams::get_abar_to_bar(address_format).unwrap();
@@ -627,10 +620,7 @@ mod smoke_axfr_compatibility {
 
         let note: AXfrNote = serde_json::from_str(&note).unwrap();
 
-        let address_format = match note.folding_instance {
-            AXfrAddressFoldingInstance::Secp256k1(_) => SECP256K1,
-            AXfrAddressFoldingInstance::Ed25519(_) => ED25519,
-        };
+        let address_format = note.folding_kind.get_address_format();
 
         let verifier_params = VerifierParams::load_abar_to_abar(6, 6, address_format).unwrap();
         let hash = random_hasher([
@@ -654,10 +644,7 @@ mod smoke_axfr_compatibility {
     ) {
         let mut prng = test_rng();
 
-        let address_format = match sender.get_sk_ref() {
-            SecretKey::Ed25519(_) => ED25519,
-            SecretKey::Secp256k1(_) => SECP256K1,
-        };
+        let address_format = sender.address_format();
 
         let params =
             ProverParams::gen_abar_to_abar(abars.len(), outputs.len(), address_format).unwrap();
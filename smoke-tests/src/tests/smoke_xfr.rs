@@ -81,7 +81,7 @@ mod smoke_xfr {
         let recv_ar = AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
 
         // create xfr_note
-        let xfr_note = gen_xfr_note(&mut prng, &[ar], &[recv_ar], &[&sender]).unwrap();
+        let xfr_note = gen_xfr_note(&mut prng, &params, &[ar], &[recv_ar], &[&sender]).unwrap();
 
         // verify
         let policies = XfrNotePolicies::empty_policies(1, 1);
@@ -120,7 +120,7 @@ mod smoke_xfr {
         let recv_ar = AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
 
         // create xfr_note
-        let xfr_note = gen_xfr_note(&mut prng, &[ar], &[recv_ar], &[&sender]).unwrap();
+        let xfr_note = gen_xfr_note(&mut prng, &params, &[ar], &[recv_ar], &[&sender]).unwrap();
 
         // verify
         let policies = XfrNotePolicies::empty_policies(1, 1);
@@ -209,6 +209,7 @@ mod smoke_xfr {
         // create xfr_note
         let xfr_note = gen_xfr_note(
             &mut prng,
+            &params,
             &[ar_in1, ar_in2, ar_in3],
             &[ar_out1, ar_out2, ar_out3, ar_out4],
             &[&sender1, &sender2, &sender3],
@@ -179,6 +179,7 @@ mod smoke_xfr_identity {
         // create xfr_note
         let xfr_note = gen_xfr_note(
             &mut prng,
+            &params,
             &[ar_in1, ar_in2],
             &[ar_out],
             &[&sender1, &sender2],
@@ -359,7 +360,14 @@ mod smoke_xfr_identity {
         .unwrap();
 
         // create xfr_note
-        let xfr_note = gen_xfr_note(&mut prng, &[ar_in], &[ar_out1, ar_out2], &[&sender1]).unwrap();
+        let xfr_note = gen_xfr_note(
+            &mut prng,
+            &params,
+            &[ar_in],
+            &[ar_out1, ar_out2],
+            &[&sender1],
+        )
+        .unwrap();
 
         // verify
         let policies = XfrNotePoliciesRef::new(
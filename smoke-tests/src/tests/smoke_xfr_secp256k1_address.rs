@@ -116,6 +116,7 @@ mod smoke_xfr_secp256k1_address {
         // create xfr_note
         let xfr_note = gen_xfr_note(
             &mut prng,
+            &params,
             &[ar1, ar2, ar3],
             &[recv_ar1, recv_ar2, recv_ar3],
             &[&sender, &receiver, &receiver],
@@ -122,6 +122,7 @@ mod smoke_xfr_tracing {
         // create xfr_note
         let xfr_note = gen_xfr_note(
             &mut prng,
+            &params,
             &[ar_in1, ar_in2],
             &[ar_out1, ar_out2],
             &[&sender1, &sender2],
@@ -221,7 +222,8 @@ mod smoke_xfr_tracing {
             AssetRecord::from_template_no_identity_tracing(&mut prng, &template2).unwrap();
 
         // create xfr_note
-        let xfr_note = gen_xfr_note(&mut prng, &[ar], &[ar_out1, ar_out2], &[&sender1]).unwrap();
+        let xfr_note =
+            gen_xfr_note(&mut prng, &params, &[ar], &[ar_out1, ar_out2], &[&sender1]).unwrap();
 
         // verify
         let policies = XfrNotePoliciesRef::new(
@@ -334,6 +334,7 @@ fn complex_transaction() {
     // create xfr_note
     let xfr_note = gen_xfr_note(
         &mut prng,
+        &params,
         &[ar_in1, ar_in2, ar_in3],
         &[
             output_asset_record1,